@@ -0,0 +1,6 @@
+-- Speed up duplicate upload detection by content checksum.
+-- Partial, non-unique: older rows may have no checksum, and the same audio may
+-- legitimately be attached to more than one session.
+CREATE INDEX IF NOT EXISTS idx_audio_files_checksum
+    ON audio_files (checksum)
+    WHERE checksum IS NOT NULL;
@@ -0,0 +1,8 @@
+-- Track when a task was completed; maintained by the task repository on
+-- status changes. Tasks completed before this column existed are backfilled
+-- with their last update time, the closest known approximation.
+ALTER TABLE tasks ADD COLUMN IF NOT EXISTS completed_at TIMESTAMPTZ;
+
+UPDATE tasks
+SET completed_at = updated_at
+WHERE status = 'completed' AND completed_at IS NULL;
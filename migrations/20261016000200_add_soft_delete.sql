@@ -0,0 +1,16 @@
+-- Soft delete for analysis results, ideas, tasks and structured notes. Rows
+-- with deleted_at set are hidden from default queries until they are restored
+-- or purged. Partial indexes keep lookups of live rows cheap.
+ALTER TABLE analysis_results ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+ALTER TABLE ideas ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+ALTER TABLE tasks ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+ALTER TABLE structured_notes ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_analysis_results_live
+    ON analysis_results (created_at) WHERE deleted_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_ideas_live
+    ON ideas (created_at) WHERE deleted_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_tasks_live
+    ON tasks (created_at) WHERE deleted_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_structured_notes_live
+    ON structured_notes (created_at) WHERE deleted_at IS NULL;
@@ -0,0 +1,4 @@
+-- Timestamped segments of a transcript, as a JSON array of
+-- {start_ms, end_ms, text, speaker?, confidence?} objects. Empty for
+-- transcripts whose provider reported no timestamps.
+ALTER TABLE transcripts ADD COLUMN IF NOT EXISTS segments JSONB NOT NULL DEFAULT '[]'::jsonb;
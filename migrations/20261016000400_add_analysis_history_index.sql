@@ -0,0 +1,4 @@
+-- A session keeps every analysis run on it; the most recent live one is its
+-- current analysis. Index the per-session history in creation order.
+CREATE INDEX IF NOT EXISTS idx_analysis_results_session_created
+    ON analysis_results (session_id, created_at) WHERE deleted_at IS NULL;
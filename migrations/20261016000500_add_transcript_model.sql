@@ -0,0 +1,6 @@
+-- Record the exact model behind each transcript, next to its provider, and
+-- index the model columns so results can be filtered by model.
+ALTER TABLE transcripts ADD COLUMN IF NOT EXISTS model_used VARCHAR(255);
+
+CREATE INDEX IF NOT EXISTS idx_transcripts_model_used ON transcripts (model_used);
+CREATE INDEX IF NOT EXISTS idx_analysis_results_model_version ON analysis_results (model_version);
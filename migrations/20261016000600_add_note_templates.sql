@@ -0,0 +1,193 @@
+-- Note templates, previously hardcoded in the API. `schema` is the JSON
+-- schema the content of notes created from a template must match. System
+-- templates are seeded here and cannot be changed or deleted through the API.
+CREATE TABLE IF NOT EXISTS note_templates (
+    id VARCHAR(64) PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    note_type VARCHAR(50) NOT NULL,
+    schema JSONB NOT NULL,
+    default_content JSONB NOT NULL DEFAULT '{}'::jsonb,
+    sections JSONB NOT NULL DEFAULT '[]'::jsonb,
+    is_system BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Template usage counts look notes up by template
+CREATE INDEX IF NOT EXISTS idx_structured_notes_template
+    ON structured_notes (template_id) WHERE deleted_at IS NULL;
+
+INSERT INTO note_templates (id, name, description, note_type, schema, default_content, sections, is_system)
+VALUES
+    ('meeting_notes', 'Meeting Notes', 'Structured template for meeting notes', 'meeting_notes',
+     '{
+       "type": "object",
+       "properties": {
+         "meeting_info": {
+           "type": "object",
+           "properties": {
+             "date": {
+               "type": "string",
+               "format": "date-time"
+             },
+             "attendees": {
+               "type": "array",
+               "items": {
+                 "type": "string"
+               }
+             },
+             "agenda": {
+               "type": "string"
+             }
+           }
+         },
+         "discussion_points": {
+           "type": "array",
+           "items": {
+             "type": "object",
+             "properties": {
+               "topic": {
+                 "type": "string"
+               },
+               "discussion": {
+                 "type": "string"
+               },
+               "decisions": {
+                 "type": "array",
+                 "items": {
+                   "type": "string"
+                 }
+               }
+             }
+           }
+         },
+         "action_items": {
+           "type": "array",
+           "items": {
+             "type": "object",
+             "properties": {
+               "task": {
+                 "type": "string"
+               },
+               "assignee": {
+                 "type": "string"
+               },
+               "due_date": {
+                 "type": "string",
+                 "format": "date"
+               }
+             }
+           }
+         }
+       }
+     }',
+     '{
+       "meeting_info": {
+         "date": "",
+         "attendees": [],
+         "agenda": ""
+       },
+       "discussion_points": [],
+       "action_items": []
+     }',
+     '[
+       {
+         "id": "meeting_info",
+         "name": "Meeting Information",
+         "description": "Basic meeting details",
+         "required": true,
+         "field_type": "json",
+         "default_value": null
+       },
+       {
+         "id": "discussion_points",
+         "name": "Discussion Points",
+         "description": "Key topics discussed",
+         "required": false,
+         "field_type": "list",
+         "default_value": null
+       },
+       {
+         "id": "action_items",
+         "name": "Action Items",
+         "description": "Tasks and follow-ups",
+         "required": false,
+         "field_type": "table",
+         "default_value": null
+       }
+     ]',
+     TRUE),
+    ('research_summary', 'Research Summary', 'Template for research findings and analysis', 'research',
+     '{
+       "type": "object",
+       "properties": {
+         "research_topic": {
+           "type": "string"
+         },
+         "methodology": {
+           "type": "string"
+         },
+         "key_findings": {
+           "type": "array",
+           "items": {
+             "type": "object",
+             "properties": {
+               "finding": {
+                 "type": "string"
+               },
+               "evidence": {
+                 "type": "string"
+               },
+               "significance": {
+                 "type": "string"
+               }
+             }
+           }
+         },
+         "conclusions": {
+           "type": "string"
+         },
+         "next_steps": {
+           "type": "array",
+           "items": {
+             "type": "string"
+           }
+         }
+       }
+     }',
+     '{
+       "research_topic": "",
+       "methodology": "",
+       "key_findings": [],
+       "conclusions": "",
+       "next_steps": []
+     }',
+     '[
+       {
+         "id": "research_topic",
+         "name": "Research Topic",
+         "description": "Main research question or topic",
+         "required": true,
+         "field_type": "text",
+         "default_value": null
+       },
+       {
+         "id": "methodology",
+         "name": "Methodology",
+         "description": "Research approach and methods",
+         "required": false,
+         "field_type": "markdown",
+         "default_value": null
+       },
+       {
+         "id": "key_findings",
+         "name": "Key Findings",
+         "description": "Important discoveries and insights",
+         "required": true,
+         "field_type": "list",
+         "default_value": null
+       }
+     ]',
+     TRUE)
+ON CONFLICT (id) DO NOTHING;
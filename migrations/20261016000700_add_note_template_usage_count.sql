@@ -0,0 +1,9 @@
+-- Template usage is counted as notes are created instead of being looked
+-- up on every read. Backfill from the notes that already use a template.
+ALTER TABLE note_templates ADD COLUMN IF NOT EXISTS usage_count BIGINT NOT NULL DEFAULT 0;
+
+UPDATE note_templates t
+SET usage_count = (SELECT COUNT(*) FROM structured_notes n WHERE n.template_id = t.id);
+
+-- "Most used templates" sorts by usage
+CREATE INDEX IF NOT EXISTS idx_note_templates_usage ON note_templates (usage_count DESC, id);
@@ -0,0 +1,11 @@
+-- Earlier content of manually corrected transcripts, so corrections can be
+-- reviewed and reverted. A version is saved whenever the content changes;
+-- only the latest versions of each transcript are kept.
+CREATE TABLE IF NOT EXISTS transcript_versions (
+    transcript_id UUID NOT NULL REFERENCES transcripts(id) ON DELETE CASCADE,
+    version INTEGER NOT NULL,
+    content TEXT NOT NULL,
+    segments JSONB NOT NULL DEFAULT '[]'::jsonb,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (transcript_id, version)
+);
@@ -0,0 +1,7 @@
+-- Sessions assembled from several clips, such as resumed recordings, have
+-- one audio file per clip. Drop the one-file-per-session constraint, if the
+-- schema has it, and index the clips of a session in recording order.
+ALTER TABLE audio_files DROP CONSTRAINT IF EXISTS audio_files_session_id_key;
+
+CREATE INDEX IF NOT EXISTS idx_audio_files_session_created_at
+    ON audio_files (session_id, created_at);
@@ -0,0 +1,5 @@
+-- Filter sessions by custom metadata attributes (`?meta.project=alpha`).
+-- The filters use containment (`metadata @> '{"project": "alpha"}'`), which
+-- a jsonb_path_ops GIN index answers for any key.
+CREATE INDEX IF NOT EXISTS idx_sessions_metadata
+    ON sessions USING GIN (metadata jsonb_path_ops);
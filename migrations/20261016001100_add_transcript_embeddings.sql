@@ -0,0 +1,15 @@
+-- Embeddings of transcript content for semantic session search, one per
+-- transcript and embedding model. Sessions are ranked by cosine similarity
+-- in the application, so plain REAL[] columns are enough and no vector
+-- extension is needed. Embeddings are dropped when the content changes and
+-- computed again on the next semantic search.
+CREATE TABLE IF NOT EXISTS transcript_embeddings (
+    transcript_id UUID NOT NULL REFERENCES transcripts(id) ON DELETE CASCADE,
+    model TEXT NOT NULL,
+    embedding REAL[] NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (transcript_id, model)
+);
+
+CREATE INDEX IF NOT EXISTS idx_transcript_embeddings_model
+    ON transcript_embeddings (model);
@@ -0,0 +1,14 @@
+-- Keep the full result of an analysis next to its title and summary, and
+-- allow regenerating it in place: `updated_at` records the last regeneration
+-- and `metadata.previous_results` the results it replaced. Existing analyses
+-- were never changed after creation.
+ALTER TABLE analysis_results ADD COLUMN IF NOT EXISTS result_data JSONB;
+ALTER TABLE analysis_results ADD COLUMN IF NOT EXISTS metadata JSONB;
+ALTER TABLE analysis_results ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ;
+
+UPDATE analysis_results
+SET updated_at = created_at
+WHERE updated_at IS NULL;
+
+ALTER TABLE analysis_results ALTER COLUMN updated_at SET DEFAULT NOW();
+ALTER TABLE analysis_results ALTER COLUMN updated_at SET NOT NULL;
@@ -1,30 +1,45 @@
 // ai.rs
 use anyhow::Result;
-use async_openai::config::OpenAIConfig;
-use async_openai::types::{
-    ChatCompletionRequestMessage, ChatCompletionRequestSystemMessageArgs,
-    ChatCompletionRequestUserMessageArgs, CreateChatCompletionRequestArgs,
-};
-use async_openai::Client;
-use log::{info /* , warn, error */};
+use log::{info, warn /* , error */};
 use std::fmt;
 
-use crate::ollama::analyze_with_ollama_v2;
-use crate::storage::{AnalysisResult, Task, Priority, StructuredNote, NoteType};
+use crate::ollama::{analyze_with_ollama_v2, AnalysisType, ModelOptions};
+use crate::storage::{
+    AnalysisResult, NoteType, Priority, StructuredNote, Task, TranscriptSegment, Transcription,
+    VoiceSession,
+};
 
 use crate::config::AiProvider;
-use std::path::Path;
-use std::process::{Command, Stdio};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 use tokio::fs;
 use chrono::Utc;
 
 pub async fn transcribe_audio(audio_path: &Path) -> Result<String, anyhow::Error> {
-    let config = crate::config::load_config().await?;
+    Ok(transcribe_audio_segments(audio_path).await?.text)
+}
 
-    match config.ai_provider {
+/// Transcribe a session's recording, normalizing it first when
+/// `transcription.normalize_audio` is enabled
+pub async fn transcribe_session_audio(session: &mut VoiceSession) -> Result<Transcription, anyhow::Error> {
+    let input = crate::normalize::transcription_input(session).await;
+    transcribe_audio_segments(&input).await
+}
+
+/// Transcribe `audio_path`, keeping segment timestamps when the provider
+/// reports them
+pub async fn transcribe_audio_segments(audio_path: &Path) -> Result<Transcription, anyhow::Error> {
+    // Fail here rather than with an opaque provider error
+    let path = audio_path.to_path_buf();
+    tokio::task::spawn_blocking(move || crate::probe::check(&path)).await??;
+    let config = crate::config::current();
+
+    match config.transcription.provider {
         AiProvider::OpenAI => {
-            if let Some(_api_key) = &config.api_keys.openai_api_key {
-                transcribe_with_openai(audio_path, _api_key).await
+            if config.is_openai_configured() {
+                crate::openai::transcribe(audio_path, &config.openai).await
             } else {
                 // error!("OpenAI API key is not configured. Please set it in the config file.");
                 Err(anyhow::anyhow!(
@@ -35,10 +50,24 @@ pub async fn transcribe_audio(audio_path: &Path) -> Result<String, anyhow::Error
 
         AiProvider::WhisperCpp => {
             if let (Some(model_path), Some(executable_path)) = (
-                &config.speech_model.whisper_model_path,
-                &config.speech_model.whisper_executable_path,
+                &config.transcription.whisper_model_path,
+                &config.transcription.whisper_executable_path,
             ) {
-                transcribe_with_whisper_cpp(audio_path, model_path, executable_path).await
+                let transcription = transcribe_with_whisper_cpp(
+                    audio_path,
+                    &model_path.to_string_lossy(),
+                    &executable_path.to_string_lossy(),
+                )
+                .await?;
+                // Model files are named after the model, e.g. ggml-large-v3.bin
+                let model = model_path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned());
+                Ok(Transcription {
+                    provider: Some("whisper_cpp".to_string()),
+                    model,
+                    ..transcription
+                })
             } else {
                 // error!("Whisper.cpp model path or executable path not set in config.");
                 Err(anyhow::anyhow!(
@@ -58,7 +87,7 @@ async fn transcribe_with_whisper_cpp(
     audio_path: &Path,
     model_path: &str,
     executable_path: &str,
-) -> Result<String, anyhow::Error> {
+) -> Result<Transcription, anyhow::Error> {
     info!(
         "[Whisper.cpp] Attempting transcription:\n  Executable: {}\n  Model: {}\n  Audio file: {}",
         executable_path,
@@ -82,14 +111,17 @@ async fn transcribe_with_whisper_cpp(
     );
 
     let command_str = format!(
-        "{} -m {} -f {} -l auto -otxt",
+        "{} -m {} -f {} -l auto -otxt -ojf",
         executable_path,
         model_path,
         absolute_audio_path.to_str().unwrap_or("INVALID_PATH")
     );
     info!("[Whisper.cpp] Executing command: {}", command_str);
 
-    let output = Command::new(executable_path)
+    // Whisper.cpp overwrites its output files; older ones next to the audio
+    // are from an earlier run
+    let run_started = SystemTime::now();
+    let output = tokio::process::Command::new(executable_path)
         .arg("-m")
         .arg(model_path)
         .arg("-f")
@@ -101,9 +133,11 @@ async fn transcribe_with_whisper_cpp(
         .arg("-l")
         .arg("auto") // Specify Chinese language
         .arg("-otxt") // Output as plain text
+        .arg("-ojf") // and as full JSON, which carries segment timestamps, token probabilities and the language
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
-        .output();
+        .output()
+        .await;
 
     match output {
         Ok(output_result) => {
@@ -117,7 +151,7 @@ async fn transcribe_with_whisper_cpp(
             info!("[Whisper.cpp] STDERR:\n{}", stderr_str);
 
             if output_result.status.success() {
-                let output_txt_path = absolute_audio_path.with_extension("wav.txt");
+                let output_txt_path = whisper_output_path(&absolute_audio_path, "txt");
                 info!(
                     "[Whisper.cpp] Attempting to read transcript from: {}",
                     output_txt_path.display()
@@ -131,7 +165,18 @@ async fn transcribe_with_whisper_cpp(
                         );
                         // Optionally, remove the .txt file after reading
                         // fs::remove_file(output_txt_path).await.ok();
-                        Ok(content.trim().to_string())
+                        let (segments, language) =
+                            read_whisper_json(&whisper_output_path(&absolute_audio_path, "json"), run_started).await;
+                        let transcription = if segments.is_empty() {
+                            Transcription {
+                                text: content.trim().to_string(),
+                                segments,
+                                ..Default::default()
+                            }
+                        } else {
+                            Transcription::from_segments(segments)
+                        };
+                        Ok(Transcription { language, ..transcription })
                     }
                     Err(e) => {
                         // error!("[Whisper.cpp] ERROR: Failed to read transcript file {}: {}", output_txt_path.display(), e);
@@ -160,6 +205,116 @@ async fn transcribe_with_whisper_cpp(
     }
 }
 
+/// Whisper.cpp names its output files after the whole input file name,
+/// e.g. `memo.mp3.txt` for `memo.mp3`
+fn whisper_output_path(audio_path: &Path, extension: &str) -> PathBuf {
+    let mut path = audio_path.as_os_str().to_owned();
+    path.push(".");
+    path.push(extension);
+    PathBuf::from(path)
+}
+
+#[derive(serde::Deserialize)]
+struct WhisperJsonOutput {
+    #[serde(default)]
+    result: Option<WhisperJsonResult>,
+    transcription: Vec<WhisperJsonSegment>,
+}
+
+#[derive(serde::Deserialize)]
+struct WhisperJsonResult {
+    language: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct WhisperJsonSegment {
+    offsets: WhisperJsonOffsets,
+    text: String,
+    /// Only present in the full (`-ojf`) output
+    #[serde(default)]
+    tokens: Vec<WhisperJsonToken>,
+}
+
+#[derive(serde::Deserialize)]
+struct WhisperJsonToken {
+    text: String,
+    p: f32,
+}
+
+impl WhisperJsonSegment {
+    /// Mean probability of the segment's text tokens, skipping special
+    /// tokens such as `[_BEG_]` and timestamps
+    fn confidence(&self) -> Option<f32> {
+        let probabilities: Vec<f32> = self
+            .tokens
+            .iter()
+            .filter(|token| !token.text.starts_with("[_"))
+            .map(|token| token.p)
+            .collect();
+        (!probabilities.is_empty())
+            .then(|| probabilities.iter().sum::<f32>() / probabilities.len() as f32)
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct WhisperJsonOffsets {
+    from: u64,
+    to: u64,
+}
+
+/// Slack for file systems that store modification times in whole seconds
+/// (FAT even in two-second steps)
+const MTIME_GRANULARITY: Duration = Duration::from_secs(2);
+
+/// Segments and detected language from Whisper.cpp's JSON output; empty
+/// when the file is missing, unreadable or was not written by the run that
+/// started at `run_started`, e.g. by a Whisper.cpp without `-ojf`, in which
+/// case the plain-text transcript is used alone
+async fn read_whisper_json(json_path: &Path, run_started: SystemTime) -> (Vec<TranscriptSegment>, Option<String>) {
+    let modified = fs::metadata(json_path).await.and_then(|metadata| metadata.modified());
+    match modified {
+        Ok(modified) if modified + MTIME_GRANULARITY < run_started => {
+            warn!("[Whisper.cpp] No segment timestamps, ignoring {} from an earlier run", json_path.display());
+            return (Vec::new(), None);
+        }
+        Ok(_) => {}
+        Err(e) => {
+            warn!("[Whisper.cpp] No segment timestamps, failed to read {}: {}", json_path.display(), e);
+            return (Vec::new(), None);
+        }
+    }
+
+    let content = match fs::read_to_string(json_path).await {
+        Ok(content) => content,
+        Err(e) => {
+            warn!("[Whisper.cpp] No segment timestamps, failed to read {}: {}", json_path.display(), e);
+            return (Vec::new(), None);
+        }
+    };
+
+    match serde_json::from_str::<WhisperJsonOutput>(&content) {
+        Ok(output) => {
+            let segments = output
+                .transcription
+                .into_iter()
+                .filter(|segment| !segment.text.trim().is_empty())
+                .map(|segment| TranscriptSegment {
+                    start_ms: segment.offsets.from,
+                    end_ms: segment.offsets.to,
+                    text: segment.text.trim().to_string(),
+                    speaker: None,
+                    confidence: segment.confidence(),
+                })
+                .collect();
+            (segments, output.result.and_then(|result| result.language))
+        }
+        Err(e) => {
+            warn!("[Whisper.cpp] No segment timestamps, failed to parse {}: {}", json_path.display(), e);
+            (Vec::new(), None)
+        }
+    }
+}
+
 // 创建离线模式下的默认分析结果
 fn create_offline_analysis_result(transcript: &str) -> AnalysisResult {
     // 从转录文本中提取前几个词作为标题
@@ -191,11 +346,13 @@ fn create_offline_analysis_result(transcript: &str) -> AnalysisResult {
         title,
         summary,
         ideas: vec!["[离线模式] 无法连接到AI服务，无法提取想法".to_string()],
+        idea_confidences: Vec::new(),
         tasks: vec![Task {
             title: "检查网络连接".to_string(),
             description: Some("当前处于离线模式，无法连接到AI服务进行分析".to_string()),
             priority: Priority::Medium,
             due_date: None,
+            confidence: None,
         }],
         structured_notes: vec![StructuredNote {
             title: "离线模式通知".to_string(),
@@ -205,11 +362,144 @@ fn create_offline_analysis_result(transcript: &str) -> AnalysisResult {
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }],
+        processing_time_ms: None,
+        provider: None,
+        model: None,
+        detected_language: None,
+        warnings: Vec::new(),
     }
 }
 
+/// Whether an analysis failed because its provider could not be reached or
+/// did not answer in time, rather than because of the transcript or the
+/// model's output; such analyses are worth retrying later
+pub fn is_provider_unavailable(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(|error| error.is_connect() || error.is_timeout())
+    })
+}
+
 pub async fn analyze_transcript(transcript: &str) -> Result<AnalysisResult, anyhow::Error> {
-    let config = crate::config::load_config().await?;
+    analyze_transcript_as(transcript, AnalysisType::Full, None, None, None).await
+}
+
+/// Analyze a transcript, limiting the Ollama analysis to `analysis_type`
+///
+/// The analysis is written in `output_language`, falling back to
+/// `analysis.output_language` and then to the transcript's own language.
+/// `timeout_secs` replaces `analysis.timeout_secs` for this analysis, and
+/// the Ollama generation `options` set take precedence over `ollama.options`.
+/// The OpenAI path always performs a full analysis.
+pub async fn analyze_transcript_as(
+    transcript: &str,
+    analysis_type: AnalysisType,
+    output_language: Option<&str>,
+    timeout_secs: Option<u64>,
+    options: Option<ModelOptions>,
+) -> Result<AnalysisResult, anyhow::Error> {
+    let mut config = crate::config::current();
+    if let Some(timeout_secs) = timeout_secs {
+        Arc::make_mut(&mut config).analysis.timeout_secs = timeout_secs;
+    }
+    if let Some(options) = options {
+        let ollama = &mut Arc::make_mut(&mut config).ollama;
+        ollama.options = options.or(ollama.options);
+    }
+    analyze_with_config(transcript, analysis_type, output_language, None, &config).await
+}
+
+/// Run a full analysis with `provider` (`ollama` or `openai`) and, if given,
+/// `model` and `timeout_secs` instead of `analysis.default_provider`, its
+/// configured model and `analysis.timeout_secs`
+pub async fn analyze_transcript_with_model(
+    transcript: &str,
+    provider: &str,
+    model: Option<&str>,
+    timeout_secs: Option<u64>,
+) -> Result<AnalysisResult, anyhow::Error> {
+    let mut config = (*crate::config::current()).clone();
+    if let Some(timeout_secs) = timeout_secs {
+        config.analysis.timeout_secs = timeout_secs;
+    }
+    use_provider(&mut config, provider, model)?;
+    analyze_with_config(transcript, AnalysisType::Full, None, None, &config).await
+}
+
+/// Run a full analysis again with `custom_prompt` as additional instructions
+/// placed before the analysis prompt and, if given, `provider` and `model`
+/// instead of the configured ones
+pub async fn regenerate_analysis(
+    transcript: &str,
+    provider: Option<&str>,
+    model: Option<&str>,
+    custom_prompt: Option<&str>,
+) -> Result<AnalysisResult, anyhow::Error> {
+    let mut config = (*crate::config::current()).clone();
+    let provider = provider.map(str::to_string).unwrap_or_else(|| config.analysis.default_provider.clone());
+    use_provider(&mut config, &provider, model)?;
+    analyze_with_config(transcript, AnalysisType::Full, None, custom_prompt, &config).await
+}
+
+/// Extract only the action items of `transcript`, with `provider` and
+/// `model` instead of the configured ones if given
+///
+/// Ollama is asked for the tasks alone, which is much quicker than a full
+/// analysis; OpenAI always performs a full analysis, of which only the
+/// tasks are kept. Tasks less confident than `min_confidence`, or
+/// `analysis.min_confidence_to_persist`, are left out.
+pub async fn extract_action_items(
+    transcript: &str,
+    provider: Option<&str>,
+    model: Option<&str>,
+    output_language: Option<&str>,
+    min_confidence: Option<f32>,
+) -> Result<AnalysisResult, anyhow::Error> {
+    let mut config = (*crate::config::current()).clone();
+    let provider = provider.map(str::to_string).unwrap_or_else(|| config.analysis.default_provider.clone());
+    use_provider(&mut config, &provider, model)?;
+    if min_confidence.is_some() {
+        config.analysis.min_confidence_to_persist = min_confidence;
+    }
+
+    let mut analysis = analyze_with_config(transcript, AnalysisType::Tasks, output_language, None, &config).await?;
+    // Only set by OpenAI's full analysis, and not asked for
+    analysis.title.clear();
+    analysis.summary.clear();
+    analysis.ideas.clear();
+    analysis.idea_confidences.clear();
+    analysis.structured_notes.clear();
+    Ok(analysis)
+}
+
+/// Make `provider` (`ollama` or `openai`) the analysis provider of `config`,
+/// with `model` as its model if given
+fn use_provider(config: &mut crate::config::Config, provider: &str, model: Option<&str>) -> Result<(), anyhow::Error> {
+    let provider = provider.to_lowercase();
+    match (provider.as_str(), model) {
+        ("ollama", Some(model)) => config.ollama.default_model = model.to_string(),
+        ("openai", Some(model)) => config.openai.analysis_model = model.to_string(),
+        ("ollama" | "openai", None) => {}
+        _ => anyhow::bail!("Unsupported analysis provider: {}. Supported providers: ollama, openai", provider),
+    }
+    config.analysis.default_provider = provider;
+    Ok(())
+}
+
+/// Analyze `transcript` with the provider and models of `config`; the
+/// optional `instructions` are given to the model before its usual prompt
+async fn analyze_with_config(
+    transcript: &str,
+    analysis_type: AnalysisType,
+    output_language: Option<&str>,
+    instructions: Option<&str>,
+    config: &crate::config::Config,
+) -> Result<AnalysisResult, anyhow::Error> {
+    let output_language = output_language
+        .or(config.analysis.output_language.as_deref())
+        .map(str::trim)
+        .filter(|language| !language.is_empty());
 
     // 检查是否有OFFLINE环境变量或命令行参数
     let offline_mode = std::env::var("OFFLINE").is_ok();
@@ -218,24 +508,32 @@ pub async fn analyze_transcript(transcript: &str) -> Result<AnalysisResult, anyh
         return Ok(create_offline_analysis_result(transcript));
     }
 
-    // Determine which text model provider to use for analysis.
-    // If the main ai_provider is WhisperCpp, we look at the text_model.provider configuration.
-    // Otherwise, the main ai_provider (OpenAI, Ollama, Local) dictates the analysis method.
-    let provider_for_analysis = AiProvider::Ollama;
-    let use_openai_key: Option<String> = None;
+    // The analysis provider is chosen by `analysis.default_provider`,
+    // independently of the transcription provider.
+    let provider_for_analysis = match config.analysis.default_provider.to_lowercase().as_str() {
+        "openai" => Some(AiProvider::OpenAI),
+        "ollama" => Some(AiProvider::Ollama),
+        _ => None,
+    };
 
-    let ollama_settings_for_analysis = config.text_model.ollama_settings.clone();
-    let local_model_endpoint_for_analysis = config.text_model.local_model_path.clone();
+    let Some(provider_for_analysis) = provider_for_analysis else {
+        // warn!("No analysis provider configured or recognized. Skipping analysis.");
+        return Ok(AnalysisResult::default_with_summary(
+            "No analysis performed.".to_string(),
+        ));
+    };
 
     info!(
         "Analyzing transcript with provider: {}",
         provider_for_analysis
     );
 
-    match provider_for_analysis {
+    // Time the whole provider call, including JSON repair and fallback parsing
+    let started = Instant::now();
+    let mut analysis = match provider_for_analysis {
         AiProvider::OpenAI => {
-            if let Some(api_key) = use_openai_key {
-                analyze_with_openai(transcript, &api_key).await
+            if config.is_openai_configured() {
+                analyze_with_openai(transcript, &config.openai, output_language, instructions).await
             } else {
                 // error!("OpenAI API key not configured for analysis.");
                 Err(anyhow::anyhow!(
@@ -244,73 +542,111 @@ pub async fn analyze_transcript(transcript: &str) -> Result<AnalysisResult, anyh
             }
         }
         AiProvider::Ollama => {
-            if let Some(ollama_settings) = ollama_settings_for_analysis {
-                if ollama_settings.enabled {
-                    // 使用 v2 版本的 Ollama 分析函数
-                    analyze_with_ollama_v2(transcript, &ollama_settings.endpoint).await
-                } else {
-                    // warn!("Ollama is disabled in config. Skipping analysis.");
-                    Ok(AnalysisResult::default_with_summary(
-                        "Ollama analysis skipped (disabled).".to_string(),
-                    ))
-                }
-            } else {
-                // error!("Ollama settings not configured for analysis.");
-                Err(anyhow::anyhow!(
-                    "Ollama settings not configured for analysis."
-                ))
-            }
+            // 使用 v2 版本的 Ollama 分析函数
+            let timeout = Duration::from_secs(config.analysis.timeout_secs);
+            analyze_with_ollama_v2(transcript, &config.ollama, analysis_type, output_language, instructions, timeout).await
         }
         _ => {
             // warn!("No analysis provider configured or recognized. Skipping analysis.");
-            Ok(AnalysisResult::default_with_summary(
+            return Ok(AnalysisResult::default_with_summary(
                 "No analysis performed.".to_string(),
-            ))
+            ));
+        }
+    }?;
+
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    info!("Analysis finished in {} ms", elapsed_ms);
+    analysis.processing_time_ms = Some(elapsed_ms);
+    let (provider, model) = match provider_for_analysis {
+        AiProvider::Ollama => ("ollama", crate::ollama::qualified_model_name(&config.ollama.default_model)),
+        _ => ("openai", config.openai.analysis_model.clone()),
+    };
+    analysis.provider = Some(provider.to_string());
+    analysis.model = Some(model);
+    // Recorded for both providers, to help explain misclassified transcripts
+    let detection = crate::ollama::detect_language_with_confidence(transcript, &config.analysis.language_detection);
+    analysis.detected_language = Some(detection.into());
+    if let Some(min_confidence) = config.analysis.min_confidence_to_persist {
+        let dropped = analysis.retain_confident(min_confidence);
+        if dropped > 0 {
+            info!("Dropped {} tasks and ideas with a confidence below {}", dropped, min_confidence);
         }
     }
+    Ok(analysis)
 }
 
-async fn transcribe_with_openai(
-    audio_path: &Path,
-    _api_key: &str,
-) -> Result<String, anyhow::Error> {
-    info!("[OpenAI] Transcribing audio file: {}", audio_path.display());
-    // Placeholder for actual OpenAI transcription logic
-    Ok(format!("OpenAI transcription of {}", audio_path.display()))
+/// Analyze a transcript for each of `analysis_types` and combine the results
+///
+/// `Full`, or no type at all, runs a single full analysis. Otherwise every
+/// type gets its own focused request and only the fields it produces are
+/// kept, so e.g. the summary of an `Ideas` analysis doesn't leak into the
+/// result. `processing_time_ms` is the total over all requests.
+pub async fn analyze_transcript_sections(
+    transcript: &str,
+    analysis_types: &[AnalysisType],
+    output_language: Option<&str>,
+    timeout_secs: Option<u64>,
+    options: Option<ModelOptions>,
+) -> Result<AnalysisResult, anyhow::Error> {
+    if analysis_types.is_empty() || analysis_types.contains(&AnalysisType::Full) {
+        return analyze_transcript_as(transcript, AnalysisType::Full, output_language, timeout_secs, options).await;
+    }
+
+    let mut combined = AnalysisResult::default();
+    let mut requested: Vec<AnalysisType> = Vec::new();
+    for &analysis_type in analysis_types {
+        if requested.contains(&analysis_type) {
+            continue;
+        }
+        requested.push(analysis_type);
+
+        let analysis = analyze_transcript_as(transcript, analysis_type, output_language, timeout_secs, options).await?;
+        combined.provider = analysis.provider;
+        combined.model = analysis.model;
+        combined.detected_language = analysis.detected_language;
+        match analysis_type {
+            AnalysisType::Summary => {
+                combined.title = analysis.title;
+                combined.summary = analysis.summary;
+            }
+            AnalysisType::Ideas => combined.ideas = analysis.ideas,
+            AnalysisType::Tasks => combined.tasks = analysis.tasks,
+            AnalysisType::StructuredNotes => combined.structured_notes = analysis.structured_notes,
+            AnalysisType::Full => unreachable!("full analyses are handled above"),
+        }
+        if let Some(ms) = analysis.processing_time_ms {
+            combined.processing_time_ms = Some(combined.processing_time_ms.unwrap_or(0) + ms);
+        }
+        combined.warnings.extend(analysis.warnings);
+    }
+
+    Ok(combined)
 }
 
 async fn analyze_with_openai(
     transcript: &str,
-    api_key: &str,
+    openai_config: &crate::config::OpenAIConfig,
+    output_language: Option<&str>,
+    instructions: Option<&str>,
 ) -> Result<AnalysisResult, anyhow::Error> {
     info!("[OpenAI Analysis] Analyzing transcript: '{}'", transcript);
-    let client = Client::with_config(OpenAIConfig::new().with_api_key(api_key));
-
-    let system_message = ChatCompletionRequestMessage::System(ChatCompletionRequestSystemMessageArgs::default()
-        .content("You are a helpful assistant that analyzes meeting transcripts. Extract key ideas, tasks, and structured notes. Provide a concise summary.")
-        .build()?);
-
-    let user_message = ChatCompletionRequestMessage::User(
-        ChatCompletionRequestUserMessageArgs::default()
-            .content(format!(
-                "Analyze the following transcript:\n\n{}",
-                transcript
-            ))
-            .build()?,
-    );
-
-    let request = CreateChatCompletionRequestArgs::default()
-        .model("gpt-4o") // Or another suitable model like "gpt-3.5-turbo"
-        .messages(vec![system_message, user_message])
-        .build()?;
-
-    let response = client.chat().create(request).await?;
-
-    let analysis_text = response.choices[0]
-        .message
-        .content
-        .clone()
-        .unwrap_or_default();
+    let mut system_prompt = "You are a helpful assistant that analyzes meeting transcripts. Extract key ideas, tasks, and structured notes. Provide a concise summary.".to_string();
+    if let Some(output_language) = output_language {
+        system_prompt = format!(
+            "{} {}",
+            system_prompt,
+            crate::ollama::output_language_instruction(output_language)
+        );
+    }
+    if let Some(instructions) = instructions.map(str::trim).filter(|instructions| !instructions.is_empty()) {
+        system_prompt = format!("{} {}", system_prompt, instructions);
+    }
+    let analysis_text = crate::openai::chat(
+        openai_config,
+        &system_prompt,
+        &format!("Analyze the following transcript:\n\n{}", transcript),
+    )
+    .await?;
     info!("[OpenAI Analysis] Raw analysis response: {}", analysis_text);
 
     // Simple parsing for demonstration. A more robust solution would use structured JSON output from the AI.
@@ -323,9 +659,15 @@ async fn analyze_with_openai(
     Ok(AnalysisResult {
         title,
         ideas,
+        idea_confidences: Vec::new(),
         tasks,
         structured_notes,
         summary,
+        processing_time_ms: None,
+        provider: None,
+        model: None,
+        detected_language: None,
+        warnings: Vec::new(),
     })
 }
 
@@ -371,6 +713,7 @@ fn extract_tasks(text: &str, prefix: &str) -> Vec<crate::storage::Task> {
                 description: None,
                 priority: crate::storage::Priority::Medium,
                 due_date: None,
+                confidence: None,
             })
         })
         .collect()
@@ -410,3 +753,16 @@ impl fmt::Display for AiProvider {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whisper_output_files_are_named_after_the_whole_input_file_name() {
+        assert_eq!(whisper_output_path(Path::new("/audio/memo.mp3"), "txt"), Path::new("/audio/memo.mp3.txt"));
+        assert_eq!(whisper_output_path(Path::new("/audio/memo.wav"), "txt"), Path::new("/audio/memo.wav.txt"));
+        assert_eq!(whisper_output_path(Path::new("/audio/v1.2.flac"), "txt"), Path::new("/audio/v1.2.flac.txt"));
+        assert_eq!(whisper_output_path(Path::new("/audio/memo.m4a"), "json"), Path::new("/audio/memo.m4a.json"));
+    }
+}
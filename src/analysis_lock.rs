@@ -0,0 +1,116 @@
+// src/analysis_lock.rs
+//! One analysis of a session at a time
+//!
+//! The web server's analyze endpoint, `backfill`, `retry-analysis` and
+//! `reanalyze` can all pick up the same stored session. Run at the same time
+//! they would analyze it twice, and the later save would replace the earlier
+//! analysis. Whoever processes a session holds its lock; others skip it.
+//!
+//! Within a process the lock is an entry in a shared list. Across processes
+//! using the same storage directory it is an exclusive advisory lock on
+//! `locks/<id>.analysis`, which the OS releases when the file is closed, so
+//! also when the holding process crashes. The lock file itself is left in
+//! place: removing it on release would let a process that has just opened
+//! it lock a file no one else can see.
+
+use anyhow::{bail, Context, Result};
+use fs2::FileExt;
+use log::info;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Ids of sessions locked by this process
+static LOCKED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Exclusive right to analyze one session, released on drop
+pub struct AnalysisLock {
+    session_id: String,
+    /// Holds the advisory lock until it is closed
+    _file: File,
+}
+
+impl Drop for AnalysisLock {
+    fn drop(&mut self) {
+        LOCKED.lock().unwrap().retain(|id| id != &self.session_id);
+    }
+}
+
+/// Lock a session for analysis; `None` if it is being analyzed by another
+/// task or process
+pub fn try_lock(session_id: &str) -> Result<Option<AnalysisLock>> {
+    if !is_valid_id(session_id) {
+        bail!("Invalid session id {:?}", session_id);
+    }
+    {
+        let mut locked = LOCKED.lock().unwrap();
+        if locked.iter().any(|id| id == session_id) {
+            info!("[{}] Session is already being analyzed by this process", session_id);
+            return Ok(None);
+        }
+        locked.push(session_id.to_string());
+    }
+
+    match lock_file(session_id) {
+        Ok(Some(file)) => Ok(Some(AnalysisLock { session_id: session_id.to_string(), _file: file })),
+        result => {
+            LOCKED.lock().unwrap().retain(|id| id != session_id);
+            result.map(|_| None)
+        }
+    }
+}
+
+/// Session ids become file names; anything that could leave `locks/` is refused
+fn is_valid_id(session_id: &str) -> bool {
+    !session_id.is_empty() && session_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn lock_path(session_id: &str) -> PathBuf {
+    crate::config::get_storage_dir().join("locks").join(format!("{}.analysis", session_id))
+}
+
+/// Open the session's lock file and lock it, writing our process id into
+/// it; `None` if another process holds the lock
+fn lock_file(session_id: &str) -> Result<Option<File>> {
+    let path = lock_path(session_id);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)
+        .with_context(|| format!("Failed to open analysis lock {}", path.display()))?;
+    if let Err(e) = file.try_lock_exclusive() {
+        if e.kind() != fs2::lock_contended_error().kind() {
+            return Err(e).with_context(|| format!("Failed to lock {}", path.display()));
+        }
+        let holder = fs::read_to_string(&path).unwrap_or_default();
+        info!("[{}] Session is already being analyzed by process {}", session_id, holder.trim());
+        return Ok(None);
+    }
+
+    // Only informational, for the message above
+    file.set_len(0)?;
+    let _ = writeln!(file, "{}", std::process::id());
+    Ok(Some(file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_that_could_leave_the_locks_directory_are_refused() {
+        assert!(is_valid_id("0b7c1a52-4f7e-4d8a-9a51-6f3c2e1d0b9a"));
+        assert!(is_valid_id("session_1700000000"));
+        assert!(!is_valid_id(""));
+        assert!(!is_valid_id("../sessions/x"));
+        assert!(!is_valid_id("..\\x"));
+        assert!(!is_valid_id("a/b"));
+    }
+}
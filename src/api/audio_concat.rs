@@ -0,0 +1,57 @@
+// src/api/audio_concat.rs
+//! Joining the clips of a session into one WAV file
+//!
+//! Sessions assembled from several clips, such as resumed recordings, are
+//! played back and exported as a single file. Only WAV clips recorded with
+//! the same sample rate, channel count and sample format can be joined;
+//! anything else has to be downloaded clip by clip.
+
+use std::io::Cursor;
+
+/// The clips, in order, as a single WAV file
+///
+/// Fails with a message naming the first clip (1-based) that cannot be
+/// read or does not match the format of the first one.
+pub fn concat_wav(clips: &[Vec<u8>]) -> Result<Vec<u8>, String> {
+    let mut readers = clips
+        .iter()
+        .enumerate()
+        .map(|(index, clip)| {
+            hound::WavReader::new(Cursor::new(clip.as_slice()))
+                .map_err(|e| format!("clip {} is not a readable WAV file: {}", index + 1, e))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let Some(spec) = readers.first().map(|reader| reader.spec()) else {
+        return Err("there are no clips to join".to_string());
+    };
+    if let Some(index) = readers.iter().position(|reader| reader.spec() != spec) {
+        let other = readers[index].spec();
+        return Err(format!(
+            "clip {} is {} Hz, {} channel(s), {} bit, unlike the first clip ({} Hz, {} channel(s), {} bit)",
+            index + 1,
+            other.sample_rate,
+            other.channels,
+            other.bits_per_sample,
+            spec.sample_rate,
+            spec.channels,
+            spec.bits_per_sample
+        ));
+    }
+
+    let mut out = Cursor::new(Vec::new());
+    let mut writer = hound::WavWriter::new(&mut out, spec).map_err(|e| format!("cannot write the WAV file: {}", e))?;
+    for (index, reader) in readers.iter_mut().enumerate() {
+        let copied = match spec.sample_format {
+            hound::SampleFormat::Float => reader
+                .samples::<f32>()
+                .try_for_each(|sample| writer.write_sample(sample?)),
+            hound::SampleFormat::Int => reader
+                .samples::<i32>()
+                .try_for_each(|sample| writer.write_sample(sample?)),
+        };
+        copied.map_err(|e| format!("cannot copy the audio of clip {}: {}", index + 1, e))?;
+    }
+    writer.finalize().map_err(|e| format!("cannot write the WAV file: {}", e))?;
+
+    Ok(out.into_inner())
+}
@@ -0,0 +1,42 @@
+// src/api/auth.rs
+//! Bearer token guard for destructive endpoints
+//!
+//! Routes wrapped with [`require_admin_token`] only run when the request
+//! carries `Authorization: Bearer <server.admin_token>`. When no admin token is
+//! configured these routes are disabled rather than left open.
+
+use axum::{
+    extract::Request,
+    http::header::AUTHORIZATION,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::api::error::ApiError;
+
+/// Middleware rejecting requests without the configured admin bearer token
+pub async fn require_admin_token(request: Request, next: Next) -> Response {
+    let Some(expected) = crate::config::current().server.admin_token.clone() else {
+        return ApiError::Forbidden(
+            "This endpoint is disabled; set server.admin_token (or ADMIN_TOKEN) to enable it".to_string(),
+        )
+        .into_response();
+    };
+
+    let provided = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match provided {
+        Some(token) if constant_time_eq(token.as_bytes(), expected.as_bytes()) => next.run(request).await,
+        Some(_) => ApiError::Unauthorized("Invalid admin token".to_string()).into_response(),
+        None => ApiError::Unauthorized("Missing bearer token".to_string()).into_response(),
+    }
+}
+
+/// Compare two byte strings without short-circuiting on the first difference
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -0,0 +1,153 @@
+// src/api/combined_export.rs
+//! Several transcripts as one plain text or markdown document
+//!
+//! Each transcript gets a section headed by its session's title and, with
+//! metadata, its date, in the order given. Markdown documents start with a
+//! table of contents linking to the sections through explicit anchors,
+//! since titles are often not ASCII and heading slugs differ between
+//! renderers.
+
+use chrono::{DateTime, Utc};
+use std::fmt::Write;
+
+use crate::storage::TranscriptSegment;
+
+/// Title used for sessions that have none
+const UNTITLED: &str = "Untitled session";
+
+/// A transcript to be written as one section of the document
+#[derive(Debug, Clone)]
+pub struct CombinedTranscript<'a> {
+    pub title: Option<&'a str>,
+    pub recorded_at: DateTime<Utc>,
+    pub content: &'a str,
+    /// Written as `[mm:ss] text` lines instead of `content` when not empty
+    pub segments: &'a [TranscriptSegment],
+}
+
+impl CombinedTranscript<'_> {
+    fn title(&self) -> &str {
+        self.title.map(str::trim).filter(|title| !title.is_empty()).unwrap_or(UNTITLED)
+    }
+
+    fn date(&self) -> String {
+        self.recorded_at.format("%Y-%m-%d %H:%M UTC").to_string()
+    }
+
+    fn body(&self) -> String {
+        if self.segments.is_empty() {
+            return self.content.trim().to_string();
+        }
+        self.segments
+            .iter()
+            .map(|segment| format!("[{}] {}", format_offset(segment.start_ms), segment.text.trim()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Render `transcripts` as markdown with a table of contents, with the
+/// session dates if `include_metadata` is set
+pub fn to_markdown(transcripts: &[CombinedTranscript<'_>], include_metadata: bool) -> String {
+    let mut out = String::from("# Transcripts\n\n## Contents\n\n");
+    for (index, transcript) in transcripts.iter().enumerate() {
+        let date = if include_metadata { format!(" ({})", transcript.date()) } else { String::new() };
+        let _ = writeln!(
+            out,
+            "{}. [{}{}](#transcript-{})",
+            index + 1,
+            escape_markdown(transcript.title()),
+            date,
+            index + 1
+        );
+    }
+
+    for (index, transcript) in transcripts.iter().enumerate() {
+        let _ = write!(
+            out,
+            "\n<a id=\"transcript-{}\"></a>\n\n## {}\n\n",
+            index + 1,
+            escape_markdown(transcript.title())
+        );
+        if include_metadata {
+            let _ = write!(out, "_{}_\n\n", transcript.date());
+        }
+        let _ = writeln!(out, "{}", transcript.body());
+    }
+    out
+}
+
+/// Render `transcripts` as plain text, one underlined header per transcript,
+/// with the session dates if `include_metadata` is set
+pub fn to_text(transcripts: &[CombinedTranscript<'_>], include_metadata: bool) -> String {
+    transcripts
+        .iter()
+        .map(|transcript| {
+            let header = if include_metadata {
+                format!("{} ({})", transcript.title(), transcript.date())
+            } else {
+                transcript.title().to_string()
+            };
+            let rule = "=".repeat(header.chars().count());
+            format!("{}\n{}\n\n{}\n", header, rule, transcript.body())
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// `mm:ss`, or `h:mm:ss` from an hour on
+fn format_offset(ms: u64) -> String {
+    let secs = ms / 1000;
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{:02}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+/// `text` on one line, with the characters markdown would read as
+/// formatting, links, HTML or heading markers escaped
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.split_whitespace().collect::<Vec<_>>().join(" ").chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|' | '~' | '!') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transcript<'a>(title: Option<&'a str>, content: &'a str) -> CombinedTranscript<'a> {
+        CombinedTranscript {
+            title,
+            recorded_at: DateTime::parse_from_rfc3339("2026-10-16T09:30:00Z").unwrap().with_timezone(&Utc),
+            content,
+            segments: &[],
+        }
+    }
+
+    #[test]
+    fn markdown_titles_are_escaped() {
+        let markdown = to_markdown(&[transcript(Some("# *Q4* [plan] <b>\nnext"), "Hello")], true);
+        assert!(markdown.contains("1. [\\# \\*Q4\\* \\[plan\\] \\<b\\> next (2026-10-16 09:30 UTC)](#transcript-1)"));
+        assert!(markdown.contains("\n## \\# \\*Q4\\* \\[plan\\] \\<b\\> next\n"));
+    }
+
+    #[test]
+    fn dates_are_left_out_without_metadata() {
+        let sections = [transcript(Some("周会"), "Hello"), transcript(None, "World")];
+
+        let markdown = to_markdown(&sections, false);
+        assert!(!markdown.contains("2026-10-16"));
+        assert!(markdown.contains("1. [周会](#transcript-1)\n2. [Untitled session](#transcript-2)\n"));
+        assert!(markdown.contains("## 周会\n\nHello\n"));
+
+        assert_eq!(to_text(&sections, false), "周会\n==\n\nHello\n\n\nUntitled session\n================\n\nWorld\n");
+        assert!(to_text(&sections, true).starts_with("周会 (2026-10-16 09:30 UTC)\n"));
+    }
+}
@@ -0,0 +1,228 @@
+// src/api/docx.rs
+//! Minimal Word (.docx) writer for exporting analyses
+//!
+//! A .docx file is a zip archive of WordprocessingML parts. Only what an
+//! analysis needs is written: the title as Heading 1, the summary, a bulleted
+//! list of ideas and a table of tasks. Word's built-in style names are used,
+//! so the document can be restyled in Word or LibreOffice. Runs default to an
+//! East Asian font as well, so Chinese text is not rendered in a fallback
+//! font.
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use std::fmt::Write as _;
+use std::io::{Cursor, Write};
+
+/// MIME type of .docx files
+pub const CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument.wordprocessingml.document";
+
+/// Title used for analyses that have none
+const UNTITLED: &str = "Untitled analysis";
+
+/// Width of the text area of an A4 page with 2.54 cm margins, in twips
+const TEXT_WIDTH: u32 = 9026;
+/// Widths of the priority and due date columns of the task table, in twips
+const NARROW_COLUMN: u32 = 1800;
+
+/// A task to be written as a row of the task table
+#[derive(Debug, Clone)]
+pub struct DocxTask<'a> {
+    pub title: &'a str,
+    pub priority: String,
+    pub due: Option<DateTime<Utc>>,
+}
+
+/// The parts of an analysis written to the document, in order
+#[derive(Debug, Clone)]
+pub struct AnalysisDocument<'a> {
+    pub title: Option<&'a str>,
+    pub summary: Option<&'a str>,
+    pub ideas: Vec<&'a str>,
+    pub tasks: Vec<DocxTask<'a>>,
+}
+
+/// Render `document` as a .docx file
+pub fn analysis_to_docx(document: &AnalysisDocument<'_>) -> anyhow::Result<Vec<u8>> {
+    let parts = [
+        ("[Content_Types].xml", CONTENT_TYPES.to_string()),
+        ("_rels/.rels", PACKAGE_RELS.to_string()),
+        ("word/_rels/document.xml.rels", DOCUMENT_RELS.to_string()),
+        ("word/styles.xml", STYLES.to_string()),
+        ("word/numbering.xml", NUMBERING.to_string()),
+        ("word/document.xml", document_xml(document)),
+    ];
+
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for (name, content) in parts {
+        zip.start_file(name, options).with_context(|| format!("Failed to add {} to the document", name))?;
+        zip.write_all(content.as_bytes()).with_context(|| format!("Failed to write {}", name))?;
+    }
+    Ok(zip.finish().context("Failed to finish the document")?.into_inner())
+}
+
+fn document_xml(document: &AnalysisDocument<'_>) -> String {
+    let mut body = String::new();
+    let title = document.title.map(str::trim).filter(|title| !title.is_empty()).unwrap_or(UNTITLED);
+    paragraph(&mut body, Some("Heading1"), title);
+
+    if let Some(summary) = document.summary.map(str::trim).filter(|summary| !summary.is_empty()) {
+        paragraph(&mut body, Some("Heading2"), "Summary");
+        for line in summary.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            paragraph(&mut body, None, line);
+        }
+    }
+
+    if !document.ideas.is_empty() {
+        paragraph(&mut body, Some("Heading2"), "Ideas");
+        for idea in &document.ideas {
+            paragraph(&mut body, Some("ListBullet"), idea.trim());
+        }
+    }
+
+    if !document.tasks.is_empty() {
+        paragraph(&mut body, Some("Heading2"), "Tasks");
+        task_table(&mut body, &document.tasks);
+    }
+
+    format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>{}"#,
+            r#"<w:sectPr><w:pgSz w:w="11906" w:h="16838"/>"#,
+            r#"<w:pgMar w:top="1440" w:right="1440" w:bottom="1440" w:left="1440" w:header="708" w:footer="708" w:gutter="0"/>"#,
+            r#"</w:sectPr></w:body></w:document>"#
+        ),
+        body
+    )
+}
+
+fn task_table(body: &mut String, tasks: &[DocxTask<'_>]) {
+    let widths = [TEXT_WIDTH - 2 * NARROW_COLUMN, NARROW_COLUMN, NARROW_COLUMN];
+    body.push_str(r#"<w:tbl><w:tblPr><w:tblStyle w:val="TableGrid"/><w:tblW w:w="0" w:type="auto"/></w:tblPr><w:tblGrid>"#);
+    for width in widths {
+        let _ = write!(body, r#"<w:gridCol w:w="{}"/>"#, width);
+    }
+    body.push_str("</w:tblGrid>");
+
+    // Repeated at the top of every page the table spans
+    table_row(body, &widths, ["Task", "Priority", "Due"], true);
+    for task in tasks {
+        let due = task.due.map(|due| due.format("%Y-%m-%d").to_string()).unwrap_or_default();
+        table_row(body, &widths, [task.title.trim(), &task.priority, &due], false);
+    }
+    body.push_str("</w:tbl>");
+}
+
+fn table_row(body: &mut String, widths: &[u32; 3], cells: [&str; 3], header: bool) {
+    body.push_str("<w:tr>");
+    if header {
+        body.push_str("<w:trPr><w:tblHeader/></w:trPr>");
+    }
+    for (width, text) in widths.iter().zip(cells) {
+        let _ = write!(
+            body,
+            r#"<w:tc><w:tcPr><w:tcW w:w="{}" w:type="dxa"/></w:tcPr><w:p>{}</w:p></w:tc>"#,
+            width,
+            run(text, header)
+        );
+    }
+    body.push_str("</w:tr>");
+}
+
+fn paragraph(body: &mut String, style: Option<&str>, text: &str) {
+    body.push_str("<w:p>");
+    if let Some(style) = style {
+        let _ = write!(body, r#"<w:pPr><w:pStyle w:val="{}"/></w:pPr>"#, style);
+    }
+    body.push_str(&run(text, false));
+    body.push_str("</w:p>");
+}
+
+fn run(text: &str, bold: bool) -> String {
+    format!(
+        r#"<w:r>{}<w:t xml:space="preserve">{}</w:t></w:r>"#,
+        if bold { "<w:rPr><w:b/></w:rPr>" } else { "" },
+        escape_xml(text)
+    )
+}
+
+/// Escape markup characters and drop those XML 1.0 does not allow, such as
+/// control characters pasted into a transcript
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\t' | '\n' | '\r' => escaped.push(' '),
+            c if c < ' ' || matches!(c, '\u{FFFE}' | '\u{FFFF}') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+const CONTENT_TYPES: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#,
+    r#"<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>"#,
+    r#"<Default Extension="xml" ContentType="application/xml"/>"#,
+    r#"<Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/>"#,
+    r#"<Override PartName="/word/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml"/>"#,
+    r#"<Override PartName="/word/numbering.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.numbering+xml"/>"#,
+    r#"</Types>"#
+);
+
+const PACKAGE_RELS: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/>"#,
+    r#"</Relationships>"#
+);
+
+const DOCUMENT_RELS: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/>"#,
+    r#"<Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/numbering" Target="numbering.xml"/>"#,
+    r#"</Relationships>"#
+);
+
+/// Defaults for Latin and East Asian text, and the styles the document uses
+const STYLES: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">"#,
+    r#"<w:docDefaults><w:rPrDefault><w:rPr>"#,
+    r#"<w:rFonts w:ascii="Calibri" w:hAnsi="Calibri" w:eastAsia="Microsoft YaHei" w:cs="Calibri"/>"#,
+    r#"<w:sz w:val="22"/><w:szCs w:val="22"/><w:lang w:val="en-US" w:eastAsia="zh-CN"/>"#,
+    r#"</w:rPr></w:rPrDefault><w:pPrDefault><w:pPr><w:spacing w:after="120" w:line="276" w:lineRule="auto"/></w:pPr></w:pPrDefault></w:docDefaults>"#,
+    r#"<w:style w:type="paragraph" w:default="1" w:styleId="Normal"><w:name w:val="Normal"/><w:qFormat/></w:style>"#,
+    r#"<w:style w:type="paragraph" w:styleId="Heading1"><w:name w:val="heading 1"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/>"#,
+    r#"<w:pPr><w:keepNext/><w:spacing w:before="240" w:after="240"/><w:outlineLvl w:val="0"/></w:pPr>"#,
+    r#"<w:rPr><w:b/><w:bCs/><w:sz w:val="36"/><w:szCs w:val="36"/></w:rPr></w:style>"#,
+    r#"<w:style w:type="paragraph" w:styleId="Heading2"><w:name w:val="heading 2"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/>"#,
+    r#"<w:pPr><w:keepNext/><w:spacing w:before="240" w:after="120"/><w:outlineLvl w:val="1"/></w:pPr>"#,
+    r#"<w:rPr><w:b/><w:bCs/><w:sz w:val="28"/><w:szCs w:val="28"/></w:rPr></w:style>"#,
+    r#"<w:style w:type="paragraph" w:styleId="ListBullet"><w:name w:val="List Bullet"/><w:basedOn w:val="Normal"/>"#,
+    r#"<w:pPr><w:numPr><w:numId w:val="1"/></w:numPr><w:spacing w:after="60"/></w:pPr></w:style>"#,
+    r#"<w:style w:type="table" w:styleId="TableGrid"><w:name w:val="Table Grid"/><w:tblPr><w:tblBorders>"#,
+    r#"<w:top w:val="single" w:sz="4" w:space="0" w:color="auto"/><w:left w:val="single" w:sz="4" w:space="0" w:color="auto"/>"#,
+    r#"<w:bottom w:val="single" w:sz="4" w:space="0" w:color="auto"/><w:right w:val="single" w:sz="4" w:space="0" w:color="auto"/>"#,
+    r#"<w:insideH w:val="single" w:sz="4" w:space="0" w:color="auto"/><w:insideV w:val="single" w:sz="4" w:space="0" w:color="auto"/>"#,
+    r#"</w:tblBorders><w:tblCellMar><w:left w:w="108" w:type="dxa"/><w:right w:w="108" w:type="dxa"/></w:tblCellMar></w:tblPr></w:style>"#,
+    r#"</w:styles>"#
+);
+
+/// A single bullet list definition, `numId` 1
+const NUMBERING: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<w:numbering xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">"#,
+    r#"<w:abstractNum w:abstractNumId="0"><w:multiLevelType w:val="singleLevel"/>"#,
+    r#"<w:lvl w:ilvl="0"><w:start w:val="1"/><w:numFmt w:val="bullet"/><w:lvlText w:val="•"/><w:lvlJc w:val="left"/>"#,
+    r#"<w:pPr><w:ind w:left="720" w:hanging="360"/></w:pPr></w:lvl></w:abstractNum>"#,
+    r#"<w:num w:numId="1"><w:abstractNumId w:val="0"/></w:num>"#,
+    r#"</w:numbering>"#
+);
@@ -0,0 +1,160 @@
+// src/api/duplicates.rs
+//! Finding sessions that hold the same recording
+//!
+//! The same meeting is sometimes recorded or uploaded twice. Two sessions
+//! with transcripts are compared by the words they share, or by their
+//! transcript embeddings when both have one; sessions without a transcript
+//! can only match by an identical audio file. Sessions linked by similar
+//! pairs form one group, so a meeting uploaded three times is one group.
+
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use super::semantic::cosine_similarity;
+
+/// What a session is compared by
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub session_id: Uuid,
+    /// Words of the transcript, see [`token_set`]; `None` without a transcript
+    pub tokens: Option<HashSet<String>>,
+    /// Transcript embedding, when comparing by embeddings
+    pub embedding: Option<Vec<f32>>,
+    /// Checksums of the session's audio files
+    pub checksums: Vec<String>,
+}
+
+/// How the sessions of a group were found to match
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchedBy {
+    Transcript,
+    Embedding,
+    AudioChecksum,
+}
+
+/// Sessions that are likely the same recording
+#[derive(Debug, Clone)]
+pub struct Group {
+    /// In the order of the candidates
+    pub session_ids: Vec<Uuid>,
+    /// Similarity of the group's closest pair, from 0 to 1
+    pub similarity: f64,
+    pub matched_by: MatchedBy,
+}
+
+/// Lowercased words of `text`; Chinese, which has no spaces, is split into
+/// overlapping pairs of characters
+pub fn token_set(text: &str) -> HashSet<String> {
+    let mut tokens = HashSet::new();
+    let mut run: Vec<char> = Vec::new();
+    let mut run_is_cjk = false;
+    let mut flush = |run: &mut Vec<char>, is_cjk: bool| {
+        if is_cjk && run.len() > 1 {
+            tokens.extend(run.windows(2).map(|pair| pair.iter().collect::<String>()));
+        } else if !run.is_empty() {
+            tokens.insert(run.iter().collect());
+        }
+        run.clear();
+    };
+
+    for c in text.chars().flat_map(char::to_lowercase) {
+        let cjk = is_cjk(c);
+        if !cjk && !c.is_alphanumeric() {
+            flush(&mut run, run_is_cjk);
+            continue;
+        }
+        if cjk != run_is_cjk {
+            flush(&mut run, run_is_cjk);
+            run_is_cjk = cjk;
+        }
+        run.push(c);
+    }
+    flush(&mut run, run_is_cjk);
+    tokens
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32, 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF | 0x20000..=0x2FA1F)
+}
+
+/// Share of the two sets' words that they have in common (Jaccard index)
+pub fn token_set_similarity(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 0.0;
+    }
+    let (smaller, larger) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    let shared = smaller.iter().filter(|token| larger.contains(*token)).count();
+    shared as f64 / (a.len() + b.len() - shared) as f64
+}
+
+/// Groups of candidates at least `threshold` similar, most similar first
+pub fn find_groups(candidates: &[Candidate], threshold: f64) -> Vec<Group> {
+    let mut parent: Vec<usize> = (0..candidates.len()).collect();
+    let mut links = Vec::new();
+    for i in 0..candidates.len() {
+        for j in i + 1..candidates.len() {
+            if let Some((similarity, matched_by)) = compare(&candidates[i], &candidates[j], threshold) {
+                let (root_i, root_j) = (find(&mut parent, i), find(&mut parent, j));
+                parent[root_j.max(root_i)] = root_i.min(root_j);
+                links.push((i, similarity, matched_by));
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Group> = HashMap::new();
+    for (i, similarity, matched_by) in links {
+        let root = find(&mut parent, i);
+        let group = groups.entry(root).or_insert(Group { session_ids: Vec::new(), similarity, matched_by });
+        if similarity > group.similarity {
+            group.similarity = similarity;
+            group.matched_by = matched_by;
+        }
+    }
+    for (index, candidate) in candidates.iter().enumerate() {
+        let root = find(&mut parent, index);
+        if let Some(group) = groups.get_mut(&root) {
+            group.session_ids.push(candidate.session_id);
+        }
+    }
+
+    let mut groups: Vec<_> = groups.into_values().collect();
+    groups.sort_by(|a, b| {
+        b.similarity
+            .total_cmp(&a.similarity)
+            .then_with(|| b.session_ids.len().cmp(&a.session_ids.len()))
+            .then_with(|| a.session_ids.cmp(&b.session_ids))
+    });
+    groups
+}
+
+/// Similarity of two candidates if it reaches `threshold`
+fn compare(a: &Candidate, b: &Candidate, threshold: f64) -> Option<(f64, MatchedBy)> {
+    let (similarity, matched_by) = match (&a.tokens, &b.tokens) {
+        (Some(tokens_a), Some(tokens_b)) => match (&a.embedding, &b.embedding) {
+            (Some(embedding_a), Some(embedding_b)) => {
+                (cosine_similarity(embedding_a, embedding_b) as f64, MatchedBy::Embedding)
+            }
+            _ => {
+                // The index cannot exceed the ratio of the set sizes
+                let (smaller, larger) = (tokens_a.len().min(tokens_b.len()), tokens_a.len().max(tokens_b.len()));
+                if larger == 0 || (smaller as f64) < threshold * larger as f64 {
+                    return None;
+                }
+                (token_set_similarity(tokens_a, tokens_b), MatchedBy::Transcript)
+            }
+        },
+        _ if a.checksums.iter().any(|checksum| b.checksums.contains(checksum)) => (1.0, MatchedBy::AudioChecksum),
+        _ => return None,
+    };
+    (similarity >= threshold).then_some((similarity, matched_by))
+}
+
+fn find(parent: &mut [usize], index: usize) -> usize {
+    let mut root = index;
+    while parent[root] != root {
+        root = parent[root];
+    }
+    parent[index] = root;
+    root
+}
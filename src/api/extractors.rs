@@ -0,0 +1,119 @@
+// src/api/extractors.rs
+//! Request extractors reporting failures as [`ApiError`]s
+//!
+//! [`Json`] replaces `axum::Json` in the routes: a body that cannot be
+//! deserialized, including one with a field rejected by
+//! `#[serde(deny_unknown_fields)]`, becomes a 400 naming the problem.
+//!
+//! List queries flatten the shared pagination, search and sort parameters,
+//! which serde cannot combine with `deny_unknown_fields`. [`StrictQuery`]
+//! checks the query keys against [`ListQueryKeys`] before deserializing.
+
+use axum::{
+    async_trait,
+    extract::{
+        rejection::{JsonRejection, QueryRejection},
+        FromRequest, FromRequestParts, Query, Request,
+    },
+    http::request::Parts,
+    response::{IntoResponse, Response},
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::api::error::ApiError;
+use crate::api::responses::{PaginationParams, SearchParams, SortParams};
+
+/// JSON body extractor and response, rejecting with [`ApiError::BadRequest`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Json<T>(pub T);
+
+#[async_trait]
+impl<S, T> FromRequest<S> for Json<T>
+where
+    axum::Json<T>: FromRequest<S, Rejection = JsonRejection>,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        match axum::Json::<T>::from_request(request, state).await {
+            Ok(axum::Json(value)) => Ok(Json(value)),
+            Err(rejection) => Err(ApiError::BadRequest(rejection.body_text())),
+        }
+    }
+}
+
+impl<T: Serialize> IntoResponse for Json<T> {
+    fn into_response(self) -> Response {
+        axum::Json(self.0).into_response()
+    }
+}
+
+/// Query keys a list query accepts besides the shared pagination, search and
+/// sort parameters
+pub trait ListQueryKeys {
+    const KEYS: &'static [&'static str];
+
+    /// Prefixes of further keys, such as `meta.` for `meta.project`
+    const PREFIXES: &'static [&'static str] = &[];
+}
+
+/// Query extractor rejecting keys the target list query does not know
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StrictQuery<T>(pub T);
+
+#[async_trait]
+impl<S, T> FromRequestParts<S> for StrictQuery<T>
+where
+    T: DeserializeOwned + ListQueryKeys,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Query(pairs) =
+            Query::<Vec<(String, String)>>::try_from_uri(&parts.uri).map_err(query_error)?;
+
+        let mut unknown: Vec<&str> = Vec::new();
+        for (key, _) in &pairs {
+            if !is_known_key::<T>(key) && !unknown.contains(&key.as_str()) {
+                unknown.push(key);
+            }
+        }
+        if !unknown.is_empty() {
+            return Err(ApiError::BadRequest(format!(
+                "Unknown query parameter(s): {}. Allowed parameters: {}",
+                unknown.join(", "),
+                allowed_keys::<T>().join(", ")
+            )));
+        }
+
+        let Query(value) = Query::<T>::try_from_uri(&parts.uri).map_err(query_error)?;
+        Ok(StrictQuery(value))
+    }
+}
+
+fn is_known_key<T: ListQueryKeys>(key: &str) -> bool {
+    PaginationParams::QUERY_KEYS.contains(&key)
+        || SearchParams::QUERY_KEYS.contains(&key)
+        || SortParams::QUERY_KEYS.contains(&key)
+        || T::KEYS.contains(&key)
+        || T::PREFIXES
+            .iter()
+            .any(|prefix| key.strip_prefix(prefix).is_some_and(|rest| !rest.is_empty()))
+}
+
+fn allowed_keys<T: ListQueryKeys>() -> Vec<String> {
+    PaginationParams::QUERY_KEYS
+        .iter()
+        .chain(SearchParams::QUERY_KEYS)
+        .chain(SortParams::QUERY_KEYS)
+        .chain(T::KEYS)
+        .map(|key| key.to_string())
+        .chain(T::PREFIXES.iter().map(|prefix| format!("{}<key>", prefix)))
+        .collect()
+}
+
+fn query_error(rejection: QueryRejection) -> ApiError {
+    ApiError::BadRequest(rejection.body_text())
+}
@@ -0,0 +1,117 @@
+// src/api/highlight.rs
+//! Match locations and context snippets for search results
+//!
+//! Offsets are counted in characters rather than bytes so clients can index
+//! into transcripts containing multi-byte text (e.g. Chinese) directly, and so
+//! snippet extraction never slices through a UTF-8 sequence.
+
+use serde::Serialize;
+
+/// Maximum number of matches reported per document
+const MAX_MATCHES: usize = 5;
+
+/// Number of surrounding words included on each side of a match
+const CONTEXT_WORDS: usize = 5;
+
+/// Upper bound on context characters per side, for text without word breaks
+const MAX_CONTEXT_CHARS: usize = 40;
+
+/// A single occurrence of the search term within a document
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchMatch {
+    /// Character offset of the match within the full text
+    pub offset: usize,
+    /// Length of the match in characters
+    pub length: usize,
+    /// The match with a few words of surrounding context
+    pub snippet: String,
+    /// Character offset of the match within `snippet`
+    pub snippet_offset: usize,
+}
+
+/// Find case-insensitive occurrences of `term` in `text`
+///
+/// Returns at most [`MAX_MATCHES`] non-overlapping matches, in order.
+pub fn find_matches(text: &str, term: &str) -> Vec<SearchMatch> {
+    let needle: Vec<char> = term.trim().chars().map(fold_case).collect();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+
+    let chars: Vec<char> = text.chars().collect();
+    let folded: Vec<char> = chars.iter().copied().map(fold_case).collect();
+
+    let mut matches = Vec::new();
+    let mut position = 0;
+    while position + needle.len() <= folded.len() && matches.len() < MAX_MATCHES {
+        if folded[position..position + needle.len()] == needle[..] {
+            matches.push(build_match(&chars, position, needle.len()));
+            position += needle.len();
+        } else {
+            position += 1;
+        }
+    }
+
+    matches
+}
+
+/// Lowercase a single character without changing the character count
+fn fold_case(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+fn build_match(chars: &[char], offset: usize, length: usize) -> SearchMatch {
+    let start = context_start(chars, offset);
+    let end = context_end(chars, offset + length);
+
+    let mut snippet: String = chars[start..end].iter().collect();
+    let mut snippet_offset = offset - start;
+    if start > 0 {
+        snippet.insert(0, '…');
+        snippet_offset += 1;
+    }
+    if end < chars.len() {
+        snippet.push('…');
+    }
+
+    SearchMatch {
+        offset,
+        length,
+        snippet,
+        snippet_offset,
+    }
+}
+
+/// Walk back from `from` over up to [`CONTEXT_WORDS`] words
+fn context_start(chars: &[char], from: usize) -> usize {
+    let limit = from.saturating_sub(MAX_CONTEXT_CHARS);
+    let mut words = 0;
+    let mut index = from;
+    while index > limit {
+        if chars[index - 1].is_whitespace() && index < from && !chars[index].is_whitespace() {
+            words += 1;
+            if words == CONTEXT_WORDS {
+                break;
+            }
+        }
+        index -= 1;
+    }
+    index
+}
+
+/// Walk forward from `from` over up to [`CONTEXT_WORDS`] words
+fn context_end(chars: &[char], from: usize) -> usize {
+    let limit = (from + MAX_CONTEXT_CHARS).min(chars.len());
+    let mut words = 0;
+    let mut index = from;
+    while index < limit {
+        if chars[index].is_whitespace() && index > from && !chars[index - 1].is_whitespace() {
+            words += 1;
+            if words == CONTEXT_WORDS {
+                break;
+            }
+        }
+        index += 1;
+    }
+    index
+}
@@ -0,0 +1,111 @@
+// src/api/ical.rs
+//! Minimal iCalendar (RFC 5545) writer for exporting tasks
+//!
+//! Tasks with a due date become `VEVENT`s at the deadline so they show up in
+//! calendar views; tasks without one become `VTODO`s. Text values are escaped
+//! and content lines are folded at 75 octets without splitting UTF-8
+//! sequences.
+
+use chrono::{DateTime, Utc};
+
+/// Maximum length of a content line in octets, excluding the CRLF
+const MAX_LINE_OCTETS: usize = 75;
+
+/// A task to be written as a calendar component
+#[derive(Debug, Clone)]
+pub struct CalendarTask<'a> {
+    pub uid: String,
+    pub summary: &'a str,
+    pub description: Option<&'a str>,
+    pub due: Option<DateTime<Utc>>,
+    pub created: DateTime<Utc>,
+    pub priority: &'a str,
+    pub completed: bool,
+}
+
+/// Render `tasks` as a complete `VCALENDAR` document
+pub fn tasks_to_ics(tasks: &[CalendarTask<'_>]) -> String {
+    let stamp = format_datetime(Utc::now());
+    let mut out = String::new();
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, "PRODID:-//voice-recorder//Tasks//EN");
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+
+    for task in tasks {
+        let component = if task.due.is_some() { "VEVENT" } else { "VTODO" };
+        push_line(&mut out, &format!("BEGIN:{}", component));
+        push_line(&mut out, &format!("UID:{}", escape_text(&task.uid)));
+        push_line(&mut out, &format!("DTSTAMP:{}", stamp));
+        push_line(&mut out, &format!("CREATED:{}", format_datetime(task.created)));
+        if let Some(due) = task.due {
+            push_line(&mut out, &format!("DTSTART:{}", format_datetime(due)));
+        }
+        push_line(&mut out, &format!("SUMMARY:{}", escape_text(task.summary)));
+        if let Some(description) = task.description.filter(|d| !d.trim().is_empty()) {
+            push_line(&mut out, &format!("DESCRIPTION:{}", escape_text(description)));
+        }
+        push_line(&mut out, &format!("PRIORITY:{}", ical_priority(task.priority)));
+        if task.due.is_none() {
+            let status = if task.completed { "COMPLETED" } else { "NEEDS-ACTION" };
+            push_line(&mut out, &format!("STATUS:{}", status));
+        }
+        push_line(&mut out, &format!("END:{}", component));
+    }
+
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+/// Map a task priority name to the iCalendar 1 (highest) to 9 (lowest) scale
+fn ical_priority(priority: &str) -> u8 {
+    match priority.to_lowercase().as_str() {
+        "critical" | "urgent" => 1,
+        "high" => 3,
+        "medium" => 5,
+        "low" => 9,
+        _ => 0,
+    }
+}
+
+fn format_datetime(value: DateTime<Utc>) -> String {
+    value.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escape a TEXT value: backslashes, separators and line breaks
+fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\r' => {
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                escaped.push_str("\\n");
+            }
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Append `line` folded to [`MAX_LINE_OCTETS`], terminated by CRLF
+///
+/// Continuation lines start with a space, which counts towards their length.
+fn push_line(out: &mut String, line: &str) {
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            octets = 1;
+        }
+        out.push(c);
+        octets += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
@@ -0,0 +1,281 @@
+// src/api/idempotency.rs
+//! Idempotency key support for non-idempotent endpoints
+//!
+//! Clients may send an `Idempotency-Key` header with batch create requests.
+//! The first request for a key is processed normally and its response body is
+//! remembered; retries with the same key receive the stored response instead of
+//! creating the records a second time. Keys expire after a configurable TTL.
+//!
+//! A key is bound to a hash of the request body it was first used with;
+//! reusing it with a different body is rejected instead of replaying a response
+//! to another request. A claim on a key is released when the request ends
+//! without completing, e.g. on an error or a dropped connection, so the client
+//! can retry right away.
+
+use axum::{
+    async_trait,
+    extract::{FromRequest, Request},
+    http::{HeaderMap, HeaderValue},
+    response::{IntoResponse, Json, Response},
+};
+use serde::{de::DeserializeOwned, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::api::{error::ApiError, extractors, ApiResult};
+
+/// Request header carrying the client-supplied idempotency key
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Response header set when a stored response is replayed
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// Maximum accepted length of an idempotency key
+const MAX_KEY_LENGTH: usize = 255;
+
+/// State of a key within the store, with the hash of the request body it was
+/// first used with
+enum Entry {
+    /// A request with this key is currently being processed
+    InFlight { started_at: Instant, fingerprint: String },
+    /// A request with this key has completed with the given response body
+    Completed {
+        stored_at: Instant,
+        fingerprint: String,
+        body: serde_json::Value,
+    },
+}
+
+impl Entry {
+    fn fingerprint(&self) -> &str {
+        match self {
+            Entry::InFlight { fingerprint, .. } | Entry::Completed { fingerprint, .. } => fingerprint,
+        }
+    }
+}
+
+/// Outcome of claiming an idempotency key
+pub enum IdempotencyStatus<'a> {
+    /// The key has not been seen; the caller should process the request and
+    /// complete the claim with its response
+    New(IdempotencyClaim<'a>),
+    /// The key is still being processed by an earlier request
+    InFlight,
+    /// The key has already been processed; replay the stored body
+    Replay(serde_json::Value),
+    /// The key was used before with a different request body
+    Mismatch,
+}
+
+/// Claim on a new idempotency key, released on drop unless completed
+pub struct IdempotencyClaim<'a> {
+    store: &'a IdempotencyStore,
+    scoped_key: String,
+    fingerprint: String,
+    completed: bool,
+}
+
+impl IdempotencyClaim<'_> {
+    /// Record the response of the claimed request for replay
+    ///
+    /// If the response cannot be serialized the claim is released so the
+    /// client can retry.
+    pub fn complete<T: Serialize>(mut self, response: &T) {
+        match serde_json::to_value(response) {
+            Ok(body) => {
+                self.store.entries.lock().unwrap().insert(
+                    self.scoped_key.clone(),
+                    Entry::Completed {
+                        stored_at: Instant::now(),
+                        fingerprint: std::mem::take(&mut self.fingerprint),
+                        body,
+                    },
+                );
+                self.completed = true;
+            }
+            Err(e) => {
+                tracing::warn!("Failed to store idempotent response for key {}: {}", self.scoped_key, e);
+            }
+        }
+    }
+}
+
+impl Drop for IdempotencyClaim<'_> {
+    fn drop(&mut self) {
+        if !self.completed {
+            self.store.entries.lock().unwrap().remove(&self.scoped_key);
+        }
+    }
+}
+
+/// In-memory store of processed idempotency keys with TTL-based expiry
+pub struct IdempotencyStore {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl IdempotencyStore {
+    /// Create a new store whose keys expire after `ttl`
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Claim `key` within `scope` for a request whose body hashes to
+    /// `fingerprint`, returning how the caller should proceed
+    pub fn begin(&self, scope: &str, key: &str, fingerprint: &str) -> IdempotencyStatus<'_> {
+        let mut entries = self.entries.lock().unwrap();
+        self.purge_expired(&mut entries);
+
+        let scoped_key = Self::scoped_key(scope, key);
+        match entries.get(&scoped_key) {
+            Some(entry) if entry.fingerprint() != fingerprint => IdempotencyStatus::Mismatch,
+            Some(Entry::Completed { body, .. }) => IdempotencyStatus::Replay(body.clone()),
+            Some(Entry::InFlight { .. }) => IdempotencyStatus::InFlight,
+            None => {
+                entries.insert(
+                    scoped_key.clone(),
+                    Entry::InFlight {
+                        started_at: Instant::now(),
+                        fingerprint: fingerprint.to_string(),
+                    },
+                );
+                IdempotencyStatus::New(IdempotencyClaim {
+                    store: self,
+                    scoped_key,
+                    fingerprint: fingerprint.to_string(),
+                    completed: false,
+                })
+            }
+        }
+    }
+
+    fn purge_expired(&self, entries: &mut HashMap<String, Entry>) {
+        let ttl = self.ttl;
+        entries.retain(|_, entry| match entry {
+            Entry::InFlight { started_at, .. } => started_at.elapsed() < ttl,
+            Entry::Completed { stored_at, .. } => stored_at.elapsed() < ttl,
+        });
+    }
+
+    fn scoped_key(scope: &str, key: &str) -> String {
+        format!("{}:{}", scope, key)
+    }
+}
+
+/// Extract and validate the `Idempotency-Key` header, if present
+pub fn idempotency_key(headers: &HeaderMap) -> ApiResult<Option<String>> {
+    let Some(value) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+
+    let key = value
+        .to_str()
+        .map_err(|_| ApiError::BadRequest("Idempotency-Key must be valid ASCII".to_string()))?
+        .trim();
+
+    if key.is_empty() || key.len() > MAX_KEY_LENGTH {
+        return Err(ApiError::BadRequest(format!(
+            "Idempotency-Key must be between 1 and {} characters",
+            MAX_KEY_LENGTH
+        )));
+    }
+
+    Ok(Some(key.to_string()))
+}
+
+/// JSON body extractor that also hashes the body, for binding an
+/// idempotency key to the request it was used with
+///
+/// The hash is taken over the parsed JSON, so formatting differences between
+/// retries do not count as a different payload.
+pub struct FingerprintedJson<T> {
+    pub body: T,
+    pub fingerprint: String,
+}
+
+#[async_trait]
+impl<S, T> FromRequest<S> for FingerprintedJson<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let extractors::Json(value) = extractors::Json::<serde_json::Value>::from_request(request, state).await?;
+        let fingerprint = format!("{:x}", Sha256::digest(value.to_string().as_bytes()));
+        let body = serde_json::from_value(value).map_err(|e| {
+            ApiError::BadRequest(format!("Failed to deserialize the JSON body into the target type: {}", e))
+        })?;
+        Ok(Self { body, fingerprint })
+    }
+}
+
+/// Error returned when a key is reused with a different request body
+pub fn mismatch_error() -> ApiError {
+    ApiError::UnprocessableEntity(
+        "This Idempotency-Key was already used with a different request body".to_string(),
+    )
+}
+
+/// Build the response returned for a replayed request
+pub fn replay_response(body: serde_json::Value) -> Response {
+    let mut response = Json(body).into_response();
+    response
+        .headers_mut()
+        .insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store() -> IdempotencyStore {
+        IdempotencyStore::new(Duration::from_secs(60))
+    }
+
+    #[test]
+    fn completed_key_is_replayed() {
+        let store = store();
+        let IdempotencyStatus::New(claim) = store.begin("scope", "key", "a") else {
+            panic!("first use of a key should be new");
+        };
+        claim.complete(&serde_json::json!({ "created": 1 }));
+
+        match store.begin("scope", "key", "a") {
+            IdempotencyStatus::Replay(body) => assert_eq!(body["created"], 1),
+            _ => panic!("completed key should be replayed"),
+        };
+    }
+
+    #[test]
+    fn dropped_claim_releases_key() {
+        let store = store();
+        let IdempotencyStatus::New(claim) = store.begin("scope", "key", "a") else {
+            panic!("first use of a key should be new");
+        };
+        assert!(matches!(store.begin("scope", "key", "a"), IdempotencyStatus::InFlight));
+
+        drop(claim);
+        assert!(matches!(store.begin("scope", "key", "a"), IdempotencyStatus::New(_)));
+    }
+
+    #[test]
+    fn key_reused_with_other_body_is_rejected() {
+        let store = store();
+        let IdempotencyStatus::New(claim) = store.begin("scope", "key", "a") else {
+            panic!("first use of a key should be new");
+        };
+        assert!(matches!(store.begin("scope", "key", "b"), IdempotencyStatus::Mismatch));
+
+        claim.complete(&serde_json::json!({}));
+        assert!(matches!(store.begin("scope", "key", "b"), IdempotencyStatus::Mismatch));
+        assert!(matches!(store.begin("other", "key", "b"), IdempotencyStatus::New(_)));
+    }
+}
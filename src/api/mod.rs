@@ -9,36 +9,70 @@ pub mod routes;
 pub mod middleware;
 pub mod responses;
 pub mod extractors;
-
+pub mod idempotency;
+pub mod highlight;
+pub mod ical;
+pub mod outline;
+pub mod schema;
+pub mod note_generation;
+pub mod combined_export;
+pub mod docx;
+pub mod audio_concat;
+pub mod duplicates;
+pub mod semantic;
+pub mod auth;
+
+use axum::extract::DefaultBodyLimit;
 use axum::Router;
 use std::sync::Arc;
+use tower::layer::util::{Identity, Stack};
 use tower::ServiceBuilder;
 use tower_http::cors::CorsLayer;
+use tower_http::limit::RequestBodyLimitLayer;
 use tower_http::trace::TraceLayer;
 use tower_http::timeout::TimeoutLayer;
 use std::time::Duration;
 
 use crate::services::ServiceManager;
 use crate::repository::RepositoryManager;
-use crate::config::Config;
+pub use crate::repository::relations::{index_by_id, page_relations, unique_ids};
+use crate::config::SharedConfig;
+use idempotency::IdempotencyStore;
 
 /// API application state
 #[derive(Clone)]
 pub struct AppState<R: RepositoryManager> {
     pub services: Arc<ServiceManager<R>>,
-    pub config: Arc<Config>,
+    pub config: SharedConfig,
+    pub idempotency: Arc<IdempotencyStore>,
 }
 
 impl<R: RepositoryManager> AppState<R> {
-    pub fn new(services: Arc<ServiceManager<R>>, config: Arc<Config>) -> Self {
-        Self { services, config }
+    pub fn new(services: Arc<ServiceManager<R>>, config: SharedConfig) -> Self {
+        let idempotency = Arc::new(IdempotencyStore::new(Duration::from_secs(
+            config.current().server.idempotency_ttl_secs,
+        )));
+        Self { services, config, idempotency }
     }
 }
 
+/// Reload the configuration on SIGHUP, pushing the reloaded values into the
+/// long-lived services of `state`
+///
+/// Call this once when serving the API; handlers read `state.config` per
+/// request and see the new configuration right away.
+pub fn spawn_config_reload<R: RepositoryManager + 'static>(state: &AppState<R>) -> anyhow::Result<()> {
+    let services = state.services.clone();
+    crate::config::reload::spawn_sighup_handler(state.config.clone(), move |config| {
+        services.apply_config(config)
+    })
+}
+
 /// Create the main API router with all routes and middleware
 pub fn create_router<R: RepositoryManager + 'static>(state: AppState<R>) -> Router {
+    let config = state.config.current();
     let cors = CorsLayer::new()
-        .allow_origin(state.config.server.cors_origins.iter().map(|origin| {
+        .allow_origin(config.server.cors_origins.iter().map(|origin| {
             origin.parse().expect("Invalid CORS origin")
         }).collect::<Vec<_>>())
         .allow_methods([
@@ -57,18 +91,31 @@ pub fn create_router<R: RepositoryManager + 'static>(state: AppState<R>) -> Rout
         .layer(TraceLayer::new_for_http())
         .layer(cors)
         .layer(TimeoutLayer::new(Duration::from_secs(
-            state.config.server.request_timeout_secs,
+            config.server.request_timeout_secs,
         )))
         .layer(middleware::request_id::RequestIdLayer::new())
         .layer(middleware::logging::LoggingLayer::new());
 
     Router::new()
-        .nest("/api/v1", routes::v1::create_routes())
+        .nest("/api/v1", routes::v1::create_routes(&config))
         .nest("/health", routes::health::create_routes())
         .layer(middleware)
         .with_state(state)
 }
 
+/// Layer limiting request bodies to `max` bytes, answering `413 Payload Too Large`
+///
+/// A `Content-Length` above the limit is rejected before any of the body is
+/// read, and bodies without one are cut off as soon as they exceed it, so an
+/// oversized body is never buffered in full. It replaces axum's fixed 2 MB
+/// default for the body extractors. Applied per route group, since audio
+/// uploads get a larger limit than the other endpoints.
+pub fn body_limit(max: usize) -> ServiceBuilder<Stack<RequestBodyLimitLayer, Stack<DefaultBodyLimit, Identity>>> {
+    ServiceBuilder::new()
+        .layer(DefaultBodyLimit::disable())
+        .layer(RequestBodyLimitLayer::new(max))
+}
+
 /// API error types
 pub mod error {
     use axum::{
@@ -175,44 +222,6 @@ pub mod error {
 /// API result type
 pub type ApiResult<T> = Result<T, error::ApiError>;
 
-/// Common API response wrapper
-#[derive(serde::Serialize)]
-pub struct ApiResponse<T> {
-    pub success: bool,
-    pub data: Option<T>,
-    pub message: Option<String>,
-    pub timestamp: chrono::DateTime<chrono::Utc>,
-}
-
-impl<T> ApiResponse<T> {
-    pub fn success(data: T) -> Self {
-        Self {
-            success: true,
-            data: Some(data),
-            message: None,
-            timestamp: chrono::Utc::now(),
-        }
-    }
-
-    pub fn success_with_message(data: T, message: String) -> Self {
-        Self {
-            success: true,
-            data: Some(data),
-            message: Some(message),
-            timestamp: chrono::Utc::now(),
-        }
-    }
-
-    pub fn error(message: String) -> ApiResponse<()> {
-        ApiResponse {
-            success: false,
-            data: None,
-            message: Some(message),
-            timestamp: chrono::Utc::now(),
-        }
-    }
-}
-
 /// Pagination parameters
 #[derive(serde::Deserialize, Debug)]
 pub struct PaginationParams {
@@ -231,6 +240,8 @@ fn default_page_size() -> i64 {
 }
 
 impl PaginationParams {
+    pub const QUERY_KEYS: &'static [&'static str] = &["page", "page_size"];
+
     pub fn offset(&self) -> i64 {
         (self.page - 1) * self.page_size
     }
@@ -262,6 +273,10 @@ pub struct SearchParams {
     pub pagination: PaginationParams,
 }
 
+impl SearchParams {
+    pub const QUERY_KEYS: &'static [&'static str] = &["q"];
+}
+
 /// Sort parameters
 #[derive(serde::Deserialize, Debug)]
 pub struct SortParams {
@@ -270,6 +285,8 @@ pub struct SortParams {
 }
 
 impl SortParams {
+    pub const QUERY_KEYS: &'static [&'static str] = &["sort_by", "sort_order"];
+
     pub fn validate_sort_by(&self, allowed_fields: &[&str]) -> ApiResult<()> {
         if let Some(sort_by) = &self.sort_by {
             if !allowed_fields.contains(&sort_by.as_str()) {
@@ -294,4 +311,114 @@ impl SortParams {
             Ok(())
         }
     }
-}
\ No newline at end of file
+}
+
+/// Check that an enum-like `field` holds one of the `allowed` values
+pub fn validate_choice(field: &str, value: Option<&str>, allowed: &[&str]) -> ApiResult<()> {
+    match value {
+        Some(value) if !allowed.contains(&value) => Err(error::ApiError::UnprocessableEntity(format!(
+            "Invalid {} '{}'. Allowed values: {}",
+            field,
+            value,
+            allowed.join(", ")
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Check that a `min_confidence` filter lies between 0 and 1
+pub fn validate_min_confidence(min_confidence: Option<f64>) -> ApiResult<()> {
+    match min_confidence {
+        Some(min_confidence) if !(0.0..=1.0).contains(&min_confidence) => Err(error::ApiError::UnprocessableEntity(
+            "min_confidence must be between 0 and 1".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Check the Ollama generation `options` given with an analysis request
+pub fn validate_options(options: Option<&crate::ollama::ModelOptions>) -> ApiResult<()> {
+    match options.map(crate::ollama::ModelOptions::validate) {
+        Some(Err(e)) => Err(error::ApiError::UnprocessableEntity(format!("Invalid options: {}", e))),
+        _ => Ok(()),
+    }
+}
+
+/// Query parameters of the `DELETE /:id` endpoints of soft-deleted resources
+#[derive(serde::Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct DeleteQuery {
+    /// Delete permanently instead of soft-deleting
+    #[serde(default)]
+    pub purge: bool,
+}
+
+/// Days covered by the daily series of the stats endpoints by default
+const DEFAULT_STATS_DAYS: i64 = 30;
+const MAX_STATS_DAYS: i64 = 365;
+
+/// Query parameters of the `/stats` endpoints
+#[derive(serde::Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct StatsQuery {
+    /// IANA time zone name for the daily buckets, UTC by default
+    pub tz: Option<String>,
+    /// Number of days in the daily series, including today
+    pub days: Option<i64>,
+}
+
+impl StatsQuery {
+    pub fn window(&self) -> ApiResult<crate::repository::traits::StatsWindow> {
+        let days = self.days.unwrap_or(DEFAULT_STATS_DAYS);
+        if !(1..=MAX_STATS_DAYS).contains(&days) {
+            return Err(error::ApiError::BadRequest(format!(
+                "Days must be between 1 and {}",
+                MAX_STATS_DAYS
+            )));
+        }
+
+        let time_zone = self.tz.as_deref().map(str::trim).unwrap_or("UTC");
+        if !is_time_zone_name(time_zone) {
+            return Err(error::ApiError::BadRequest(format!(
+                "Invalid time zone '{}'; expected an IANA name such as 'Asia/Shanghai'",
+                time_zone
+            )));
+        }
+
+        Ok(crate::repository::traits::StatsWindow {
+            time_zone: time_zone.to_string(),
+            since: chrono::Utc::now() - chrono::Duration::days(days - 1),
+        })
+    }
+}
+
+/// Whether `name` is shaped like an IANA time zone name
+///
+/// Whether the zone exists is checked by the database, which owns the zone
+/// data the buckets are computed with.
+fn is_time_zone_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && !name.starts_with('/')
+        && !name.contains("..")
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '_' | '-' | '+'))
+}
+
+/// Map a stats query failure, reporting an unknown time zone as a bad request
+pub fn stats_error(err: anyhow::Error, what: &str) -> error::ApiError {
+    match err.downcast_ref::<crate::repository::traits::UnknownTimeZone>() {
+        Some(unknown) => error::ApiError::BadRequest(unknown.to_string()),
+        None => error::ApiError::InternalServerError(format!("Failed to get {} stats: {}", what, err)),
+    }
+}
+
+/// `part / total`, or 0 when there is nothing to divide
+pub fn rate(part: i64, total: i64) -> f64 {
+    if total > 0 {
+        part as f64 / total as f64
+    } else {
+        0.0
+    }
+}
@@ -0,0 +1,189 @@
+// src/api/note_generation.rs
+//! Filling note templates from analysis results
+//!
+//! Every top-level property of a template's schema is a section. A section is
+//! filled from the analysis field of the same name or, failing that, from the
+//! field it usually corresponds to (`action_items` from `tasks`, `key_findings`
+//! from `ideas`, ...). Values are reshaped to the section's schema: lists
+//! become text where the schema wants a string, and fields of list items are
+//! matched by name and alias. A section whose generated value still does not
+//! match its schema keeps the template's default content, so the result
+//! always conforms to the template.
+
+use serde_json::{json, Map, Value};
+
+use crate::api::schema;
+
+/// Analysis fields used, in order, when a field of the same name is missing
+const FIELD_ALIASES: &[(&str, &[&str])] = &[
+    // Sections
+    ("meeting_summary", &["summary"]),
+    ("overview", &["summary"]),
+    ("conclusions", &["summary"]),
+    ("research_topic", &["title"]),
+    ("key_points", &["ideas"]),
+    ("key_findings", &["ideas"]),
+    ("discussion_points", &["ideas"]),
+    ("action_items", &["tasks"]),
+    ("next_steps", &["tasks"]),
+    // Fields of list items
+    ("task", &["title", "content"]),
+    ("topic", &["category", "title"]),
+    ("discussion", &["content", "description"]),
+    ("finding", &["content", "title"]),
+    ("evidence", &["description"]),
+    ("significance", &["category"]),
+];
+
+/// Object fields used, in order, when an object has to become text
+const LABEL_FIELDS: &[&str] = &["title", "content", "name", "text"];
+
+/// Schema of notes generated without a template
+pub fn generic_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "summary": { "type": "string" },
+            "key_points": { "type": "array", "items": { "type": "string" } },
+            "action_items": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "task": { "type": "string" },
+                        "description": { "type": "string" },
+                        "priority": { "type": "string" },
+                        "due_date": { "type": "string" }
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Requested sections that are not sections of `schema`
+pub fn unknown_sections<'a>(schema: &Value, requested: &'a [String]) -> Vec<&'a str> {
+    let properties = schema.get("properties").and_then(Value::as_object);
+    requested
+        .iter()
+        .filter(|name| !properties.map_or(false, |properties| properties.contains_key(name.as_str())))
+        .map(String::as_str)
+        .collect()
+}
+
+/// Note content for `schema` filled from an analysis's `result_data`
+///
+/// With `include_sections`, other sections are left out unless the schema
+/// requires them, in which case they keep their default content.
+pub fn fill_template(
+    analysis: &Value,
+    schema: &Value,
+    default_content: &Value,
+    include_sections: Option<&[String]>,
+) -> Value {
+    let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+        return default_content.clone();
+    };
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|required| required.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    let mut content = Map::new();
+    for (section, section_schema) in properties {
+        let included = include_sections.map_or(true, |sections| sections.iter().any(|s| s == section));
+        let generated = if included {
+            analysis
+                .as_object()
+                .and_then(|analysis| lookup(analysis, section))
+                .and_then(|value| fit(value, section_schema))
+                .filter(|value| schema::validate(value, section_schema).is_empty())
+        } else if required.contains(&section.as_str()) {
+            None
+        } else {
+            continue;
+        };
+        if let Some(value) = generated.or_else(|| default_content.get(section).cloned()) {
+            content.insert(section.clone(), value);
+        }
+    }
+    Value::Object(content)
+}
+
+/// `key` from `object`, or the first of its aliases that is present
+fn lookup<'a>(object: &'a Map<String, Value>, key: &str) -> Option<&'a Value> {
+    let aliases = FIELD_ALIASES
+        .iter()
+        .find(|(name, _)| *name == key)
+        .map_or(&[][..], |(_, aliases)| *aliases);
+    std::iter::once(key)
+        .chain(aliases.iter().copied())
+        .filter_map(|name| object.get(name))
+        .find(|value| !value.is_null())
+}
+
+/// `value` reshaped to the type `schema` expects, if it has any content
+fn fit(value: &Value, schema: &Value) -> Option<Value> {
+    if value.is_null() {
+        return None;
+    }
+    match schema_type(schema) {
+        Some("string") => as_text(value).map(Value::String),
+        Some("array") => {
+            let items = match value {
+                Value::Array(items) => items.as_slice(),
+                other => std::slice::from_ref(other),
+            };
+            let item_schema = schema.get("items").unwrap_or(&Value::Null);
+            Some(Value::Array(items.iter().filter_map(|item| fit(item, item_schema)).collect()))
+        }
+        Some("object") => {
+            // Plain strings, like the ideas of older analyses, stand for content
+            if value.is_string() {
+                return fit(&json!({ "content": value }), schema);
+            }
+            let source = value.as_object()?;
+            let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+                return Some(value.clone());
+            };
+            // Fields that do not match, such as a free-text due date, are
+            // dropped rather than invalidating the whole section
+            let object: Map<String, Value> = properties
+                .iter()
+                .filter_map(|(key, property)| {
+                    let value = fit(lookup(source, key)?, property)?;
+                    schema::validate(&value, property).is_empty().then(|| (key.clone(), value))
+                })
+                .collect();
+            (!object.is_empty()).then_some(Value::Object(object))
+        }
+        _ => Some(value.clone()),
+    }
+}
+
+/// The first type named by `schema`
+fn schema_type(schema: &Value) -> Option<&str> {
+    match schema.get("type")? {
+        Value::String(name) => Some(name),
+        Value::Array(names) => names.first().and_then(Value::as_str),
+        _ => None,
+    }
+}
+
+/// Text for a string section: lists become one `- item` line per element
+fn as_text(value: &Value) -> Option<String> {
+    let text = match value {
+        Value::String(text) => text.trim().to_string(),
+        Value::Number(_) | Value::Bool(_) => value.to_string(),
+        Value::Array(items) => items
+            .iter()
+            .filter_map(as_text)
+            .map(|item| format!("- {}", item))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Value::Object(object) => LABEL_FIELDS.iter().find_map(|field| object.get(*field).and_then(as_text))?,
+        Value::Null => return None,
+    };
+    (!text.is_empty()).then_some(text)
+}
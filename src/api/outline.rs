@@ -0,0 +1,191 @@
+// src/api/outline.rs
+//! Outline rendering of structured JSON content for note exports
+//!
+//! Object keys become outline nodes and array elements become child items.
+//! The JSON tree is walked with an explicit stack, so deeply nested content
+//! cannot overflow the call stack.
+
+use serde_json::Value;
+
+/// Object fields used, in order, as the label of an object inside an array
+const LABEL_FIELDS: &[&str] = &["title", "name", "text", "content"];
+
+/// A step of a depth-first walk over a JSON value
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutlineEvent {
+    /// A node without children, with an optional key label
+    Leaf { label: Option<String>, text: String },
+    /// Start of a node whose children follow until the matching `Close`
+    Open { label: String },
+    Close,
+}
+
+enum Frame<'a> {
+    Entry { label: Option<String>, value: &'a Value },
+    Close,
+}
+
+/// Walk `value` depth-first, yielding outline events in document order
+///
+/// A scalar at the top level yields a single unlabeled leaf.
+pub fn outline_events(value: &Value) -> Vec<OutlineEvent> {
+    let mut events = Vec::new();
+    let mut stack = Vec::new();
+    push_children(&mut stack, value);
+    if stack.is_empty() && !is_empty_container(value) {
+        stack.push(Frame::Entry { label: None, value });
+    }
+
+    while let Some(frame) = stack.pop() {
+        let (label, value) = match frame {
+            Frame::Close => {
+                events.push(OutlineEvent::Close);
+                continue;
+            }
+            Frame::Entry { label, value } => (label, value),
+        };
+
+        if let Some(text) = scalar_text(value) {
+            events.push(OutlineEvent::Leaf { label, text });
+            continue;
+        }
+
+        // Objects inside arrays are labeled by a descriptive field, which is
+        // then not repeated as a child
+        let (label, skip) = match (label, value) {
+            (Some(label), _) => (label, None),
+            (None, Value::Object(map)) => match LABEL_FIELDS
+                .iter()
+                .find_map(|field| map.get(*field).and_then(Value::as_str).map(|text| (*field, text)))
+            {
+                Some((field, text)) => (text.to_string(), Some(field)),
+                None => ("Item".to_string(), None),
+            },
+            (None, _) => ("Item".to_string(), None),
+        };
+
+        events.push(OutlineEvent::Open { label });
+        stack.push(Frame::Close);
+        match (value, skip) {
+            (Value::Object(map), Some(skip)) => {
+                for (key, child) in map.iter().rev().filter(|(key, _)| key.as_str() != skip) {
+                    stack.push(Frame::Entry { label: Some(key.clone()), value: child });
+                }
+            }
+            _ => push_children(&mut stack, value),
+        }
+    }
+
+    events
+}
+
+/// Render `value` as a nested Markdown list; a top-level scalar is returned as is
+pub fn to_markdown_list(value: &Value) -> String {
+    if let Some(text) = scalar_text(value) {
+        return text;
+    }
+
+    let mut lines = Vec::new();
+    let mut depth = 0usize;
+    for event in outline_events(value) {
+        let indent = "  ".repeat(depth);
+        match event {
+            OutlineEvent::Leaf { label: Some(label), text } => {
+                lines.push(format!("{}- **{}:** {}", indent, label, text))
+            }
+            OutlineEvent::Leaf { label: None, text } => lines.push(format!("{}- {}", indent, text)),
+            OutlineEvent::Open { label } => {
+                lines.push(format!("{}- **{}**", indent, label));
+                depth += 1;
+            }
+            OutlineEvent::Close => depth = depth.saturating_sub(1),
+        }
+    }
+    lines.join("\n")
+}
+
+/// Render `value` as an OPML 2.0 document titled `title`
+pub fn to_opml(title: &str, created: chrono::DateTime<chrono::Utc>, value: &Value) -> String {
+    let mut opml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opml version=\"2.0\">\n");
+    opml.push_str("  <head>\n");
+    opml.push_str(&format!("    <title>{}</title>\n", escape_xml(title)));
+    opml.push_str(&format!("    <dateCreated>{}</dateCreated>\n", created.to_rfc2822()));
+    opml.push_str("  </head>\n  <body>\n");
+
+    let mut depth = 2usize;
+    for event in outline_events(value) {
+        let indent = "  ".repeat(depth);
+        match event {
+            OutlineEvent::Leaf { label, text } => {
+                let text = match label {
+                    Some(label) => format!("{}: {}", label, text),
+                    None => text,
+                };
+                opml.push_str(&format!("{}<outline text=\"{}\"/>\n", indent, escape_xml(&text)));
+            }
+            OutlineEvent::Open { label } => {
+                opml.push_str(&format!("{}<outline text=\"{}\">\n", indent, escape_xml(&label)));
+                depth += 1;
+            }
+            OutlineEvent::Close => {
+                depth = depth.saturating_sub(1);
+                opml.push_str(&format!("{}</outline>\n", "  ".repeat(depth)));
+            }
+        }
+    }
+
+    opml.push_str("  </body>\n</opml>\n");
+    opml
+}
+
+/// Push the children of a container in reverse, so they pop in order
+fn push_children<'a>(stack: &mut Vec<Frame<'a>>, value: &'a Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map.iter().rev() {
+                stack.push(Frame::Entry { label: Some(key.clone()), value: child });
+            }
+        }
+        Value::Array(items) => {
+            for child in items.iter().rev() {
+                stack.push(Frame::Entry { label: None, value: child });
+            }
+        }
+        _ => {}
+    }
+}
+
+fn is_empty_container(value: &Value) -> bool {
+    match value {
+        Value::Object(map) => map.is_empty(),
+        Value::Array(items) => items.is_empty(),
+        _ => false,
+    }
+}
+
+fn scalar_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Null => Some(String::new()),
+        Value::Bool(_) | Value::Number(_) => Some(value.to_string()),
+        Value::Array(_) | Value::Object(_) => None,
+    }
+}
+
+/// Escape text for use in XML element content and attribute values
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,8 @@
+// src/api/responses.rs
+//! Response envelope and list parameters used by the route handlers
+//!
+//! The envelope is shared with the web UI routes and lives in
+//! `crate::response`.
+
+pub use super::{PaginationParams, SearchParams, SortParams};
+pub use crate::response::ApiResponse;
@@ -54,7 +54,7 @@ async fn readiness_check<R: RepositoryManager>(
     }
     
     // Check storage directory
-    let storage_accessible = tokio::fs::metadata(&state.config.storage.audio_directory)
+    let storage_accessible = tokio::fs::metadata(&state.config.current().storage.audio_directory)
         .await
         .is_ok();
     checks.insert("storage", if storage_accessible { "accessible" } else { "inaccessible" });
@@ -102,6 +102,7 @@ async fn liveness_check() -> ApiResult<Json<Value>> {
 async fn detailed_health_check<R: RepositoryManager>(
     State(state): State<AppState<R>>,
 ) -> ApiResult<Json<Value>> {
+    let config = state.config.current();
     let mut checks = HashMap::new();
     let mut overall_status = "healthy";
     
@@ -115,9 +116,9 @@ async fn detailed_health_check<R: RepositoryManager>(
     
     checks.insert("ollama", json!({
         "status": if ollama_available { "available" } else { "unavailable" },
-        "base_url": state.config.ollama.base_url,
+        "base_url": config.ollama.base_url,
         "models_count": ollama_models.len(),
-        "default_model": state.config.ollama.default_model
+        "default_model": config.ollama.default_model
     }));
     
     if !ollama_available {
@@ -125,13 +126,13 @@ async fn detailed_health_check<R: RepositoryManager>(
     }
     
     // Check storage
-    let storage_info = get_storage_info(&state.config.storage.audio_directory).await;
+    let storage_info = get_storage_info(&config.storage.audio_directory).await;
     checks.insert("storage", storage_info);
     
     // Check OpenAI configuration
     checks.insert("openai", json!({
-        "configured": state.config.is_openai_configured(),
-        "model": state.config.openai.analysis_model
+        "configured": config.is_openai_configured(),
+        "model": config.openai.analysis_model
     }));
     
     // System information
@@ -145,11 +146,11 @@ async fn detailed_health_check<R: RepositoryManager>(
     
     // Configuration summary
     checks.insert("configuration", json!({
-        "server_address": state.config.server_address(),
-        "default_provider": state.config.analysis.default_provider,
-        "auto_analyze": state.config.analysis.auto_analyze,
-        "max_file_size_mb": state.config.storage.max_file_size / (1024 * 1024),
-        "allowed_formats": state.config.storage.allowed_formats
+        "server_address": config.server_address(),
+        "default_provider": config.analysis.default_provider,
+        "auto_analyze": config.analysis.auto_analyze,
+        "max_file_size_mb": config.storage.max_file_size / (1024 * 1024),
+        "allowed_formats": config.storage.allowed_formats
     }));
     
     Ok(Json(json!({
@@ -5,33 +5,55 @@
 
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    http::{HeaderMap, StatusCode},
+    response::sse::{Event, KeepAlive, Sse},
     routing::{delete, get, patch, post},
     Router,
 };
+use futures::stream::{BoxStream, StreamExt};
 use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::time::Instant;
 use uuid::Uuid;
 
 use crate::api::{
+    docx,
+    extractors::{Json, ListQueryKeys, StrictQuery},
     error::ApiError,
     responses::{ApiResponse, PaginationParams, SearchParams, SortParams},
-    AppState, ApiResult,
+    stats_error, validate_choice, validate_min_confidence, validate_options, AppState, ApiResult, DeleteQuery,
+    StatsQuery,
 };
 use crate::repository::{
-    traits::{AnalysisRepository, NewAnalysisResult, UpdateAnalysisResult},
+    traits::{
+        AnalysisRegeneration, AnalysisRepository, AnalysisResult, IdeaRepository, NewAnalysisResult, NewSession, NewTask,
+        SessionRepository, TaskRepository, Transcript, TranscriptRepository, UpdateAnalysisResult, UpdateSession,
+    },
     RepositoryManager,
 };
-use crate::services::traits::AnalysisService;
+use crate::ollama::ModelOptions;
+use crate::services::traits::{
+    AnalysisService, AnalysisStream, AnalysisStreamEvent, OllamaOptions, OllamaService, SessionService, TextStream,
+};
+
+/// Number of leading words of the text used to title an ad-hoc session
+const ADHOC_TITLE_WORDS: usize = 8;
+
+/// Length cap for ad-hoc session titles, for text without word breaks
+const ADHOC_TITLE_MAX_CHARS: usize = 60;
 
 /// Create analysis routes
 pub fn create_routes<R: RepositoryManager + 'static>() -> Router<AppState<R>> {
     Router::new()
         .route("/", get(list_analysis_results).post(create_analysis))
         .route("/:id", get(get_analysis_result).patch(update_analysis_result).delete(delete_analysis_result))
+        .route("/:id/restore", post(restore_analysis_result))
         .route("/:id/export", get(export_analysis_result))
+        .route("/:id/regenerate", post(regenerate_analysis))
         .route("/transcript/:transcript_id", post(analyze_transcript))
         .route("/text", post(analyze_text))
+        .route("/tasks", post(extract_tasks))
+        .route("/stream", get(stream_analysis))
         .route("/batch", post(batch_analyze))
         .route("/search", get(search_analysis_results))
         .route("/stats", get(analysis_stats))
@@ -40,18 +62,47 @@ pub fn create_routes<R: RepositoryManager + 'static>() -> Router<AppState<R>> {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct CreateAnalysisRequest {
     transcript_id: Option<Uuid>,
     text_content: Option<String>,
     analysis_type: String, // "summary", "ideas", "tasks", "structured", "custom"
     provider: Option<String>, // "openai" or "ollama"
     language: Option<String>,
+    /// Language to write the results in; defaults to the transcript's
+    output_language: Option<String>,
     model: Option<String>,
     custom_prompt: Option<String>,
     metadata: Option<serde_json::Value>,
+    /// Drop extracted tasks and ideas the model is less confident about,
+    /// from 0 to 1; defaults to `analysis.min_confidence_to_persist`
+    min_confidence: Option<f64>,
+    /// Ollama generation options, e.g. `{"temperature": 0.8}`, over `ollama.options`
+    options: Option<ModelOptions>,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RegenerateAnalysisRequest {
+    /// Additional instructions given to the model before its usual prompt
+    custom_prompt: Option<String>,
+    provider: Option<String>, // "openai" or "ollama"
+    model: Option<String>,
+}
+
+const ANALYSIS_PROVIDERS: &[&str] = &["openai", "ollama"];
+
+impl RegenerateAnalysisRequest {
+    fn validate(&self) -> ApiResult<()> {
+        if self.custom_prompt.as_deref().is_some_and(|prompt| prompt.trim().is_empty()) {
+            return Err(ApiError::UnprocessableEntity("custom_prompt must not be empty".to_string()));
+        }
+        validate_choice("provider", self.provider.as_deref(), ANALYSIS_PROVIDERS)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct UpdateAnalysisResultRequest {
     result_data: Option<serde_json::Value>,
     confidence_score: Option<f64>,
@@ -71,43 +122,139 @@ struct AnalysisListQuery {
     transcript_id: Option<Uuid>,
     analysis_type: Option<String>,
     provider: Option<String>,
+    /// Exact model name, e.g. `deepseek-r1:7b`
+    model: Option<String>,
     language: Option<String>,
     status: Option<String>,
     min_confidence: Option<f64>,
     created_after: Option<chrono::DateTime<chrono::Utc>>,
     created_before: Option<chrono::DateTime<chrono::Utc>>,
+    include_deleted: Option<bool>,
+}
+
+impl ListQueryKeys for AnalysisListQuery {
+    const KEYS: &'static [&'static str] = &[
+        "session_id",
+        "transcript_id",
+        "analysis_type",
+        "provider",
+        "model",
+        "language",
+        "status",
+        "min_confidence",
+        "created_after",
+        "created_before",
+        "include_deleted",
+    ];
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct AnalyzeTranscriptRequest {
     analysis_types: Vec<String>,
     provider: Option<String>,
     language: Option<String>,
+    /// Language to write the results in; defaults to the transcript's
+    output_language: Option<String>,
     model: Option<String>,
     custom_prompts: Option<std::collections::HashMap<String, String>>,
     save_results: Option<bool>,
+    /// Drop extracted tasks and ideas the model is less confident about,
+    /// from 0 to 1; defaults to `analysis.min_confidence_to_persist`
+    min_confidence: Option<f64>,
+    /// Ollama generation options, e.g. `{"temperature": 0.8}`, over `ollama.options`
+    options: Option<ModelOptions>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StreamAnalysisQuery {
+    transcript_id: Uuid,
+    /// Language to write the results in; defaults to the transcript's
+    output_language: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct AnalyzeTextRequest {
     text: String,
     analysis_types: Vec<String>,
     provider: Option<String>,
     language: Option<String>,
+    /// Language to write the results in; defaults to the transcript's
+    output_language: Option<String>,
     model: Option<String>,
     custom_prompts: Option<std::collections::HashMap<String, String>>,
     save_results: Option<bool>,
     session_id: Option<Uuid>,
+    /// Create a session for the results when `session_id` is not given
+    create_session: Option<bool>,
+    /// Drop extracted tasks and ideas the model is less confident about,
+    /// from 0 to 1; defaults to `analysis.min_confidence_to_persist`
+    min_confidence: Option<f64>,
+    /// Ollama generation options, e.g. `{"temperature": 0.8}`, over `ollama.options`
+    options: Option<ModelOptions>,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExtractTasksRequest {
+    /// Text to extract the tasks of, unless `transcript_id` is given
+    text: Option<String>,
+    transcript_id: Option<Uuid>,
+    provider: Option<String>,
+    /// Language to write the tasks in; defaults to the text's
+    output_language: Option<String>,
+    model: Option<String>,
+    /// Save the tasks to `session_id`, or to the session of the transcript
+    save: Option<bool>,
+    session_id: Option<Uuid>,
+    /// Drop extracted tasks the model is less confident about, from 0 to 1;
+    /// defaults to `analysis.min_confidence_to_persist`
+    min_confidence: Option<f64>,
+}
+
+impl ExtractTasksRequest {
+    fn validate(&self) -> ApiResult<()> {
+        if self.text.is_some() == self.transcript_id.is_some() {
+            return Err(ApiError::BadRequest("Exactly one of text or transcript_id must be provided".to_string()));
+        }
+        if self.text.as_deref().is_some_and(|text| text.trim().is_empty()) {
+            return Err(ApiError::UnprocessableEntity("text must not be empty".to_string()));
+        }
+        if self.session_id.is_some() && !self.save.unwrap_or(false) {
+            return Err(ApiError::BadRequest("session_id is only used with save".to_string()));
+        }
+        validate_min_confidence(self.min_confidence)?;
+        validate_choice("provider", self.provider.as_deref(), ANALYSIS_PROVIDERS)
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ExtractTasksResponse {
+    tasks: Vec<crate::storage::Task>,
+    /// The analysis the tasks were saved as
+    analysis_id: Option<Uuid>,
+    session_id: Option<Uuid>,
+    processing_time_ms: i64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct BatchAnalyzeRequest {
     transcript_ids: Vec<Uuid>,
     analysis_types: Vec<String>,
     provider: Option<String>,
     language: Option<String>,
+    /// Language to write the results in; defaults to the transcript's
+    output_language: Option<String>,
     model: Option<String>,
     custom_prompts: Option<std::collections::HashMap<String, String>>,
+    /// Drop extracted tasks and ideas the model is less confident about,
+    /// from 0 to 1; defaults to `analysis.min_confidence_to_persist`
+    min_confidence: Option<f64>,
+    /// Ollama generation options, e.g. `{"temperature": 0.8}`, over `ollama.options`
+    options: Option<ModelOptions>,
 }
 
 #[derive(Debug, Serialize)]
@@ -127,6 +274,7 @@ struct AnalysisResultResponse {
     metadata: Option<serde_json::Value>,
     created_at: chrono::DateTime<chrono::Utc>,
     updated_at: chrono::DateTime<chrono::Utc>,
+    deleted_at: Option<chrono::DateTime<chrono::Utc>>,
     // Related data
     transcript_content: Option<String>,
     session_title: Option<String>,
@@ -137,6 +285,8 @@ struct AnalysisStatsResponse {
     total_analyses: i64,
     analysis_types: std::collections::HashMap<String, i64>,
     providers: std::collections::HashMap<String, i64>,
+    /// Count per exact model
+    models: std::collections::HashMap<String, i64>,
     languages: std::collections::HashMap<String, i64>,
     status_distribution: std::collections::HashMap<String, i64>,
     avg_confidence_score: f64,
@@ -196,12 +346,16 @@ struct ProviderInfo {
     supported_models: Vec<String>,
     default_model: String,
     capabilities: Vec<String>,
+    /// Whether the default model can answer without loading first
+    ready: bool,
+    /// Expected first-request delay when not ready, if known
+    estimated_cold_start_ms: Option<u64>,
 }
 
 /// List analysis results with filtering and pagination
 async fn list_analysis_results<R: RepositoryManager>(
     State(state): State<AppState<R>>,
-    Query(query): Query<AnalysisListQuery>,
+    StrictQuery(query): StrictQuery<AnalysisListQuery>,
 ) -> ApiResult<Json<ApiResponse<Vec<AnalysisResultResponse>>>> {
     let analysis_results = state.repositories.analysis()
         .find_with_filters(
@@ -209,11 +363,13 @@ async fn list_analysis_results<R: RepositoryManager>(
             query.transcript_id,
             query.analysis_type.as_deref(),
             query.provider.as_deref(),
+            query.model.as_deref(),
             query.language.as_deref(),
             query.status.as_deref(),
             query.min_confidence,
             query.created_after,
             query.created_before,
+            query.include_deleted.unwrap_or(false),
             Some(query.pagination.limit),
             Some(query.pagination.offset),
             query.sort.sort_by.as_deref(),
@@ -228,11 +384,13 @@ async fn list_analysis_results<R: RepositoryManager>(
             query.transcript_id,
             query.analysis_type.as_deref(),
             query.provider.as_deref(),
+            query.model.as_deref(),
             query.language.as_deref(),
             query.status.as_deref(),
             query.min_confidence,
             query.created_after,
             query.created_before,
+            query.include_deleted.unwrap_or(false),
         )
         .await
         .map_err(|e| ApiError::InternalServerError(format!("Failed to count analysis results: {}", e)))?;
@@ -272,17 +430,13 @@ async fn list_analysis_results<R: RepositoryManager>(
             metadata: analysis.metadata,
             created_at: analysis.created_at,
             updated_at: analysis.updated_at,
+            deleted_at: analysis.deleted_at,
             transcript_content: transcript.map(|t| t.content),
             session_title: session.and_then(|s| s.title),
         });
     }
 
-    Ok(Json(ApiResponse {
-        data: responses,
-        total: Some(total),
-        page: Some(query.pagination.page()),
-        per_page: Some(query.pagination.limit),
-    }))
+    Ok(Json(ApiResponse::paginated(responses, total, query.pagination.page(), query.pagination.limit)))
 }
 
 /// Create a new analysis
@@ -290,6 +444,9 @@ async fn create_analysis<R: RepositoryManager>(
     State(state): State<AppState<R>>,
     Json(request): Json<CreateAnalysisRequest>,
 ) -> ApiResult<Json<ApiResponse<AnalysisResultResponse>>> {
+    validate_min_confidence(request.min_confidence)?;
+    validate_options(request.options.as_ref())?;
+
     let analysis_result = if let Some(transcript_id) = request.transcript_id {
         // Analyze existing transcript
         let transcript = state.repositories.transcript()
@@ -304,12 +461,15 @@ async fn create_analysis<R: RepositoryManager>(
                 &[request.analysis_type],
                 request.provider.as_deref(),
                 request.language.as_deref(),
+                request.output_language.as_deref(),
                 request.model.as_deref(),
                 request.custom_prompt.as_ref().map(|p| {
                     let mut prompts = std::collections::HashMap::new();
                     prompts.insert("custom".to_string(), p.clone());
                     prompts
                 }).as_ref(),
+                request.min_confidence,
+                request.options,
             )
             .await
             .map_err(|e| ApiError::InternalServerError(format!("Failed to analyze transcript: {}", e)))?
@@ -324,12 +484,15 @@ async fn create_analysis<R: RepositoryManager>(
                 &[request.analysis_type],
                 request.provider.as_deref(),
                 request.language.as_deref(),
+                request.output_language.as_deref(),
                 request.model.as_deref(),
                 request.custom_prompt.as_ref().map(|p| {
                     let mut prompts = std::collections::HashMap::new();
                     prompts.insert("custom".to_string(), p.clone());
                     prompts
                 }).as_ref(),
+                request.min_confidence,
+                request.options,
                 None, // session_id would need to be provided
             )
             .await
@@ -373,16 +536,12 @@ async fn create_analysis<R: RepositoryManager>(
         metadata: analysis_result.metadata,
         created_at: analysis_result.created_at,
         updated_at: analysis_result.updated_at,
+        deleted_at: analysis_result.deleted_at,
         transcript_content: transcript.map(|t| t.content),
         session_title: session.and_then(|s| s.title),
     };
 
-    Ok(Json(ApiResponse {
-        data: response,
-        total: None,
-        page: None,
-        per_page: None,
-    }))
+    Ok(Json(ApiResponse::success(response)))
 }
 
 /// Get a specific analysis result by ID
@@ -428,16 +587,12 @@ async fn get_analysis_result<R: RepositoryManager>(
         metadata: analysis_result.metadata,
         created_at: analysis_result.created_at,
         updated_at: analysis_result.updated_at,
+        deleted_at: analysis_result.deleted_at,
         transcript_content: transcript.map(|t| t.content),
         session_title: session.and_then(|s| s.title),
     };
 
-    Ok(Json(ApiResponse {
-        data: response,
-        total: None,
-        page: None,
-        per_page: None,
-    }))
+    Ok(Json(ApiResponse::success(response)))
 }
 
 /// Update an analysis result
@@ -497,23 +652,109 @@ async fn update_analysis_result<R: RepositoryManager>(
         metadata: updated_analysis.metadata,
         created_at: updated_analysis.created_at,
         updated_at: updated_analysis.updated_at,
+        deleted_at: updated_analysis.deleted_at,
         transcript_content: transcript.map(|t| t.content),
         session_title: session.and_then(|s| s.title),
     };
 
-    Ok(Json(ApiResponse {
-        data: response,
-        total: None,
-        page: None,
-        per_page: None,
-    }))
+    Ok(Json(ApiResponse::success(response)))
 }
 
-/// Delete an analysis result
+/// Re-run an analysis on its session's transcript, replacing its result in
+/// place
+///
+/// The analysis keeps its id and `updated_at` is bumped; the previous result
+/// is kept in `metadata.previous_results`.
+async fn regenerate_analysis<R: RepositoryManager>(
+    State(state): State<AppState<R>>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<RegenerateAnalysisRequest>,
+) -> ApiResult<Json<ApiResponse<AnalysisResultResponse>>> {
+    request.validate()?;
+
+    let analysis_result = state.repositories.analysis()
+        .find_by_id(&id)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to get analysis result: {}", e)))?
+        .ok_or_else(|| ApiError::NotFound("Analysis result not found".to_string()))?;
+    let transcript = state.repositories.transcript()
+        .find_by_session_id(&analysis_result.session_id)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to get transcript: {}", e)))?
+        .ok_or_else(|| ApiError::UnprocessableEntity("The analysis's session has no transcript to analyze".to_string()))?;
+
+    let analysis = crate::ai::regenerate_analysis(
+        &transcript.content,
+        request.provider.as_deref(),
+        request.model.as_deref(),
+        request.custom_prompt.as_deref(),
+    )
+    .await
+    .map_err(|e| ApiError::InternalServerError(format!("Failed to regenerate analysis: {}", e)))?;
+    let result_data = serde_json::to_value(&analysis)
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to serialize analysis: {}", e)))?;
+
+    let regenerated = state.repositories.analysis()
+        .regenerate(&id, &AnalysisRegeneration {
+            title: Some(analysis.title).filter(|title| !title.is_empty()),
+            summary: Some(analysis.summary).filter(|summary| !summary.is_empty()),
+            provider: analysis.provider.unwrap_or_else(|| "ollama".to_string()),
+            model_version: analysis.model,
+            processing_time_ms: analysis.processing_time_ms.map(|ms| i32::try_from(ms).unwrap_or(i32::MAX)),
+            result_data,
+        })
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to save regenerated analysis: {}", e)))?
+        // Deleted while the model was running
+        .ok_or_else(|| ApiError::NotFound("Analysis result not found".to_string()))?;
+
+    let session = state.repositories.session()
+        .find_by_id(regenerated.session_id)
+        .await
+        .ok()
+        .flatten();
+
+    let response = AnalysisResultResponse {
+        id: regenerated.id,
+        session_id: regenerated.session_id,
+        transcript_id: Some(transcript.id),
+        analysis_type: regenerated.analysis_type,
+        provider: regenerated.provider,
+        model_used: regenerated.model_used,
+        language: regenerated.language,
+        result_data: regenerated.result_data,
+        confidence_score: regenerated.confidence_score,
+        processing_time_ms: regenerated.processing_time_ms,
+        token_usage: regenerated.token_usage,
+        status: regenerated.status,
+        metadata: regenerated.metadata,
+        created_at: regenerated.created_at,
+        updated_at: regenerated.updated_at,
+        deleted_at: regenerated.deleted_at,
+        transcript_content: Some(transcript.content),
+        session_title: session.and_then(|s| s.title),
+    };
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
+/// Soft-delete an analysis result; `?purge=true` deletes it permanently
 async fn delete_analysis_result<R: RepositoryManager>(
     State(state): State<AppState<R>>,
     Path(id): Path<Uuid>,
+    Query(query): Query<DeleteQuery>,
 ) -> ApiResult<StatusCode> {
+    if query.purge {
+        let purged = state.repositories.analysis()
+            .purge(id)
+            .await
+            .map_err(|e| ApiError::InternalServerError(format!("Failed to purge analysis result: {}", e)))?;
+        if !purged {
+            return Err(ApiError::NotFound("Analysis result not found".to_string()));
+        }
+        return Ok(StatusCode::NO_CONTENT);
+    }
+
     // Check if analysis result exists
     let _analysis_result = state.repositories.analysis()
         .find_by_id(id)
@@ -529,11 +770,28 @@ async fn delete_analysis_result<R: RepositoryManager>(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Restore a soft-deleted analysis result
+async fn restore_analysis_result<R: RepositoryManager>(
+    State(state): State<AppState<R>>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<ApiResponse<AnalysisResultResponse>>> {
+    let restored = state.repositories.analysis()
+        .restore(id)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to restore analysis result: {}", e)))?;
+    if !restored {
+        return Err(ApiError::NotFound("Deleted analysis result not found".to_string()));
+    }
+
+    get_analysis_result(State(state), Path(id)).await
+}
+
 /// Export analysis result
 async fn export_analysis_result<R: RepositoryManager>(
     State(state): State<AppState<R>>,
     Path(id): Path<Uuid>,
     Query(params): Query<std::collections::HashMap<String, String>>,
+    headers: HeaderMap,
 ) -> ApiResult<axum::response::Response> {
     let analysis_result = state.repositories.analysis()
         .find_by_id(id)
@@ -555,7 +813,7 @@ async fn export_analysis_result<R: RepositoryManager>(
                 serde_json::to_string_pretty(&analysis_result.result_data)
                     .map_err(|e| ApiError::InternalServerError(format!("Failed to serialize analysis: {}", e)))?
             };
-            (json_data, "application/json", format!("analysis_{}.json", analysis_result.id))
+            (json_data.into_bytes(), "application/json", format!("analysis_{}.json", analysis_result.id))
         }
         "txt" => {
             let text_content = format!(
@@ -566,40 +824,71 @@ async fn export_analysis_result<R: RepositoryManager>(
                 analysis_result.created_at,
                 serde_json::to_string_pretty(&analysis_result.result_data).unwrap_or_default()
             );
-            (text_content, "text/plain", format!("analysis_{}.txt", analysis_result.id))
+            (text_content.into_bytes(), "text/plain", format!("analysis_{}.txt", analysis_result.id))
         }
         "csv" => {
-            // Simple CSV export for structured data
-            let csv_content = "Type,Provider,Language,Created,Result\n".to_string() +
-                &format!(
-                    "{},{},{},{},\"{}\"\n",
-                    analysis_result.analysis_type,
-                    analysis_result.provider,
-                    analysis_result.language,
-                    analysis_result.created_at,
-                    serde_json::to_string(&analysis_result.result_data).unwrap_or_default().replace('"', """")
-                );
-            (csv_content, "text/csv", format!("analysis_{}.csv", analysis_result.id))
+            let csv_content = analysis_to_csv(&analysis_result)
+                .map_err(|e| ApiError::InternalServerError(format!("Failed to export analysis as CSV: {}", e)))?;
+            (csv_content.into_bytes(), "text/csv", format!("analysis_{}.csv", analysis_result.id))
+        }
+        "docx" => {
+            let content = analysis_docx(&state, &analysis_result, analysis_result.title.as_deref()).await?;
+            (content, docx::CONTENT_TYPE, format!("analysis_{}.docx", analysis_result.id))
         }
         _ => {
             return Err(ApiError::BadRequest(format!(
-                "Unsupported export format: {}. Supported formats: json, txt, csv",
+                "Unsupported export format: {}. Supported formats: json, txt, csv, docx",
                 format
             )));
         }
     };
 
-    Ok((
-        [
-            (axum::http::header::CONTENT_TYPE, content_type),
-            (
-                axum::http::header::CONTENT_DISPOSITION,
-                &format!("attachment; filename=\"{}\"", filename),
-            ),
-        ],
-        content,
+    Ok(crate::etag::content_response(&headers, content_type, Some(&filename), content))
+}
+
+/// The analysis with its ideas and tasks as a Word document headed `title`,
+/// for the `docx` exports of analyses and sessions
+pub(super) async fn analysis_docx<R: RepositoryManager>(
+    state: &AppState<R>,
+    analysis: &crate::repository::traits::AnalysisResult,
+    title: Option<&str>,
+) -> ApiResult<Vec<u8>> {
+    let (ideas, tasks) = futures::try_join!(
+        state.repositories.idea().find_by_analysis_id(&analysis.id),
+        state.repositories.task().find_by_analysis_id(&analysis.id),
     )
-        .into_response())
+    .map_err(|e| ApiError::InternalServerError(format!("Failed to get ideas and tasks: {}", e)))?;
+
+    let document = docx::AnalysisDocument {
+        title,
+        summary: analysis.summary.as_deref(),
+        ideas: ideas.iter().map(|idea| idea.content.as_str()).collect(),
+        tasks: tasks
+            .iter()
+            .map(|task| docx::DocxTask {
+                title: &task.title,
+                priority: format!("{:?}", task.priority),
+                due: task.due_date,
+            })
+            .collect(),
+    };
+    docx::analysis_to_docx(&document)
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to export analysis as DOCX: {}", e)))
+}
+
+/// A header row and one row for the analysis; the writer quotes cells
+/// containing commas, quotes or line breaks
+fn analysis_to_csv(analysis: &crate::repository::traits::AnalysisResult) -> anyhow::Result<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(["Title", "Summary", "Provider", "Model", "Created"])?;
+    writer.write_record([
+        analysis.title.as_deref().unwrap_or_default(),
+        analysis.summary.as_deref().unwrap_or_default(),
+        analysis.provider.as_str(),
+        analysis.model_version.as_deref().unwrap_or_default(),
+        analysis.created_at.to_rfc3339().as_str(),
+    ])?;
+    Ok(String::from_utf8(writer.into_inner()?)?)
 }
 
 /// Analyze a transcript
@@ -608,14 +897,20 @@ async fn analyze_transcript<R: RepositoryManager>(
     Path(transcript_id): Path<Uuid>,
     Json(request): Json<AnalyzeTranscriptRequest>,
 ) -> ApiResult<Json<ApiResponse<Vec<AnalysisResultResponse>>>> {
+    validate_min_confidence(request.min_confidence)?;
+    validate_options(request.options.as_ref())?;
+
     let analysis_results = state.services.analysis()
         .analyze_transcript(
             transcript_id,
             &request.analysis_types,
             request.provider.as_deref(),
             request.language.as_deref(),
+            request.output_language.as_deref(),
             request.model.as_deref(),
             request.custom_prompts.as_ref(),
+            request.min_confidence,
+            request.options,
         )
         .await
         .map_err(|e| ApiError::InternalServerError(format!("Failed to analyze transcript: {}", e)))?;
@@ -638,36 +933,217 @@ async fn analyze_transcript<R: RepositoryManager>(
             metadata: analysis.metadata,
             created_at: analysis.created_at,
             updated_at: analysis.updated_at,
+            deleted_at: analysis.deleted_at,
             transcript_content: None, // Would need to fetch if required
             session_title: None, // Would need to fetch if required
         })
         .collect();
 
-    Ok(Json(ApiResponse {
-        data: responses,
-        total: Some(responses.len() as i64),
-        page: None,
-        per_page: None,
-    }))
+    Ok(Json(ApiResponse::success(responses).with_total(responses.len() as i64)))
+}
+
+/// Analyze a transcript, streaming progress as server-sent events
+///
+/// Emits `progress` and `partial` events while the model generates, then a
+/// `complete` event carrying the saved analysis id, or an `error` event. When
+/// the client disconnects the event stream is dropped, which cancels the
+/// in-flight model request.
+async fn stream_analysis<R: RepositoryManager + 'static>(
+    State(state): State<AppState<R>>,
+    Query(query): Query<StreamAnalysisQuery>,
+) -> ApiResult<Sse<BoxStream<'static, Result<Event, Infallible>>>> {
+    let transcript = state.repositories.transcript()
+        .find_by_id(&query.transcript_id)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to get transcript: {}", e)))?
+        .ok_or_else(|| ApiError::NotFound("Transcript not found".to_string()))?;
+
+    let analysis_stream = analysis_stream(&state, &transcript, query.output_language.as_deref())
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to start analysis: {}", e)))?;
+
+    let events = analysis_stream
+        .map(|item| {
+            let event = match item {
+                Ok(AnalysisStreamEvent::Complete { analysis }) => Event::default()
+                    .event("complete")
+                    .json_data(serde_json::json!({
+                        "analysis_id": analysis.id,
+                        "analysis": analysis,
+                    })),
+                Ok(event @ AnalysisStreamEvent::Progress { .. }) => {
+                    Event::default().event("progress").json_data(&event)
+                }
+                Ok(event @ AnalysisStreamEvent::Partial { .. }) => {
+                    Event::default().event("partial").json_data(&event)
+                }
+                Err(e) => Ok(Event::default().event("error").data(format!("{:#}", e))),
+            };
+            Ok(event.unwrap_or_else(|e| Event::default().event("error").data(e.to_string())))
+        })
+        .boxed();
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+/// A streaming analysis in progress
+struct StreamedAnalysis<R: RepositoryManager> {
+    state: AppState<R>,
+    chunks: TextStream,
+    session_id: Uuid,
+    transcript: String,
+    model: String,
+    output: String,
+    chars_generated: usize,
+    started: Instant,
+}
+
+/// Run a full analysis of `transcript` on Ollama, streaming the model output
+///
+/// When the model finishes, the output is parsed like a non-streaming
+/// analysis and its title and summary are saved, with `processing_time_ms`
+/// measured from the request until the last chunk.
+async fn analysis_stream<R: RepositoryManager + 'static>(
+    state: &AppState<R>,
+    transcript: &Transcript,
+    output_language: Option<&str>,
+) -> anyhow::Result<AnalysisStream> {
+    let config = state.config.current();
+    let model = config.ollama.default_model.clone();
+    let prompt = crate::ollama::analysis_prompt(&transcript.content, transcript.language.as_deref(), output_language).await?;
+    let configured = config.ollama.options;
+    let options = OllamaOptions {
+        temperature: configured.temperature.map(|temperature| temperature as f32),
+        top_p: configured.top_p.map(|top_p| top_p as f32),
+        top_k: configured.top_k.and_then(|top_k| i32::try_from(top_k).ok()),
+        repeat_penalty: configured.repeat_penalty.map(|penalty| penalty as f32),
+        seed: configured.seed.and_then(|seed| i32::try_from(seed).ok()),
+        num_predict: i32::try_from(config.ollama.num_predict).ok(),
+        stop: None,
+    };
+
+    let started = Instant::now();
+    let chunks = state.services.ollama().generate_stream(&model, &prompt, Some(options)).await?;
+    let analysis = StreamedAnalysis {
+        state: state.clone(),
+        chunks,
+        session_id: transcript.session_id,
+        transcript: transcript.content.clone(),
+        model,
+        output: String::new(),
+        chars_generated: 0,
+        started,
+    };
+
+    let events = futures::stream::unfold(Some(analysis), |analysis| async move {
+        let mut analysis = analysis?;
+        match analysis.chunks.next().await {
+            Some(Ok(text)) => {
+                analysis.output.push_str(&text);
+                analysis.chars_generated += text.chars().count();
+                let events = vec![
+                    Ok(AnalysisStreamEvent::Partial { text }),
+                    Ok(AnalysisStreamEvent::Progress { chars_generated: analysis.chars_generated }),
+                ];
+                Some((events, Some(analysis)))
+            }
+            Some(Err(e)) => Some((vec![Err(e)], None)),
+            None => {
+                let saved = save_streamed_analysis(analysis)
+                    .await
+                    .map(|analysis| AnalysisStreamEvent::Complete { analysis });
+                Some((vec![saved], None))
+            }
+        }
+    });
+
+    Ok(events.flat_map(futures::stream::iter).boxed())
+}
+
+/// Parse the finished output of a streaming analysis and save it
+async fn save_streamed_analysis<R: RepositoryManager>(analysis: StreamedAnalysis<R>) -> anyhow::Result<AnalysisResult> {
+    let processing_time_ms = i32::try_from(analysis.started.elapsed().as_millis()).unwrap_or(i32::MAX);
+    let parsed = crate::ollama::parse_analysis_output(&analysis.transcript, &analysis.output)?;
+    analysis.state.repositories.analysis()
+        .create(&NewAnalysisResult {
+            session_id: analysis.session_id,
+            title: Some(parsed.title).filter(|title| !title.trim().is_empty()),
+            summary: Some(parsed.summary).filter(|summary| !summary.trim().is_empty()),
+            provider: "ollama".to_string(),
+            model_version: Some(analysis.model),
+            processing_time_ms: Some(processing_time_ms),
+        })
+        .await
 }
 
 /// Analyze raw text
+///
+/// With `create_session` and no `session_id`, the results go to a new
+/// session, which is deleted again if the analysis fails.
 async fn analyze_text<R: RepositoryManager>(
     State(state): State<AppState<R>>,
     Json(request): Json<AnalyzeTextRequest>,
 ) -> ApiResult<Json<ApiResponse<Vec<AnalysisResultResponse>>>> {
+    validate_min_confidence(request.min_confidence)?;
+    validate_options(request.options.as_ref())?;
+
+    let created_session = match request.session_id {
+        None if request.create_session.unwrap_or(false) => {
+            Some(create_adhoc_session(&state, &request.text).await?)
+        }
+        _ => None,
+    };
+
     let analysis_results = state.services.analysis()
         .analyze_text(
             &request.text,
             &request.analysis_types,
             request.provider.as_deref(),
             request.language.as_deref(),
+            request.output_language.as_deref(),
             request.model.as_deref(),
             request.custom_prompts.as_ref(),
-            request.session_id,
+            request.min_confidence,
+            request.options,
+            request.session_id.or(created_session),
         )
-        .await
-        .map_err(|e| ApiError::InternalServerError(format!("Failed to analyze text: {}", e)))?;
+        .await;
+    let analysis_results = match analysis_results {
+        Ok(analysis_results) => analysis_results,
+        Err(e) => {
+            // The session was only created to hold this analysis
+            if let Some(session_id) = created_session {
+                if let Err(purge_error) = state.repositories.session().purge(&session_id).await {
+                    tracing::warn!("Failed to delete ad-hoc session {}: {:#}", session_id, purge_error);
+                }
+            }
+            return Err(ApiError::InternalServerError(format!("Failed to analyze text: {}", e)));
+        }
+    };
+
+    // Prefer the title the model gave the text over its first words
+    if let Some(session_id) = created_session {
+        let analysis_title = analysis_results.iter().find_map(|analysis| {
+            analysis.result_data
+                .get("title")
+                .and_then(serde_json::Value::as_str)
+                .map(str::trim)
+                .filter(|title| !title.is_empty())
+                .map(str::to_string)
+        });
+        if let Some(title) = analysis_title {
+            state.services.session()
+                .update_session(session_id, UpdateSession {
+                    title: Some(title),
+                    description: None,
+                    status: None,
+                    tags: None,
+                    metadata: None,
+                })
+                .await
+                .map_err(|e| ApiError::InternalServerError(format!("Failed to update session: {}", e)))?;
+        }
+    }
 
     let responses: Vec<AnalysisResultResponse> = analysis_results
         .into_iter()
@@ -687,17 +1163,138 @@ async fn analyze_text<R: RepositoryManager>(
             metadata: analysis.metadata,
             created_at: analysis.created_at,
             updated_at: analysis.updated_at,
+            deleted_at: analysis.deleted_at,
             transcript_content: None,
             session_title: None,
         })
         .collect();
 
-    Ok(Json(ApiResponse {
-        data: responses,
-        total: Some(responses.len() as i64),
-        page: None,
-        per_page: None,
-    }))
+    Ok(Json(ApiResponse::success(responses).with_total(responses.len() as i64)))
+}
+
+/// Extract only the action items of a text or transcript
+///
+/// Much quicker than a full analysis, since only the tasks are asked for.
+/// Nothing is stored unless `save` is set, in which case the tasks go to
+/// `session_id` or, for a transcript, to the transcript's session.
+async fn extract_tasks<R: RepositoryManager>(
+    State(state): State<AppState<R>>,
+    Json(request): Json<ExtractTasksRequest>,
+) -> ApiResult<Json<ApiResponse<ExtractTasksResponse>>> {
+    request.validate()?;
+    let save = request.save.unwrap_or(false);
+
+    let (content, session_id) = match (request.text, request.transcript_id) {
+        (Some(text), _) => (text, request.session_id),
+        (None, Some(transcript_id)) => {
+            let transcript = state.repositories.transcript()
+                .find_by_id(transcript_id)
+                .await
+                .map_err(|e| ApiError::InternalServerError(format!("Failed to get transcript: {}", e)))?
+                .ok_or_else(|| ApiError::NotFound("Transcript not found".to_string()))?;
+            (transcript.content, request.session_id.or(Some(transcript.session_id)))
+        }
+        (None, None) => unreachable!("checked by validate"),
+    };
+
+    let session_id = match session_id {
+        Some(session_id) if save => {
+            state.repositories.session()
+                .find_by_id(session_id)
+                .await
+                .map_err(|e| ApiError::InternalServerError(format!("Failed to get session: {}", e)))?
+                .ok_or_else(|| ApiError::NotFound("Session not found".to_string()))?;
+            Some(session_id)
+        }
+        None if save => {
+            return Err(ApiError::BadRequest("Saving tasks of a text requires a session_id".to_string()));
+        }
+        _ => None,
+    };
+
+    let extracted = crate::ai::extract_action_items(
+        &content,
+        request.provider.as_deref(),
+        request.model.as_deref(),
+        request.output_language.as_deref(),
+        request.min_confidence.map(|min_confidence| min_confidence as f32),
+    )
+    .await
+    .map_err(|e| ApiError::InternalServerError(format!("Failed to extract tasks: {}", e)))?;
+    let processing_time_ms = extracted.processing_time_ms.unwrap_or(0);
+
+    let analysis_id = match session_id {
+        Some(session_id) => Some(save_tasks(&state, session_id, &extracted).await?),
+        None => None,
+    };
+
+    let total = extracted.tasks.len() as i64;
+    Ok(Json(ApiResponse::success(ExtractTasksResponse {
+        tasks: extracted.tasks,
+        analysis_id,
+        session_id,
+        processing_time_ms: i64::try_from(processing_time_ms).unwrap_or(i64::MAX),
+    }).with_total(total)))
+}
+
+/// Save extracted tasks as a new analysis of `session_id`, returning its id
+async fn save_tasks<R: RepositoryManager>(
+    state: &AppState<R>,
+    session_id: Uuid,
+    extracted: &crate::storage::AnalysisResult,
+) -> ApiResult<Uuid> {
+    let analysis = state.repositories.analysis()
+        .create(&NewAnalysisResult {
+            session_id,
+            title: None,
+            summary: None,
+            provider: extracted.provider.clone().unwrap_or_else(|| "ollama".to_string()),
+            model_version: extracted.model.clone(),
+            processing_time_ms: extracted.processing_time_ms.map(|ms| i32::try_from(ms).unwrap_or(i32::MAX)),
+        })
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to save analysis: {}", e)))?;
+
+    for task in &extracted.tasks {
+        state.repositories.task()
+            .create(&NewTask {
+                analysis_id: analysis.id,
+                title: task.title.clone(),
+                description: task.description.clone(),
+                priority: task.priority.clone(),
+                due_date: task.due_date,
+            })
+            .await
+            .map_err(|e| ApiError::InternalServerError(format!("Failed to save task: {}", e)))?;
+    }
+    Ok(analysis.id)
+}
+
+/// Create a session to hold the results of a text analysis
+///
+/// Titled with the first words of the text until the analysis provides a title.
+async fn create_adhoc_session<R: RepositoryManager>(state: &AppState<R>, text: &str) -> ApiResult<Uuid> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let leading = words.iter().take(ADHOC_TITLE_WORDS).copied().collect::<Vec<_>>().join(" ");
+    let mut title: String = leading.chars().take(ADHOC_TITLE_MAX_CHARS).collect();
+    if words.len() > ADHOC_TITLE_WORDS || title.len() < leading.len() {
+        title.push('…');
+    }
+    if title.is_empty() {
+        title = "Text analysis".to_string();
+    }
+
+    let session = state.services.session()
+        .create_session(NewSession {
+            title,
+            description: None,
+            tags: Vec::new(),
+            metadata: Some(serde_json::json!({ "source": "analyze_text" })),
+        })
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to create session: {}", e)))?;
+
+    Ok(session.id)
 }
 
 /// Batch analyze multiple transcripts
@@ -705,6 +1302,9 @@ async fn batch_analyze<R: RepositoryManager>(
     State(state): State<AppState<R>>,
     Json(request): Json<BatchAnalyzeRequest>,
 ) -> ApiResult<Json<BatchAnalysisResponse>> {
+    validate_min_confidence(request.min_confidence)?;
+    validate_options(request.options.as_ref())?;
+
     let mut results = Vec::new();
     let mut successful = 0;
     let mut failed = 0;
@@ -716,8 +1316,11 @@ async fn batch_analyze<R: RepositoryManager>(
                 &request.analysis_types,
                 request.provider.as_deref(),
                 request.language.as_deref(),
+                request.output_language.as_deref(),
                 request.model.as_deref(),
                 request.custom_prompts.as_ref(),
+                request.min_confidence,
+                request.options,
             )
             .await
         {
@@ -741,6 +1344,7 @@ async fn batch_analyze<R: RepositoryManager>(
                         metadata: analysis.metadata,
                         created_at: analysis.created_at,
                         updated_at: analysis.updated_at,
+                        deleted_at: analysis.deleted_at,
                         transcript_content: None,
                         session_title: None,
                     })
@@ -776,21 +1380,47 @@ async fn batch_analyze<R: RepositoryManager>(
 /// Search analysis results
 async fn search_analysis_results<R: RepositoryManager>(
     State(state): State<AppState<R>>,
-    Query(query): Query<AnalysisListQuery>,
+    StrictQuery(query): StrictQuery<AnalysisListQuery>,
 ) -> ApiResult<Json<ApiResponse<Vec<AnalysisResultResponse>>>> {
     // Enhanced search functionality
-    list_analysis_results(State(state), Query(query)).await
+    list_analysis_results(State(state), StrictQuery(query)).await
 }
 
 /// Get analysis statistics
+///
+/// `tz` selects the time zone of the `analyses_per_day` buckets and `days`
+/// their number.
 async fn analysis_stats<R: RepositoryManager>(
     State(state): State<AppState<R>>,
+    Query(query): Query<StatsQuery>,
 ) -> ApiResult<Json<AnalysisStatsResponse>> {
-    let stats = state.services.analysis()
+    let window = query.window()?;
+    let mut stats = state.services.analysis()
         .get_analysis_stats()
         .await
         .map_err(|e| ApiError::InternalServerError(format!("Failed to get analysis stats: {}", e)))?;
 
+    // Averaged over analyses that recorded a duration, so rows created
+    // before timing was tracked don't drag the figure towards zero
+    stats.avg_processing_time_ms = state.repositories.analysis()
+        .average_processing_time_ms()
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to get analysis stats: {}", e)))?
+        .unwrap_or(0.0);
+
+    stats.models = state.repositories.analysis()
+        .count_by_model()
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to get analysis stats: {}", e)))?;
+
+    stats.analyses_per_day = state.repositories.analysis()
+        .daily_counts(&window)
+        .await
+        .map_err(|e| stats_error(e, "analysis"))?
+        .into_iter()
+        .map(|day| DailyCount { date: day.date, count: day.count })
+        .collect();
+
     Ok(Json(stats))
 }
 
@@ -885,7 +1515,8 @@ async fn get_analysis_providers<R: RepositoryManager>(
     State(state): State<AppState<R>>,
 ) -> ApiResult<Json<ProvidersResponse>> {
     let ollama_available = state.services.ollama().is_available().await;
-    let openai_configured = state.config.is_openai_configured();
+    let config = state.config.current();
+    let openai_configured = config.is_openai_configured();
 
     let mut providers = vec![];
 
@@ -899,12 +1530,14 @@ async fn get_analysis_providers<R: RepositoryManager>(
                 "gpt-4-turbo".to_string(),
                 "gpt-3.5-turbo".to_string(),
             ],
-            default_model: state.config.openai.analysis_model.clone(),
+            default_model: config.openai.analysis_model.clone(),
             capabilities: vec![
                 "text_analysis".to_string(),
                 "structured_output".to_string(),
                 "multilingual".to_string(),
             ],
+            ready: true,
+            estimated_cold_start_ms: None,
         });
     }
 
@@ -914,18 +1547,61 @@ async fn get_analysis_providers<R: RepositoryManager>(
         vec![]
     };
 
+    let readiness = if ollama_available {
+        state.services.ollama()
+            .model_readiness(&config.ollama.default_model)
+            .await
+            .ok()
+    } else {
+        None
+    };
+
     providers.push(ProviderInfo {
         name: "ollama".to_string(),
         display_name: "Ollama (Local)".to_string(),
         available: ollama_available,
-        supported_models: ollama_models,
-        default_model: state.config.ollama.default_model.clone(),
+        supported_models: ollama_models.into_iter().map(|model| model.name).collect(),
+        default_model: config.ollama.default_model.clone(),
         capabilities: vec![
             "text_analysis".to_string(),
             "structured_output".to_string(),
             "language_detection".to_string(),
         ],
+        ready: readiness.as_ref().is_some_and(|r| r.loaded),
+        estimated_cold_start_ms: readiness.and_then(|r| r.estimated_cold_start_ms),
     });
 
     Ok(Json(ProvidersResponse { providers }))
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_export_round_trips_commas_quotes_and_line_breaks() {
+        let analysis = crate::repository::traits::AnalysisResult {
+            id: Uuid::new_v4(),
+            session_id: Uuid::new_v4(),
+            title: Some("Plan, \"final\"".to_string()),
+            summary: Some("Ship it, then say \"done\".\nNext: \"\", \r\n".to_string()),
+            provider: "ollama".to_string(),
+            model_version: None,
+            created_at: chrono::Utc::now(),
+            processing_time_ms: None,
+            result_data: None,
+            metadata: None,
+            updated_at: chrono::Utc::now(),
+            deleted_at: None,
+        };
+
+        let csv = analysis_to_csv(&analysis).unwrap();
+        let mut reader = csv::Reader::from_reader(csv.as_bytes());
+        let records: Vec<csv::StringRecord> = reader.records().collect::<Result<_, _>>().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(&records[0][0], analysis.title.as_deref().unwrap());
+        assert_eq!(&records[0][1], analysis.summary.as_deref().unwrap());
+        assert_eq!(&records[0][2], "ollama");
+        assert_eq!(&records[0][3], "");
+    }
+}
@@ -0,0 +1,514 @@
+// src/api/routes/v1/archive.rs
+//! Full data export and import
+//!
+//! `GET /export/all` streams every row of every table (sessions, audio file
+//! records, transcripts, analyses, ideas, tasks, notes and note templates,
+//! soft-deleted rows included) as an archive, reading each table a page at a
+//! time. `POST /import/all` loads such an archive back. Both require the
+//! admin token.
+//!
+//! An archive is a sequence of records: a `header`, one `row` per database
+//! row and a `footer` with the row count, so a truncated download is
+//! detected on import. As JSONL every record is one line; as JSON the records
+//! are wrapped in `{"records": [...]}`. Audio files themselves are not part
+//! of the archive.
+//!
+//! Import is idempotent: rows whose ID already exists are skipped, so an
+//! import that failed halfway can simply be run again.
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{DefaultBodyLimit, Query, State},
+    http::{header, HeaderMap},
+    middleware,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Router,
+};
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::api::{
+    extractors::Json,
+    error::ApiError,
+    responses::ApiResponse,
+    validate_choice, AppState, ApiResult,
+};
+use crate::repository::{
+    traits::{ArchiveEntity, ArchiveRepository},
+    RepositoryManager,
+};
+
+/// Value of `format` in the archive header
+const ARCHIVE_FORMAT: &str = "voice-recorder-archive";
+
+/// Current archive version; older versions are still imported
+const ARCHIVE_VERSION: u32 = 1;
+
+/// Rows read per query on export and inserted per transaction on import
+const PAGE_SIZE: i64 = 500;
+
+const ARCHIVE_FORMATS: &[&str] = &["jsonl", "json"];
+
+/// Create archive routes
+pub fn create_routes<R: RepositoryManager + 'static>() -> Router<AppState<R>> {
+    Router::new()
+        .route(
+            "/export/all",
+            get(export_all).route_layer(middleware::from_fn(crate::api::auth::require_admin_token)),
+        )
+        .route(
+            "/import/all",
+            post(import_all)
+                // JSONL archives are streamed, so they may exceed the usual body limit
+                .layer(DefaultBodyLimit::disable())
+                .route_layer(middleware::from_fn(crate::api::auth::require_admin_token)),
+        )
+}
+
+/// One record of an archive
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ArchiveRecord {
+    Header {
+        format: String,
+        version: u32,
+        exported_at: DateTime<Utc>,
+    },
+    Row {
+        entity: ArchiveEntity,
+        /// The row's columns, as exported by the database
+        data: serde_json::Value,
+    },
+    Footer {
+        /// Number of `row` records in the archive
+        rows: u64,
+    },
+}
+
+/// An archive in the `json` format
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ArchiveDocument {
+    records: Vec<ArchiveRecord>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExportQuery {
+    /// `jsonl` (default) or `json`
+    format: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct ImportCounts {
+    imported: u64,
+    /// Rows skipped because their ID already exists
+    skipped: u64,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct ImportSummary {
+    rows: u64,
+    imported: u64,
+    skipped: u64,
+    entities: HashMap<ArchiveEntity, ImportCounts>,
+}
+
+/// Export all data as a downloadable archive
+async fn export_all<R: RepositoryManager + 'static>(
+    State(state): State<AppState<R>>,
+    Query(query): Query<ExportQuery>,
+) -> ApiResult<Response> {
+    validate_choice("format", query.format.as_deref(), ARCHIVE_FORMATS)?;
+    let json = query.format.as_deref() == Some("json");
+
+    let exported_at = Utc::now();
+    let filename = format!(
+        "voice-recorder-export-{}.{}",
+        exported_at.format("%Y%m%dT%H%M%SZ"),
+        if json { "json" } else { "jsonl" }
+    );
+    let content_type = if json { "application/json" } else { "application/x-ndjson" };
+
+    let cursor = ExportCursor {
+        state,
+        json,
+        exported_at,
+        stage: ExportStage::Header,
+        entity: 0,
+        after: None,
+        rows: 0,
+    };
+    // An error after the first chunk can only abort the download; the
+    // missing footer then marks the archive as incomplete
+    let chunks = futures::stream::unfold(cursor, |mut cursor| async move {
+        match cursor.next_chunk().await {
+            Ok(Some(chunk)) => Some((Ok(Bytes::from(chunk)), cursor)),
+            Ok(None) => None,
+            Err(e) => {
+                tracing::error!("Full export failed: {:#}", e);
+                cursor.stage = ExportStage::Done;
+                Some((Err(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())), cursor))
+            }
+        }
+    });
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, crate::disposition::attachment(&filename)),
+        ],
+        Body::from_stream(chunks),
+    )
+        .into_response())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExportStage {
+    Header,
+    Rows,
+    Done,
+}
+
+/// Position of a running export
+struct ExportCursor<R: RepositoryManager> {
+    state: AppState<R>,
+    json: bool,
+    exported_at: DateTime<Utc>,
+    stage: ExportStage,
+    /// Index into `ArchiveEntity::ALL` of the entity being exported
+    entity: usize,
+    /// ID of the last row exported of that entity
+    after: Option<String>,
+    rows: u64,
+}
+
+impl<R: RepositoryManager> ExportCursor<R> {
+    /// The next piece of the archive: the header, a page of rows or the footer
+    async fn next_chunk(&mut self) -> anyhow::Result<Option<Vec<u8>>> {
+        let mut chunk = Vec::new();
+        match self.stage {
+            ExportStage::Header => {
+                if self.json {
+                    chunk.extend_from_slice(b"{\"records\": [\n");
+                }
+                let header = ArchiveRecord::Header {
+                    format: ARCHIVE_FORMAT.to_string(),
+                    version: ARCHIVE_VERSION,
+                    exported_at: self.exported_at,
+                };
+                self.write_record(&mut chunk, &header, true)?;
+                self.stage = ExportStage::Rows;
+            }
+            ExportStage::Rows => loop {
+                let Some(&entity) = ArchiveEntity::ALL.get(self.entity) else {
+                    self.write_record(&mut chunk, &ArchiveRecord::Footer { rows: self.rows }, false)?;
+                    if self.json {
+                        chunk.extend_from_slice(b"\n]}\n");
+                    }
+                    self.stage = ExportStage::Done;
+                    break;
+                };
+
+                let page = self
+                    .state
+                    .repositories
+                    .archive()
+                    .export_page(entity, self.after.as_deref(), PAGE_SIZE)
+                    .await?;
+                if (page.len() as i64) < PAGE_SIZE {
+                    self.entity += 1;
+                    self.after = None;
+                } else {
+                    let last_id = page.last().and_then(|row| row.get("id")).and_then(|id| id.as_str());
+                    let Some(last_id) = last_id else {
+                        anyhow::bail!("Exported {} row has no string id", entity.table());
+                    };
+                    self.after = Some(last_id.to_string());
+                }
+                if page.is_empty() {
+                    continue;
+                }
+
+                self.rows += page.len() as u64;
+                for data in page {
+                    self.write_record(&mut chunk, &ArchiveRecord::Row { entity, data }, false)?;
+                }
+                break;
+            },
+            ExportStage::Done => return Ok(None),
+        }
+        Ok(Some(chunk))
+    }
+
+    fn write_record(&self, chunk: &mut Vec<u8>, record: &ArchiveRecord, first: bool) -> anyhow::Result<()> {
+        if self.json && !first {
+            chunk.extend_from_slice(b",\n");
+        }
+        serde_json::to_writer(&mut *chunk, record)?;
+        if !self.json {
+            chunk.push(b'\n');
+        }
+        Ok(())
+    }
+}
+
+/// Import an archive produced by `GET /export/all`
+///
+/// `Content-Type: application/json` archives are read whole and must fit in
+/// `server.max_body_size`; anything else is read as JSONL line by line.
+async fn import_all<R: RepositoryManager>(
+    State(state): State<AppState<R>>,
+    headers: HeaderMap,
+    body: Body,
+) -> ApiResult<Json<ApiResponse<ImportSummary>>> {
+    let is_json = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map_or(false, |value| value.starts_with("application/json"));
+
+    let mut importer = Importer::new(&state);
+    if is_json {
+        let max_body_size = crate::config::current().server.max_body_size;
+        let bytes = axum::body::to_bytes(body, max_body_size).await.map_err(|_| {
+            ApiError::BadRequest(format!(
+                "JSON archives are limited to {} bytes; import the archive as JSONL instead",
+                max_body_size
+            ))
+        })?;
+        let document: ArchiveDocument = serde_json::from_slice(&bytes)
+            .map_err(|e| ApiError::UnprocessableEntity(format!("Invalid archive: {}", e)))?;
+        for record in document.records {
+            importer.push(record).await?;
+        }
+    } else {
+        // A single record is held to the same limit as a JSON body
+        let mut lines = LineSplitter::new(crate::config::current().server.max_body_size);
+        let mut stream = body.into_data_stream();
+        let mut line = 0;
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| ApiError::BadRequest(format!("Failed to read archive: {}", e)))?;
+            for text in lines.push(&chunk)? {
+                line += 1;
+                importer.push_line(&text, line).await?;
+            }
+        }
+        importer.push_line(&lines.finish(), line + 1).await?;
+    }
+
+    let summary = importer.finish().await?;
+    Ok(Json(ApiResponse::success(summary)))
+}
+
+/// Splits a byte stream into lines, searching each byte for a newline once
+struct LineSplitter {
+    buffer: Vec<u8>,
+    /// Length of the start of `buffer` known not to contain a newline
+    scanned: usize,
+    max_line_len: usize,
+}
+
+impl LineSplitter {
+    fn new(max_line_len: usize) -> Self {
+        Self {
+            buffer: Vec::new(),
+            scanned: 0,
+            max_line_len,
+        }
+    }
+
+    /// Append `chunk`, returning the lines it completes without their newline
+    ///
+    /// Fails when a line grows longer than `max_line_len`.
+    fn push(&mut self, chunk: &[u8]) -> ApiResult<Vec<Vec<u8>>> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut lines = Vec::new();
+        let mut start = 0;
+        while let Some(newline) = self.buffer[self.scanned..].iter().position(|&b| b == b'\n') {
+            let end = self.scanned + newline;
+            self.check_len(end - start)?;
+            lines.push(self.buffer[start..end].to_vec());
+            start = end + 1;
+            self.scanned = start;
+        }
+        self.buffer.drain(..start);
+        self.scanned = self.buffer.len();
+        self.check_len(self.buffer.len())?;
+        Ok(lines)
+    }
+
+    /// The last line, which has no newline after it
+    fn finish(self) -> Vec<u8> {
+        self.buffer
+    }
+
+    fn check_len(&self, len: usize) -> ApiResult<()> {
+        if len > self.max_line_len {
+            return Err(ApiError::UnprocessableEntity(format!(
+                "Archive record is longer than {} bytes",
+                self.max_line_len
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Feeds archive records into the database in batches of one entity
+struct Importer<'a, R: RepositoryManager> {
+    state: &'a AppState<R>,
+    header_seen: bool,
+    footer_rows: Option<u64>,
+    batch_entity: Option<ArchiveEntity>,
+    batch: Vec<serde_json::Value>,
+    summary: ImportSummary,
+}
+
+impl<'a, R: RepositoryManager> Importer<'a, R> {
+    fn new(state: &'a AppState<R>) -> Self {
+        Self {
+            state,
+            header_seen: false,
+            footer_rows: None,
+            batch_entity: None,
+            batch: Vec::new(),
+            summary: ImportSummary::default(),
+        }
+    }
+
+    /// Import one JSONL line; blank lines are ignored
+    async fn push_line(&mut self, text: &[u8], line: usize) -> ApiResult<()> {
+        if text.iter().all(u8::is_ascii_whitespace) {
+            return Ok(());
+        }
+        let record = serde_json::from_slice(text)
+            .map_err(|e| ApiError::UnprocessableEntity(format!("Line {}: invalid archive record: {}", line, e)))?;
+        self.push(record).await
+    }
+
+    async fn push(&mut self, record: ArchiveRecord) -> ApiResult<()> {
+        if self.footer_rows.is_some() {
+            return Err(ApiError::UnprocessableEntity("Archive has records after its footer".to_string()));
+        }
+        match record {
+            ArchiveRecord::Header { format, version, .. } => {
+                if self.header_seen {
+                    return Err(ApiError::UnprocessableEntity("Archive has more than one header".to_string()));
+                }
+                if format != ARCHIVE_FORMAT {
+                    return Err(ApiError::UnprocessableEntity(format!("Not a {} file", ARCHIVE_FORMAT)));
+                }
+                if version > ARCHIVE_VERSION {
+                    return Err(ApiError::UnprocessableEntity(format!(
+                        "Archive version {} is newer than the supported version {}",
+                        version, ARCHIVE_VERSION
+                    )));
+                }
+                self.header_seen = true;
+            }
+            ArchiveRecord::Row { entity, data } => {
+                if !self.header_seen {
+                    return Err(ApiError::UnprocessableEntity("Archive must start with a header".to_string()));
+                }
+                if !data.is_object() {
+                    return Err(ApiError::UnprocessableEntity(format!(
+                        "Archived {} row is not an object",
+                        entity.table()
+                    )));
+                }
+                if self.batch_entity != Some(entity) || self.batch.len() >= PAGE_SIZE as usize {
+                    self.flush().await?;
+                    self.batch_entity = Some(entity);
+                }
+                self.batch.push(data);
+            }
+            ArchiveRecord::Footer { rows } => {
+                if !self.header_seen {
+                    return Err(ApiError::UnprocessableEntity("Archive must start with a header".to_string()));
+                }
+                self.flush().await?;
+                self.footer_rows = Some(rows);
+            }
+        }
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> ApiResult<()> {
+        let Some(entity) = self.batch_entity else {
+            return Ok(());
+        };
+        if self.batch.is_empty() {
+            return Ok(());
+        }
+
+        let imported = self
+            .state
+            .repositories
+            .archive()
+            .import_rows(entity, &self.batch)
+            .await
+            .map_err(|e| {
+                ApiError::InternalServerError(format!(
+                    "Failed to import {} after {} imported rows: {:#}",
+                    entity.table(),
+                    self.summary.imported,
+                    e
+                ))
+            })?;
+        let rows = self.batch.len() as u64;
+        let skipped = rows - imported;
+
+        self.summary.rows += rows;
+        self.summary.imported += imported;
+        self.summary.skipped += skipped;
+        let counts = self.summary.entities.entry(entity).or_default();
+        counts.imported += imported;
+        counts.skipped += skipped;
+        self.batch.clear();
+        Ok(())
+    }
+
+    /// Import what is left and check the archive was complete
+    async fn finish(mut self) -> ApiResult<ImportSummary> {
+        self.flush().await?;
+        match self.footer_rows {
+            None => Err(ApiError::UnprocessableEntity(format!(
+                "Archive is truncated (no footer); {} rows were imported. Import the complete archive again",
+                self.summary.imported
+            ))),
+            Some(rows) if rows != self.summary.rows => Err(ApiError::UnprocessableEntity(format!(
+                "Archive footer counts {} rows but {} were found",
+                rows, self.summary.rows
+            ))),
+            Some(_) => Ok(self.summary),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_split_across_chunks_are_joined() {
+        let mut lines = LineSplitter::new(100);
+        assert!(lines.push(b"{\"a\":").unwrap().is_empty());
+        assert_eq!(lines.push(b"1}\n{\"b\":2}\n{").unwrap(), vec![b"{\"a\":1}".to_vec(), b"{\"b\":2}".to_vec()]);
+        assert_eq!(lines.push(b"}\n\n").unwrap(), vec![b"{}".to_vec(), Vec::new()]);
+        assert!(lines.push(b"{\"c\":3}").unwrap().is_empty());
+        assert_eq!(lines.finish(), b"{\"c\":3}".to_vec());
+    }
+
+    #[test]
+    fn overlong_lines_are_rejected() {
+        let mut lines = LineSplitter::new(4);
+        assert!(lines.push(b"1234\n12").is_ok());
+        assert!(lines.push(b"345").is_err());
+
+        let mut lines = LineSplitter::new(4);
+        assert!(lines.push(b"12345\n").is_err());
+    }
+}
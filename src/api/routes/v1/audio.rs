@@ -5,7 +5,7 @@
 
 use axum::{
     body::Bytes,
-    extract::{Multipart, Path, Query, State},
+    extract::{Multipart, Path, State},
     http::{header, StatusCode},
     response::{IntoResponse, Response},
     routing::{delete, get, post},
@@ -15,9 +15,10 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::api::{
+    extractors::{Json, ListQueryKeys, StrictQuery},
     error::ApiError,
     responses::{ApiResponse, PaginationParams, SearchParams, SortParams},
-    AppState, ApiResult,
+    body_limit, AppState, ApiResult,
 };
 use crate::repository::{
     traits::{AudioRepository, NewAudioFile},
@@ -26,18 +27,27 @@ use crate::repository::{
 use crate::services::traits::{AudioService, FileStorageService};
 
 /// Create audio routes
-pub fn create_routes<R: RepositoryManager + 'static>() -> Router<AppState<R>> {
+///
+/// Upload requests may carry up to `max_upload_size` bytes; the other routes
+/// are held to `max_body_size`.
+pub fn create_routes<R: RepositoryManager + 'static>(max_body_size: usize, max_upload_size: usize) -> Router<AppState<R>> {
+    let uploads = Router::new()
+        .route("/", post(upload_audio))
+        .route("/upload/chunk", post(upload_audio_chunk))
+        .layer(body_limit(max_upload_size));
+
     Router::new()
-        .route("/", get(list_audio_files).post(upload_audio))
+        .route("/", get(list_audio_files))
         .route("/:id", get(get_audio_file).delete(delete_audio_file))
         .route("/:id/download", get(download_audio_file))
         .route("/:id/stream", get(stream_audio_file))
         .route("/:id/metadata", get(get_audio_metadata))
         .route("/:id/transcribe", post(transcribe_audio))
-        .route("/upload/chunk", post(upload_audio_chunk))
         .route("/upload/complete", post(complete_chunked_upload))
         .route("/formats", get(get_supported_formats))
         .route("/stats", get(get_audio_stats))
+        .layer(body_limit(max_body_size))
+        .merge(uploads)
 }
 
 #[derive(Debug, Deserialize)]
@@ -56,6 +66,17 @@ struct AudioListQuery {
     uploaded_before: Option<chrono::DateTime<chrono::Utc>>,
 }
 
+impl ListQueryKeys for AudioListQuery {
+    const KEYS: &'static [&'static str] = &[
+        "session_id",
+        "format",
+        "min_duration",
+        "max_duration",
+        "uploaded_after",
+        "uploaded_before",
+    ];
+}
+
 #[derive(Debug, Serialize)]
 struct AudioFileResponse {
     id: Uuid,
@@ -78,6 +99,7 @@ struct AudioFileResponse {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct TranscribeRequest {
     provider: Option<String>, // "openai" or "local"
     language: Option<String>,
@@ -87,6 +109,7 @@ struct TranscribeRequest {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct ChunkUploadRequest {
     upload_id: String,
     chunk_number: i32,
@@ -96,6 +119,7 @@ struct ChunkUploadRequest {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct CompleteUploadRequest {
     upload_id: String,
     filename: String,
@@ -127,7 +151,7 @@ struct SupportedFormatsResponse {
 /// List audio files with filtering and pagination
 async fn list_audio_files<R: RepositoryManager>(
     State(state): State<AppState<R>>,
-    Query(query): Query<AudioListQuery>,
+    StrictQuery(query): StrictQuery<AudioListQuery>,
 ) -> ApiResult<Json<ApiResponse<Vec<AudioFileResponse>>>> {
     let audio_files = state.repositories.audio()
         .find_with_filters(
@@ -180,12 +204,7 @@ async fn list_audio_files<R: RepositoryManager>(
         })
         .collect();
 
-    Ok(Json(ApiResponse {
-        data: responses,
-        total: Some(total),
-        page: Some(query.pagination.page()),
-        per_page: Some(query.pagination.limit),
-    }))
+    Ok(Json(ApiResponse::paginated(responses, total, query.pagination.page(), query.pagination.limit)))
 }
 
 /// Upload an audio file
@@ -240,19 +259,20 @@ async fn upload_audio<R: RepositoryManager>(
         .ok_or_else(|| ApiError::BadRequest("Unable to determine file format".to_string()))?
         .to_lowercase();
 
-    if !state.config.storage.allowed_formats.contains(&format) {
+    let config = state.config.current();
+    if !config.storage.allowed_formats.contains(&format) {
         return Err(ApiError::BadRequest(format!(
             "Unsupported file format: {}. Allowed formats: {:?}",
-            format, state.config.storage.allowed_formats
+            format, config.storage.allowed_formats
         )));
     }
 
     // Validate file size
-    if data.len() as u64 > state.config.storage.max_file_size {
+    if data.len() as u64 > config.storage.max_file_size {
         return Err(ApiError::BadRequest(format!(
             "File size ({} bytes) exceeds maximum allowed size ({} bytes)",
             data.len(),
-            state.config.storage.max_file_size
+            config.storage.max_file_size
         )));
     }
 
@@ -311,12 +331,7 @@ async fn upload_audio<R: RepositoryManager>(
         updated_at: audio_file.updated_at,
     };
 
-    Ok(Json(ApiResponse {
-        data: response,
-        total: None,
-        page: None,
-        per_page: None,
-    }))
+    Ok(Json(ApiResponse::success(response)))
 }
 
 /// Get a specific audio file by ID
@@ -350,12 +365,7 @@ async fn get_audio_file<R: RepositoryManager>(
         updated_at: audio_file.updated_at,
     };
 
-    Ok(Json(ApiResponse {
-        data: response,
-        total: None,
-        page: None,
-        per_page: None,
-    }))
+    Ok(Json(ApiResponse::success(response)))
 }
 
 /// Delete an audio file
@@ -408,7 +418,8 @@ async fn download_audio_file<R: RepositoryManager>(
             (header::CONTENT_TYPE, content_type),
             (
                 header::CONTENT_DISPOSITION,
-                &format!("attachment; filename=\"{}\"", audio_file.original_filename),
+                // The original name is whatever the uploader sent
+                &crate::disposition::attachment(&audio_file.original_filename),
             ),
             (header::CONTENT_LENGTH, &file_data.len().to_string()),
         ],
@@ -531,9 +542,10 @@ async fn complete_chunked_upload<R: RepositoryManager>(
 async fn get_supported_formats<R: RepositoryManager>(
     State(state): State<AppState<R>>,
 ) -> ApiResult<Json<SupportedFormatsResponse>> {
+    let config = state.config.current();
     Ok(Json(SupportedFormatsResponse {
-        audio_formats: state.config.storage.allowed_formats.clone(),
-        max_file_size_mb: state.config.storage.max_file_size / (1024 * 1024),
+        audio_formats: config.storage.allowed_formats.clone(),
+        max_file_size_mb: config.storage.max_file_size / (1024 * 1024),
         max_duration_hours: 24.0, // TODO: Make this configurable
         supported_sample_rates: vec![8000, 16000, 22050, 44100, 48000, 96000],
         supported_channels: vec![1, 2],
@@ -5,8 +5,8 @@
 
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     routing::{delete, get, patch, post},
     Router,
 };
@@ -14,12 +14,14 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::api::{
+    extractors::{Json, ListQueryKeys, StrictQuery},
     error::ApiError,
+    idempotency::{idempotency_key, mismatch_error, replay_response, FingerprintedJson, IdempotencyStatus},
     responses::{ApiResponse, PaginationParams, SearchParams, SortParams},
-    AppState, ApiResult,
+    page_relations, rate, stats_error, validate_choice, AppState, ApiResult, DeleteQuery, StatsQuery,
 };
 use crate::repository::{
-    traits::{IdeaRepository, NewIdea, UpdateIdea},
+    traits::{AnalysisRepository, IdeaRepository, NewIdea, SessionRepository, UpdateIdea},
     RepositoryManager,
 };
 use crate::services::traits::IdeaService;
@@ -29,6 +31,7 @@ pub fn create_routes<R: RepositoryManager + 'static>() -> Router<AppState<R>> {
     Router::new()
         .route("/", get(list_ideas).post(create_idea))
         .route("/:id", get(get_idea).patch(update_idea).delete(delete_idea))
+        .route("/:id/restore", post(restore_idea))
         .route("/:id/export", get(export_idea))
         .route("/session/:session_id", get(list_session_ideas))
         .route("/analysis/:analysis_id", get(list_analysis_ideas))
@@ -42,6 +45,7 @@ pub fn create_routes<R: RepositoryManager + 'static>() -> Router<AppState<R>> {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct CreateIdeaRequest {
     session_id: Uuid,
     analysis_id: Option<Uuid>,
@@ -57,6 +61,7 @@ struct CreateIdeaRequest {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct UpdateIdeaRequest {
     title: Option<String>,
     description: Option<String>,
@@ -69,6 +74,23 @@ struct UpdateIdeaRequest {
     metadata: Option<serde_json::Value>,
 }
 
+const IDEA_PRIORITIES: &[&str] = &["low", "medium", "high", "critical"];
+const IDEA_STATUSES: &[&str] = &["new", "in_progress", "completed", "archived"];
+
+impl CreateIdeaRequest {
+    fn validate(&self) -> ApiResult<()> {
+        validate_choice("priority", self.priority.as_deref(), IDEA_PRIORITIES)?;
+        validate_choice("status", self.status.as_deref(), IDEA_STATUSES)
+    }
+}
+
+impl UpdateIdeaRequest {
+    fn validate(&self) -> ApiResult<()> {
+        validate_choice("priority", self.priority.as_deref(), IDEA_PRIORITIES)?;
+        validate_choice("status", self.status.as_deref(), IDEA_STATUSES)
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct IdeasListQuery {
     #[serde(flatten)]
@@ -86,19 +108,38 @@ struct IdeasListQuery {
     min_confidence: Option<f64>,
     created_after: Option<chrono::DateTime<chrono::Utc>>,
     created_before: Option<chrono::DateTime<chrono::Utc>>,
+    include_deleted: Option<bool>,
+}
+
+impl ListQueryKeys for IdeasListQuery {
+    const KEYS: &'static [&'static str] = &[
+        "session_id",
+        "analysis_id",
+        "category",
+        "priority",
+        "status",
+        "tags",
+        "min_confidence",
+        "created_after",
+        "created_before",
+        "include_deleted",
+    ];
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct BatchCreateIdeasRequest {
     ideas: Vec<CreateIdeaRequest>,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct BatchDeleteIdeasRequest {
     idea_ids: Vec<Uuid>,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct MergeIdeasRequest {
     source_idea_ids: Vec<Uuid>,
     target_idea: CreateIdeaRequest,
@@ -121,6 +162,7 @@ struct IdeaResponse {
     metadata: Option<serde_json::Value>,
     created_at: chrono::DateTime<chrono::Utc>,
     updated_at: chrono::DateTime<chrono::Utc>,
+    deleted_at: Option<chrono::DateTime<chrono::Utc>>,
     // Related data
     session_title: Option<String>,
     analysis_type: Option<String>,
@@ -145,6 +187,9 @@ struct DailyCount {
     count: i64,
 }
 
+/// Number of categories listed in `top_categories`
+const TOP_CATEGORIES: usize = 5;
+
 #[derive(Debug, Serialize)]
 struct CategoryStats {
     category: String,
@@ -212,7 +257,7 @@ struct TagInfo {
 /// List ideas with filtering and pagination
 async fn list_ideas<R: RepositoryManager>(
     State(state): State<AppState<R>>,
-    Query(query): Query<IdeasListQuery>,
+    StrictQuery(query): StrictQuery<IdeasListQuery>,
 ) -> ApiResult<Json<ApiResponse<Vec<IdeaResponse>>>> {
     let tags_filter = query.tags.as_ref().map(|t| {
         t.split(',')
@@ -232,6 +277,7 @@ async fn list_ideas<R: RepositoryManager>(
             query.created_after,
             query.created_before,
             query.search.q.as_deref(),
+            query.include_deleted.unwrap_or(false),
             Some(query.pagination.limit),
             Some(query.pagination.offset),
             query.sort.sort_by.as_deref(),
@@ -252,28 +298,24 @@ async fn list_ideas<R: RepositoryManager>(
             query.created_after,
             query.created_before,
             query.search.q.as_deref(),
+            query.include_deleted.unwrap_or(false),
         )
         .await
         .map_err(|e| ApiError::InternalServerError(format!("Failed to count ideas: {}", e)))?;
 
-    // Enrich with related data
+    // Enrich with related data, loaded for the whole page at once
+    let (sessions, analyses) = page_relations(
+        state.repositories.session(),
+        state.repositories.analysis(),
+        ideas.iter().map(|idea| idea.session_id),
+        ideas.iter().filter_map(|idea| idea.analysis_id),
+    )
+    .await;
+
     let mut responses = Vec::new();
     for idea in ideas {
-        let session = state.repositories.session()
-            .find_by_id(idea.session_id)
-            .await
-            .ok()
-            .flatten();
-        
-        let analysis = if let Some(analysis_id) = idea.analysis_id {
-            state.repositories.analysis()
-                .find_by_id(analysis_id)
-                .await
-                .ok()
-                .flatten()
-        } else {
-            None
-        };
+        let session = sessions.get(&idea.session_id);
+        let analysis = idea.analysis_id.and_then(|analysis_id| analyses.get(&analysis_id));
 
         responses.push(IdeaResponse {
             id: idea.id,
@@ -290,17 +332,13 @@ async fn list_ideas<R: RepositoryManager>(
             metadata: idea.metadata,
             created_at: idea.created_at,
             updated_at: idea.updated_at,
-            session_title: session.and_then(|s| s.title),
-            analysis_type: analysis.map(|a| a.analysis_type),
+            deleted_at: idea.deleted_at,
+            session_title: session.and_then(|s| s.title.clone()),
+            analysis_type: analysis.map(|a| a.analysis_type.clone()),
         });
     }
 
-    Ok(Json(ApiResponse {
-        data: responses,
-        total: Some(total),
-        page: Some(query.pagination.page()),
-        per_page: Some(query.pagination.limit),
-    }))
+    Ok(Json(ApiResponse::paginated(responses, total, query.pagination.page(), query.pagination.limit)))
 }
 
 /// Create a new idea
@@ -308,6 +346,8 @@ async fn create_idea<R: RepositoryManager>(
     State(state): State<AppState<R>>,
     Json(request): Json<CreateIdeaRequest>,
 ) -> ApiResult<Json<ApiResponse<IdeaResponse>>> {
+    request.validate()?;
+
     // Validate session exists
     let _session = state.repositories.session()
         .find_by_id(request.session_id)
@@ -374,16 +414,12 @@ async fn create_idea<R: RepositoryManager>(
         metadata: idea.metadata,
         created_at: idea.created_at,
         updated_at: idea.updated_at,
+        deleted_at: idea.deleted_at,
         session_title: session.and_then(|s| s.title),
         analysis_type: analysis.map(|a| a.analysis_type),
     };
 
-    Ok(Json(ApiResponse {
-        data: response,
-        total: None,
-        page: None,
-        per_page: None,
-    }))
+    Ok(Json(ApiResponse::success(response)))
 }
 
 /// Get a specific idea by ID
@@ -428,16 +464,12 @@ async fn get_idea<R: RepositoryManager>(
         metadata: idea.metadata,
         created_at: idea.created_at,
         updated_at: idea.updated_at,
+        deleted_at: idea.deleted_at,
         session_title: session.and_then(|s| s.title),
         analysis_type: analysis.map(|a| a.analysis_type),
     };
 
-    Ok(Json(ApiResponse {
-        data: response,
-        total: None,
-        page: None,
-        per_page: None,
-    }))
+    Ok(Json(ApiResponse::success(response)))
 }
 
 /// Update an idea
@@ -446,6 +478,8 @@ async fn update_idea<R: RepositoryManager>(
     Path(id): Path<Uuid>,
     Json(request): Json<UpdateIdeaRequest>,
 ) -> ApiResult<Json<ApiResponse<IdeaResponse>>> {
+    request.validate()?;
+
     // Check if idea exists
     let _idea = state.repositories.idea()
         .find_by_id(id)
@@ -501,23 +535,31 @@ async fn update_idea<R: RepositoryManager>(
         metadata: updated_idea.metadata,
         created_at: updated_idea.created_at,
         updated_at: updated_idea.updated_at,
+        deleted_at: updated_idea.deleted_at,
         session_title: session.and_then(|s| s.title),
         analysis_type: analysis.map(|a| a.analysis_type),
     };
 
-    Ok(Json(ApiResponse {
-        data: response,
-        total: None,
-        page: None,
-        per_page: None,
-    }))
+    Ok(Json(ApiResponse::success(response)))
 }
 
-/// Delete an idea
+/// Soft-delete an idea; `?purge=true` deletes it permanently
 async fn delete_idea<R: RepositoryManager>(
     State(state): State<AppState<R>>,
     Path(id): Path<Uuid>,
+    Query(query): Query<DeleteQuery>,
 ) -> ApiResult<StatusCode> {
+    if query.purge {
+        let purged = state.repositories.idea()
+            .purge(id)
+            .await
+            .map_err(|e| ApiError::InternalServerError(format!("Failed to purge idea: {}", e)))?;
+        if !purged {
+            return Err(ApiError::NotFound("Idea not found".to_string()));
+        }
+        return Ok(StatusCode::NO_CONTENT);
+    }
+
     // Check if idea exists
     let _idea = state.repositories.idea()
         .find_by_id(id)
@@ -533,11 +575,28 @@ async fn delete_idea<R: RepositoryManager>(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Restore a soft-deleted idea
+async fn restore_idea<R: RepositoryManager>(
+    State(state): State<AppState<R>>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<ApiResponse<IdeaResponse>>> {
+    let restored = state.repositories.idea()
+        .restore(id)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to restore idea: {}", e)))?;
+    if !restored {
+        return Err(ApiError::NotFound("Deleted idea not found".to_string()));
+    }
+
+    get_idea(State(state), Path(id)).await
+}
+
 /// Export idea
 async fn export_idea<R: RepositoryManager>(
     State(state): State<AppState<R>>,
     Path(id): Path<Uuid>,
     Query(params): Query<std::collections::HashMap<String, String>>,
+    headers: HeaderMap,
 ) -> ApiResult<axum::response::Response> {
     let idea = state.repositories.idea()
         .find_by_id(id)
@@ -604,46 +663,53 @@ async fn export_idea<R: RepositoryManager>(
         }
     };
 
-    Ok((
-        [
-            (axum::http::header::CONTENT_TYPE, content_type),
-            (
-                axum::http::header::CONTENT_DISPOSITION,
-                &format!("attachment; filename=\"{}\"", filename),
-            ),
-        ],
-        content,
-    )
-        .into_response())
+    Ok(crate::etag::content_response(&headers, content_type, Some(&filename), content))
 }
 
 /// List ideas for a specific session
 async fn list_session_ideas<R: RepositoryManager>(
     State(state): State<AppState<R>>,
     Path(session_id): Path<Uuid>,
-    Query(query): Query<IdeasListQuery>,
+    StrictQuery(query): StrictQuery<IdeasListQuery>,
 ) -> ApiResult<Json<ApiResponse<Vec<IdeaResponse>>>> {
     let mut modified_query = query;
     modified_query.session_id = Some(session_id);
-    list_ideas(State(state), Query(modified_query)).await
+    list_ideas(State(state), StrictQuery(modified_query)).await
 }
 
 /// List ideas for a specific analysis
 async fn list_analysis_ideas<R: RepositoryManager>(
     State(state): State<AppState<R>>,
     Path(analysis_id): Path<Uuid>,
-    Query(query): Query<IdeasListQuery>,
+    StrictQuery(query): StrictQuery<IdeasListQuery>,
 ) -> ApiResult<Json<ApiResponse<Vec<IdeaResponse>>>> {
     let mut modified_query = query;
     modified_query.analysis_id = Some(analysis_id);
-    list_ideas(State(state), Query(modified_query)).await
+    list_ideas(State(state), StrictQuery(modified_query)).await
 }
 
 /// Batch create ideas
+///
+/// Honors the `Idempotency-Key` header so a retried batch is not inserted twice.
 async fn batch_create_ideas<R: RepositoryManager>(
     State(state): State<AppState<R>>,
-    Json(request): Json<BatchCreateIdeasRequest>,
-) -> ApiResult<Json<BatchCreateResponse>> {
+    headers: HeaderMap,
+    FingerprintedJson { body: request, fingerprint }: FingerprintedJson<BatchCreateIdeasRequest>,
+) -> ApiResult<Response> {
+    let mut claim = None;
+    if let Some(key) = idempotency_key(&headers)? {
+        match state.idempotency.begin("ideas:batch_create", &key, &fingerprint) {
+            IdempotencyStatus::Replay(body) => return Ok(replay_response(body)),
+            IdempotencyStatus::InFlight => {
+                return Err(ApiError::Conflict(
+                    "A request with this Idempotency-Key is already being processed".to_string(),
+                ));
+            }
+            IdempotencyStatus::Mismatch => return Err(mismatch_error()),
+            IdempotencyStatus::New(new_claim) => claim = Some(new_claim),
+        }
+    }
+
     let mut created_ideas = Vec::new();
     let mut errors = Vec::new();
     let mut created_count = 0;
@@ -662,12 +728,18 @@ async fn batch_create_ideas<R: RepositoryManager>(
         }
     }
 
-    Ok(Json(BatchCreateResponse {
+    let response = BatchCreateResponse {
         created: created_count,
         failed: failed_count,
         ideas: created_ideas,
         errors,
-    }))
+    };
+
+    if let Some(claim) = claim {
+        claim.complete(&response);
+    }
+
+    Ok(Json(response).into_response())
 }
 
 /// Batch delete ideas
@@ -699,22 +771,58 @@ async fn batch_delete_ideas<R: RepositoryManager>(
 /// Search ideas
 async fn search_ideas<R: RepositoryManager>(
     State(state): State<AppState<R>>,
-    Query(query): Query<IdeasListQuery>,
+    StrictQuery(query): StrictQuery<IdeasListQuery>,
 ) -> ApiResult<Json<ApiResponse<Vec<IdeaResponse>>>> {
     // Enhanced search functionality
-    list_ideas(State(state), Query(query)).await
+    list_ideas(State(state), StrictQuery(query)).await
 }
 
 /// Get ideas statistics
+///
+/// `tz` selects the time zone of the `ideas_per_day` buckets and `days` their
+/// number; everything else covers all ideas.
 async fn ideas_stats<R: RepositoryManager>(
     State(state): State<AppState<R>>,
+    Query(query): Query<StatsQuery>,
 ) -> ApiResult<Json<IdeasStatsResponse>> {
-    let stats = state.services.idea()
-        .get_ideas_stats()
+    let window = query.window()?;
+    let stats = state.repositories.idea()
+        .stats(&window)
         .await
-        .map_err(|e| ApiError::InternalServerError(format!("Failed to get ideas stats: {}", e)))?;
+        .map_err(|e| stats_error(e, "ideas"))?;
+
+    let categories = stats
+        .categories
+        .iter()
+        .map(|category| (category.category.clone(), category.count))
+        .collect();
+    let top_categories = stats
+        .categories
+        .into_iter()
+        .take(TOP_CATEGORIES)
+        .map(|category| CategoryStats {
+            completion_rate: rate(category.completed, category.count),
+            avg_confidence: category.avg_confidence.unwrap_or(0.0),
+            category: category.category,
+            count: category.count,
+        })
+        .collect();
 
-    Ok(Json(stats))
+    Ok(Json(IdeasStatsResponse {
+        total_ideas: stats.total,
+        categories,
+        priorities: stats.by_priority,
+        statuses: stats.by_status,
+        tags: stats.by_tag,
+        avg_confidence_score: stats.avg_confidence.unwrap_or(0.0),
+        ideas_per_day: stats
+            .per_day
+            .into_iter()
+            .map(|day| DailyCount { date: day.date, count: day.count })
+            .collect(),
+        completion_rate: rate(stats.completed, stats.total),
+        top_categories,
+    }))
 }
 
 /// Get idea categories
@@ -766,6 +874,8 @@ async fn merge_ideas<R: RepositoryManager>(
     State(state): State<AppState<R>>,
     Json(request): Json<MergeIdeasRequest>,
 ) -> ApiResult<Json<MergeIdeasResponse>> {
+    request.target_idea.validate()?;
+
     // Validate source ideas exist
     let mut source_ideas = Vec::new();
     for idea_id in &request.source_idea_ids {
@@ -819,6 +929,8 @@ async fn create_idea_internal<R: RepositoryManager>(
     state: &AppState<R>,
     request: CreateIdeaRequest,
 ) -> Result<IdeaResponse, ApiError> {
+    request.validate()?;
+
     // Validate session exists
     let _session = state.repositories.session()
         .find_by_id(request.session_id)
@@ -885,6 +997,7 @@ async fn create_idea_internal<R: RepositoryManager>(
         metadata: idea.metadata,
         created_at: idea.created_at,
         updated_at: idea.updated_at,
+        deleted_at: idea.deleted_at,
         session_title: session.and_then(|s| s.title),
         analysis_type: analysis.map(|a| a.analysis_type),
     })
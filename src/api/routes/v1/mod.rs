@@ -11,20 +11,36 @@ pub mod ideas;
 pub mod tasks;
 pub mod notes;
 pub mod ollama;
+pub mod archive;
 
 use axum::Router;
-use crate::api::AppState;
+use crate::api::{body_limit, AppState};
+use crate::config::Config;
 use crate::repository::RepositoryManager;
 
+/// Bytes allowed in an upload request on top of `storage.max_file_size`
+const MULTIPART_OVERHEAD: usize = 64 * 1024;
+
 /// Create all v1 API routes
-pub fn create_routes<R: RepositoryManager + 'static>() -> Router<AppState<R>> {
+///
+/// Request bodies are limited to `server.max_body_size`. Audio routes set
+/// their own limits, and the archive import reads its body itself.
+pub fn create_routes<R: RepositoryManager + 'static>(config: &Config) -> Router<AppState<R>> {
+    // Leave room for the multipart boundaries and form fields around the file
+    let max_upload_size = usize::try_from(config.storage.max_file_size)
+        .unwrap_or(usize::MAX)
+        .saturating_add(MULTIPART_OVERHEAD);
+
     Router::new()
         .nest("/sessions", sessions::create_routes())
-        .nest("/audio", audio::create_routes())
         .nest("/transcripts", transcripts::create_routes())
         .nest("/analysis", analysis::create_routes())
         .nest("/ideas", ideas::create_routes())
         .nest("/tasks", tasks::create_routes())
         .nest("/notes", notes::create_routes())
         .nest("/ollama", ollama::create_routes())
+        // Only covers the routes added above
+        .layer(body_limit(config.server.max_body_size))
+        .nest("/audio", audio::create_routes(config.server.max_body_size, max_upload_size))
+        .merge(archive::create_routes())
 }
\ No newline at end of file
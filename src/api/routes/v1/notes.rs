@@ -5,30 +5,40 @@
 
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     routing::{delete, get, patch, post},
     Router,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 use crate::api::{
+    extractors::{Json, ListQueryKeys, StrictQuery},
     error::ApiError,
+    note_generation,
+    outline,
+    schema,
+    idempotency::{idempotency_key, mismatch_error, replay_response, FingerprintedJson, IdempotencyStatus},
     responses::{ApiResponse, PaginationParams, SearchParams, SortParams},
-    AppState, ApiResult,
+    page_relations, stats_error, validate_choice, AppState, ApiResult, DeleteQuery, StatsQuery,
 };
 use crate::repository::{
-    traits::{StructuredNoteRepository, NewStructuredNote, UpdateStructuredNote},
+    traits::{
+        AnalysisRepository, NewNoteTemplate, NewStructuredNote, NoteTemplateRepository, NoteTemplateUpdate,
+        SessionRepository, StructuredNoteRepository, TranscriptRepository, UpdateStructuredNote,
+    },
     RepositoryManager,
 };
-use crate::services::traits::StructuredNoteService;
+use crate::services::traits::{OllamaService, StructuredNoteService};
 
 /// Create structured notes routes
 pub fn create_routes<R: RepositoryManager + 'static>() -> Router<AppState<R>> {
     Router::new()
         .route("/", get(list_notes).post(create_note))
         .route("/:id", get(get_note).patch(update_note).delete(delete_note))
+        .route("/:id/restore", post(restore_note))
         .route("/:id/export", get(export_note))
         .route("/:id/share", post(share_note))
         .route("/session/:session_id", get(list_session_notes))
@@ -36,7 +46,11 @@ pub fn create_routes<R: RepositoryManager + 'static>() -> Router<AppState<R>> {
         .route("/batch", post(batch_create_notes).delete(batch_delete_notes))
         .route("/search", get(search_notes))
         .route("/stats", get(notes_stats))
-        .route("/templates", get(get_note_templates))
+        .route("/templates", get(list_note_templates).post(create_note_template))
+        .route(
+            "/templates/:template_id",
+            get(get_note_template).patch(update_note_template).delete(delete_note_template),
+        )
         .route("/tags", get(get_note_tags))
         .route("/merge", post(merge_notes))
         .route("/duplicate", post(find_duplicate_notes))
@@ -44,6 +58,7 @@ pub fn create_routes<R: RepositoryManager + 'static>() -> Router<AppState<R>> {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct CreateNoteRequest {
     session_id: Uuid,
     analysis_id: Option<Uuid>,
@@ -54,9 +69,12 @@ struct CreateNoteRequest {
     tags: Option<Vec<String>>,
     is_public: Option<bool>,
     metadata: Option<serde_json::Value>,
+    /// Store `content` without checking it against the template's schema
+    free_form: Option<bool>,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct UpdateNoteRequest {
     title: Option<String>,
     content: Option<serde_json::Value>,
@@ -65,6 +83,87 @@ struct UpdateNoteRequest {
     tags: Option<Vec<String>>,
     is_public: Option<bool>,
     metadata: Option<serde_json::Value>,
+    /// Store `content` without checking it against the template's schema
+    free_form: Option<bool>,
+}
+
+const NOTE_TYPES: &[&str] = &["summary", "meeting_notes", "research", "custom"];
+
+impl CreateNoteRequest {
+    fn validate(&self) -> ApiResult<()> {
+        validate_choice("note_type", Some(&self.note_type), NOTE_TYPES)
+    }
+
+    /// Check the content against the template's schema
+    async fn validate_content<R: RepositoryManager>(&self, state: &AppState<R>) -> ApiResult<()> {
+        validate_note_content(state, self.template_id.as_deref(), &self.content, self.free_form.unwrap_or(false)).await
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CreateTemplateRequest {
+    id: String,
+    name: String,
+    description: Option<String>,
+    note_type: String,
+    /// JSON schema the content of notes using the template must match
+    schema: serde_json::Value,
+    default_content: Option<serde_json::Value>,
+    sections: Option<Vec<TemplateSection>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct UpdateTemplateRequest {
+    name: Option<String>,
+    description: Option<String>,
+    note_type: Option<String>,
+    schema: Option<serde_json::Value>,
+    default_content: Option<serde_json::Value>,
+    sections: Option<Vec<TemplateSection>>,
+}
+
+/// Longest accepted template ID, matching the `note_templates.id` column
+const MAX_TEMPLATE_ID_LEN: usize = 64;
+
+impl CreateTemplateRequest {
+    fn validate(&self) -> ApiResult<()> {
+        let valid_id = !self.id.is_empty()
+            && self.id.len() <= MAX_TEMPLATE_ID_LEN
+            && self.id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+        if !valid_id {
+            return Err(ApiError::UnprocessableEntity(format!(
+                "id must be 1-{} lowercase letters, digits, '_' or '-'",
+                MAX_TEMPLATE_ID_LEN
+            )));
+        }
+        if self.name.trim().is_empty() {
+            return Err(ApiError::UnprocessableEntity("name must not be empty".to_string()));
+        }
+        validate_choice("note_type", Some(&self.note_type), NOTE_TYPES)?;
+        validate_template_schema(&self.schema, self.default_content.as_ref().unwrap_or(&serde_json::json!({})))
+    }
+}
+
+impl UpdateTemplateRequest {
+    /// Validate the update of `template`, including the schema and default
+    /// content it ends up with, the same way a new template is validated
+    fn validate(&self, template: &crate::repository::traits::NoteTemplate) -> ApiResult<()> {
+        if self.name.as_deref().map_or(false, |name| name.trim().is_empty()) {
+            return Err(ApiError::UnprocessableEntity("name must not be empty".to_string()));
+        }
+        validate_choice("note_type", self.note_type.as_deref(), NOTE_TYPES)?;
+        let schema = self.schema.as_ref().unwrap_or(&template.schema);
+        let default_content = self.default_content.as_ref().unwrap_or(&template.default_content);
+        validate_template_schema(schema, default_content)
+    }
+}
+
+impl UpdateNoteRequest {
+    fn validate(&self) -> ApiResult<()> {
+        validate_choice("note_type", self.note_type.as_deref(), NOTE_TYPES)
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -83,19 +182,37 @@ struct NotesListQuery {
     is_public: Option<bool>,
     created_after: Option<chrono::DateTime<chrono::Utc>>,
     created_before: Option<chrono::DateTime<chrono::Utc>>,
+    include_deleted: Option<bool>,
+}
+
+impl ListQueryKeys for NotesListQuery {
+    const KEYS: &'static [&'static str] = &[
+        "session_id",
+        "analysis_id",
+        "note_type",
+        "template_id",
+        "tags",
+        "is_public",
+        "created_after",
+        "created_before",
+        "include_deleted",
+    ];
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct BatchCreateNotesRequest {
     notes: Vec<CreateNoteRequest>,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct BatchDeleteNotesRequest {
     note_ids: Vec<Uuid>,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct ShareNoteRequest {
     share_type: String, // "public", "link", "email"
     expires_at: Option<chrono::DateTime<chrono::Utc>>,
@@ -104,6 +221,7 @@ struct ShareNoteRequest {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct MergeNotesRequest {
     source_note_ids: Vec<Uuid>,
     target_note: CreateNoteRequest,
@@ -112,6 +230,7 @@ struct MergeNotesRequest {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct GenerateNoteRequest {
     analysis_id: Uuid,
     template_id: Option<String>,
@@ -136,6 +255,7 @@ struct NoteResponse {
     metadata: Option<serde_json::Value>,
     created_at: chrono::DateTime<chrono::Utc>,
     updated_at: chrono::DateTime<chrono::Utc>,
+    deleted_at: Option<chrono::DateTime<chrono::Utc>>,
     // Related data
     session_title: Option<String>,
     analysis_type: Option<String>,
@@ -162,6 +282,9 @@ struct DailyCount {
     count: i64,
 }
 
+/// Number of templates listed in `popular_templates`
+const POPULAR_TEMPLATES: i64 = 5;
+
 #[derive(Debug, Serialize)]
 struct TemplateStats {
     template_id: String,
@@ -228,7 +351,8 @@ struct NoteTemplate {
     is_system: bool,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct TemplateSection {
     id: String,
     name: String,
@@ -253,7 +377,7 @@ struct TagInfo {
 /// List structured notes with filtering and pagination
 async fn list_notes<R: RepositoryManager>(
     State(state): State<AppState<R>>,
-    Query(query): Query<NotesListQuery>,
+    StrictQuery(query): StrictQuery<NotesListQuery>,
 ) -> ApiResult<Json<ApiResponse<Vec<NoteResponse>>>> {
     let tags_filter = query.tags.as_ref().map(|t| {
         t.split(',')
@@ -272,6 +396,7 @@ async fn list_notes<R: RepositoryManager>(
             query.created_after,
             query.created_before,
             query.search.q.as_deref(),
+            query.include_deleted.unwrap_or(false),
             Some(query.pagination.limit),
             Some(query.pagination.offset),
             query.sort.sort_by.as_deref(),
@@ -291,28 +416,26 @@ async fn list_notes<R: RepositoryManager>(
             query.created_after,
             query.created_before,
             query.search.q.as_deref(),
+            query.include_deleted.unwrap_or(false),
         )
         .await
         .map_err(|e| ApiError::InternalServerError(format!("Failed to count notes: {}", e)))?;
 
-    // Enrich with related data
+    // Enrich with related data, loaded for the whole page at once
+    let (template_names, (sessions, analyses)) = futures::join!(
+        template_names(&state),
+        page_relations(
+            state.repositories.session(),
+            state.repositories.analysis(),
+            notes.iter().map(|note| note.session_id),
+            notes.iter().filter_map(|note| note.analysis_id),
+        ),
+    );
+
     let mut responses = Vec::new();
     for note in notes {
-        let session = state.repositories.session()
-            .find_by_id(note.session_id)
-            .await
-            .ok()
-            .flatten();
-        
-        let analysis = if let Some(analysis_id) = note.analysis_id {
-            state.repositories.analysis()
-                .find_by_id(analysis_id)
-                .await
-                .ok()
-                .flatten()
-        } else {
-            None
-        };
+        let session = sessions.get(&note.session_id);
+        let analysis = note.analysis_id.and_then(|analysis_id| analyses.get(&analysis_id));
 
         responses.push(NoteResponse {
             id: note.id,
@@ -329,18 +452,14 @@ async fn list_notes<R: RepositoryManager>(
             metadata: note.metadata,
             created_at: note.created_at,
             updated_at: note.updated_at,
-            session_title: session.and_then(|s| s.title),
-            analysis_type: analysis.map(|a| a.analysis_type),
-            template_name: note.template_id.clone(), // Would need template lookup for actual name
+            deleted_at: note.deleted_at,
+            session_title: session.and_then(|s| s.title.clone()),
+            analysis_type: analysis.map(|a| a.analysis_type.clone()),
+            template_name: note.template_id.as_ref().and_then(|id| template_names.get(id).cloned()),
         });
     }
 
-    Ok(Json(ApiResponse {
-        data: responses,
-        total: Some(total),
-        page: Some(query.pagination.page()),
-        per_page: Some(query.pagination.limit),
-    }))
+    Ok(Json(ApiResponse::paginated(responses, total, query.pagination.page(), query.pagination.limit)))
 }
 
 /// Create a new structured note
@@ -348,6 +467,9 @@ async fn create_note<R: RepositoryManager>(
     State(state): State<AppState<R>>,
     Json(request): Json<CreateNoteRequest>,
 ) -> ApiResult<Json<ApiResponse<NoteResponse>>> {
+    request.validate()?;
+    request.validate_content(&state).await?;
+
     // Validate session exists
     let _session = state.repositories.session()
         .find_by_id(request.session_id)
@@ -380,15 +502,11 @@ async fn create_note<R: RepositoryManager>(
         .create(new_note)
         .await
         .map_err(|e| ApiError::InternalServerError(format!("Failed to create note: {}", e)))?;
+    record_template_use(&state, note.template_id.as_deref()).await;
 
     let response = create_note_response(&state, note).await?;
 
-    Ok(Json(ApiResponse {
-        data: response,
-        total: None,
-        page: None,
-        per_page: None,
-    }))
+    Ok(Json(ApiResponse::success(response)))
 }
 
 /// Get a specific structured note by ID
@@ -409,12 +527,7 @@ async fn get_note<R: RepositoryManager>(
 
     let response = create_note_response(&state, note).await?;
 
-    Ok(Json(ApiResponse {
-        data: response,
-        total: None,
-        page: None,
-        per_page: None,
-    }))
+    Ok(Json(ApiResponse::success(response)))
 }
 
 /// Update a structured note
@@ -423,13 +536,26 @@ async fn update_note<R: RepositoryManager>(
     Path(id): Path<Uuid>,
     Json(request): Json<UpdateNoteRequest>,
 ) -> ApiResult<Json<ApiResponse<NoteResponse>>> {
+    request.validate()?;
+
     // Check if note exists
-    let _note = state.repositories.structured_note()
+    let note = state.repositories.structured_note()
         .find_by_id(id)
         .await
         .map_err(|e| ApiError::InternalServerError(format!("Failed to get note: {}", e)))?
         .ok_or_else(|| ApiError::NotFound("Note not found".to_string()))?;
 
+    // Changing the content or the template must leave the note matching its template
+    if request.content.is_some() || request.template_id.is_some() {
+        validate_note_content(
+            &state,
+            request.template_id.as_deref().or(note.template_id.as_deref()),
+            request.content.as_ref().unwrap_or(&note.content),
+            request.free_form.unwrap_or(false),
+        )
+        .await?;
+    }
+
     let update_note = UpdateStructuredNote {
         title: request.title,
         content: request.content,
@@ -447,19 +573,26 @@ async fn update_note<R: RepositoryManager>(
 
     let response = create_note_response(&state, updated_note).await?;
 
-    Ok(Json(ApiResponse {
-        data: response,
-        total: None,
-        page: None,
-        per_page: None,
-    }))
+    Ok(Json(ApiResponse::success(response)))
 }
 
-/// Delete a structured note
+/// Soft-delete a structured note; `?purge=true` deletes it permanently
 async fn delete_note<R: RepositoryManager>(
     State(state): State<AppState<R>>,
     Path(id): Path<Uuid>,
+    Query(query): Query<DeleteQuery>,
 ) -> ApiResult<StatusCode> {
+    if query.purge {
+        let purged = state.repositories.structured_note()
+            .purge(id)
+            .await
+            .map_err(|e| ApiError::InternalServerError(format!("Failed to purge note: {}", e)))?;
+        if !purged {
+            return Err(ApiError::NotFound("Note not found".to_string()));
+        }
+        return Ok(StatusCode::NO_CONTENT);
+    }
+
     // Check if note exists
     let _note = state.repositories.structured_note()
         .find_by_id(id)
@@ -475,11 +608,28 @@ async fn delete_note<R: RepositoryManager>(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Restore a soft-deleted note
+async fn restore_note<R: RepositoryManager>(
+    State(state): State<AppState<R>>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<ApiResponse<NoteResponse>>> {
+    let restored = state.repositories.structured_note()
+        .restore(id)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to restore note: {}", e)))?;
+    if !restored {
+        return Err(ApiError::NotFound("Deleted note not found".to_string()));
+    }
+
+    get_note(State(state), Path(id)).await
+}
+
 /// Export structured note
 async fn export_note<R: RepositoryManager>(
     State(state): State<AppState<R>>,
     Path(id): Path<Uuid>,
     Query(params): Query<std::collections::HashMap<String, String>>,
+    headers: HeaderMap,
 ) -> ApiResult<axum::response::Response> {
     let note = state.repositories.structured_note()
         .find_by_id(id)
@@ -513,6 +663,10 @@ async fn export_note<R: RepositoryManager>(
                 .map_err(|e| ApiError::InternalServerError(format!("Failed to convert to markdown: {}", e)))?;
             (markdown_content, "text/markdown", format!("note_{}.md", note.id))
         }
+        "opml" => {
+            let opml_content = outline::to_opml(&note.title, note.created_at, &note.content);
+            (opml_content, "text/x-opml; charset=utf-8", format!("note_{}.opml", note.id))
+        }
         "html" => {
             let html_content = convert_note_to_html(&note)
                 .map_err(|e| ApiError::InternalServerError(format!("Failed to convert to HTML: {}", e)))?;
@@ -524,23 +678,13 @@ async fn export_note<R: RepositoryManager>(
         }
         _ => {
             return Err(ApiError::BadRequest(format!(
-                "Unsupported export format: {}. Supported formats: json, md, html",
+                "Unsupported export format: {}. Supported formats: json, md, opml, html",
                 format
             )));
         }
     };
 
-    Ok((
-        [
-            (axum::http::header::CONTENT_TYPE, content_type),
-            (
-                axum::http::header::CONTENT_DISPOSITION,
-                &format!("attachment; filename=\"{}\"", filename),
-            ),
-        ],
-        content,
-    )
-        .into_response())
+    Ok(crate::etag::content_response(&headers, content_type, Some(&filename), content))
 }
 
 /// Share a structured note
@@ -556,7 +700,7 @@ async fn share_note<R: RepositoryManager>(
         .ok_or_else(|| ApiError::NotFound("Note not found".to_string()))?;
 
     let share_token = uuid::Uuid::new_v4().to_string();
-    let base_url = state.config.server.base_url.clone();
+    let base_url = state.config.current().server.base_url.clone();
     let share_url = format!("{}/shared/notes/{}", base_url, share_token);
 
     // Update note with share token
@@ -585,29 +729,46 @@ async fn share_note<R: RepositoryManager>(
 async fn list_session_notes<R: RepositoryManager>(
     State(state): State<AppState<R>>,
     Path(session_id): Path<Uuid>,
-    Query(query): Query<NotesListQuery>,
+    StrictQuery(query): StrictQuery<NotesListQuery>,
 ) -> ApiResult<Json<ApiResponse<Vec<NoteResponse>>>> {
     let mut modified_query = query;
     modified_query.session_id = Some(session_id);
-    list_notes(State(state), Query(modified_query)).await
+    list_notes(State(state), StrictQuery(modified_query)).await
 }
 
 /// List notes for a specific analysis
 async fn list_analysis_notes<R: RepositoryManager>(
     State(state): State<AppState<R>>,
     Path(analysis_id): Path<Uuid>,
-    Query(query): Query<NotesListQuery>,
+    StrictQuery(query): StrictQuery<NotesListQuery>,
 ) -> ApiResult<Json<ApiResponse<Vec<NoteResponse>>>> {
     let mut modified_query = query;
     modified_query.analysis_id = Some(analysis_id);
-    list_notes(State(state), Query(modified_query)).await
+    list_notes(State(state), StrictQuery(modified_query)).await
 }
 
 /// Batch create notes
+///
+/// Honors the `Idempotency-Key` header so a retried batch is not inserted twice.
 async fn batch_create_notes<R: RepositoryManager>(
     State(state): State<AppState<R>>,
-    Json(request): Json<BatchCreateNotesRequest>,
-) -> ApiResult<Json<BatchCreateResponse>> {
+    headers: HeaderMap,
+    FingerprintedJson { body: request, fingerprint }: FingerprintedJson<BatchCreateNotesRequest>,
+) -> ApiResult<Response> {
+    let mut claim = None;
+    if let Some(key) = idempotency_key(&headers)? {
+        match state.idempotency.begin("notes:batch_create", &key, &fingerprint) {
+            IdempotencyStatus::Replay(body) => return Ok(replay_response(body)),
+            IdempotencyStatus::InFlight => {
+                return Err(ApiError::Conflict(
+                    "A request with this Idempotency-Key is already being processed".to_string(),
+                ));
+            }
+            IdempotencyStatus::Mismatch => return Err(mismatch_error()),
+            IdempotencyStatus::New(new_claim) => claim = Some(new_claim),
+        }
+    }
+
     let mut created_notes = Vec::new();
     let mut errors = Vec::new();
     let mut created_count = 0;
@@ -626,12 +787,18 @@ async fn batch_create_notes<R: RepositoryManager>(
         }
     }
 
-    Ok(Json(BatchCreateResponse {
+    let response = BatchCreateResponse {
         created: created_count,
         failed: failed_count,
         notes: created_notes,
         errors,
-    }))
+    };
+
+    if let Some(claim) = claim {
+        claim.complete(&response);
+    }
+
+    Ok(Json(response).into_response())
 }
 
 /// Batch delete notes
@@ -663,173 +830,273 @@ async fn batch_delete_notes<R: RepositoryManager>(
 /// Search notes
 async fn search_notes<R: RepositoryManager>(
     State(state): State<AppState<R>>,
-    Query(query): Query<NotesListQuery>,
+    StrictQuery(query): StrictQuery<NotesListQuery>,
 ) -> ApiResult<Json<ApiResponse<Vec<NoteResponse>>>> {
     // Enhanced search functionality
-    list_notes(State(state), Query(query)).await
+    list_notes(State(state), StrictQuery(query)).await
 }
 
 /// Get notes statistics
+///
+/// `tz` selects the time zone of the `notes_per_day` buckets and `days` their
+/// number; everything else covers all notes.
 async fn notes_stats<R: RepositoryManager>(
     State(state): State<AppState<R>>,
+    Query(query): Query<StatsQuery>,
 ) -> ApiResult<Json<NotesStatsResponse>> {
-    let stats = state.services.structured_note()
-        .get_notes_stats()
+    let window = query.window()?;
+    let stats = state.repositories.structured_note()
+        .stats(&window)
         .await
-        .map_err(|e| ApiError::InternalServerError(format!("Failed to get notes stats: {}", e)))?;
+        .map_err(|e| stats_error(e, "notes"))?;
+
+    let popular_templates = state.repositories.note_templates()
+        .most_used(POPULAR_TEMPLATES)
+        .await
+        .map_err(|e| stats_error(e, "notes"))?
+        .into_iter()
+        .map(|template| TemplateStats {
+            template_id: template.id,
+            template_name: template.name,
+            usage_count: template.usage_count,
+            avg_rating: 0.0, // Notes are not rated yet
+        })
+        .collect();
 
-    Ok(Json(stats))
+    Ok(Json(NotesStatsResponse {
+        total_notes: stats.total,
+        note_types: stats.by_note_type,
+        templates: stats.by_template,
+        tags: stats.by_tag,
+        public_notes: stats.public,
+        private_notes: stats.total - stats.public,
+        total_views: stats.total_views,
+        avg_content_length: stats.avg_content_length.unwrap_or(0.0),
+        notes_per_day: stats
+            .per_day
+            .into_iter()
+            .map(|day| DailyCount { date: day.date, count: day.count })
+            .collect(),
+        popular_templates,
+    }))
 }
 
-/// Get note templates
-async fn get_note_templates<R: RepositoryManager>(
-    State(_state): State<AppState<R>>,
+/// List note templates, system templates first
+async fn list_note_templates<R: RepositoryManager>(
+    State(state): State<AppState<R>>,
 ) -> ApiResult<Json<TemplatesResponse>> {
-    // For now, return predefined templates
-    // In a real implementation, these would be stored in the database
-    let templates = vec![
-        NoteTemplate {
-            id: "meeting_notes".to_string(),
-            name: "Meeting Notes".to_string(),
-            description: "Structured template for meeting notes".to_string(),
-            note_type: "meeting_notes".to_string(),
-            schema: serde_json::json!({
-                "type": "object",
-                "properties": {
-                    "meeting_info": {
-                        "type": "object",
-                        "properties": {
-                            "date": { "type": "string", "format": "date-time" },
-                            "attendees": { "type": "array", "items": { "type": "string" } },
-                            "agenda": { "type": "string" }
-                        }
-                    },
-                    "discussion_points": {
-                        "type": "array",
-                        "items": {
-                            "type": "object",
-                            "properties": {
-                                "topic": { "type": "string" },
-                                "discussion": { "type": "string" },
-                                "decisions": { "type": "array", "items": { "type": "string" } }
-                            }
-                        }
-                    },
-                    "action_items": {
-                        "type": "array",
-                        "items": {
-                            "type": "object",
-                            "properties": {
-                                "task": { "type": "string" },
-                                "assignee": { "type": "string" },
-                                "due_date": { "type": "string", "format": "date" }
-                            }
-                        }
-                    }
-                }
-            }),
-            default_content: serde_json::json!({
-                "meeting_info": {
-                    "date": "",
-                    "attendees": [],
-                    "agenda": ""
-                },
-                "discussion_points": [],
-                "action_items": []
-            }),
-            sections: vec![
-                TemplateSection {
-                    id: "meeting_info".to_string(),
-                    name: "Meeting Information".to_string(),
-                    description: "Basic meeting details".to_string(),
-                    required: true,
-                    field_type: "json".to_string(),
-                    default_value: None,
-                },
-                TemplateSection {
-                    id: "discussion_points".to_string(),
-                    name: "Discussion Points".to_string(),
-                    description: "Key topics discussed".to_string(),
-                    required: false,
-                    field_type: "list".to_string(),
-                    default_value: None,
-                },
-                TemplateSection {
-                    id: "action_items".to_string(),
-                    name: "Action Items".to_string(),
-                    description: "Tasks and follow-ups".to_string(),
-                    required: false,
-                    field_type: "table".to_string(),
-                    default_value: None,
-                },
-            ],
-            usage_count: 0,
-            is_system: true,
-        },
-        NoteTemplate {
-            id: "research_summary".to_string(),
-            name: "Research Summary".to_string(),
-            description: "Template for research findings and analysis".to_string(),
-            note_type: "research".to_string(),
-            schema: serde_json::json!({
-                "type": "object",
-                "properties": {
-                    "research_topic": { "type": "string" },
-                    "methodology": { "type": "string" },
-                    "key_findings": {
-                        "type": "array",
-                        "items": {
-                            "type": "object",
-                            "properties": {
-                                "finding": { "type": "string" },
-                                "evidence": { "type": "string" },
-                                "significance": { "type": "string" }
-                            }
-                        }
-                    },
-                    "conclusions": { "type": "string" },
-                    "next_steps": { "type": "array", "items": { "type": "string" } }
-                }
-            }),
-            default_content: serde_json::json!({
-                "research_topic": "",
-                "methodology": "",
-                "key_findings": [],
-                "conclusions": "",
-                "next_steps": []
-            }),
-            sections: vec![
-                TemplateSection {
-                    id: "research_topic".to_string(),
-                    name: "Research Topic".to_string(),
-                    description: "Main research question or topic".to_string(),
-                    required: true,
-                    field_type: "text".to_string(),
-                    default_value: None,
-                },
-                TemplateSection {
-                    id: "methodology".to_string(),
-                    name: "Methodology".to_string(),
-                    description: "Research approach and methods".to_string(),
-                    required: false,
-                    field_type: "markdown".to_string(),
-                    default_value: None,
-                },
-                TemplateSection {
-                    id: "key_findings".to_string(),
-                    name: "Key Findings".to_string(),
-                    description: "Important discoveries and insights".to_string(),
-                    required: true,
-                    field_type: "list".to_string(),
-                    default_value: None,
-                },
-            ],
-            usage_count: 0,
-            is_system: true,
-        },
-    ];
+    let templates = state.repositories.note_templates()
+        .list()
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to list note templates: {}", e)))?;
+
+    Ok(Json(TemplatesResponse {
+        templates: templates.into_iter().map(template_response).collect(),
+    }))
+}
+
+/// Get a note template by ID
+async fn get_note_template<R: RepositoryManager>(
+    State(state): State<AppState<R>>,
+    Path(template_id): Path<String>,
+) -> ApiResult<Json<ApiResponse<NoteTemplate>>> {
+    let template = find_template(&state, &template_id).await?;
 
-    Ok(Json(TemplatesResponse { templates }))
+    Ok(Json(ApiResponse::success(template_response(template))))
+}
+
+/// Create a custom note template
+async fn create_note_template<R: RepositoryManager>(
+    State(state): State<AppState<R>>,
+    Json(request): Json<CreateTemplateRequest>,
+) -> ApiResult<(StatusCode, Json<ApiResponse<NoteTemplate>>)> {
+    request.validate()?;
+
+    let existing = state.repositories.note_templates()
+        .find_by_id(&request.id)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to get note template: {}", e)))?;
+    if existing.is_some() {
+        return Err(ApiError::Conflict(format!("Note template '{}' already exists", request.id)));
+    }
+
+    let new_template = NewNoteTemplate {
+        id: request.id,
+        name: request.name,
+        description: request.description.unwrap_or_default(),
+        note_type: request.note_type,
+        default_content: request.default_content.unwrap_or_else(|| serde_json::json!({})),
+        schema: request.schema,
+        sections: serde_json::to_value(request.sections.unwrap_or_default())?,
+    };
+
+    let template = state.repositories.note_templates()
+        .create(&new_template)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to create note template: {}", e)))?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse::success(template_response(template))),
+    ))
+}
+
+/// Update a custom note template; system templates are read-only
+///
+/// Notes already using the template are not re-validated against a changed
+/// schema.
+async fn update_note_template<R: RepositoryManager>(
+    State(state): State<AppState<R>>,
+    Path(template_id): Path<String>,
+    Json(request): Json<UpdateTemplateRequest>,
+) -> ApiResult<Json<ApiResponse<NoteTemplate>>> {
+    let template = find_template(&state, &template_id).await?;
+    if template.is_system {
+        return Err(ApiError::Forbidden(format!("System template '{}' cannot be changed", template_id)));
+    }
+    request.validate(&template)?;
+
+    let updates = NoteTemplateUpdate {
+        name: request.name,
+        description: request.description,
+        note_type: request.note_type,
+        schema: request.schema,
+        default_content: request.default_content,
+        sections: request.sections.map(serde_json::to_value).transpose()?,
+    };
+
+    let template = state.repositories.note_templates()
+        .update(&template_id, &updates)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to update note template: {}", e)))?
+        .ok_or_else(|| ApiError::NotFound("Note template not found".to_string()))?;
+
+    Ok(Json(ApiResponse::success(template_response(template))))
+}
+
+/// Delete a custom note template; system templates cannot be deleted
+///
+/// Notes created from the template keep its `template_id` but are no longer
+/// validated against it.
+async fn delete_note_template<R: RepositoryManager>(
+    State(state): State<AppState<R>>,
+    Path(template_id): Path<String>,
+) -> ApiResult<StatusCode> {
+    let template = find_template(&state, &template_id).await?;
+    if template.is_system {
+        return Err(ApiError::Forbidden(format!("System template '{}' cannot be deleted", template_id)));
+    }
+
+    let deleted = state.repositories.note_templates()
+        .delete(&template_id)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to delete note template: {}", e)))?;
+    if !deleted {
+        return Err(ApiError::NotFound("Note template not found".to_string()));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn find_template<R: RepositoryManager>(
+    state: &AppState<R>,
+    template_id: &str,
+) -> ApiResult<crate::repository::traits::NoteTemplate> {
+    state.repositories.note_templates()
+        .find_by_id(template_id)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to get note template: {}", e)))?
+        .ok_or_else(|| ApiError::NotFound("Note template not found".to_string()))
+}
+
+fn template_response(template: crate::repository::traits::NoteTemplate) -> NoteTemplate {
+    NoteTemplate {
+        id: template.id,
+        name: template.name,
+        description: template.description,
+        note_type: template.note_type,
+        schema: template.schema,
+        default_content: template.default_content,
+        // Sections are only ever written from `TemplateSection`s
+        sections: serde_json::from_value(template.sections).unwrap_or_default(),
+        usage_count: template.usage_count,
+        is_system: template.is_system,
+    }
+}
+
+/// Count a new note towards its template's `usage_count`
+///
+/// Free-form notes may name a template that does not exist (or was deleted
+/// meanwhile); those are skipped, and a failed update never fails the note
+/// that was already created.
+async fn record_template_use<R: RepositoryManager>(state: &AppState<R>, template_id: Option<&str>) {
+    let Some(template_id) = template_id else {
+        return;
+    };
+    match state.repositories.note_templates().increment_usage(template_id).await {
+        Ok(true) => {}
+        Ok(false) => tracing::debug!("Note uses unknown template {}; usage not counted", template_id),
+        Err(e) => tracing::warn!("Failed to count usage of note template {}: {}", template_id, e),
+    }
+}
+
+/// Names of all note templates by ID, for labelling notes
+///
+/// Lookup failures leave the names out, like the other enrichment lookups.
+async fn template_names<R: RepositoryManager>(state: &AppState<R>) -> HashMap<String, String> {
+    state.repositories.note_templates()
+        .list()
+        .await
+        .map(|templates| templates.into_iter().map(|t| (t.id, t.name)).collect())
+        .unwrap_or_default()
+}
+
+/// Check that `schema` is an object schema and `default_content` matches it
+fn validate_template_schema(schema: &serde_json::Value, default_content: &serde_json::Value) -> ApiResult<()> {
+    if !schema.is_object() {
+        return Err(ApiError::UnprocessableEntity("schema must be a JSON object".to_string()));
+    }
+    let errors = schema::validate(default_content, schema);
+    if !errors.is_empty() {
+        return Err(ApiError::UnprocessableEntity(format!(
+            "default_content does not match the schema: {}",
+            errors.join("; ")
+        )));
+    }
+    Ok(())
+}
+
+/// Check note content against the schema of its template
+///
+/// Notes without a template, and `free_form` notes, are stored as given.
+async fn validate_note_content<R: RepositoryManager>(
+    state: &AppState<R>,
+    template_id: Option<&str>,
+    content: &serde_json::Value,
+    free_form: bool,
+) -> ApiResult<()> {
+    let Some(template_id) = template_id else {
+        return Ok(());
+    };
+    if free_form {
+        return Ok(());
+    }
+    let template = state.repositories.note_templates()
+        .find_by_id(template_id)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to get note template: {}", e)))?
+        .ok_or_else(|| ApiError::UnprocessableEntity(format!("Unknown template_id '{}'", template_id)))?;
+
+    let errors = schema::validate(content, &template.schema);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(ApiError::UnprocessableEntity(format!(
+            "content does not match template '{}': {}",
+            template_id,
+            errors.join("; ")
+        )))
+    }
 }
 
 /// Get note tags
@@ -858,6 +1125,8 @@ async fn merge_notes<R: RepositoryManager>(
     State(state): State<AppState<R>>,
     Json(request): Json<MergeNotesRequest>,
 ) -> ApiResult<Json<MergeNotesResponse>> {
+    request.target_note.validate()?;
+
     // Validate source notes exist
     let mut source_notes = Vec::new();
     for note_id in &request.source_note_ids {
@@ -906,30 +1175,135 @@ async fn find_duplicate_notes<R: RepositoryManager>(
     Ok(Json(DuplicateNotesResponse { duplicates }))
 }
 
-/// Generate note from analysis
+/// Generate a note from an analysis, filled into the requested template
+///
+/// Without a template the note gets a summary, key points and action items.
+/// With `custom_prompt` the analysis summary is rewritten by the Ollama model
+/// first, following the prompt.
 async fn generate_note_from_analysis<R: RepositoryManager>(
     State(state): State<AppState<R>>,
     Json(request): Json<GenerateNoteRequest>,
 ) -> ApiResult<Json<ApiResponse<NoteResponse>>> {
-    let generated_note = state.services.structured_note()
-        .generate_from_analysis(
-            request.analysis_id,
-            &request.note_type,
-            request.template_id.as_deref(),
-            request.custom_prompt.as_deref(),
-            request.include_sections.as_ref().map(|v| v.as_slice()),
-        )
+    validate_choice("note_type", Some(&request.note_type), NOTE_TYPES)?;
+
+    let analysis = state.repositories.analysis()
+        .find_by_id(request.analysis_id)
         .await
-        .map_err(|e| ApiError::InternalServerError(format!("Failed to generate note: {}", e)))?;
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to get analysis: {}", e)))?
+        .ok_or_else(|| ApiError::NotFound("Analysis not found".to_string()))?;
+    if !analysis.result_data.is_object() {
+        return Err(ApiError::UnprocessableEntity(
+            "Analysis has no result data to generate a note from".to_string(),
+        ));
+    }
 
-    let response = create_note_response(&state, generated_note).await?;
+    let template = match request.template_id.as_deref() {
+        Some(template_id) => Some(find_template(&state, template_id).await?),
+        None => None,
+    };
+    let (schema, default_content) = match &template {
+        Some(template) => (template.schema.clone(), template.default_content.clone()),
+        None => (note_generation::generic_schema(), serde_json::json!({})),
+    };
+    if let Some(sections) = &request.include_sections {
+        let unknown = note_generation::unknown_sections(&schema, sections);
+        if !unknown.is_empty() {
+            return Err(ApiError::UnprocessableEntity(format!("Unknown sections: {}", unknown.join(", "))));
+        }
+    }
 
-    Ok(Json(ApiResponse {
-        data: response,
-        total: None,
-        page: None,
-        per_page: None,
-    }))
+    let mut result_data = analysis.result_data.clone();
+    let mut summarized_with = None;
+    if let Some(prompt) = request.custom_prompt.as_deref().filter(|p| !p.trim().is_empty()) {
+        let (model, summary) = resummarize(&state, &analysis, prompt).await?;
+        result_data["summary"] = serde_json::Value::String(summary);
+        summarized_with = Some(model);
+    }
+
+    let content = note_generation::fill_template(
+        &result_data,
+        &schema,
+        &default_content,
+        request.include_sections.as_deref(),
+    );
+    let title = result_data.get("title")
+        .and_then(|t| t.as_str())
+        .filter(|t| !t.trim().is_empty())
+        .map(str::to_string)
+        .or_else(|| template.as_ref().map(|t| t.name.clone()))
+        .unwrap_or_else(|| "Generated note".to_string());
+
+    let new_note = NewStructuredNote {
+        session_id: analysis.session_id,
+        analysis_id: Some(analysis.id),
+        title,
+        content,
+        note_type: request.note_type,
+        template_id: request.template_id,
+        tags: Vec::new(),
+        is_public: false,
+        metadata: Some(serde_json::json!({
+            "generated_from_analysis": analysis.id,
+            "include_sections": request.include_sections,
+            "summary_model": summarized_with,
+        })),
+    };
+
+    let note = state.repositories.structured_note()
+        .create(new_note)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to create note: {}", e)))?;
+    record_template_use(&state, note.template_id.as_deref()).await;
+
+    let response = create_note_response(&state, note).await?;
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
+/// Rewrite an analysis summary following `prompt`, returning the model used
+///
+/// Uses the model that made the analysis if it came from Ollama, otherwise
+/// `ollama.default_model`. The transcript is included when available so the
+/// model can draw on more than the old summary.
+async fn resummarize<R: RepositoryManager>(
+    state: &AppState<R>,
+    analysis: &crate::repository::traits::AnalysisResult,
+    prompt: &str,
+) -> ApiResult<(String, String)> {
+    let model = analysis.model_used.clone()
+        .filter(|_| analysis.provider == "ollama")
+        .unwrap_or_else(|| state.config.current().ollama.default_model.clone());
+
+    let transcript = match analysis.transcript_id {
+        Some(transcript_id) => state.repositories.transcript()
+            .find_by_id(transcript_id)
+            .await
+            .ok()
+            .flatten()
+            .map(|t| t.content),
+        None => None,
+    };
+    let summary = analysis.result_data.get("summary").and_then(|s| s.as_str()).unwrap_or_default();
+
+    let mut full_prompt = format!(
+        "{}\n\nRewrite the summary below following the instructions above. \
+         Reply with the new summary only.\n\nSummary:\n{}\n",
+        prompt.trim(),
+        summary
+    );
+    if let Some(transcript) = transcript {
+        full_prompt.push_str(&format!("\nTranscript:\n{}\n", transcript));
+    }
+
+    let rewritten = state.services.ollama()
+        .generate(&model, &full_prompt, None)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to summarize with {}: {}", model, e)))?;
+    let rewritten = rewritten.trim();
+    if rewritten.is_empty() {
+        return Err(ApiError::InternalServerError(format!("{} returned an empty summary", model)));
+    }
+    Ok((model, rewritten.to_string()))
 }
 
 // Helper functions
@@ -954,6 +1328,16 @@ async fn create_note_response<R: RepositoryManager>(
         None
     };
 
+    let template_name = match &note.template_id {
+        Some(template_id) => state.repositories.note_templates()
+            .find_by_id(template_id)
+            .await
+            .ok()
+            .flatten()
+            .map(|template| template.name),
+        None => None,
+    };
+
     Ok(NoteResponse {
         id: note.id,
         session_id: note.session_id,
@@ -969,9 +1353,10 @@ async fn create_note_response<R: RepositoryManager>(
         metadata: note.metadata,
         created_at: note.created_at,
         updated_at: note.updated_at,
+        deleted_at: note.deleted_at,
         session_title: session.and_then(|s| s.title),
         analysis_type: analysis.map(|a| a.analysis_type),
-        template_name: note.template_id, // Would need template lookup for actual name
+        template_name,
     })
 }
 
@@ -979,6 +1364,9 @@ async fn create_note_internal<R: RepositoryManager>(
     state: &AppState<R>,
     request: CreateNoteRequest,
 ) -> Result<NoteResponse, ApiError> {
+    request.validate()?;
+    request.validate_content(state).await?;
+
     // Validate session exists
     let _session = state.repositories.session()
         .find_by_id(request.session_id)
@@ -1011,6 +1399,7 @@ async fn create_note_internal<R: RepositoryManager>(
         .create(new_note)
         .await
         .map_err(|e| ApiError::InternalServerError(format!("Failed to create note: {}", e)))?;
+    record_template_use(state, note.template_id.as_deref()).await;
 
     create_note_response(state, note).await
 }
@@ -1029,7 +1418,7 @@ fn convert_note_to_markdown(note: &crate::repository::traits::StructuredNote) ->
     if let Ok(content_obj) = serde_json::from_value::<serde_json::Map<String, serde_json::Value>>(note.content.clone()) {
         for (key, value) in content_obj {
             markdown.push_str(&format!("## {}\n\n", key.replace('_', " ").to_title_case()));
-            markdown.push_str(&json_value_to_markdown(&value));
+            markdown.push_str(&outline::to_markdown_list(&value));
             markdown.push_str("\n\n");
         }
     } else {
@@ -1084,25 +1473,6 @@ fn convert_note_to_html(note: &crate::repository::traits::StructuredNote) -> Res
     Ok(html)
 }
 
-fn json_value_to_markdown(value: &serde_json::Value) -> String {
-    match value {
-        serde_json::Value::String(s) => s.clone(),
-        serde_json::Value::Array(arr) => {
-            arr.iter()
-                .map(|v| format!("- {}", json_value_to_markdown(v)))
-                .collect::<Vec<_>>()
-                .join("\n")
-        }
-        serde_json::Value::Object(obj) => {
-            obj.iter()
-                .map(|(k, v)| format!("**{}:** {}", k, json_value_to_markdown(v)))
-                .collect::<Vec<_>>()
-                .join("\n")
-        }
-        _ => value.to_string(),
-    }
-}
-
 fn json_value_to_html(value: &serde_json::Value) -> String {
     match value {
         serde_json::Value::String(s) => format!("<p>{}</p>", s),
@@ -4,15 +4,19 @@
 //! This module provides endpoints for interacting with the Ollama local AI service.
 
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, State},
     http::StatusCode,
-    response::Json,
-    routing::{get, post},
+    middleware,
+    response::sse::{Event, KeepAlive, Sse},
+    routing::{delete, get, post},
     Router,
 };
+use futures::stream::{BoxStream, StreamExt};
 use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
 
 use crate::api::{
+    extractors::Json,
     error::ApiError,
     responses::ApiResponse,
     AppState, ApiResult,
@@ -26,27 +30,44 @@ pub fn create_routes<R: RepositoryManager + 'static>() -> Router<AppState<R>> {
         .route("/status", get(get_ollama_status))
         .route("/models", get(list_models))
         .route("/models/pull", post(pull_model))
-        .route("/models/delete", post(delete_model))
+        .route(
+            // Names may contain slashes, e.g. `namespace/model:tag`
+            "/models/*model",
+            delete(delete_model).route_layer(middleware::from_fn(crate::api::auth::require_admin_token)),
+        )
         .route("/generate", post(generate_text))
         .route("/chat", post(chat_completion))
         .route("/embeddings", post(generate_embeddings))
         .route("/detect-language", post(detect_language))
         .route("/analyze", post(analyze_text))
         .route("/config", get(get_ollama_config))
+        .route("/warmup", post(warmup_model))
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct PullModelRequest {
     model: String,
     insecure: Option<bool>,
 }
 
-#[derive(Debug, Deserialize)]
-struct DeleteModelRequest {
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WarmupRequest {
+    /// Model to load; defaults to the configured default model
+    model: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct WarmupResponse {
     model: String,
+    /// Whether the model was already loaded before this request
+    already_loaded: bool,
+    load_time_ms: u64,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct GenerateRequest {
     model: String,
     prompt: String,
@@ -60,6 +81,7 @@ struct GenerateRequest {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct ChatMessage {
     role: String, // "system", "user", "assistant"
     content: String,
@@ -67,6 +89,7 @@ struct ChatMessage {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct ChatRequest {
     model: String,
     messages: Vec<ChatMessage>,
@@ -76,6 +99,7 @@ struct ChatRequest {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct EmbeddingsRequest {
     model: String,
     prompt: String,
@@ -83,12 +107,14 @@ struct EmbeddingsRequest {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct DetectLanguageRequest {
     text: String,
     model: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct AnalyzeTextRequest {
     text: String,
     analysis_type: String, // "summary", "ideas", "tasks", "structured"
@@ -110,9 +136,9 @@ struct OllamaStatusResponse {
 #[derive(Debug, Serialize)]
 struct ModelInfo {
     name: String,
-    size: Option<u64>,
-    digest: Option<String>,
-    modified_at: Option<chrono::DateTime<chrono::Utc>>,
+    size: i64,
+    digest: String,
+    modified_at: chrono::DateTime<chrono::Utc>,
     details: Option<serde_json::Value>,
 }
 
@@ -192,27 +218,37 @@ async fn get_ollama_status<R: RepositoryManager>(
         vec![]
     };
 
+    let config = state.config.current();
     let response = OllamaStatusResponse {
         available: is_available,
-        base_url: state.config.ollama.base_url.clone(),
+        base_url: config.ollama.base_url.clone(),
         version: None, // TODO: Get version from Ollama API
         models_count: models.len(),
-        default_model: state.config.ollama.default_model.clone(),
+        default_model: config.ollama.default_model.clone(),
         last_check: chrono::Utc::now(),
     };
 
-    Ok(Json(ApiResponse {
-        data: response,
-        total: None,
-        page: None,
-        per_page: None,
-    }))
+    Ok(Json(ApiResponse::success(response)))
 }
 
-/// List available models
+/// Reject the request up front when the Ollama server cannot be reached
+async fn ensure_ollama_available<R: RepositoryManager>(state: &AppState<R>) -> ApiResult<()> {
+    if state.services.ollama().is_available().await {
+        Ok(())
+    } else {
+        Err(ApiError::ServiceUnavailable(format!(
+            "Ollama is not reachable at {}",
+            state.config.current().ollama.base_url
+        )))
+    }
+}
+
+/// List installed models
 async fn list_models<R: RepositoryManager>(
     State(state): State<AppState<R>>,
 ) -> ApiResult<Json<ApiResponse<Vec<ModelInfo>>>> {
+    ensure_ollama_available(&state).await?;
+
     let models = state.services.ollama()
         .list_models()
         .await
@@ -221,45 +257,86 @@ async fn list_models<R: RepositoryManager>(
     let model_infos: Vec<ModelInfo> = models
         .into_iter()
         .map(|model| ModelInfo {
-            name: model,
-            size: None, // TODO: Get model size from Ollama API
-            digest: None,
-            modified_at: None,
-            details: None,
+            name: model.name,
+            size: model.size,
+            digest: model.digest,
+            modified_at: model.modified_at,
+            details: model.details.and_then(|details| serde_json::to_value(details).ok()),
         })
         .collect();
+    let total = model_infos.len() as i64;
 
-    Ok(Json(ApiResponse {
-        data: model_infos,
-        total: Some(model_infos.len() as i64),
-        page: None,
-        per_page: None,
-    }))
+    Ok(Json(ApiResponse::success(model_infos).with_total(total)))
 }
 
-/// Pull a model from Ollama registry
+/// Pull a model from the Ollama registry, streaming progress as server-sent events
+///
+/// Emits `progress` events with the download status and byte counts, then a
+/// `complete` event, or an `error` event if the pull fails part-way.
 async fn pull_model<R: RepositoryManager>(
     State(state): State<AppState<R>>,
     Json(request): Json<PullModelRequest>,
-) -> ApiResult<Json<serde_json::Value>> {
-    state.services.ollama()
-        .pull_model(&request.model)
+) -> ApiResult<Sse<BoxStream<'static, Result<Event, Infallible>>>> {
+    ensure_ollama_available(&state).await?;
+
+    let progress = state.services.ollama()
+        .pull_model_stream(&request.model)
         .await
         .map_err(|e| ApiError::InternalServerError(format!("Failed to pull model: {}", e)))?;
 
-    Ok(Json(serde_json::json!({
-        "message": format!("Model '{}' pulled successfully", request.model),
-        "model": request.model
-    })))
+    // A trailing `None` marks the end of the pull; stop after the first error
+    // so `complete` is only sent for a finished pull
+    let model = request.model;
+    let events = progress
+        .map(Some)
+        .chain(futures::stream::once(async { None }))
+        .scan(false, move |failed, item| {
+            if *failed {
+                return futures::future::ready(None);
+            }
+            let event = match item {
+                Some(Ok(progress)) => Event::default().event("progress").json_data(&progress),
+                Some(Err(e)) => {
+                    *failed = true;
+                    Ok(Event::default().event("error").data(format!("{:#}", e)))
+                }
+                None => Event::default()
+                    .event("complete")
+                    .json_data(serde_json::json!({ "model": &model })),
+            };
+            futures::future::ready(Some(Ok(
+                event.unwrap_or_else(|e| Event::default().event("error").data(e.to_string())),
+            )))
+        })
+        .boxed();
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
 }
 
-/// Delete a model
+/// Delete an installed model
+///
+/// Guarded by the admin bearer token, see [`crate::api::auth`].
 async fn delete_model<R: RepositoryManager>(
     State(state): State<AppState<R>>,
-    Json(request): Json<DeleteModelRequest>,
+    Path(model): Path<String>,
 ) -> ApiResult<StatusCode> {
-    // Note: Ollama doesn't have a direct delete API, so this might not be implemented
-    Err(ApiError::NotImplemented("Model deletion not supported by Ollama".to_string()))
+    ensure_ollama_available(&state).await?;
+
+    let exists = state.services.ollama()
+        .get_model_info(&model)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to look up model: {}", e)))?
+        .is_some();
+    if !exists {
+        return Err(ApiError::NotFound(format!("Model '{}' not found", model)));
+    }
+
+    state.services.ollama()
+        .delete_model(&model)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to delete model: {}", e)))?;
+
+    Ok(StatusCode::NO_CONTENT)
 }
 
 /// Generate text using Ollama
@@ -355,7 +432,7 @@ async fn detect_language<R: RepositoryManager>(
     State(state): State<AppState<R>>,
     Json(request): Json<DetectLanguageRequest>,
 ) -> ApiResult<Json<ApiResponse<LanguageDetectionResponse>>> {
-    let model = request.model.unwrap_or_else(|| state.config.ollama.default_model.clone());
+    let model = request.model.unwrap_or_else(|| state.config.current().ollama.default_model.clone());
     
     let detected_language = state.services.ollama()
         .detect_language(&request.text, &model)
@@ -373,12 +450,7 @@ async fn detect_language<R: RepositoryManager>(
         ],
     };
 
-    Ok(Json(ApiResponse {
-        data: response,
-        total: None,
-        page: None,
-        per_page: None,
-    }))
+    Ok(Json(ApiResponse::success(response)))
 }
 
 /// Analyze text using Ollama
@@ -388,7 +460,7 @@ async fn analyze_text<R: RepositoryManager>(
 ) -> ApiResult<Json<ApiResponse<AnalysisResponse>>> {
     let start_time = std::time::Instant::now();
     
-    let model = request.model.unwrap_or_else(|| state.config.ollama.default_model.clone());
+    let model = request.model.unwrap_or_else(|| state.config.current().ollama.default_model.clone());
     
     // Detect language if not provided
     let language = match request.language {
@@ -484,12 +556,7 @@ async fn analyze_text<R: RepositoryManager>(
         processing_time_ms: duration.as_millis() as u64,
     };
 
-    Ok(Json(ApiResponse {
-        data: response,
-        total: None,
-        page: None,
-        per_page: None,
-    }))
+    Ok(Json(ApiResponse::success(response)))
 }
 
 /// Get Ollama configuration
@@ -501,19 +568,49 @@ async fn get_ollama_config<R: RepositoryManager>(
         .await
         .unwrap_or_default();
 
+    let config = state.config.current();
     let response = OllamaConfigResponse {
-        base_url: state.config.ollama.base_url.clone(),
-        default_model: state.config.ollama.default_model.clone(),
-        timeout_seconds: state.config.ollama.timeout_seconds,
-        max_retries: state.config.ollama.max_retries,
+        base_url: config.ollama.base_url.clone(),
+        default_model: config.ollama.default_model.clone(),
+        timeout_seconds: config.ollama.timeout_seconds,
+        max_retries: config.ollama.max_retries,
         available_models,
         supported_formats: vec!["text".to_string(), "json".to_string()],
     };
 
-    Ok(Json(ApiResponse {
-        data: response,
-        total: None,
-        page: None,
-        per_page: None,
-    }))
-}
\ No newline at end of file
+    Ok(Json(ApiResponse::success(response)))
+}
+
+/// Load a model into memory so the next analysis starts without a cold start
+async fn warmup_model<R: RepositoryManager>(
+    State(state): State<AppState<R>>,
+    request: Option<Json<WarmupRequest>>,
+) -> ApiResult<Json<ApiResponse<WarmupResponse>>> {
+    ensure_ollama_available(&state).await?;
+
+    let Json(request) = request.unwrap_or_default();
+    let model = request.model.unwrap_or_else(|| state.config.current().ollama.default_model.clone());
+
+    let readiness = state.services.ollama()
+        .model_readiness(&model)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to check model state: {}", e)))?;
+    if !readiness.installed {
+        return Err(ApiError::NotFound(format!("Model '{}' is not installed", model)));
+    }
+
+    let load_time_ms = if readiness.loaded {
+        0
+    } else {
+        state.services.ollama()
+            .warmup(&model)
+            .await
+            .map_err(|e| ApiError::InternalServerError(format!("Failed to warm up model: {}", e)))?
+    };
+
+    Ok(Json(ApiResponse::success(WarmupResponse {
+        model,
+        already_loaded: readiness.loaded,
+        load_time_ms,
+    })))
+}
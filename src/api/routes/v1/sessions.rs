@@ -5,47 +5,93 @@
 
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    http::{HeaderMap, StatusCode},
     routing::{delete, get, patch, post},
     Router,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 use crate::api::{
+    docx,
+    extractors::{Json, ListQueryKeys, StrictQuery},
     error::ApiError,
+    highlight::{find_matches, SearchMatch},
     responses::{ApiResponse, PaginationParams, SearchParams, SortParams},
-    AppState, ApiResult,
+    duplicates, semantic, unique_ids, validate_choice, AppState, ApiResult,
 };
 use crate::repository::{
-    traits::{NewSession, SessionRepository, UpdateSession},
+    traits::{
+        AnalysisRepository, AnalysisResult, AudioRepository, Idea, IdeaRepository, NewSession, NewTranscript, Priority,
+        Session, SessionFilter, SessionRepository, SessionSortBy, SessionStatus, SortOrder, StructuredNote,
+        StructuredNoteRepository, Task, TaskRepository, TaskStatus, Transcript, TranscriptRepository, UpdateSession,
+    },
     RepositoryManager,
 };
-use crate::services::traits::SessionService;
+use crate::services::traits::{FileStorageService, SessionService};
 
 /// Create session routes
 pub fn create_routes<R: RepositoryManager + 'static>() -> Router<AppState<R>> {
     Router::new()
         .route("/", get(list_sessions).post(create_session))
         .route("/:id", get(get_session).patch(update_session).delete(delete_session))
+        .route("/:id/full", get(get_session_full))
+        .route("/:id/metadata", patch(patch_session_metadata))
         .route("/:id/audio", get(list_session_audio))
+        .route("/:id/audio/combined", get(combined_session_audio))
         .route("/:id/transcripts", get(list_session_transcripts))
         .route("/:id/analysis", get(list_session_analysis))
+        .route("/:id/analyses", get(list_session_analysis_history))
         .route("/:id/export", get(export_session))
+        .route("/:id/summary", get(session_summary))
         .route("/search", get(search_sessions))
+        .route("/find-duplicates", post(find_duplicate_sessions))
         .route("/stats", get(session_stats))
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct CreateSessionRequest {
     title: Option<String>,
     description: Option<String>,
     tags: Option<Vec<String>>,
     metadata: Option<serde_json::Value>,
+    /// Transcript made elsewhere, for sessions without audio
+    transcript: Option<SessionTranscriptRequest>,
 }
 
+/// A transcript created along with its session
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SessionTranscriptRequest {
+    content: String,
+    language: Option<String>,
+    /// What produced the transcript; [`IMPORTED_TRANSCRIPT_PROVIDER`] by default
+    provider: Option<String>,
+}
+
+/// Provider recorded for transcripts sent with a new session
+const IMPORTED_TRANSCRIPT_PROVIDER: &str = "import";
+
+impl CreateSessionRequest {
+    fn validate(&self) -> ApiResult<()> {
+        validate_metadata(self.metadata.as_ref())?;
+        if let Some(transcript) = &self.transcript {
+            if transcript.content.trim().is_empty() {
+                return Err(ApiError::UnprocessableEntity("transcript.content must not be empty".to_string()));
+            }
+        } else if self.title.as_deref().is_none_or(|title| title.trim().is_empty()) {
+            return Err(ApiError::UnprocessableEntity(
+                "A session needs a title or a transcript".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct UpdateSessionRequest {
     title: Option<String>,
     description: Option<String>,
@@ -66,6 +112,82 @@ struct SessionListQuery {
     tags: Option<String>, // Comma-separated tags
     created_after: Option<chrono::DateTime<chrono::Utc>>,
     created_before: Option<chrono::DateTime<chrono::Utc>>,
+    /// `meta.<key>=<value>` filters; only such keys are left by the others
+    #[serde(flatten)]
+    metadata: std::collections::BTreeMap<String, String>,
+}
+
+impl ListQueryKeys for SessionListQuery {
+    const KEYS: &'static [&'static str] = &["status", "tags", "created_after", "created_before"];
+    const PREFIXES: &'static [&'static str] = &[METADATA_FILTER_PREFIX];
+}
+
+/// Query key prefix of metadata filters, as in `?meta.project=alpha`
+const METADATA_FILTER_PREFIX: &str = "meta.";
+
+impl SessionListQuery {
+    /// Metadata keys and values the sessions must have
+    fn metadata_filters(&self) -> Vec<(String, String)> {
+        self.metadata
+            .iter()
+            .filter_map(|(key, value)| {
+                let key = key.strip_prefix(METADATA_FILTER_PREFIX)?;
+                Some((key.to_string(), value.clone()))
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SessionSearchQuery {
+    #[serde(flatten)]
+    list: SessionListQuery,
+    /// `keyword` (the default) or `semantic`
+    mode: Option<String>,
+}
+
+impl ListQueryKeys for SessionSearchQuery {
+    const KEYS: &'static [&'static str] = &["status", "tags", "created_after", "created_before", "mode"];
+    const PREFIXES: &'static [&'static str] = &[METADATA_FILTER_PREFIX];
+}
+
+/// Search modes of `GET /search`
+const SEARCH_MODES: &[&str] = &["keyword", "semantic"];
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SessionExportQuery {
+    /// `json` (the default) or `docx`
+    format: Option<String>,
+}
+
+/// Formats of `GET /:id/export`
+const SESSION_EXPORT_FORMATS: &[&str] = &["json", "docx"];
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FindDuplicatesQuery {
+    /// Similarity from 0 to 1 from which sessions count as duplicates
+    threshold: Option<f64>,
+    /// `text` (the default) or `embedding`
+    method: Option<String>,
+}
+
+/// Similarity from which sessions count as duplicates when not given
+const DEFAULT_DUPLICATE_THRESHOLD: f64 = 0.9;
+
+/// Ways of comparing sessions in `POST /find-duplicates`
+const DUPLICATE_METHODS: &[&str] = &["text", "embedding"];
+
+/// Reject session metadata that is not a JSON object
+///
+/// Custom fields are the object's keys, which the `meta.<key>` filters and
+/// `PATCH /:id/metadata` rely on.
+fn validate_metadata(metadata: Option<&serde_json::Value>) -> ApiResult<()> {
+    match metadata {
+        None | Some(serde_json::Value::Object(_)) => Ok(()),
+        Some(_) => Err(ApiError::UnprocessableEntity("metadata must be a JSON object".to_string())),
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -83,6 +205,75 @@ struct SessionResponse {
     updated_at: chrono::DateTime<chrono::Utc>,
 }
 
+#[derive(Debug, Serialize)]
+struct SessionSearchResult {
+    #[serde(flatten)]
+    session: SessionResponse,
+    /// Where the search term occurs in the session's transcript, with surrounding context
+    transcript_matches: Vec<SearchMatch>,
+    /// Similarity of the session's closest transcript to the query, from -1
+    /// to 1; only set by semantic search
+    #[serde(skip_serializing_if = "Option::is_none")]
+    score: Option<f32>,
+}
+
+#[derive(Debug, Serialize)]
+struct DuplicateSessionGroup {
+    /// Similarity of the closest two sessions of the group, from 0 to 1
+    similarity_score: f64,
+    matched_by: duplicates::MatchedBy,
+    /// Oldest first
+    sessions: Vec<SessionResponse>,
+}
+
+#[derive(Debug, Serialize)]
+struct SessionAnalysisResponse {
+    #[serde(flatten)]
+    analysis: AnalysisResult,
+    /// Whether this is the session's most recent analysis, the one returned
+    /// by the single-analysis endpoints
+    current: bool,
+}
+
+/// Number of tasks included in a session summary
+const SUMMARY_TASK_COUNT: usize = 3;
+
+/// A session with its transcript, current analysis and the ideas, tasks and
+/// notes extracted by that analysis
+#[derive(Debug, Serialize)]
+struct SessionFullResponse {
+    #[serde(flatten)]
+    session: SessionResponse,
+    transcript: Option<Transcript>,
+    analysis: Option<AnalysisResult>,
+    ideas: Vec<Idea>,
+    tasks: Vec<Task>,
+    structured_notes: Vec<StructuredNote>,
+}
+
+#[derive(Debug, Serialize)]
+struct SessionSummaryResponse {
+    id: Uuid,
+    title: String,
+    duration_ms: i64,
+    has_transcript: bool,
+    /// Length of the transcript in characters, 0 without one
+    transcript_length: usize,
+    has_analysis: bool,
+    summary: Option<String>,
+    /// Open tasks of the analysis, highest priority and earliest due date first
+    top_tasks: Vec<SessionSummaryTask>,
+}
+
+#[derive(Debug, Serialize)]
+struct SessionSummaryTask {
+    id: Uuid,
+    title: String,
+    priority: Priority,
+    status: TaskStatus,
+    due_date: Option<chrono::DateTime<chrono::Utc>>,
+}
+
 #[derive(Debug, Serialize)]
 struct SessionStatsResponse {
     total_sessions: i64,
@@ -99,7 +290,7 @@ struct SessionStatsResponse {
 /// List all sessions with filtering and pagination
 async fn list_sessions<R: RepositoryManager>(
     State(state): State<AppState<R>>,
-    Query(query): Query<SessionListQuery>,
+    StrictQuery(query): StrictQuery<SessionListQuery>,
 ) -> ApiResult<Json<ApiResponse<Vec<SessionResponse>>>> {
     let sessions = state.services.session()
         .list_sessions(
@@ -110,6 +301,7 @@ async fn list_sessions<R: RepositoryManager>(
             query.tags.as_deref().map(|t| t.split(',').map(|s| s.trim().to_string()).collect()),
             query.created_after,
             query.created_before,
+            &query.metadata_filters(),
             query.sort.sort_by.as_deref(),
             query.sort.sort_order.as_deref(),
         )
@@ -121,52 +313,23 @@ async fn list_sessions<R: RepositoryManager>(
         .await
         .map_err(|e| ApiError::InternalServerError(format!("Failed to count sessions: {}", e)))?;
 
-    let session_responses = futures::future::try_join_all(
-        sessions.into_iter().map(|session| async {
-            let audio_count = state.repositories.audio()
-                .count_by_session(session.id)
-                .await
-                .unwrap_or(0);
-            
-            let transcript_count = state.repositories.transcript()
-                .count_by_session(session.id)
-                .await
-                .unwrap_or(0);
-            
-            let analysis_count = state.repositories.analysis()
-                .count_by_session(session.id)
-                .await
-                .unwrap_or(0);
-
-            Ok::<SessionResponse, ApiError>(SessionResponse {
-                id: session.id,
-                title: session.title,
-                description: session.description,
-                status: session.status,
-                tags: session.tags,
-                metadata: session.metadata,
-                audio_count,
-                transcript_count,
-                analysis_count,
-                created_at: session.created_at,
-                updated_at: session.updated_at,
-            })
-        })
-    ).await?;
+    let session_responses = futures::future::join_all(
+        sessions.into_iter().map(|session| session_response(&state, session))
+    ).await;
 
-    Ok(Json(ApiResponse {
-        data: session_responses,
-        total: Some(total),
-        page: Some(query.pagination.page()),
-        per_page: Some(query.pagination.limit),
-    }))
+    Ok(Json(ApiResponse::paginated(session_responses, total, query.pagination.page(), query.pagination.limit)))
 }
 
 /// Create a new session
+///
+/// Sessions can be created without audio, e.g. to analyze a transcript made
+/// with another tool: the transcript is created along with the session.
 async fn create_session<R: RepositoryManager>(
     State(state): State<AppState<R>>,
     Json(request): Json<CreateSessionRequest>,
 ) -> ApiResult<Json<ApiResponse<SessionResponse>>> {
+    request.validate()?;
+
     let new_session = NewSession {
         title: request.title,
         description: request.description,
@@ -179,6 +342,14 @@ async fn create_session<R: RepositoryManager>(
         .await
         .map_err(|e| ApiError::InternalServerError(format!("Failed to create session: {}", e)))?;
 
+    let transcript_count = match request.transcript {
+        Some(transcript) => {
+            create_session_transcript(&state, session.id, transcript).await?;
+            1
+        }
+        None => 0,
+    };
+
     let response = SessionResponse {
         id: session.id,
         title: session.title,
@@ -187,18 +358,49 @@ async fn create_session<R: RepositoryManager>(
         tags: session.tags,
         metadata: session.metadata,
         audio_count: 0,
-        transcript_count: 0,
+        transcript_count,
         analysis_count: 0,
         created_at: session.created_at,
         updated_at: session.updated_at,
     };
 
-    Ok(Json(ApiResponse {
-        data: response,
-        total: None,
-        page: None,
-        per_page: None,
-    }))
+    Ok(Json(ApiResponse::success(response)))
+}
+
+/// Create the transcript sent with a new session, deleting the session again
+/// if that fails so that no session is left without the transcript it was
+/// created for
+async fn create_session_transcript<R: RepositoryManager>(
+    state: &AppState<R>,
+    session_id: Uuid,
+    transcript: SessionTranscriptRequest,
+) -> ApiResult<()> {
+    let created = state.repositories.transcript()
+        .create(&NewTranscript {
+            session_id,
+            content: transcript.content,
+            language: transcript.language,
+            confidence_score: None,
+            provider: transcript.provider.unwrap_or_else(|| IMPORTED_TRANSCRIPT_PROVIDER.to_string()),
+            processing_time_ms: None,
+            segments: serde_json::Value::Array(Vec::new()),
+            model_used: None,
+        })
+        .await;
+
+    let transcript = match created {
+        Ok(transcript) => transcript,
+        Err(e) => {
+            if let Err(rollback) = state.repositories.session().delete(&session_id).await {
+                tracing::warn!("Failed to delete session {} after its transcript failed: {:#}", session_id, rollback);
+            }
+            return Err(ApiError::InternalServerError(format!("Failed to create transcript: {}", e)));
+        }
+    };
+
+    let config = state.config.current();
+    semantic::index_transcript(state.repositories.transcript(), &config.ollama, transcript.id, &transcript.content).await;
+    Ok(())
 }
 
 /// Get a specific session by ID
@@ -212,22 +414,63 @@ async fn get_session<R: RepositoryManager>(
         .map_err(|e| ApiError::InternalServerError(format!("Failed to get session: {}", e)))?
         .ok_or_else(|| ApiError::NotFound("Session not found".to_string()))?;
 
-    let audio_count = state.repositories.audio()
-        .count_by_session(session.id)
-        .await
-        .unwrap_or(0);
-    
-    let transcript_count = state.repositories.transcript()
-        .count_by_session(session.id)
-        .await
-        .unwrap_or(0);
-    
-    let analysis_count = state.repositories.analysis()
-        .count_by_session(session.id)
+    Ok(Json(ApiResponse::success(session_response(&state, session).await)))
+}
+
+/// Get a session with everything the session detail view shows, loading the
+/// related entities concurrently instead of one request per entity
+async fn get_session_full<R: RepositoryManager>(
+    State(state): State<AppState<R>>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<ApiResponse<SessionFullResponse>>> {
+    let session = state.repositories.session()
+        .find_by_id(id)
         .await
-        .unwrap_or(0);
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to get session: {}", e)))?
+        .ok_or_else(|| ApiError::NotFound("Session not found".to_string()))?;
 
-    let response = SessionResponse {
+    let (session, transcript, analysis) = futures::join!(
+        session_response(&state, session),
+        state.repositories.transcript().find_by_session_id(&id),
+        state.repositories.analysis().find_by_session_id(&id),
+    );
+    let transcript = transcript
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to get transcript: {}", e)))?;
+    let analysis = analysis
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to get analysis: {}", e)))?;
+
+    // Ideas, tasks and notes belong to the current analysis
+    let (ideas, tasks, structured_notes) = match &analysis {
+        Some(analysis) => futures::try_join!(
+            state.repositories.idea().find_by_analysis_id(&analysis.id),
+            state.repositories.task().find_by_analysis_id(&analysis.id),
+            state.repositories.structured_note().find_by_analysis_id(&analysis.id),
+        )
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to get analysis items: {}", e)))?,
+        None => (Vec::new(), Vec::new(), Vec::new()),
+    };
+
+    Ok(Json(ApiResponse::success(SessionFullResponse {
+        session,
+        transcript,
+        analysis,
+        ideas,
+        tasks,
+        structured_notes,
+    })))
+}
+
+/// A session with the number of audio files, transcripts and analyses it has
+async fn session_response<R: RepositoryManager>(state: &AppState<R>, session: Session) -> SessionResponse {
+    let (audio_count, transcript_count, analysis_count) = futures::join!(
+        state.repositories.audio().count_by_session(session.id),
+        state.repositories.transcript().count_by_session(session.id),
+        state.repositories.analysis().count_by_session(session.id),
+    );
+    let (audio_count, transcript_count, analysis_count) =
+        (audio_count.unwrap_or(0), transcript_count.unwrap_or(0), analysis_count.unwrap_or(0));
+
+    SessionResponse {
         id: session.id,
         title: session.title,
         description: session.description,
@@ -239,14 +482,7 @@ async fn get_session<R: RepositoryManager>(
         analysis_count,
         created_at: session.created_at,
         updated_at: session.updated_at,
-    };
-
-    Ok(Json(ApiResponse {
-        data: response,
-        total: None,
-        page: None,
-        per_page: None,
-    }))
+    }
 }
 
 /// Update a session
@@ -255,6 +491,8 @@ async fn update_session<R: RepositoryManager>(
     Path(id): Path<Uuid>,
     Json(request): Json<UpdateSessionRequest>,
 ) -> ApiResult<Json<ApiResponse<SessionResponse>>> {
+    validate_metadata(request.metadata.as_ref())?;
+
     // Check if session exists
     let _session = state.repositories.session()
         .find_by_id(id)
@@ -304,12 +542,7 @@ async fn update_session<R: RepositoryManager>(
         updated_at: updated_session.updated_at,
     };
 
-    Ok(Json(ApiResponse {
-        data: response,
-        total: None,
-        page: None,
-        per_page: None,
-    }))
+    Ok(Json(ApiResponse::success(response)))
 }
 
 /// Delete a session
@@ -361,12 +594,96 @@ async fn list_session_audio<R: RepositoryManager>(
         .map(|audio| serde_json::to_value(audio).unwrap_or_default())
         .collect();
 
-    Ok(Json(ApiResponse {
-        data: audio_responses,
-        total: Some(total),
-        page: Some(pagination.page()),
-        per_page: Some(pagination.limit),
-    }))
+    Ok(Json(ApiResponse::paginated(audio_responses, total, pagination.page(), pagination.limit)))
+}
+
+/// Set or remove custom metadata fields of a session
+///
+/// The body is an object of the keys to change; a `null` value removes the
+/// key and keys not mentioned are kept. Responds with the resulting metadata.
+async fn patch_session_metadata<R: RepositoryManager>(
+    State(state): State<AppState<R>>,
+    Path(id): Path<Uuid>,
+    Json(patch): Json<serde_json::Value>,
+) -> ApiResult<Json<ApiResponse<serde_json::Value>>> {
+    validate_metadata(Some(&patch))?;
+    let serde_json::Value::Object(patch) = patch else {
+        unreachable!("checked by validate_metadata");
+    };
+
+    let session = state.repositories.session()
+        .merge_metadata(&id, &patch)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to update session metadata: {}", e)))?
+        .ok_or_else(|| ApiError::NotFound("Session not found".to_string()))?;
+
+    let metadata = session.metadata.unwrap_or_else(|| serde_json::json!({}));
+    Ok(Json(ApiResponse::success(metadata)))
+}
+
+/// All audio of a session as one file
+///
+/// A session with a single clip gets that clip as is. The clips of a session
+/// assembled from several recordings are joined in recording order, which
+/// requires WAV clips of the same format; otherwise the response is a 422
+/// and the clips listed by `/:id/audio` have to be downloaded one by one.
+async fn combined_session_audio<R: RepositoryManager>(
+    State(state): State<AppState<R>>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> ApiResult<axum::response::Response> {
+    let session = state.repositories.session()
+        .find_by_id(id)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to get session: {}", e)))?
+        .ok_or_else(|| ApiError::NotFound("Session not found".to_string()))?;
+
+    let audio_files = state.repositories.audio()
+        .find_all_by_session_id(&session.id)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to list audio files: {}", e)))?;
+    if audio_files.is_empty() {
+        return Err(ApiError::NotFound("Session has no audio".to_string()));
+    }
+
+    if audio_files.len() > 1 {
+        if let Some(audio_file) = audio_files.iter().find(|audio_file| audio_file.format != "wav") {
+            return Err(ApiError::UnprocessableEntity(format!(
+                "Only WAV clips can be joined, but audio file {} is {}; download the clips separately",
+                audio_file.id, audio_file.format
+            )));
+        }
+    }
+
+    let mut clips = Vec::with_capacity(audio_files.len());
+    for audio_file in &audio_files {
+        let data = state.services.file_storage()
+            .read_file(&audio_file.file_path)
+            .await
+            .map_err(|e| ApiError::InternalServerError(format!("Failed to read audio file: {}", e)))?;
+        clips.push(data);
+    }
+
+    let (format, content) = if let [audio_file] = audio_files.as_slice() {
+        (audio_file.format.as_str(), clips.remove(0))
+    } else {
+        let joined = crate::api::audio_concat::concat_wav(&clips).map_err(|e| {
+            ApiError::UnprocessableEntity(format!("Cannot join the clips: {}; download them separately", e))
+        })?;
+        ("wav", joined)
+    };
+
+    let content_type = match format {
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "m4a" => "audio/mp4",
+        "ogg" => "audio/ogg",
+        "flac" => "audio/flac",
+        _ => "application/octet-stream",
+    };
+    let filename = format!("{}.{}", session.title, format);
+
+    Ok(crate::etag::content_response(&headers, content_type, Some(&filename), content))
 }
 
 /// List transcripts for a session
@@ -398,12 +715,7 @@ async fn list_session_transcripts<R: RepositoryManager>(
         .map(|transcript| serde_json::to_value(transcript).unwrap_or_default())
         .collect();
 
-    Ok(Json(ApiResponse {
-        data: transcript_responses,
-        total: Some(total),
-        page: Some(pagination.page()),
-        per_page: Some(pagination.limit),
-    }))
+    Ok(Json(ApiResponse::paginated(transcript_responses, total, pagination.page(), pagination.limit)))
 }
 
 /// List analysis results for a session
@@ -435,34 +747,371 @@ async fn list_session_analysis<R: RepositoryManager>(
         .map(|analysis| serde_json::to_value(analysis).unwrap_or_default())
         .collect();
 
-    Ok(Json(ApiResponse {
-        data: analysis_responses,
-        total: Some(total),
-        page: Some(pagination.page()),
-        per_page: Some(pagination.limit),
-    }))
+    Ok(Json(ApiResponse::paginated(analysis_responses, total, pagination.page(), pagination.limit)))
+}
+
+/// Every analysis of a session, oldest first, to compare the output of
+/// different models and prompts
+async fn list_session_analysis_history<R: RepositoryManager>(
+    State(state): State<AppState<R>>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<ApiResponse<Vec<SessionAnalysisResponse>>>> {
+    let _session = state.repositories.session()
+        .find_by_id(id)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to get session: {}", e)))?
+        .ok_or_else(|| ApiError::NotFound("Session not found".to_string()))?;
+
+    let analyses = state.repositories.analysis()
+        .find_all_by_session_id(&id)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to list analysis history: {}", e)))?;
+
+    let total = analyses.len();
+    let history: Vec<SessionAnalysisResponse> = analyses
+        .into_iter()
+        .enumerate()
+        .map(|(index, analysis)| SessionAnalysisResponse {
+            analysis,
+            current: index + 1 == total,
+        })
+        .collect();
+
+    Ok(Json(ApiResponse::success(history).with_total(total as i64)))
 }
 
 /// Export session data
+///
+/// `format=docx` exports the session's current analysis as a Word document
+/// headed by the session title instead of the session data as JSON.
 async fn export_session<R: RepositoryManager>(
     State(state): State<AppState<R>>,
     Path(id): Path<Uuid>,
-) -> ApiResult<Json<serde_json::Value>> {
+    Query(query): Query<SessionExportQuery>,
+    headers: HeaderMap,
+) -> ApiResult<axum::response::Response> {
+    validate_choice("format", query.format.as_deref(), SESSION_EXPORT_FORMATS)?;
+    if query.format.as_deref() == Some("docx") {
+        let session = state.repositories.session()
+            .find_by_id(id)
+            .await
+            .map_err(|e| ApiError::InternalServerError(format!("Failed to get session: {}", e)))?
+            .ok_or_else(|| ApiError::NotFound("Session not found".to_string()))?;
+        let analysis = state.repositories.analysis()
+            .find_by_session_id(&session.id)
+            .await
+            .map_err(|e| ApiError::InternalServerError(format!("Failed to get analysis: {}", e)))?
+            .ok_or_else(|| ApiError::UnprocessableEntity("Session has no analysis to export".to_string()))?;
+
+        let title = session.title.as_deref().or(analysis.title.as_deref());
+        let content = super::analysis::analysis_docx(&state, &analysis, title).await?;
+        let filename = format!("session_{}.docx", session.id);
+        return Ok(crate::etag::content_response(&headers, docx::CONTENT_TYPE, Some(&filename), content));
+    }
+
     let export_data = state.services.session()
         .export_session(id)
         .await
         .map_err(|e| ApiError::InternalServerError(format!("Failed to export session: {}", e)))?;
+    let content = serde_json::to_vec(&export_data)
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to serialize export: {}", e)))?;
+
+    Ok(crate::etag::content_response(&headers, "application/json", None, content))
+}
+
+/// Title, transcript length, analysis summary and top tasks of a session in
+/// one request, for the session cards of the web UI
+async fn session_summary<R: RepositoryManager>(
+    State(state): State<AppState<R>>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<ApiResponse<SessionSummaryResponse>>> {
+    let session = state.repositories.session()
+        .find_by_id(id)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to get session: {}", e)))?
+        .ok_or_else(|| ApiError::NotFound("Session not found".to_string()))?;
+
+    let transcript = state.repositories.transcript()
+        .find_by_session_id(&session.id)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to get transcript: {}", e)))?;
 
-    Ok(Json(export_data))
+    let analysis = state.repositories.analysis()
+        .find_by_session_id(&session.id)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to get analysis: {}", e)))?;
+
+    let mut tasks = match &analysis {
+        Some(analysis) => state.repositories.task()
+            .find_by_analysis_id(&analysis.id)
+            .await
+            .map_err(|e| ApiError::InternalServerError(format!("Failed to get tasks: {}", e)))?,
+        None => Vec::new(),
+    };
+    tasks.retain(|task| !matches!(task.status, TaskStatus::Completed | TaskStatus::Cancelled));
+    tasks.sort_by(compare_task_urgency);
+
+    let response = SessionSummaryResponse {
+        id: session.id,
+        title: session.title,
+        duration_ms: session.duration_ms,
+        has_transcript: transcript.is_some(),
+        transcript_length: transcript.as_ref().map_or(0, |t| t.content.chars().count()),
+        has_analysis: analysis.is_some(),
+        summary: analysis.and_then(|analysis| analysis.summary),
+        top_tasks: tasks
+            .into_iter()
+            .take(SUMMARY_TASK_COUNT)
+            .map(|task| SessionSummaryTask {
+                id: task.id,
+                title: task.title,
+                priority: task.priority,
+                status: task.status,
+                due_date: task.due_date,
+            })
+            .collect(),
+    };
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
+/// Order tasks by priority, then due date (undated last), then age
+fn compare_task_urgency(a: &Task, b: &Task) -> std::cmp::Ordering {
+    fn rank(priority: &Priority) -> u8 {
+        match priority {
+            Priority::Urgent => 0,
+            Priority::High => 1,
+            Priority::Medium => 2,
+            Priority::Low => 3,
+        }
+    }
+
+    rank(&a.priority)
+        .cmp(&rank(&b.priority))
+        .then_with(|| match (a.due_date, b.due_date) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        })
+        .then_with(|| a.created_at.cmp(&b.created_at))
 }
 
 /// Search sessions
+///
+/// `mode=semantic` ranks sessions by how close their transcripts are in
+/// meaning to `q`. Without an `ollama.embedding_model`, or when the query
+/// cannot be embedded, it falls back to the keyword search and says so in
+/// `message`.
 async fn search_sessions<R: RepositoryManager>(
     State(state): State<AppState<R>>,
-    Query(query): Query<SessionListQuery>,
-) -> ApiResult<Json<ApiResponse<Vec<SessionResponse>>>> {
-    // This is similar to list_sessions but with enhanced search capabilities
-    list_sessions(State(state), Query(query)).await
+    StrictQuery(query): StrictQuery<SessionSearchQuery>,
+) -> ApiResult<Json<ApiResponse<Vec<SessionSearchResult>>>> {
+    validate_choice("mode", query.mode.as_deref(), SEARCH_MODES)?;
+    let term = query.list.search.q.clone().unwrap_or_default();
+
+    let mut fallback = None;
+    if query.mode.as_deref() == Some("semantic") && !term.trim().is_empty() {
+        match semantic_search(&state, &query.list, &term).await? {
+            Ok(response) => return Ok(Json(response)),
+            Err(reason) => fallback = Some(reason),
+        }
+    }
+
+    let Json(mut response) = list_sessions(State(state.clone()), StrictQuery(query.list)).await?;
+
+    let sessions = std::mem::take(&mut response.data);
+    let mut matches = transcript_matches(&state, sessions.iter().map(|session| session.id), &term).await?;
+    let results = sessions
+        .into_iter()
+        .map(|session| SessionSearchResult {
+            transcript_matches: matches.remove(&session.id).unwrap_or_default(),
+            session,
+            score: None,
+        })
+        .collect();
+
+    let response = response.map(|_| results);
+    Ok(Json(match fallback {
+        Some(reason) => response.with_message(format!("{}; showing keyword results", reason)),
+        None => response,
+    }))
+}
+
+/// Sessions ranked by the similarity of their transcripts to `term`
+///
+/// The inner error is the reason semantic search is unavailable, for the
+/// keyword search that replaces it.
+async fn semantic_search<R: RepositoryManager>(
+    state: &AppState<R>,
+    query: &SessionListQuery,
+    term: &str,
+) -> ApiResult<Result<ApiResponse<Vec<SessionSearchResult>>, String>> {
+    if query.status.is_some()
+        || query.tags.is_some()
+        || query.created_after.is_some()
+        || query.created_before.is_some()
+        || !query.metadata_filters().is_empty()
+    {
+        return Err(ApiError::UnprocessableEntity(
+            "Semantic search does not support the status, tags, created_after, created_before and meta.* filters".to_string(),
+        ));
+    }
+
+    let config = state.config.current();
+    let Some(model) = semantic::embedding_model(&config.ollama) else {
+        return Ok(Err("Semantic search is not configured (set ollama.embedding_model)".to_string()));
+    };
+
+    let query_embedding = match crate::ollama::embed(term, &config.ollama, model).await {
+        Ok(embedding) => embedding,
+        Err(e) => {
+            tracing::warn!("Failed to embed search query with {}: {:#}", model, e);
+            return Ok(Err(format!("Semantic search is unavailable: {}", e)));
+        }
+    };
+
+    let embeddings = state.repositories.transcript()
+        .embeddings(model)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to load transcript embeddings: {}", e)))?;
+    let ranked = semantic::rank_sessions(&query_embedding, &embeddings);
+    let total = ranked.len() as i64;
+
+    let page: Vec<_> = ranked
+        .into_iter()
+        .skip(query.pagination.offset.max(0) as usize)
+        .take(query.pagination.limit.max(0) as usize)
+        .collect();
+    let ids = unique_ids(page.iter().map(|(session_id, _)| *session_id));
+    let mut sessions: HashMap<Uuid, Session> = state.repositories.session()
+        .find_by_ids(&ids)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to get sessions: {}", e)))?
+        .into_iter()
+        .map(|session| (session.id, session))
+        .collect();
+    let mut matches = transcript_matches(state, ids, term).await?;
+
+    let mut results = Vec::with_capacity(page.len());
+    for (session_id, score) in page {
+        let Some(session) = sessions.remove(&session_id) else {
+            continue;
+        };
+        results.push(SessionSearchResult {
+            session: session_response(state, session).await,
+            transcript_matches: matches.remove(&session_id).unwrap_or_default(),
+            score: Some(score),
+        });
+    }
+
+    Ok(Ok(ApiResponse::paginated(results, total, query.pagination.page(), query.pagination.limit)))
+}
+
+/// Where `term` occurs in the transcripts of the sessions in `session_ids`,
+/// loaded in one query; sessions without a match are left out
+async fn transcript_matches<R: RepositoryManager>(
+    state: &AppState<R>,
+    session_ids: impl IntoIterator<Item = Uuid>,
+    term: &str,
+) -> ApiResult<HashMap<Uuid, Vec<SearchMatch>>> {
+    let session_ids = unique_ids(session_ids);
+    if term.trim().is_empty() || session_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    Ok(state.repositories.transcript()
+        .find_by_session_ids(&session_ids)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to get transcripts: {}", e)))?
+        .into_iter()
+        .map(|transcript| (transcript.session_id, find_matches(&transcript.content, term)))
+        .filter(|(_, matches)| !matches.is_empty())
+        .collect())
+}
+
+/// Find groups of sessions that are likely the same recording
+///
+/// Sessions are compared by their transcript text, or with `method=embedding`
+/// by the embeddings of semantic search where both have one. A session
+/// without a transcript only matches one with an identical audio file.
+async fn find_duplicate_sessions<R: RepositoryManager>(
+    State(state): State<AppState<R>>,
+    Query(query): Query<FindDuplicatesQuery>,
+) -> ApiResult<Json<ApiResponse<Vec<DuplicateSessionGroup>>>> {
+    validate_choice("method", query.method.as_deref(), DUPLICATE_METHODS)?;
+    let threshold = query.threshold.unwrap_or(DEFAULT_DUPLICATE_THRESHOLD);
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(ApiError::UnprocessableEntity("threshold must be between 0 and 1".to_string()));
+    }
+
+    let mut embeddings = HashMap::new();
+    if query.method.as_deref() == Some("embedding") {
+        let config = state.config.current();
+        let model = semantic::embedding_model(&config.ollama).ok_or_else(|| {
+            ApiError::UnprocessableEntity("method=embedding requires ollama.embedding_model to be set".to_string())
+        })?;
+        let stored = state.repositories.transcript()
+            .embeddings(model)
+            .await
+            .map_err(|e| ApiError::InternalServerError(format!("Failed to load transcript embeddings: {}", e)))?;
+        embeddings.extend(stored.into_iter().map(|embedding| (embedding.session_id, embedding.embedding)));
+    }
+
+    let filter = SessionFilter {
+        sort_by: Some(SessionSortBy::CreatedAt),
+        sort_order: Some(SortOrder::Asc),
+        ..Default::default()
+    };
+    let sessions: Vec<Session> = state.repositories.session()
+        .list(&filter)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to list sessions: {}", e)))?
+        .into_iter()
+        .filter(|session| !matches!(session.status, SessionStatus::Deleted))
+        .collect();
+
+    let mut candidates = Vec::with_capacity(sessions.len());
+    for session in &sessions {
+        let tokens = state.repositories.transcript()
+            .find_by_session_id(&session.id)
+            .await
+            .map_err(|e| ApiError::InternalServerError(format!("Failed to get transcript: {}", e)))?
+            .map(|transcript| duplicates::token_set(&transcript.content))
+            .filter(|tokens| !tokens.is_empty());
+        let checksums = state.repositories.audio()
+            .find_all_by_session_id(&session.id)
+            .await
+            .map_err(|e| ApiError::InternalServerError(format!("Failed to get audio files: {}", e)))?
+            .into_iter()
+            .filter_map(|audio| audio.checksum)
+            .collect();
+        candidates.push(duplicates::Candidate {
+            session_id: session.id,
+            tokens,
+            embedding: embeddings.remove(&session.id),
+            checksums,
+        });
+    }
+
+    let groups = duplicates::find_groups(&candidates, threshold);
+    let mut sessions: HashMap<Uuid, Session> = sessions.into_iter().map(|session| (session.id, session)).collect();
+    let mut results = Vec::with_capacity(groups.len());
+    for group in groups {
+        let mut members = Vec::with_capacity(group.session_ids.len());
+        for id in &group.session_ids {
+            if let Some(session) = sessions.remove(id) {
+                members.push(session_response(&state, session).await);
+            }
+        }
+        results.push(DuplicateSessionGroup {
+            similarity_score: group.similarity,
+            matched_by: group.matched_by,
+            sessions: members,
+        });
+    }
+
+    let total = results.len() as i64;
+    Ok(Json(ApiResponse::success(results).with_total(total)))
 }
 
 /// Get session statistics
@@ -5,8 +5,8 @@
 
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     routing::{delete, get, patch, post},
     Router,
 };
@@ -14,12 +14,16 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::api::{
+    extractors::{Json, ListQueryKeys, StrictQuery},
     error::ApiError,
+    ical::{self, CalendarTask},
+    idempotency::{idempotency_key, mismatch_error, replay_response, FingerprintedJson, IdempotencyStatus},
     responses::{ApiResponse, PaginationParams, SearchParams, SortParams},
-    AppState, ApiResult,
+    index_by_id, page_relations, rate, stats_error, unique_ids, validate_choice, AppState, ApiResult,
+    DeleteQuery, StatsQuery,
 };
 use crate::repository::{
-    traits::{TaskRepository, NewTask, UpdateTask},
+    traits::{AnalysisRepository, SessionRepository, TaskRepository, NewTask, Task, UpdateTask},
     RepositoryManager,
 };
 use crate::services::traits::TaskService;
@@ -29,6 +33,8 @@ pub fn create_routes<R: RepositoryManager + 'static>() -> Router<AppState<R>> {
     Router::new()
         .route("/", get(list_tasks).post(create_task))
         .route("/:id", get(get_task).patch(update_task).delete(delete_task))
+        .route("/:id/restore", post(restore_task))
+        .route("/export", get(export_tasks))
         .route("/:id/export", get(export_task))
         .route("/:id/complete", post(complete_task))
         .route("/:id/reopen", post(reopen_task))
@@ -46,6 +52,7 @@ pub fn create_routes<R: RepositoryManager + 'static>() -> Router<AppState<R>> {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct CreateTaskRequest {
     session_id: Uuid,
     analysis_id: Option<Uuid>,
@@ -62,6 +69,7 @@ struct CreateTaskRequest {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct UpdateTaskRequest {
     title: Option<String>,
     description: Option<String>,
@@ -75,6 +83,23 @@ struct UpdateTaskRequest {
     metadata: Option<serde_json::Value>,
 }
 
+const TASK_PRIORITIES: &[&str] = &["low", "medium", "high", "critical"];
+const TASK_STATUSES: &[&str] = &["todo", "in_progress", "completed", "cancelled"];
+
+impl CreateTaskRequest {
+    fn validate(&self) -> ApiResult<()> {
+        validate_choice("priority", self.priority.as_deref(), TASK_PRIORITIES)?;
+        validate_choice("status", self.status.as_deref(), TASK_STATUSES)
+    }
+}
+
+impl UpdateTaskRequest {
+    fn validate(&self) -> ApiResult<()> {
+        validate_choice("priority", self.priority.as_deref(), TASK_PRIORITIES)?;
+        validate_choice("status", self.status.as_deref(), TASK_STATUSES)
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct TasksListQuery {
     #[serde(flatten)]
@@ -95,30 +120,64 @@ struct TasksListQuery {
     created_before: Option<chrono::DateTime<chrono::Utc>>,
     overdue: Option<bool>,
     completed: Option<bool>,
+    include_deleted: Option<bool>,
+}
+
+impl ListQueryKeys for TasksListQuery {
+    const KEYS: &'static [&'static str] = &[
+        "session_id",
+        "analysis_id",
+        "priority",
+        "status",
+        "tags",
+        "min_confidence",
+        "due_after",
+        "due_before",
+        "created_after",
+        "created_before",
+        "overdue",
+        "completed",
+        "include_deleted",
+    ];
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct BatchCreateTasksRequest {
     tasks: Vec<CreateTaskRequest>,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct BatchDeleteTasksRequest {
     task_ids: Vec<Uuid>,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct BatchUpdateTasksRequest {
     updates: Vec<BatchTaskUpdate>,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct BatchTaskUpdate {
     task_id: Uuid,
     update: UpdateTaskRequest,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExportTasksQuery {
+    format: Option<String>,
+    session_id: Option<Uuid>,
+    analysis_id: Option<Uuid>,
+    priority: Option<String>,
+    status: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct CalendarQuery {
     start_date: chrono::NaiveDate,
     end_date: chrono::NaiveDate,
@@ -145,6 +204,7 @@ struct TaskResponse {
     completed_at: Option<chrono::DateTime<chrono::Utc>>,
     created_at: chrono::DateTime<chrono::Utc>,
     updated_at: chrono::DateTime<chrono::Utc>,
+    deleted_at: Option<chrono::DateTime<chrono::Utc>>,
     // Computed fields
     is_overdue: bool,
     days_until_due: Option<i64>,
@@ -253,7 +313,7 @@ struct CalendarSummary {
 /// List tasks with filtering and pagination
 async fn list_tasks<R: RepositoryManager>(
     State(state): State<AppState<R>>,
-    Query(query): Query<TasksListQuery>,
+    StrictQuery(query): StrictQuery<TasksListQuery>,
 ) -> ApiResult<Json<ApiResponse<Vec<TaskResponse>>>> {
     let tags_filter = query.tags.as_ref().map(|t| {
         t.split(',')
@@ -276,6 +336,7 @@ async fn list_tasks<R: RepositoryManager>(
             query.overdue,
             query.completed,
             query.search.q.as_deref(),
+            query.include_deleted.unwrap_or(false),
             Some(query.pagination.limit),
             Some(query.pagination.offset),
             query.sort.sort_by.as_deref(),
@@ -299,30 +360,26 @@ async fn list_tasks<R: RepositoryManager>(
             query.overdue,
             query.completed,
             query.search.q.as_deref(),
+            query.include_deleted.unwrap_or(false),
         )
         .await
         .map_err(|e| ApiError::InternalServerError(format!("Failed to count tasks: {}", e)))?;
 
-    // Enrich with related data and computed fields
+    // Enrich with related data, loaded for the whole page at once, and computed fields
+    let (sessions, analyses) = page_relations(
+        state.repositories.session(),
+        state.repositories.analysis(),
+        tasks.iter().map(|task| task.session_id),
+        tasks.iter().filter_map(|task| task.analysis_id),
+    )
+    .await;
+
     let mut responses = Vec::new();
     let now = chrono::Utc::now();
     
     for task in tasks {
-        let session = state.repositories.session()
-            .find_by_id(task.session_id)
-            .await
-            .ok()
-            .flatten();
-        
-        let analysis = if let Some(analysis_id) = task.analysis_id {
-            state.repositories.analysis()
-                .find_by_id(analysis_id)
-                .await
-                .ok()
-                .flatten()
-        } else {
-            None
-        };
+        let session = sessions.get(&task.session_id);
+        let analysis = task.analysis_id.and_then(|analysis_id| analyses.get(&analysis_id));
 
         let (is_overdue, days_until_due) = if let Some(due_date) = task.due_date {
             let days_diff = (due_date - now).num_days();
@@ -348,19 +405,15 @@ async fn list_tasks<R: RepositoryManager>(
             completed_at: task.completed_at,
             created_at: task.created_at,
             updated_at: task.updated_at,
+            deleted_at: task.deleted_at,
             is_overdue,
             days_until_due,
-            session_title: session.and_then(|s| s.title),
-            analysis_type: analysis.map(|a| a.analysis_type),
+            session_title: session.and_then(|s| s.title.clone()),
+            analysis_type: analysis.map(|a| a.analysis_type.clone()),
         });
     }
 
-    Ok(Json(ApiResponse {
-        data: responses,
-        total: Some(total),
-        page: Some(query.pagination.page()),
-        per_page: Some(query.pagination.limit),
-    }))
+    Ok(Json(ApiResponse::paginated(responses, total, query.pagination.page(), query.pagination.limit)))
 }
 
 /// Create a new task
@@ -368,6 +421,8 @@ async fn create_task<R: RepositoryManager>(
     State(state): State<AppState<R>>,
     Json(request): Json<CreateTaskRequest>,
 ) -> ApiResult<Json<ApiResponse<TaskResponse>>> {
+    request.validate()?;
+
     // Validate session exists
     let _session = state.repositories.session()
         .find_by_id(request.session_id)
@@ -406,12 +461,7 @@ async fn create_task<R: RepositoryManager>(
 
     let response = create_task_response(&state, task).await?;
 
-    Ok(Json(ApiResponse {
-        data: response,
-        total: None,
-        page: None,
-        per_page: None,
-    }))
+    Ok(Json(ApiResponse::success(response)))
 }
 
 /// Get a specific task by ID
@@ -427,12 +477,7 @@ async fn get_task<R: RepositoryManager>(
 
     let response = create_task_response(&state, task).await?;
 
-    Ok(Json(ApiResponse {
-        data: response,
-        total: None,
-        page: None,
-        per_page: None,
-    }))
+    Ok(Json(ApiResponse::success(response)))
 }
 
 /// Update a task
@@ -441,6 +486,8 @@ async fn update_task<R: RepositoryManager>(
     Path(id): Path<Uuid>,
     Json(request): Json<UpdateTaskRequest>,
 ) -> ApiResult<Json<ApiResponse<TaskResponse>>> {
+    request.validate()?;
+
     // Check if task exists
     let _task = state.repositories.task()
         .find_by_id(id)
@@ -468,19 +515,26 @@ async fn update_task<R: RepositoryManager>(
 
     let response = create_task_response(&state, updated_task).await?;
 
-    Ok(Json(ApiResponse {
-        data: response,
-        total: None,
-        page: None,
-        per_page: None,
-    }))
+    Ok(Json(ApiResponse::success(response)))
 }
 
-/// Delete a task
+/// Soft-delete a task; `?purge=true` deletes it permanently
 async fn delete_task<R: RepositoryManager>(
     State(state): State<AppState<R>>,
     Path(id): Path<Uuid>,
+    Query(query): Query<DeleteQuery>,
 ) -> ApiResult<StatusCode> {
+    if query.purge {
+        let purged = state.repositories.task()
+            .purge(id)
+            .await
+            .map_err(|e| ApiError::InternalServerError(format!("Failed to purge task: {}", e)))?;
+        if !purged {
+            return Err(ApiError::NotFound("Task not found".to_string()));
+        }
+        return Ok(StatusCode::NO_CONTENT);
+    }
+
     // Check if task exists
     let _task = state.repositories.task()
         .find_by_id(id)
@@ -496,11 +550,28 @@ async fn delete_task<R: RepositoryManager>(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Restore a soft-deleted task
+async fn restore_task<R: RepositoryManager>(
+    State(state): State<AppState<R>>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<ApiResponse<TaskResponse>>> {
+    let restored = state.repositories.task()
+        .restore(id)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to restore task: {}", e)))?;
+    if !restored {
+        return Err(ApiError::NotFound("Deleted task not found".to_string()));
+    }
+
+    get_task(State(state), Path(id)).await
+}
+
 /// Export task
 async fn export_task<R: RepositoryManager>(
     State(state): State<AppState<R>>,
     Path(id): Path<Uuid>,
     Query(params): Query<std::collections::HashMap<String, String>>,
+    headers: HeaderMap,
 ) -> ApiResult<axum::response::Response> {
     let task = state.repositories.task()
         .find_by_id(id)
@@ -561,25 +632,81 @@ async fn export_task<R: RepositoryManager>(
             );
             (markdown_content, "text/markdown", format!("task_{}.md", task.id))
         }
+        "ics" => {
+            let calendar = ical::tasks_to_ics(&[calendar_task(&task)]);
+            (calendar, "text/calendar; charset=utf-8", format!("task_{}.ics", task.id))
+        }
         _ => {
             return Err(ApiError::BadRequest(format!(
-                "Unsupported export format: {}. Supported formats: json, txt, md",
+                "Unsupported export format: {}. Supported formats: json, txt, md, ics",
                 format
             )));
         }
     };
 
-    Ok((
-        [
-            (axum::http::header::CONTENT_TYPE, content_type),
-            (
-                axum::http::header::CONTENT_DISPOSITION,
-                &format!("attachment; filename=\"{}\"", filename),
-            ),
-        ],
-        content,
-    )
-        .into_response())
+    Ok(crate::etag::content_response(&headers, content_type, Some(&filename), content))
+}
+
+/// Export all tasks matching the filters as one document
+async fn export_tasks<R: RepositoryManager>(
+    State(state): State<AppState<R>>,
+    Query(query): Query<ExportTasksQuery>,
+    headers: HeaderMap,
+) -> ApiResult<axum::response::Response> {
+    let format = query.format.as_deref().unwrap_or("json");
+    if !matches!(format, "json" | "ics") {
+        return Err(ApiError::BadRequest(format!(
+            "Unsupported export format: {}. Supported formats: json, ics",
+            format
+        )));
+    }
+
+    let tasks = state.repositories.task()
+        .find_with_filters(
+            query.session_id,
+            query.analysis_id,
+            query.priority.as_deref(),
+            query.status.as_deref(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some("due_date"),
+            Some("asc"),
+        )
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to list tasks: {}", e)))?;
+
+    let (content, content_type, filename) = if format == "ics" {
+        let entries: Vec<CalendarTask<'_>> = tasks.iter().map(calendar_task).collect();
+        (ical::tasks_to_ics(&entries), "text/calendar; charset=utf-8", "tasks.ics")
+    } else {
+        let json_data = serde_json::to_string_pretty(&tasks)
+            .map_err(|e| ApiError::InternalServerError(format!("Failed to serialize tasks: {}", e)))?;
+        (json_data, "application/json", "tasks.json")
+    };
+
+    Ok(crate::etag::content_response(&headers, content_type, Some(&filename), content))
+}
+
+/// Calendar entry for a task; the description falls back to the source text
+fn calendar_task(task: &Task) -> CalendarTask<'_> {
+    CalendarTask {
+        uid: format!("task-{}@voice-recorder", task.id),
+        summary: &task.title,
+        description: task.description.as_deref().or(task.source_text.as_deref()),
+        due: task.due_date,
+        created: task.created_at,
+        priority: &task.priority,
+        completed: task.status == "completed",
+    }
 }
 
 /// Complete a task
@@ -599,12 +726,7 @@ async fn complete_task<R: RepositoryManager>(
 
     let response = create_task_response(&state, updated_task).await?;
 
-    Ok(Json(ApiResponse {
-        data: response,
-        total: None,
-        page: None,
-        per_page: None,
-    }))
+    Ok(Json(ApiResponse::success(response)))
 }
 
 /// Reopen a completed task
@@ -624,41 +746,53 @@ async fn reopen_task<R: RepositoryManager>(
 
     let response = create_task_response(&state, updated_task).await?;
 
-    Ok(Json(ApiResponse {
-        data: response,
-        total: None,
-        page: None,
-        per_page: None,
-    }))
+    Ok(Json(ApiResponse::success(response)))
 }
 
 /// List tasks for a specific session
 async fn list_session_tasks<R: RepositoryManager>(
     State(state): State<AppState<R>>,
     Path(session_id): Path<Uuid>,
-    Query(query): Query<TasksListQuery>,
+    StrictQuery(query): StrictQuery<TasksListQuery>,
 ) -> ApiResult<Json<ApiResponse<Vec<TaskResponse>>>> {
     let mut modified_query = query;
     modified_query.session_id = Some(session_id);
-    list_tasks(State(state), Query(modified_query)).await
+    list_tasks(State(state), StrictQuery(modified_query)).await
 }
 
 /// List tasks for a specific analysis
 async fn list_analysis_tasks<R: RepositoryManager>(
     State(state): State<AppState<R>>,
     Path(analysis_id): Path<Uuid>,
-    Query(query): Query<TasksListQuery>,
+    StrictQuery(query): StrictQuery<TasksListQuery>,
 ) -> ApiResult<Json<ApiResponse<Vec<TaskResponse>>>> {
     let mut modified_query = query;
     modified_query.analysis_id = Some(analysis_id);
-    list_tasks(State(state), Query(modified_query)).await
+    list_tasks(State(state), StrictQuery(modified_query)).await
 }
 
 /// Batch create tasks
+///
+/// Honors the `Idempotency-Key` header so a retried batch is not inserted twice.
 async fn batch_create_tasks<R: RepositoryManager>(
     State(state): State<AppState<R>>,
-    Json(request): Json<BatchCreateTasksRequest>,
-) -> ApiResult<Json<BatchCreateResponse>> {
+    headers: HeaderMap,
+    FingerprintedJson { body: request, fingerprint }: FingerprintedJson<BatchCreateTasksRequest>,
+) -> ApiResult<Response> {
+    let mut claim = None;
+    if let Some(key) = idempotency_key(&headers)? {
+        match state.idempotency.begin("tasks:batch_create", &key, &fingerprint) {
+            IdempotencyStatus::Replay(body) => return Ok(replay_response(body)),
+            IdempotencyStatus::InFlight => {
+                return Err(ApiError::Conflict(
+                    "A request with this Idempotency-Key is already being processed".to_string(),
+                ));
+            }
+            IdempotencyStatus::Mismatch => return Err(mismatch_error()),
+            IdempotencyStatus::New(new_claim) => claim = Some(new_claim),
+        }
+    }
+
     let mut created_tasks = Vec::new();
     let mut errors = Vec::new();
     let mut created_count = 0;
@@ -677,12 +811,18 @@ async fn batch_create_tasks<R: RepositoryManager>(
         }
     }
 
-    Ok(Json(BatchCreateResponse {
+    let response = BatchCreateResponse {
         created: created_count,
         failed: failed_count,
         tasks: created_tasks,
         errors,
-    }))
+    };
+
+    if let Some(claim) = claim {
+        claim.complete(&response);
+    }
+
+    Ok(Json(response).into_response())
 }
 
 /// Batch delete tasks
@@ -722,6 +862,12 @@ async fn batch_update_tasks<R: RepositoryManager>(
     let mut failed_count = 0;
 
     for update_request in request.updates {
+        if let Err(e) = update_request.update.validate() {
+            errors.push(format!("Failed to update task {}: {}", update_request.task_id, e));
+            failed_count += 1;
+            continue;
+        }
+
         match state.repositories.task()
             .update(update_request.task_id, UpdateTask {
                 title: update_request.update.title,
@@ -767,22 +913,52 @@ async fn batch_update_tasks<R: RepositoryManager>(
 /// Search tasks
 async fn search_tasks<R: RepositoryManager>(
     State(state): State<AppState<R>>,
-    Query(query): Query<TasksListQuery>,
+    StrictQuery(query): StrictQuery<TasksListQuery>,
 ) -> ApiResult<Json<ApiResponse<Vec<TaskResponse>>>> {
     // Enhanced search functionality
-    list_tasks(State(state), Query(query)).await
+    list_tasks(State(state), StrictQuery(query)).await
 }
 
 /// Get tasks statistics
+///
+/// `tz` selects the time zone of the `tasks_per_day` buckets and `days` their
+/// number; everything else covers all tasks.
 async fn tasks_stats<R: RepositoryManager>(
     State(state): State<AppState<R>>,
+    Query(query): Query<StatsQuery>,
 ) -> ApiResult<Json<TasksStatsResponse>> {
-    let stats = state.services.task()
-        .get_tasks_stats()
+    let window = query.window()?;
+    let stats = state.repositories.task()
+        .stats(&window)
         .await
-        .map_err(|e| ApiError::InternalServerError(format!("Failed to get tasks stats: {}", e)))?;
-
-    Ok(Json(stats))
+        .map_err(|e| stats_error(e, "tasks"))?;
+
+    let priority_completion_rates = stats
+        .by_priority
+        .iter()
+        .map(|(priority, count)| {
+            let completed = stats.completed_by_priority.get(priority).copied().unwrap_or(0);
+            (priority.clone(), rate(completed, *count))
+        })
+        .collect();
+
+    Ok(Json(TasksStatsResponse {
+        total_tasks: stats.total,
+        completion_rate: rate(stats.completed, stats.total),
+        priorities: stats.by_priority,
+        statuses: stats.by_status,
+        tags: stats.by_tag,
+        avg_confidence_score: stats.avg_confidence.unwrap_or(0.0),
+        overdue_count: stats.overdue,
+        upcoming_count: stats.upcoming,
+        avg_completion_time: stats.avg_completion_days.unwrap_or(0.0),
+        tasks_per_day: stats
+            .per_day
+            .into_iter()
+            .map(|day| DailyCount { date: day.date, count: day.count })
+            .collect(),
+        priority_completion_rates,
+    }))
 }
 
 /// Get task priorities
@@ -874,18 +1050,18 @@ async fn get_task_statuses<R: RepositoryManager>(
 /// Get overdue tasks
 async fn get_overdue_tasks<R: RepositoryManager>(
     State(state): State<AppState<R>>,
-    Query(query): Query<TasksListQuery>,
+    StrictQuery(query): StrictQuery<TasksListQuery>,
 ) -> ApiResult<Json<ApiResponse<Vec<TaskResponse>>>> {
     let mut modified_query = query;
     modified_query.overdue = Some(true);
     modified_query.completed = Some(false);
-    list_tasks(State(state), Query(modified_query)).await
+    list_tasks(State(state), StrictQuery(modified_query)).await
 }
 
 /// Get upcoming tasks (due in next 7 days)
 async fn get_upcoming_tasks<R: RepositoryManager>(
     State(state): State<AppState<R>>,
-    Query(query): Query<TasksListQuery>,
+    StrictQuery(query): StrictQuery<TasksListQuery>,
 ) -> ApiResult<Json<ApiResponse<Vec<TaskResponse>>>> {
     let now = chrono::Utc::now();
     let next_week = now + chrono::Duration::days(7);
@@ -894,7 +1070,7 @@ async fn get_upcoming_tasks<R: RepositoryManager>(
     modified_query.due_after = Some(now);
     modified_query.due_before = Some(next_week);
     modified_query.completed = Some(false);
-    list_tasks(State(state), Query(modified_query)).await
+    list_tasks(State(state), StrictQuery(modified_query)).await
 }
 
 /// Get tasks calendar view
@@ -939,13 +1115,16 @@ async fn get_tasks_calendar<R: RepositoryManager>(
     let mut high_priority_tasks = 0;
     let now = chrono::Utc::now();
 
+    let session_ids = unique_ids(tasks.iter().map(|task| task.session_id));
+    let sessions = index_by_id(
+        state.repositories.session().find_by_ids(&session_ids).await,
+        "sessions",
+        |session| session.id,
+    );
+
     for task in tasks {
         if let Some(due_date) = task.due_date {
-            let session = state.repositories.session()
-                .find_by_id(task.session_id)
-                .await
-                .ok()
-                .flatten();
+            let session = sessions.get(&task.session_id);
 
             calendar_tasks.push(CalendarTask {
                 id: task.id,
@@ -954,7 +1133,7 @@ async fn get_tasks_calendar<R: RepositoryManager>(
                 status: task.status.clone(),
                 due_date,
                 estimated_duration: task.estimated_duration,
-                session_title: session.and_then(|s| s.title),
+                session_title: session.and_then(|s| s.title.clone()),
             });
 
             total_tasks += 1;
@@ -1033,6 +1212,7 @@ async fn create_task_response<R: RepositoryManager>(
         completed_at: task.completed_at,
         created_at: task.created_at,
         updated_at: task.updated_at,
+        deleted_at: task.deleted_at,
         is_overdue,
         days_until_due,
         session_title: session.and_then(|s| s.title),
@@ -1044,6 +1224,8 @@ async fn create_task_internal<R: RepositoryManager>(
     state: &AppState<R>,
     request: CreateTaskRequest,
 ) -> Result<TaskResponse, ApiError> {
+    request.validate()?;
+
     // Validate session exists
     let _session = state.repositories.session()
         .find_by_id(request.session_id)
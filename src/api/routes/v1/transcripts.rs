@@ -5,8 +5,7 @@
 
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    http::{HeaderMap, StatusCode},
     routing::{delete, get, patch, post},
     Router,
 };
@@ -14,15 +13,23 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::api::{
+    combined_export::{self, CombinedTranscript},
+    extractors::{Json, ListQueryKeys, StrictQuery},
     error::ApiError,
+    highlight::{find_matches, SearchMatch},
     responses::{ApiResponse, PaginationParams, SearchParams, SortParams},
-    AppState, ApiResult,
+    index_by_id, semantic, stats_error, unique_ids, validate_choice, validate_min_confidence, validate_options, AppState,
+    ApiResult, StatsQuery,
 };
 use crate::repository::{
-    traits::{NewTranscript, TranscriptRepository, UpdateTranscript},
+    traits::{
+        AudioRepository, NewTranscript, SessionRepository, TranscriptRepository, TranscriptVersion,
+        UpdateTranscript,
+    },
     RepositoryManager,
 };
-use crate::services::traits::TranscriptionService;
+use crate::ollama::ModelOptions;
+use crate::services::traits::{AnalysisService, TranscriptionService};
 
 /// Create transcript routes
 pub fn create_routes<R: RepositoryManager + 'static>() -> Router<AppState<R>> {
@@ -30,7 +37,10 @@ pub fn create_routes<R: RepositoryManager + 'static>() -> Router<AppState<R>> {
         .route("/", get(list_transcripts).post(create_transcript))
         .route("/:id", get(get_transcript).patch(update_transcript).delete(delete_transcript))
         .route("/:id/export", get(export_transcript))
+        .route("/:id/history", get(transcript_history))
+        .route("/:id/revert/:version", post(revert_transcript))
         .route("/:id/analyze", post(analyze_transcript))
+        .route("/:id/redetect-language", post(redetect_language))
         .route("/search", get(search_transcripts))
         .route("/stats", get(transcript_stats))
         .route("/batch/create", post(batch_create_transcripts))
@@ -38,6 +48,7 @@ pub fn create_routes<R: RepositoryManager + 'static>() -> Router<AppState<R>> {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct CreateTranscriptRequest {
     audio_file_id: Uuid,
     provider: Option<String>, // "openai" or "local"
@@ -46,9 +57,12 @@ struct CreateTranscriptRequest {
     model: Option<String>,
     prompt: Option<String>,
     metadata: Option<serde_json::Value>,
+    /// Transcribe again even if the audio file already has a transcript
+    force: Option<bool>,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct UpdateTranscriptRequest {
     content: Option<String>,
     language: Option<String>,
@@ -69,22 +83,77 @@ struct TranscriptListQuery {
     audio_file_id: Option<Uuid>,
     language: Option<String>,
     provider: Option<String>,
+    /// Exact model name, e.g. `whisper-1` or `ggml-large-v3`
+    model: Option<String>,
     status: Option<String>,
     min_confidence: Option<f64>,
     created_after: Option<chrono::DateTime<chrono::Utc>>,
     created_before: Option<chrono::DateTime<chrono::Utc>>,
 }
 
+impl ListQueryKeys for TranscriptListQuery {
+    const KEYS: &'static [&'static str] = &[
+        "session_id",
+        "audio_file_id",
+        "language",
+        "provider",
+        "model",
+        "status",
+        "min_confidence",
+        "created_after",
+        "created_before",
+    ];
+}
+
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct AnalyzeTranscriptRequest {
     analysis_types: Vec<String>, // ["summary", "ideas", "tasks", "structured"]
     provider: Option<String>,
     language: Option<String>,
+    /// Language to write the results in; defaults to the transcript's
+    output_language: Option<String>,
     model: Option<String>,
     custom_prompts: Option<std::collections::HashMap<String, String>>,
+    /// Drop extracted tasks and ideas the model is less confident about,
+    /// from 0 to 1; defaults to `analysis.min_confidence_to_persist`
+    min_confidence: Option<f64>,
+    /// Ollama generation options, e.g. `{"temperature": 0.8}`, over `ollama.options`
+    options: Option<ModelOptions>,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RedetectLanguageRequest {
+    /// Analyze the transcript again when its language was corrected
+    reanalyze: Option<bool>,
+    /// Analyses to run when re-analyzing, all of them by default
+    analysis_types: Option<Vec<String>>,
+    provider: Option<String>,
+    /// Language to write the results in; defaults to the detected one
+    output_language: Option<String>,
+    model: Option<String>,
+    /// Drop extracted tasks and ideas the model is less confident about,
+    /// from 0 to 1; defaults to `analysis.min_confidence_to_persist`
+    min_confidence: Option<f64>,
+    /// Ollama generation options, e.g. `{"temperature": 0.8}`, over `ollama.options`
+    options: Option<ModelOptions>,
+}
+
+#[derive(Debug, Serialize)]
+struct RedetectLanguageResponse {
+    transcript_id: Uuid,
+    old_language: Option<String>,
+    new_language: String,
+    /// Share of the text written in the detected language, from 0 to 1
+    confidence: f64,
+    changed: bool,
+    /// Results of the analyses made with the corrected language
+    analysis: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct BatchCreateRequest {
     audio_file_ids: Vec<Uuid>,
     provider: Option<String>,
@@ -95,13 +164,21 @@ struct BatchCreateRequest {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct BatchExportRequest {
     transcript_ids: Vec<Uuid>,
-    format: String, // "txt", "json", "srt", "vtt"
+    format: String, // "txt", "json", "srt", "vtt"; "txt" or "md" when combined
     include_metadata: Option<bool>,
     include_timestamps: Option<bool>,
+    /// One document with a section per transcript instead of a ZIP archive
+    combined: Option<bool>,
+    /// Mask personal data; defaults to `redaction.enabled`
+    redact: Option<bool>,
 }
 
+/// Formats of combined batch exports
+const COMBINED_EXPORT_FORMATS: &[&str] = &["txt", "md"];
+
 #[derive(Debug, Serialize)]
 struct TranscriptResponse {
     id: Uuid,
@@ -124,6 +201,46 @@ struct TranscriptResponse {
     session_title: Option<String>,
 }
 
+#[derive(Debug, Serialize)]
+struct CreateTranscriptResponse {
+    #[serde(flatten)]
+    transcript: TranscriptResponse,
+    /// Whether an existing transcript was returned instead of transcribing again
+    reused: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct TranscriptVersionResponse {
+    version: i32,
+    content: String,
+    segments: serde_json::Value,
+    word_count: usize,
+    character_count: usize,
+    /// When this content was replaced by an edit or a revert
+    replaced_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<TranscriptVersion> for TranscriptVersionResponse {
+    fn from(version: TranscriptVersion) -> Self {
+        Self {
+            version: version.version,
+            word_count: version.content.split_whitespace().count(),
+            character_count: version.content.chars().count(),
+            content: version.content,
+            segments: version.segments,
+            replaced_at: version.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct TranscriptSearchResult {
+    #[serde(flatten)]
+    transcript: TranscriptResponse,
+    /// Where the search term occurs in `content`, with surrounding context
+    matches: Vec<SearchMatch>,
+}
+
 #[derive(Debug, Serialize)]
 struct TranscriptStatsResponse {
     total_transcripts: i64,
@@ -132,6 +249,8 @@ struct TranscriptStatsResponse {
     avg_confidence_score: f64,
     languages: std::collections::HashMap<String, i64>,
     providers: std::collections::HashMap<String, i64>,
+    /// Count per exact model
+    models: std::collections::HashMap<String, i64>,
     status_distribution: std::collections::HashMap<String, i64>,
     avg_processing_time_ms: f64,
     transcripts_per_day: Vec<DailyCount>,
@@ -161,7 +280,7 @@ struct BatchResult {
 /// List transcripts with filtering and pagination
 async fn list_transcripts<R: RepositoryManager>(
     State(state): State<AppState<R>>,
-    Query(query): Query<TranscriptListQuery>,
+    StrictQuery(query): StrictQuery<TranscriptListQuery>,
 ) -> ApiResult<Json<ApiResponse<Vec<TranscriptResponse>>>> {
     let transcripts = state.repositories.transcript()
         .find_with_filters(
@@ -169,6 +288,7 @@ async fn list_transcripts<R: RepositoryManager>(
             query.audio_file_id,
             query.language.as_deref(),
             query.provider.as_deref(),
+            query.model.as_deref(),
             query.status.as_deref(),
             query.min_confidence,
             query.created_after,
@@ -187,6 +307,7 @@ async fn list_transcripts<R: RepositoryManager>(
             query.audio_file_id,
             query.language.as_deref(),
             query.provider.as_deref(),
+            query.model.as_deref(),
             query.status.as_deref(),
             query.min_confidence,
             query.created_after,
@@ -195,20 +316,20 @@ async fn list_transcripts<R: RepositoryManager>(
         .await
         .map_err(|e| ApiError::InternalServerError(format!("Failed to count transcripts: {}", e)))?;
 
-    // Enrich with related data
+    // Enrich with related data, loaded for the whole page at once
+    let audio_file_ids = unique_ids(transcripts.iter().map(|transcript| transcript.audio_file_id));
+    let session_ids = unique_ids(transcripts.iter().map(|transcript| transcript.session_id));
+    let (audio_files, sessions) = futures::join!(
+        state.repositories.audio().find_by_ids(&audio_file_ids),
+        state.repositories.session().find_by_ids(&session_ids),
+    );
+    let audio_files = index_by_id(audio_files, "audio files", |audio_file| audio_file.id);
+    let sessions = index_by_id(sessions, "sessions", |session| session.id);
+
     let mut responses = Vec::new();
     for transcript in transcripts {
-        let audio_file = state.repositories.audio()
-            .find_by_id(transcript.audio_file_id)
-            .await
-            .ok()
-            .flatten();
-        
-        let session = state.repositories.session()
-            .find_by_id(transcript.session_id)
-            .await
-            .ok()
-            .flatten();
+        let audio_file = audio_files.get(&transcript.audio_file_id);
+        let session = sessions.get(&transcript.session_id);
 
         responses.push(TranscriptResponse {
             id: transcript.id,
@@ -226,24 +347,23 @@ async fn list_transcripts<R: RepositoryManager>(
             metadata: transcript.metadata,
             created_at: transcript.created_at,
             updated_at: transcript.updated_at,
-            audio_filename: audio_file.map(|a| a.original_filename),
-            session_title: session.and_then(|s| s.title),
+            audio_filename: audio_file.map(|a| a.original_filename.clone()),
+            session_title: session.and_then(|s| s.title.clone()),
         });
     }
 
-    Ok(Json(ApiResponse {
-        data: responses,
-        total: Some(total),
-        page: Some(query.pagination.page()),
-        per_page: Some(query.pagination.limit),
-    }))
+    Ok(Json(ApiResponse::paginated(responses, total, query.pagination.page(), query.pagination.limit)))
 }
 
 /// Create a new transcript
+///
+/// If the audio file already has a transcript, the latest one is returned
+/// with `reused: true` and no transcription is run, so retried or repeated
+/// requests cost nothing. `force: true` transcribes again.
 async fn create_transcript<R: RepositoryManager>(
     State(state): State<AppState<R>>,
     Json(request): Json<CreateTranscriptRequest>,
-) -> ApiResult<Json<ApiResponse<TranscriptResponse>>> {
+) -> ApiResult<Json<ApiResponse<CreateTranscriptResponse>>> {
     // Verify audio file exists
     let audio_file = state.repositories.audio()
         .find_by_id(request.audio_file_id)
@@ -251,18 +371,55 @@ async fn create_transcript<R: RepositoryManager>(
         .map_err(|e| ApiError::InternalServerError(format!("Failed to get audio file: {}", e)))?
         .ok_or_else(|| ApiError::NotFound("Audio file not found".to_string()))?;
 
+    let existing = if request.force.unwrap_or(false) {
+        None
+    } else {
+        state.repositories.transcript()
+            .find_with_filters(
+                None,
+                Some(request.audio_file_id),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(1),
+                Some(0),
+                Some("created_at"),
+                Some("desc"),
+            )
+            .await
+            .map_err(|e| ApiError::InternalServerError(format!("Failed to get transcript: {}", e)))?
+            .into_iter()
+            .next()
+    };
+    let reused = existing.is_some();
+
+    if !reused {
+        check_audio(&audio_file.file_path).await?;
+    }
+
     // Create transcript using transcription service
-    let transcript = state.services.transcription()
-        .transcribe_audio(
-            request.audio_file_id,
-            request.provider.as_deref(),
-            request.language.as_deref(),
-            request.auto_detect_language.unwrap_or(true),
-            request.model.as_deref(),
-            request.prompt.as_deref(),
-        )
-        .await
-        .map_err(|e| ApiError::InternalServerError(format!("Failed to create transcript: {}", e)))?;
+    let transcript = match existing {
+        Some(transcript) => transcript,
+        None => state.services.transcription()
+            .transcribe_audio(
+                request.audio_file_id,
+                request.provider.as_deref(),
+                request.language.as_deref(),
+                request.auto_detect_language.unwrap_or(true),
+                request.model.as_deref(),
+                request.prompt.as_deref(),
+            )
+            .await
+            .map_err(|e| ApiError::InternalServerError(format!("Failed to create transcript: {}", e)))?,
+    };
+    if !reused {
+        let config = state.config.current();
+        semantic::index_transcript(state.repositories.transcript(), &config.ollama, transcript.id, &transcript.content).await;
+    }
 
     let session = state.repositories.session()
         .find_by_id(transcript.session_id)
@@ -290,12 +447,21 @@ async fn create_transcript<R: RepositoryManager>(
         session_title: session.and_then(|s| s.title),
     };
 
-    Ok(Json(ApiResponse {
-        data: response,
-        total: None,
-        page: None,
-        per_page: None,
-    }))
+    Ok(Json(ApiResponse::success(CreateTranscriptResponse { transcript: response, reused })))
+}
+
+/// Fail with 422 if an audio file is corrupt or not audio at all, before a
+/// transcription provider is called with it
+///
+/// Only the header is read, and for WAV files the last sample, which a
+/// truncated file lacks.
+async fn check_audio(file_path: &str) -> ApiResult<()> {
+    let path = std::path::PathBuf::from(file_path);
+    tokio::task::spawn_blocking(move || crate::probe::check(&path))
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to check audio file: {}", e)))?
+        .map(|_| ())
+        .map_err(|e| ApiError::UnprocessableEntity(e.to_string()))
 }
 
 /// Get a specific transcript by ID
@@ -341,12 +507,7 @@ async fn get_transcript<R: RepositoryManager>(
         session_title: session.and_then(|s| s.title),
     };
 
-    Ok(Json(ApiResponse {
-        data: response,
-        total: None,
-        page: None,
-        per_page: None,
-    }))
+    Ok(Json(ApiResponse::success(response)))
 }
 
 /// Update a transcript
@@ -356,14 +517,22 @@ async fn update_transcript<R: RepositoryManager>(
     Json(request): Json<UpdateTranscriptRequest>,
 ) -> ApiResult<Json<ApiResponse<TranscriptResponse>>> {
     // Check if transcript exists
-    let _transcript = state.repositories.transcript()
+    let transcript = state.repositories.transcript()
         .find_by_id(id)
         .await
         .map_err(|e| ApiError::InternalServerError(format!("Failed to get transcript: {}", e)))?
         .ok_or_else(|| ApiError::NotFound("Transcript not found".to_string()))?;
 
+    // Corrections keep the previous content in the transcript's history
+    if let Some(content) = request.content.as_deref().filter(|content| *content != transcript.content) {
+        state.repositories.transcript()
+            .update_content(&id, content)
+            .await
+            .map_err(|e| ApiError::InternalServerError(format!("Failed to update transcript: {}", e)))?;
+    }
+
     let update_transcript = UpdateTranscript {
-        content: request.content,
+        content: None,
         language: request.language,
         confidence_score: request.confidence_score,
         status: request.status,
@@ -407,12 +576,57 @@ async fn update_transcript<R: RepositoryManager>(
         session_title: session.and_then(|s| s.title),
     };
 
-    Ok(Json(ApiResponse {
-        data: response,
-        total: None,
-        page: None,
-        per_page: None,
-    }))
+    Ok(Json(ApiResponse::success(response)))
+}
+
+/// Earlier versions of a transcript, newest first
+///
+/// A version is saved whenever the content is changed by an update or a
+/// revert; only the latest `MAX_TRANSCRIPT_VERSIONS` are kept.
+async fn transcript_history<R: RepositoryManager>(
+    State(state): State<AppState<R>>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<ApiResponse<Vec<TranscriptVersionResponse>>>> {
+    let _transcript = state.repositories.transcript()
+        .find_by_id(id)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to get transcript: {}", e)))?
+        .ok_or_else(|| ApiError::NotFound("Transcript not found".to_string()))?;
+
+    let versions: Vec<TranscriptVersionResponse> = state.repositories.transcript()
+        .versions(&id)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to get transcript history: {}", e)))?
+        .into_iter()
+        .map(TranscriptVersionResponse::from)
+        .collect();
+
+    let total = versions.len() as i64;
+    Ok(Json(ApiResponse::success(versions).with_total(total)))
+}
+
+/// Restore an earlier version of a transcript
+///
+/// The content being replaced is saved as a new version, so a revert can
+/// itself be reverted.
+async fn revert_transcript<R: RepositoryManager>(
+    State(state): State<AppState<R>>,
+    Path((id, version)): Path<(Uuid, i32)>,
+) -> ApiResult<Json<ApiResponse<TranscriptResponse>>> {
+    let _transcript = state.repositories.transcript()
+        .find_by_id(id)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to get transcript: {}", e)))?
+        .ok_or_else(|| ApiError::NotFound("Transcript not found".to_string()))?;
+
+    state.repositories.transcript()
+        .revert(&id, version)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to revert transcript: {}", e)))?
+        .ok_or_else(|| ApiError::NotFound(format!("Transcript version {} not found", version)))?;
+
+    let Json(response) = get_transcript(State(state), Path(id)).await?;
+    Ok(Json(response.with_message(format!("Reverted to version {}", version))))
 }
 
 /// Delete a transcript
@@ -440,8 +654,9 @@ async fn export_transcript<R: RepositoryManager>(
     State(state): State<AppState<R>>,
     Path(id): Path<Uuid>,
     Query(params): Query<std::collections::HashMap<String, String>>,
+    headers: HeaderMap,
 ) -> ApiResult<axum::response::Response> {
-    let transcript = state.repositories.transcript()
+    let mut transcript = state.repositories.transcript()
         .find_by_id(id)
         .await
         .map_err(|e| ApiError::InternalServerError(format!("Failed to get transcript: {}", e)))?
@@ -451,6 +666,17 @@ async fn export_transcript<R: RepositoryManager>(
     let include_metadata = params.get("include_metadata")
         .and_then(|s| s.parse::<bool>().ok())
         .unwrap_or(false);
+    let redact = params.get("redact")
+        .map(|s| s.parse::<bool>())
+        .transpose()
+        .map_err(|_| ApiError::BadRequest("redact must be true or false".to_string()))?;
+
+    if crate::redact::requested(redact) {
+        let redactor = crate::redact::Redactor::from_config()
+            .map_err(|e| ApiError::InternalServerError(format!("Failed to build redactor: {}", e)))?;
+        transcript.content = redactor.redact(&transcript.content);
+        redactor.redact_segments(&mut transcript.segments);
+    }
 
     let (content, content_type, filename) = match format {
         "txt" => {
@@ -476,7 +702,8 @@ async fn export_transcript<R: RepositoryManager>(
                 serde_json::to_string_pretty(&serde_json::json!({
                     "id": transcript.id,
                     "content": transcript.content,
-                    "language": transcript.language
+                    "language": transcript.language,
+                    "segments": transcript.segments
                 }))
                 .map_err(|e| ApiError::InternalServerError(format!("Failed to serialize transcript: {}", e)))?
             };
@@ -506,17 +733,7 @@ async fn export_transcript<R: RepositoryManager>(
         }
     };
 
-    Ok((
-        [
-            (axum::http::header::CONTENT_TYPE, content_type),
-            (
-                axum::http::header::CONTENT_DISPOSITION,
-                &format!("attachment; filename=\"{}\"", filename),
-            ),
-        ],
-        content,
-    )
-        .into_response())
+    Ok(crate::etag::content_response(&headers, content_type, Some(&filename), content))
 }
 
 /// Analyze transcript content
@@ -525,6 +742,9 @@ async fn analyze_transcript<R: RepositoryManager>(
     Path(id): Path<Uuid>,
     Json(request): Json<AnalyzeTranscriptRequest>,
 ) -> ApiResult<Json<serde_json::Value>> {
+    validate_min_confidence(request.min_confidence)?;
+    validate_options(request.options.as_ref())?;
+
     let transcript = state.repositories.transcript()
         .find_by_id(id)
         .await
@@ -537,8 +757,11 @@ async fn analyze_transcript<R: RepositoryManager>(
             &request.analysis_types,
             request.provider.as_deref(),
             request.language.as_deref(),
+            request.output_language.as_deref(),
             request.model.as_deref(),
             request.custom_prompts.as_ref(),
+            request.min_confidence,
+            request.options,
         )
         .await
         .map_err(|e| ApiError::InternalServerError(format!("Failed to analyze transcript: {}", e)))?;
@@ -546,24 +769,141 @@ async fn analyze_transcript<R: RepositoryManager>(
     Ok(Json(serde_json::to_value(analysis_results).unwrap_or_default()))
 }
 
+/// Detect the language of a transcript's content again
+///
+/// The language is only updated, and the transcript only re-analyzed when
+/// `reanalyze` is set, if the detection disagrees with the stored one.
+async fn redetect_language<R: RepositoryManager>(
+    State(state): State<AppState<R>>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<RedetectLanguageRequest>,
+) -> ApiResult<Json<ApiResponse<RedetectLanguageResponse>>> {
+    validate_min_confidence(request.min_confidence)?;
+    validate_options(request.options.as_ref())?;
+
+    let transcript = state.repositories.transcript()
+        .find_by_id(id)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to get transcript: {}", e)))?
+        .ok_or_else(|| ApiError::NotFound("Transcript not found".to_string()))?;
+
+    if transcript.content.trim().is_empty() {
+        return Err(ApiError::UnprocessableEntity(
+            "Transcript has no content to detect a language from".to_string(),
+        ));
+    }
+
+    // The detection behind `detect_language_v2`, which also gives its confidence
+    let detection = crate::ollama::detect_language_with_confidence(
+        &transcript.content,
+        &crate::config::current().analysis.language_detection,
+    );
+    let new_language = detection.language;
+    let old_language = transcript.language.clone();
+    let changed = old_language.as_deref() != Some(new_language);
+
+    let mut analysis = None;
+    if changed {
+        let update_transcript = UpdateTranscript {
+            content: None,
+            language: Some(new_language.to_string()),
+            confidence_score: None,
+            status: None,
+            metadata: None,
+        };
+        state.repositories.transcript()
+            .update(id, update_transcript)
+            .await
+            .map_err(|e| ApiError::InternalServerError(format!("Failed to update transcript: {}", e)))?;
+
+        if request.reanalyze.unwrap_or(false) {
+            let analysis_types = request.analysis_types.unwrap_or_else(|| {
+                ["summary", "ideas", "tasks", "structured"].map(String::from).to_vec()
+            });
+            let analysis_results = state.services.analysis()
+                .analyze_transcript(
+                    id,
+                    &analysis_types,
+                    request.provider.as_deref(),
+                    Some(new_language),
+                    request.output_language.as_deref(),
+                    request.model.as_deref(),
+                    None,
+                    request.min_confidence,
+                    request.options,
+                )
+                .await
+                .map_err(|e| ApiError::InternalServerError(format!("Failed to analyze transcript: {}", e)))?;
+            analysis = Some(serde_json::to_value(analysis_results).unwrap_or_default());
+        }
+    }
+
+    let message = if changed {
+        format!(
+            "Language changed from {} to {}",
+            old_language.as_deref().unwrap_or("unknown"),
+            new_language
+        )
+    } else {
+        format!("Language is still {}", new_language)
+    };
+
+    Ok(Json(ApiResponse::success(RedetectLanguageResponse {
+        transcript_id: id,
+        old_language,
+        new_language: new_language.to_string(),
+        confidence: detection.confidence,
+        changed,
+        analysis,
+    }).with_message(message)))
+}
+
 /// Search transcripts
 async fn search_transcripts<R: RepositoryManager>(
     State(state): State<AppState<R>>,
-    Query(query): Query<TranscriptListQuery>,
-) -> ApiResult<Json<ApiResponse<Vec<TranscriptResponse>>>> {
-    // Enhanced search functionality
-    list_transcripts(State(state), Query(query)).await
+    StrictQuery(query): StrictQuery<TranscriptListQuery>,
+) -> ApiResult<Json<ApiResponse<Vec<TranscriptSearchResult>>>> {
+    let term = query.search.q.clone().unwrap_or_default();
+    let Json(response) = list_transcripts(State(state), StrictQuery(query)).await?;
+
+    Ok(Json(response.map(|transcripts| {
+        transcripts
+            .into_iter()
+            .map(|transcript| TranscriptSearchResult {
+                matches: find_matches(&transcript.content, &term),
+                transcript,
+            })
+            .collect()
+    })))
 }
 
 /// Get transcript statistics
+///
+/// `tz` selects the time zone of the `transcripts_per_day` buckets and `days`
+/// their number.
 async fn transcript_stats<R: RepositoryManager>(
     State(state): State<AppState<R>>,
+    Query(query): Query<StatsQuery>,
 ) -> ApiResult<Json<TranscriptStatsResponse>> {
-    let stats = state.services.transcription()
+    let window = query.window()?;
+    let mut stats = state.services.transcription()
         .get_transcript_stats()
         .await
         .map_err(|e| ApiError::InternalServerError(format!("Failed to get transcript stats: {}", e)))?;
 
+    stats.models = state.repositories.transcript()
+        .count_by_model()
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to get transcript stats: {}", e)))?;
+
+    stats.transcripts_per_day = state.repositories.transcript()
+        .daily_counts(&window)
+        .await
+        .map_err(|e| stats_error(e, "transcript"))?
+        .into_iter()
+        .map(|day| DailyCount { date: day.date, count: day.count })
+        .collect();
+
     Ok(Json(stats))
 }
 
@@ -589,6 +929,8 @@ async fn batch_create_transcripts<R: RepositoryManager>(
             .await
         {
             Ok(transcript) => {
+                let config = state.config.current();
+                semantic::index_transcript(state.repositories.transcript(), &config.ollama, transcript.id, &transcript.content).await;
                 successful += 1;
                 results.push(BatchResult {
                     id: Some(transcript.id),
@@ -616,11 +958,77 @@ async fn batch_create_transcripts<R: RepositoryManager>(
 }
 
 /// Batch export transcripts
+///
+/// With `combined`, the transcripts are written as one `txt` or `md`
+/// document with a section per session, ordered by session date; markdown
+/// documents start with a table of contents. `include_timestamps` writes
+/// timestamped segments where the transcript has them, and without
+/// `include_metadata` the session dates are left out.
 async fn batch_export_transcripts<R: RepositoryManager>(
     State(state): State<AppState<R>>,
+    headers: HeaderMap,
     Json(request): Json<BatchExportRequest>,
 ) -> ApiResult<axum::response::Response> {
-    // For now, create a ZIP file with all transcripts
-    // This would require implementing ZIP creation functionality
-    Err(ApiError::NotImplemented("Batch export not yet implemented".to_string()))
+    if !request.combined.unwrap_or(false) {
+        // For now, create a ZIP file with all transcripts
+        // This would require implementing ZIP creation functionality
+        return Err(ApiError::NotImplemented("Batch export not yet implemented".to_string()));
+    }
+    validate_choice("format", Some(request.format.as_str()), COMBINED_EXPORT_FORMATS)?;
+    if request.transcript_ids.is_empty() {
+        return Err(ApiError::UnprocessableEntity("transcript_ids must not be empty".to_string()));
+    }
+
+    let redactor = if crate::redact::requested(request.redact) {
+        Some(crate::redact::Redactor::from_config()
+            .map_err(|e| ApiError::InternalServerError(format!("Failed to build redactor: {}", e)))?)
+    } else {
+        None
+    };
+
+    let mut entries = Vec::new();
+    for id in &request.transcript_ids {
+        let mut transcript = state.repositories.transcript()
+            .find_by_id(*id)
+            .await
+            .map_err(|e| ApiError::InternalServerError(format!("Failed to get transcript: {}", e)))?
+            .ok_or_else(|| ApiError::NotFound(format!("Transcript {} not found", id)))?;
+        let session = state.repositories.session()
+            .find_by_id(transcript.session_id)
+            .await
+            .ok()
+            .flatten();
+
+        if let Some(redactor) = &redactor {
+            transcript.content = redactor.redact(&transcript.content);
+            redactor.redact_segments(&mut transcript.segments);
+        }
+        let segments: Vec<crate::storage::TranscriptSegment> = if request.include_timestamps.unwrap_or(false) {
+            serde_json::from_value(transcript.segments.clone()).unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        let recorded_at = session.as_ref().map_or(transcript.created_at, |s| s.created_at);
+        let title = session.and_then(|s| s.title);
+        entries.push((recorded_at, title, transcript.content, segments));
+    }
+    entries.sort_by_key(|(recorded_at, ..)| *recorded_at);
+
+    let sections: Vec<CombinedTranscript> = entries
+        .iter()
+        .map(|(recorded_at, title, content, segments)| CombinedTranscript {
+            title: title.as_deref(),
+            recorded_at: *recorded_at,
+            content,
+            segments,
+        })
+        .collect();
+    let include_metadata = request.include_metadata.unwrap_or(true);
+    let (content, content_type) = match request.format.as_str() {
+        "md" => (combined_export::to_markdown(&sections, include_metadata), "text/markdown; charset=utf-8"),
+        _ => (combined_export::to_text(&sections, include_metadata), "text/plain; charset=utf-8"),
+    };
+    let filename = format!("transcripts.{}", request.format);
+
+    Ok(crate::etag::content_response(&headers, content_type, Some(&filename), content))
 }
\ No newline at end of file
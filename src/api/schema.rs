@@ -0,0 +1,116 @@
+// src/api/schema.rs
+//! Validation of JSON values against note template schemas
+//!
+//! Supports the subset of JSON Schema the templates use: `type` (a name or
+//! a list of names), `properties`, `required`, `additionalProperties: false`,
+//! `items`, `enum` and the `date` and `date-time` formats. Other keywords are
+//! ignored.
+
+use serde_json::Value;
+
+/// Check `value` against `schema`, returning every violation found
+///
+/// Each error starts with the JSON pointer of the offending value, `/` for
+/// the root.
+pub fn validate(value: &Value, schema: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    validate_at(value, schema, "", &mut errors);
+    errors
+}
+
+fn validate_at(value: &Value, schema: &Value, path: &str, errors: &mut Vec<String>) {
+    let Some(schema) = schema.as_object() else {
+        return;
+    };
+    let location = if path.is_empty() { "/" } else { path };
+
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|name| has_type(value, name)) {
+            errors.push(format!("{}: expected {}, found {}", location, types.join(" or "), type_name(value)));
+            // Nested keywords would only repeat the mismatch
+            return;
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            errors.push(format!("{}: must be one of {}", location, Value::Array(allowed.clone())));
+        }
+    }
+
+    if let (Some(format), Some(text)) = (schema.get("format").and_then(Value::as_str), value.as_str()) {
+        // Empty strings stand for "not filled in yet" in the default content
+        if !text.is_empty() && !matches_format(text, format) {
+            errors.push(format!("{}: '{}' is not a valid {}", location, text, format));
+        }
+    }
+
+    if let Some(object) = value.as_object() {
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            for key in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(key) {
+                    errors.push(format!("{}: missing required property '{}'", location, key));
+                }
+            }
+        }
+        let properties = schema.get("properties").and_then(Value::as_object);
+        let closed = schema.get("additionalProperties") == Some(&Value::Bool(false));
+        for (key, item) in object {
+            let item_path = format!("{}/{}", path, escape_pointer(key));
+            match properties.and_then(|properties| properties.get(key)) {
+                Some(item_schema) => validate_at(item, item_schema, &item_path, errors),
+                None if closed => errors.push(format!("{}: unexpected property", item_path)),
+                None => {}
+            }
+        }
+    }
+
+    if let (Some(items), Some(item_schema)) = (value.as_array(), schema.get("items")) {
+        for (index, item) in items.iter().enumerate() {
+            validate_at(item, item_schema, &format!("{}/{}", path, index), errors);
+        }
+    }
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64() || value.as_f64().map_or(false, |n| n.fract() == 0.0),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        // Unknown type names are not enforced
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Object(_) => "object",
+        Value::Array(_) => "array",
+        Value::String(_) => "string",
+        Value::Number(_) => "number",
+        Value::Bool(_) => "boolean",
+        Value::Null => "null",
+    }
+}
+
+fn matches_format(text: &str, format: &str) -> bool {
+    match format {
+        "date-time" => chrono::DateTime::parse_from_rfc3339(text).is_ok(),
+        "date" => chrono::NaiveDate::parse_from_str(text, "%Y-%m-%d").is_ok(),
+        _ => true,
+    }
+}
+
+/// Escape a key for use as a JSON pointer segment (RFC 6901)
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
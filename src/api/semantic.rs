@@ -0,0 +1,124 @@
+// src/api/semantic.rs
+//! Ranking sessions by meaning rather than by matching words
+//!
+//! Transcripts are embedded with the Ollama model set as
+//! `ollama.embedding_model` when they are created, and a search query is
+//! embedded the same way. A session ranks by the cosine similarity of its
+//! closest transcript to the query. Transcripts without an embedding, such
+//! as those created before the model was set or whose content changed, are
+//! embedded a few at a time in the background, never by a search itself.
+
+use std::collections::HashMap;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::config::OllamaConfig;
+use crate::repository::{
+    traits::{TranscriptEmbedding, TranscriptRepository},
+    RepositoryManager,
+};
+
+/// Transcripts without an embedding that one backfill round embeds
+pub const INDEX_BATCH_SIZE: i64 = 20;
+
+/// Time between backfill rounds
+const BACKFILL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The configured embedding model, if semantic search is enabled
+pub fn embedding_model(ollama: &OllamaConfig) -> Option<&str> {
+    ollama.embedding_model.as_deref().map(str::trim).filter(|model| !model.is_empty())
+}
+
+/// Embed a transcript's content and store it, if an embedding model is set
+///
+/// Failures are only logged: the transcript is embedded later by the
+/// background backfill.
+pub async fn index_transcript<T>(transcripts: &T, ollama: &OllamaConfig, transcript_id: Uuid, content: &str)
+where
+    T: TranscriptRepository + ?Sized,
+{
+    let Some(model) = embedding_model(ollama) else {
+        return;
+    };
+    if content.trim().is_empty() {
+        return;
+    }
+
+    let saved = match crate::ollama::embed(content, ollama, model).await {
+        Ok(embedding) => transcripts.save_embedding(&transcript_id, model, &embedding).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = saved {
+        tracing::warn!("Failed to embed transcript {} with {}: {:#}", transcript_id, model, e);
+    }
+}
+
+/// Embed up to [`INDEX_BATCH_SIZE`] transcripts that have no embedding made
+/// with `model` yet
+pub async fn index_missing<T>(transcripts: &T, ollama: &OllamaConfig, model: &str) -> anyhow::Result<()>
+where
+    T: TranscriptRepository + ?Sized,
+{
+    for transcript in transcripts.find_without_embedding(model, INDEX_BATCH_SIZE).await? {
+        // An unreachable server or wrong model fails them all, so stop at the first error
+        let embedding = crate::ollama::embed(&transcript.content, ollama, model).await?;
+        transcripts.save_embedding(&transcript.id, model, &embedding).await?;
+    }
+    Ok(())
+}
+
+/// Embed the transcripts that have no embedding yet in the background,
+/// [`INDEX_BATCH_SIZE`] at a time every minute
+///
+/// Call this once when serving the API. Rounds are skipped while no
+/// embedding model is configured.
+pub fn spawn_backfill<R: RepositoryManager + 'static>(state: &AppState<R>) {
+    let state = state.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(BACKFILL_INTERVAL);
+        loop {
+            interval.tick().await;
+            let config = state.config.current();
+            let Some(model) = embedding_model(&config.ollama) else {
+                continue;
+            };
+            if let Err(e) = index_missing(state.repositories.transcript(), &config.ollama, model).await {
+                tracing::warn!("Failed to embed transcripts for semantic search: {:#}", e);
+            }
+        }
+    });
+}
+
+/// Cosine similarity of two vectors, from -1 to 1; 0 if their lengths
+/// differ, e.g. after switching embedding models, or either is all zeros
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let (mut dot, mut norm_a, mut norm_b) = (0.0f32, 0.0f32, 0.0f32);
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+/// Sessions with their best similarity to `query`, most similar first
+pub fn rank_sessions(query: &[f32], embeddings: &[TranscriptEmbedding]) -> Vec<(Uuid, f32)> {
+    let mut best: HashMap<Uuid, f32> = HashMap::new();
+    for embedding in embeddings {
+        let score = cosine_similarity(query, &embedding.embedding);
+        best.entry(embedding.session_id)
+            .and_modify(|best| *best = best.max(score))
+            .or_insert(score);
+    }
+
+    let mut ranked: Vec<_> = best.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ranked
+}
@@ -3,8 +3,122 @@ use anyhow::Result;
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{Device, StreamConfig};
 use hound::{WavSpec, WavWriter};
+use serde::Serialize;
+use std::fs::File;
+use std::io::BufWriter;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
+use tokio::sync::Mutex as AsyncMutex;
+
+type SharedWavWriter = Arc<Mutex<Option<WavWriter<BufWriter<File>>>>>;
+
+/// Whether the recorder is capturing audio
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordingState {
+    Idle,
+    Recording,
+}
+
+/// Returned by [`VoiceRecorder::start_recording`] when a recording is already active
+#[derive(Debug)]
+pub struct AlreadyRecording {
+    pub session_id: Option<String>,
+}
+
+impl std::fmt::Display for AlreadyRecording {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.session_id {
+            Some(id) => write!(f, "A recording is already in progress (session {})", id),
+            None => write!(f, "A recording is already in progress"),
+        }
+    }
+}
+
+impl std::error::Error for AlreadyRecording {}
+
+/// Switch `state` from `Idle` to `Recording` under its lock; `false` if it
+/// was already recording
+fn claim_recording(state: &Mutex<RecordingState>) -> bool {
+    let mut state = state.lock().unwrap();
+    if *state == RecordingState::Recording {
+        return false;
+    }
+    *state = RecordingState::Recording;
+    true
+}
+
+/// Snapshot of the current input level, both values in 0.0..=1.0
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct InputLevel {
+    /// Smoothed RMS level
+    pub rms: f32,
+    /// Peak level with a slow decay
+    pub peak: f32,
+}
+
+impl InputLevel {
+    /// RMS level in dBFS, floored at -60
+    pub fn rms_db(&self) -> f32 {
+        (20.0 * self.rms.max(1e-3).log10()).max(-60.0)
+    }
+}
+
+/// Lock-free input level meter updated from the audio callback
+///
+/// Levels are stored as `f32` bits in atomics so the audio thread never blocks.
+#[derive(Default)]
+pub struct LevelMeter {
+    rms: AtomicU32,
+    peak: AtomicU32,
+    active: AtomicBool,
+}
+
+impl LevelMeter {
+    /// Weight of the newest buffer in the smoothed RMS
+    const RMS_SMOOTHING: f32 = 0.3;
+    /// Per-buffer decay applied to the held peak
+    const PEAK_DECAY: f32 = 0.95;
+
+    /// Feed one buffer of samples into the meter
+    fn update(&self, data: &[f32]) {
+        if data.is_empty() {
+            return;
+        }
+        let mut sum_squares = 0.0f32;
+        let mut buffer_peak = 0.0f32;
+        for &sample in data {
+            sum_squares += sample * sample;
+            buffer_peak = buffer_peak.max(sample.abs());
+        }
+        let buffer_rms = (sum_squares / data.len() as f32).sqrt();
+
+        let old = self.level();
+        let rms = old.rms + (buffer_rms - old.rms) * Self::RMS_SMOOTHING;
+        let peak = buffer_peak.max(old.peak * Self::PEAK_DECAY);
+        self.rms.store(rms.min(1.0).to_bits(), Ordering::Relaxed);
+        self.peak.store(peak.min(1.0).to_bits(), Ordering::Relaxed);
+    }
+
+    fn reset(&self, active: bool) {
+        self.rms.store(0f32.to_bits(), Ordering::Relaxed);
+        self.peak.store(0f32.to_bits(), Ordering::Relaxed);
+        self.active.store(active, Ordering::Relaxed);
+    }
+
+    /// Get the current level
+    pub fn level(&self) -> InputLevel {
+        InputLevel {
+            rms: f32::from_bits(self.rms.load(Ordering::Relaxed)),
+            peak: f32::from_bits(self.peak.load(Ordering::Relaxed)),
+        }
+    }
+
+    /// Whether a recording is feeding the meter
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::Relaxed)
+    }
+}
 
 pub struct VoiceRecorder {
     device: Arc<Mutex<Device>>,
@@ -12,7 +126,9 @@ pub struct VoiceRecorder {
     current_session: Arc<Mutex<Option<crate::storage::VoiceSession>>>,
     recording_start: Arc<Mutex<Option<Instant>>>,
     stream: Arc<Mutex<Option<cpal::Stream>>>,
-    is_recording: Arc<Mutex<bool>>,
+    writer: SharedWavWriter,
+    state: Arc<Mutex<RecordingState>>,
+    level_meter: Arc<LevelMeter>,
 }
 
 // Implement Send for VoiceRecorder
@@ -37,17 +153,34 @@ impl VoiceRecorder {
             current_session: Arc::new(Mutex::new(None)),
             recording_start: Arc::new(Mutex::new(None)),
             stream: Arc::new(Mutex::new(None)),
-            is_recording: Arc::new(Mutex::new(false)),
+            writer: Arc::new(Mutex::new(None)),
+            state: Arc::new(Mutex::new(RecordingState::Idle)),
+            level_meter: Arc::new(LevelMeter::default()),
         })
     }
     
+    /// Start recording into a new session
+    ///
+    /// Fails with [`AlreadyRecording`] if a recording is already active.
     pub async fn start_recording(&mut self) -> Result<()> {
-        // Check if already recording
-        if *self.is_recording.lock().unwrap() {
-            println!("Recording is already in progress");
-            return Ok(());
+        // Of two concurrent starts only one claims the recorder; the claim is
+        // released again if setup fails
+        if !claim_recording(&self.state) {
+            return Err(AlreadyRecording {
+                session_id: self.current_session_id(),
+            }
+            .into());
         }
         
+        let result = self.begin_recording().await;
+        if result.is_err() {
+            *self.writer.lock().unwrap() = None;
+            *self.state.lock().unwrap() = RecordingState::Idle;
+        }
+        result
+    }
+    
+    async fn begin_recording(&mut self) -> Result<()> {
         let session = crate::storage::create_new_session();
         println!("Created session: {}", session.id);
         
@@ -59,8 +192,18 @@ impl VoiceRecorder {
         };
         
         let writer = WavWriter::create(&session.audio_file_path, spec)?;
-        let writer = Arc::new(Mutex::new(Some(writer)));
-        let writer_clone = writer.clone();
+        *self.writer.lock().unwrap() = Some(writer);
+        let writer_clone = self.writer.clone();
+
+        // Stop writing once the configured maximum is reached, even if the
+        // duration guard has not finalized the session yet
+        let max_samples = crate::config::current()
+            .recording
+            .max_recording_secs
+            .map(|secs| secs * spec.sample_rate as u64 * spec.channels as u64);
+        let mut samples_written: u64 = 0;
+        let level_meter = self.level_meter.clone();
+        level_meter.reset(true);
         
         let stream = {
             let device = self.device.lock().unwrap();
@@ -68,12 +211,16 @@ impl VoiceRecorder {
             device.build_input_stream(
                 &config,
                 move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                    level_meter.update(data);
+                    let remaining = max_samples.map_or(u64::MAX, |max| max.saturating_sub(samples_written));
+                    let data = &data[..data.len().min(remaining as usize)];
                     if let Ok(mut guard) = writer_clone.lock() {
                         if let Some(writer) = guard.as_mut() {
                             for &sample in data {
                                 let sample_i16 = (sample * 32767.0) as i16;
                                 let _ = writer.write_sample(sample_i16);
                             }
+                            samples_written += data.len() as u64;
                         }
                     }
                 },
@@ -99,19 +246,18 @@ impl VoiceRecorder {
             let mut start_guard = self.recording_start.lock().unwrap();
             *start_guard = Some(Instant::now());
         }
-        {
-            let mut recording_guard = self.is_recording.lock().unwrap();
-            *recording_guard = true;
-        }
         
-        println!("Recording started. Press 'e' to stop recording.");
+        println!(
+            "Recording started. Press 'e' to stop and save, '{}' to discard.",
+            crate::config::current().recording.cancel_key
+        );
         
         Ok(())
     }
     
     pub async fn stop_recording(&mut self) -> Result<()> {
         // Check recording status
-        if !*self.is_recording.lock().unwrap() {
+        if *self.state.lock().unwrap() == RecordingState::Idle {
             println!("No recording in progress");
             return Ok(());
         }
@@ -124,11 +270,15 @@ impl VoiceRecorder {
             }
         }
 
-        // Set recording status to false
-        {
-            let mut recording_guard = self.is_recording.lock().unwrap();
-            *recording_guard = false;
+        // Finalize the WAV header so the file is valid however the recording ended
+        if let Some(writer) = self.writer.lock().unwrap().take() {
+            if let Err(e) = writer.finalize() {
+                eprintln!("Failed to finalize audio file: {}", e);
+            }
         }
+
+        *self.state.lock().unwrap() = RecordingState::Idle;
+        self.level_meter.reset(false);
         
         // Get session and process it
         let session = {
@@ -148,41 +298,59 @@ impl VoiceRecorder {
             println!("🔄 Processing audio...  #[rexrex]");
             
             // Process the audio file
-            if let Ok(transcript) = crate::ai::transcribe_audio(&session.audio_file_path).await {
-                session.transcript = Some(transcript.clone());
+            if let Ok(transcription) = crate::ai::transcribe_session_audio(&mut session).await {
+                let transcript = transcription.text.clone();
+                session.set_transcription(transcription);
                 println!("📝 Transcript: {}", transcript);
                 
-                // Analyze the transcript
-                if let Ok(analysis) = crate::ai::analyze_transcript(&transcript).await {
-                    session.analysis = Some(analysis.clone());
+                // Analyze the transcript, unless analysis.auto_analyze is off
+                let analysis = if crate::config::current().analysis.auto_analyze {
+                    Some(crate::ai::analyze_transcript(&transcript).await)
+                } else {
+                    None
+                };
+                match analysis {
+                    Some(Ok(analysis)) => {
+                        session.analysis = Some(analysis.clone());
                     
-                    // Generate title from analysis
-                    if let Some(first_idea) = analysis.ideas.first() {
-                        session.title = first_idea.clone();
-                    } else if !analysis.tasks.is_empty() {
-                        session.title = analysis.tasks[0].title.clone();
-                    } else {
-                        session.title = "Voice Note".to_string();
-                    }
+                        // Generate title from analysis
+                        if let Some(first_idea) = analysis.ideas.first() {
+                            session.title = first_idea.clone();
+                        } else if !analysis.tasks.is_empty() {
+                            session.title = analysis.tasks[0].title.clone();
+                        } else {
+                            session.title = "Voice Note".to_string();
+                        }
                     
-                    // Display analysis results
-                    println!("\n📊 Analysis Results:");
-                    println!("� Ideas: {}", analysis.ideas.len());
-                    for idea in &analysis.ideas {
-                        println!("  • {}", idea);
-                    }
+                        // Display analysis results
+                        println!("\n📊 Analysis Results:");
+                        println!("� Ideas: {}", analysis.ideas.len());
+                        for idea in &analysis.ideas {
+                            println!("  • {}", idea);
+                        }
                     
-                    println!("✅ Tasks: {}", analysis.tasks.len());
-                    for task in &analysis.tasks {
-                        println!("  • {} (Priority: {:?})", task.title, task.priority);
-                    }
+                        println!("✅ Tasks: {}", analysis.tasks.len());
+                        for task in &analysis.tasks {
+                            println!("  • {} (Priority: {:?})", task.title, task.priority);
+                        }
                     
-                    println!("📝 Notes: {}", analysis.structured_notes.len());
-                    for note in &analysis.structured_notes {
-                        println!("  • {} (Type: {:?})", note.title, note.note_type);
-                    }
+                        println!("📝 Notes: {}", analysis.structured_notes.len());
+                        for note in &analysis.structured_notes {
+                            println!("  • {} (Type: {:?})", note.title, note.note_type);
+                        }
                     
-                    println!("📋 Summary: {}", analysis.summary);
+                        println!("📋 Summary: {}", analysis.summary);
+                    }
+                    Some(Err(e)) if crate::ai::is_provider_unavailable(&e) => {
+                        // Keep the recording and transcript; analyze it once the provider is back
+                        session.analysis_pending = true;
+                        println!("⏳ Analysis provider unavailable ({:#}); run `retry-analysis` later", e);
+                    }
+                    Some(Err(e)) => println!("⚠️ Analysis failed: {:#}", e),
+                    None => {
+                        session.analysis_pending = true;
+                        println!("⏭️ Automatic analysis is off; run `retry-analysis` to analyze later");
+                    }
                 }
             }
             
@@ -190,11 +358,50 @@ impl VoiceRecorder {
             let analysis_to_save = session.analysis.take();
             crate::storage::save_session(&mut session, analysis_to_save).await?;
             println!("💾 Session saved: {}", session.id);
+            crate::webhook::notify_session_processed(&session);
         }
         
         Ok(())
     }
 
+    /// Abort the current recording and delete its audio file instead of
+    /// saving a session
+    ///
+    /// Returns `false` if no recording was active.
+    pub async fn cancel_recording(&mut self) -> Result<bool> {
+        if *self.state.lock().unwrap() == RecordingState::Idle {
+            return Ok(false);
+        }
+
+        // Stop the stream before closing the file so no more samples arrive
+        self.stream.lock().unwrap().take();
+        if let Some(writer) = self.writer.lock().unwrap().take() {
+            // Closes the file; its contents are deleted below anyway
+            let _ = writer.finalize();
+        }
+
+        *self.state.lock().unwrap() = RecordingState::Idle;
+        self.level_meter.reset(false);
+        self.recording_start.lock().unwrap().take();
+        let session = self.current_session.lock().unwrap().take();
+
+        if let Some(session) = session {
+            match tokio::fs::remove_file(&session.audio_file_path).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => {
+                    return Err(anyhow::Error::new(e).context(format!(
+                        "Failed to delete audio file {}",
+                        session.audio_file_path.display()
+                    )));
+                }
+            }
+            println!("🗑️ Recording discarded: {}", session.id);
+        }
+
+        Ok(true)
+    }
+
     pub async fn play_audio_file(&self, file_path: &str) -> Result<()> {
         let file = std::fs::File::open(file_path)?;
         let mut reader = hound::WavReader::new(file)?;
@@ -237,7 +444,119 @@ impl VoiceRecorder {
         Ok(())
     }
 
+    pub fn state(&self) -> RecordingState {
+        *self.state.lock().unwrap()
+    }
+
     pub fn is_recording(&self) -> bool {
-        *self.is_recording.lock().unwrap()
+        self.state() == RecordingState::Recording
+    }
+
+    /// Get the ID of the session being recorded, if any
+    pub fn current_session_id(&self) -> Option<String> {
+        self.current_session.lock().unwrap().as_ref().map(|s| s.id.clone())
+    }
+
+    /// Get how long the current recording has been running, if recording
+    pub fn elapsed(&self) -> Option<std::time::Duration> {
+        self.recording_start.lock().unwrap().map(|start| start.elapsed())
     }
-}
\ No newline at end of file
+
+    /// Get the current input level, if recording
+    pub fn input_level(&self) -> Option<InputLevel> {
+        self.level_meter.is_active().then(|| self.level_meter.level())
+    }
+
+    /// Get a handle to the input level meter
+    pub fn level_meter(&self) -> Arc<LevelMeter> {
+        self.level_meter.clone()
+    }
+}
+
+/// Stop and save the current recording once `recording.max_recording_secs` elapses
+///
+/// Does nothing when no limit is configured. If the recording is stopped
+/// manually first, the guard notices the session changed and exits.
+pub fn spawn_max_duration_guard(recorder: Arc<AsyncMutex<VoiceRecorder>>, session_id: String) {
+    let Some(max_secs) = crate::config::current().recording.max_recording_secs else {
+        return;
+    };
+
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_secs(max_secs)).await;
+        let mut guard = recorder.lock().await;
+        if guard.current_session_id().as_deref() != Some(session_id.as_str()) {
+            return;
+        }
+        log::warn!(
+            "Recording {} reached the maximum duration of {}s; stopping and saving it",
+            session_id, max_secs
+        );
+        if let Err(e) = guard.stop_recording().await {
+            log::error!("Failed to finalize recording {}: {:?}", session_id, e);
+        }
+    });
+}
+
+/// Print a textual VU meter to stderr until the meter goes inactive
+pub async fn run_level_meter_display(meter: Arc<LevelMeter>) {
+    use std::io::Write;
+
+    const WIDTH: usize = 30;
+    let mut interval = tokio::time::interval(std::time::Duration::from_millis(100));
+    while meter.is_active() {
+        interval.tick().await;
+        let level = meter.level();
+        // Map -60..0 dBFS onto the bar width
+        let filled = (((level.rms_db() + 60.0) / 60.0) * WIDTH as f32).round() as usize;
+        let peak_pos = ((level.peak.max(1e-3).log10() * 20.0 + 60.0).max(0.0) / 60.0 * WIDTH as f32) as usize;
+        let bar: String = (0..WIDTH)
+            .map(|i| if i < filled { '#' } else if i == peak_pos.min(WIDTH - 1) { '|' } else { ' ' })
+            .collect();
+        eprint!("\r🎙  [{}] {:>4.0} dB", bar, level.rms_db());
+        let _ = std::io::stderr().flush();
+    }
+    eprintln!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Barrier;
+
+    #[test]
+    fn only_one_of_two_concurrent_starts_claims_the_recorder() {
+        let state = Arc::new(Mutex::new(RecordingState::Idle));
+        let barrier = Arc::new(Barrier::new(2));
+        let starts: Vec<_> = (0..2)
+            .map(|_| {
+                let (state, barrier) = (state.clone(), barrier.clone());
+                std::thread::spawn(move || {
+                    barrier.wait();
+                    claim_recording(&state)
+                })
+            })
+            .collect();
+
+        let claimed: Vec<bool> = starts.into_iter().map(|start| start.join().unwrap()).collect();
+        assert_eq!(claimed.iter().filter(|claimed| **claimed).count(), 1, "{:?}", claimed);
+        assert_eq!(*state.lock().unwrap(), RecordingState::Recording);
+    }
+
+    #[test]
+    fn a_stopped_recorder_can_be_claimed_again() {
+        let state = Mutex::new(RecordingState::Idle);
+        assert!(claim_recording(&state));
+        assert!(!claim_recording(&state));
+
+        *state.lock().unwrap() = RecordingState::Idle;
+        assert!(claim_recording(&state));
+    }
+
+    #[test]
+    fn rejection_names_the_active_session() {
+        let error = AlreadyRecording { session_id: Some("abc".to_string()) };
+        assert_eq!(error.to_string(), "A recording is already in progress (session abc)");
+        assert_eq!(AlreadyRecording { session_id: None }.to_string(), "A recording is already in progress");
+    }
+}
@@ -1,7 +1,10 @@
 use anyhow::{Result, Context};
 use log::{info, warn, error};
 
-use crate::ai::{transcribe_audio, analyze_transcript};
+use crate::ai::{transcribe_session_audio, analyze_transcript, is_provider_unavailable};
+use crate::analysis_lock::{self, AnalysisLock};
+use crate::storage::VoiceSession;
+use crate::summary::{Outcome, RunSummary};
 
 #[derive(Debug, Default)]
 struct BackfillStats {
@@ -10,6 +13,7 @@ struct BackfillStats {
     skipped: usize,
     transcript_generated: usize,
     analysis_generated: usize,
+    analysis_pending: usize,
     errors: usize,
 }
 
@@ -23,10 +27,30 @@ pub async fn backfill_sessions() -> Result<()> {
     };
     
     info!("Starting backfill process for {} sessions", stats.total_sessions);
+    // 分析服务不可用后，其余 session 只生成 transcript，分析留给 retry-analysis
+    let mut provider_down = false;
 
-    for (index, mut session) in sessions.into_iter().enumerate() {
-        let session_id = session.id.clone();
+    for (index, listed) in sessions.into_iter().enumerate() {
+        let session_id = listed.id;
         info!("Processing session {}/{}: {}", index + 1, stats.total_sessions, session_id);
+
+        let (_lock, mut session) = match claim(&session_id).await {
+            Ok(Claim::Locked(lock, session)) => (lock, *session),
+            Ok(Claim::Busy) => {
+                stats.skipped += 1;
+                continue;
+            }
+            Ok(Claim::Deleted) => {
+                info!("[{}] Session was deleted meanwhile, skipping", session_id);
+                stats.skipped += 1;
+                continue;
+            }
+            Err(e) => {
+                error!("[{}] Failed to lock session: {:#}", session_id, e);
+                stats.errors += 1;
+                continue;
+            }
+        };
         
         if !session.audio_file_path.exists() {
             warn!("Audio file not found for session {}, skipping", session_id);
@@ -56,11 +80,11 @@ pub async fn backfill_sessions() -> Result<()> {
         // 处理 transcript
         if needs_transcript {
             info!("[{}] Generating transcript...", session_id);
-            match transcribe_audio(&session.audio_file_path).await {
-                Ok(transcript) => {
-                    session.transcript = Some(transcript.clone());
-                    info!("[{}] Successfully generated transcript ({} chars)", 
-                          session_id, transcript.len());
+            match transcribe_session_audio(&mut session).await {
+                Ok(transcription) => {
+                    info!("[{}] Successfully generated transcript ({} chars, {} segments)", 
+                          session_id, transcription.text.len(), transcription.segments.len());
+                    session.set_transcription(transcription);
                     stats.transcript_generated += 1;
                     should_save = true;
                 }
@@ -76,23 +100,41 @@ pub async fn backfill_sessions() -> Result<()> {
         if !session_error && needs_analysis {
             info!("[{}] Generating analysis...", session_id);
             if let Some(transcript) = &session.transcript {
-                match analyze_transcript(transcript).await {
-                    Ok(analysis) => {
-                        session.analysis = Some(analysis.clone());
-                        if !analysis.title.is_empty() {
-                            session.title = analysis.title.clone();
-                        }
-                        info!("[{}] Successfully generated analysis (title: {}, {} ideas, {} tasks)", 
-                              session_id, 
-                              analysis.title,
-                              analysis.ideas.len(),
-                              analysis.tasks.len());
-                        stats.analysis_generated += 1;
+                if provider_down {
+                    info!("[{}] Analysis provider unavailable, deferring analysis", session_id);
+                    if !session.analysis_pending {
+                        session.analysis_pending = true;
                         should_save = true;
                     }
-                    Err(e) => {
-                        error!("[{}] Failed to generate analysis: {}", session_id, e);
-                        stats.errors += 1;
+                    stats.analysis_pending += 1;
+                } else {
+                    match analyze_transcript(transcript).await {
+                        Ok(analysis) => {
+                            session.analysis = Some(analysis.clone());
+                            if !analysis.title.is_empty() {
+                                session.title = analysis.title.clone();
+                            }
+                            info!("[{}] Successfully generated analysis (title: {}, {} ideas, {} tasks)", 
+                                  session_id, 
+                                  analysis.title,
+                                  analysis.ideas.len(),
+                                  analysis.tasks.len());
+                            stats.analysis_generated += 1;
+                            should_save = true;
+                        }
+                        Err(e) if is_provider_unavailable(&e) => {
+                            warn!("[{}] Analysis provider unavailable, deferring analysis: {:#}", session_id, e);
+                            provider_down = true;
+                            if !session.analysis_pending {
+                                session.analysis_pending = true;
+                                should_save = true;
+                            }
+                            stats.analysis_pending += 1;
+                        }
+                        Err(e) => {
+                            error!("[{}] Failed to generate analysis: {}", session_id, e);
+                            stats.errors += 1;
+                        }
                     }
                 }
             } else {
@@ -131,6 +173,7 @@ pub async fn backfill_sessions() -> Result<()> {
     info!("  Skipped: {}", stats.skipped);
     info!("  Transcripts generated: {}", stats.transcript_generated);
     info!("  Analysis generated: {}", stats.analysis_generated);
+    info!("  Analysis pending: {}", stats.analysis_pending);
     info!("  Errors encountered: {}", stats.errors);
 
     if stats.errors > 0 {
@@ -138,6 +181,96 @@ pub async fn backfill_sessions() -> Result<()> {
     } else {
         info!("Backfill completed successfully");
     }
+    if stats.analysis_pending > 0 {
+        warn!("{} sessions are waiting for analysis, run `retry-analysis` once the provider is back", stats.analysis_pending);
+    }
+
+    Ok(())
+}
+
+/// Analyze the sessions whose analysis was deferred, because the provider
+/// was unreachable or `analysis.auto_analyze` is off, stopping early if the
+/// provider is unreachable
+pub async fn retry_pending_analysis() -> Result<()> {
+    let sessions = crate::storage::list_sessions().await
+        .context("Failed to list sessions")?;
+    let pending: Vec<_> = sessions.into_iter()
+        .filter(|session| session.analysis_pending && session.analysis.is_none())
+        .collect();
+
+    info!("Retrying analysis for {} pending sessions", pending.len());
+    let total = pending.len();
+    let mut summary = RunSummary::default();
+
+    for (index, listed) in pending.into_iter().enumerate() {
+        let session_id = listed.id;
+        let (_lock, mut session) = match claim(&session_id).await {
+            Ok(Claim::Locked(lock, session)) => (lock, *session),
+            Ok(Claim::Busy) => {
+                summary.push(session_id, Outcome::Skipped { reason: "being analyzed elsewhere".to_string() });
+                continue;
+            }
+            Ok(Claim::Deleted) => {
+                summary.push(session_id, Outcome::Skipped { reason: "deleted meanwhile".to_string() });
+                continue;
+            }
+            Err(e) => {
+                summary.push(session_id, Outcome::Failed { error: format!("Failed to lock session: {:#}", e) });
+                continue;
+            }
+        };
+        if session.analysis.is_some() {
+            summary.push(session_id, Outcome::Skipped { reason: "analyzed meanwhile".to_string() });
+            continue;
+        }
+        let Some(transcript) = session.transcript.clone() else {
+            summary.push(session_id, Outcome::Skipped { reason: "no transcript".to_string() });
+            continue;
+        };
+        info!("Analyzing session {}/{}: {}", index + 1, total, session_id);
+
+        let outcome = match analyze_transcript(&transcript).await {
+            Ok(analysis) => {
+                if !analysis.title.is_empty() {
+                    session.title = analysis.title.clone();
+                }
+                let detail = format!("({})", analysis.model.as_deref().unwrap_or("unknown model"));
+                // save_session 会清除 analysis_pending
+                match crate::storage::save_session(&mut session, Some(analysis)).await {
+                    Ok(()) => Outcome::Done { detail },
+                    Err(e) => Outcome::Failed { error: format!("Failed to save session: {}", e) },
+                }
+            }
+            Err(e) if is_provider_unavailable(&e) => {
+                warn!("Analysis provider is still unavailable ({:#}), stopping", e);
+                break;
+            }
+            Err(e) => Outcome::Failed { error: format!("Failed to generate analysis: {}", e) },
+        };
+        summary.push(session_id, outcome);
+    }
 
+    summary.log("Retry", "analyzed", |id| id.clone());
+    info!("{} of {} sessions still pending", total - summary.done(), total);
     Ok(())
-} 
\ No newline at end of file
+}
+
+/// What locking a listed session for processing found
+enum Claim {
+    /// Locked, and loaded again since whoever held the lock before may have
+    /// saved it after it was listed
+    Locked(AnalysisLock, Box<VoiceSession>),
+    /// Being analyzed by another task or process
+    Busy,
+    Deleted,
+}
+
+async fn claim(session_id: &str) -> Result<Claim> {
+    let Some(lock) = analysis_lock::try_lock(session_id)? else {
+        return Ok(Claim::Busy);
+    };
+    Ok(match crate::storage::get_session(session_id).await? {
+        Some(session) => Claim::Locked(lock, Box::new(session)),
+        None => Claim::Deleted,
+    })
+}
@@ -0,0 +1,147 @@
+// src/batch.rs
+//! Batch transcription of a directory of audio files
+//!
+//! Each supported audio file becomes its own session. Files whose checksum
+//! matches an already transcribed session are skipped, so the command can be
+//! re-run over the same archive.
+
+use anyhow::{Context, Result};
+use log::{error, info};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::ai::transcribe_session_audio;
+use crate::storage;
+use crate::summary::{Outcome, RunSummary};
+
+/// Transcribe every supported audio file in `dir`, at most `jobs` at a time
+pub async fn transcribe_directory(dir: &Path, jobs: usize) -> Result<RunSummary<PathBuf>> {
+    let config = crate::config::current();
+    let allowed_formats: HashSet<String> = config
+        .storage
+        .allowed_formats
+        .iter()
+        .map(|f| f.to_lowercase())
+        .collect();
+
+    let mut files = Vec::new();
+    let mut entries = tokio::fs::read_dir(dir)
+        .await
+        .with_context(|| format!("Failed to read directory: {}", dir.display()))?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase());
+        if path.is_file() && extension.map_or(false, |e| allowed_formats.contains(&e)) {
+            files.push(path);
+        }
+    }
+    files.sort();
+
+    info!("Found {} audio files in {}", files.len(), dir.display());
+
+    // Checksums of sessions that already have a transcript
+    let mut seen_checksums: HashSet<String> = storage::list_sessions()
+        .await
+        .context("Failed to list sessions")?
+        .into_iter()
+        .filter(|s| s.transcript.is_some())
+        .filter_map(|s| s.audio_checksum)
+        .collect();
+
+    let mut summary = RunSummary::default();
+    let semaphore = Arc::new(Semaphore::new(jobs.max(1)));
+    let mut tasks = JoinSet::new();
+
+    for path in files {
+        let checksum = match storage::file_checksum(&path).await {
+            Ok(checksum) => checksum,
+            Err(e) => {
+                summary.push(path, Outcome::Failed { error: format!("Failed to read file: {}", e) });
+                continue;
+            }
+        };
+
+        // Also catches identical files within the same directory
+        if !seen_checksums.insert(checksum.clone()) {
+            summary.push(path, Outcome::Skipped { reason: "already transcribed".to_string() });
+            continue;
+        }
+
+        let semaphore = semaphore.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await.expect("semaphore closed");
+            let outcome = match transcribe_file(&path, checksum).await {
+                Ok(session_id) => Outcome::Done { detail: format!("-> session {}", session_id) },
+                Err(e) => Outcome::Failed { error: format!("{:#}", e) },
+            };
+            (path, outcome)
+        });
+    }
+
+    while let Some(result) = tasks.join_next().await {
+        match result {
+            Ok((path, outcome)) => summary.push(path, outcome),
+            Err(e) => error!("Transcription task panicked: {}", e),
+        }
+    }
+    summary.sort();
+
+    Ok(summary)
+}
+
+/// Import one file into a new session and transcribe it
+async fn transcribe_file(path: &Path, checksum: String) -> Result<String> {
+    let mut session = storage::create_new_session();
+    info!("[{}] Transcribing {}", session.id, path.display());
+
+    // Name the copy after the format found in the file rather than the
+    // source's extension, which may not match its content
+    let probed = {
+        let path = path.to_path_buf();
+        tokio::task::spawn_blocking(move || crate::probe::check(&path)).await?
+    };
+    let extension = match &probed {
+        Ok(info) => info.format.to_string(),
+        Err(_) => path.extension().and_then(|e| e.to_str()).unwrap_or("wav").to_lowercase(),
+    };
+    let audio_dir = crate::config::get_audio_dir();
+    tokio::fs::create_dir_all(&audio_dir)
+        .await
+        .context("Failed to create audio directory")?;
+    let audio_file_path = audio_dir.join(format!("{}.{}", session.id, extension));
+    tokio::fs::copy(path, &audio_file_path)
+        .await
+        .with_context(|| format!("Failed to copy {} into storage", path.display()))?;
+
+    session.audio_file_path = audio_file_path;
+    session.audio_checksum = Some(checksum);
+    session.title = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    session.duration_ms = probed.ok().and_then(|info| info.duration_ms).unwrap_or(0);
+
+    let transcription = match transcribe_session_audio(&mut session).await {
+        Ok(transcription) => transcription,
+        Err(e) => {
+            // Don't leave an orphaned copy behind; the file will be retried next run
+            let _ = tokio::fs::remove_file(&session.audio_file_path).await;
+            return Err(e);
+        }
+    };
+    session.set_transcription(transcription);
+    storage::save_session(&mut session, None).await?;
+
+    Ok(session.id)
+}
+
+/// Log a per-file summary of a batch run
+pub fn log_summary(summary: &RunSummary<PathBuf>) {
+    summary.log("Batch transcription", "transcribed", |path| path.display().to_string());
+}
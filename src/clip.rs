@@ -0,0 +1,124 @@
+// src/clip.rs
+//! Cutting a stretch of a recording out as a WAV file of its own
+//!
+//! Transcript segments carry timestamps, so the audio of a single decision or
+//! quote can be shared without the rest of the meeting. Only WAV recordings
+//! can be cut sample-accurately without decoding; other formats are refused.
+
+use anyhow::Context;
+use std::fmt;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+/// Why a clip could not be cut
+#[derive(Debug)]
+pub enum ClipError {
+    /// `start` and `end` do not describe a stretch of the recording
+    InvalidRange(String),
+    /// The recording is not a WAV file
+    Unsupported(String),
+    /// The recording could not be read or the clip written
+    Failed(anyhow::Error),
+}
+
+impl fmt::Display for ClipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClipError::InvalidRange(message) | ClipError::Unsupported(message) => f.write_str(message),
+            ClipError::Failed(e) => write!(f, "{:#}", e),
+        }
+    }
+}
+
+impl std::error::Error for ClipError {}
+
+/// The audio of `path` from `start_secs` to `end_secs`, as a WAV file with
+/// the recording's sample rate, channels and sample format
+pub async fn extract_clip(path: &Path, start_secs: f64, end_secs: f64) -> Result<Vec<u8>, ClipError> {
+    let is_wav = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("wav"));
+    if !is_wav {
+        return Err(ClipError::Unsupported("Only WAV recordings can be clipped".to_string()));
+    }
+
+    let path: PathBuf = path.to_path_buf();
+    tokio::task::spawn_blocking(move || cut_wav(&path, start_secs, end_secs))
+        .await
+        .context("Clip task panicked")
+        .map_err(ClipError::Failed)?
+}
+
+fn cut_wav(path: &Path, start_secs: f64, end_secs: f64) -> Result<Vec<u8>, ClipError> {
+    // Uploads are stored as `.wav` whatever their format, so an MP3 can get here
+    let reader = hound::WavReader::open(path).map_err(|e| match e {
+        hound::Error::FormatError(_) | hound::Error::Unsupported | hound::Error::InvalidSampleFormat => {
+            ClipError::Unsupported(format!("Only WAV recordings can be clipped: {}", e))
+        }
+        e => ClipError::Failed(anyhow::Error::new(e).context(format!("Failed to open WAV file: {}", path.display()))),
+    })?;
+    let spec = reader.spec();
+    let frames = reader.duration();
+    let duration_secs = frames as f64 / spec.sample_rate.max(1) as f64;
+
+    if !start_secs.is_finite() || !end_secs.is_finite() || start_secs < 0.0 {
+        return Err(ClipError::InvalidRange("start and end must be seconds from 0".to_string()));
+    }
+    if start_secs >= end_secs {
+        return Err(ClipError::InvalidRange("start must be before end".to_string()));
+    }
+    if end_secs > duration_secs {
+        return Err(ClipError::InvalidRange(format!(
+            "end ({}s) is past the end of the recording ({:.3}s)",
+            end_secs, duration_secs
+        )));
+    }
+
+    let start_frame = (start_secs * spec.sample_rate as f64).floor() as u32;
+    let end_frame = ((end_secs * spec.sample_rate as f64).ceil() as u32).min(frames);
+    let samples = (end_frame - start_frame) as usize * spec.channels as usize;
+
+    copy_frames(reader, start_frame, samples).map_err(ClipError::Failed)
+}
+
+/// `samples` samples of `reader` from `start_frame` on, as a new WAV file
+fn copy_frames<R: std::io::Read + std::io::Seek>(
+    mut reader: hound::WavReader<R>,
+    start_frame: u32,
+    samples: usize,
+) -> anyhow::Result<Vec<u8>> {
+    let spec = reader.spec();
+    reader.seek(start_frame).context("Failed to seek in the WAV file")?;
+    let mut out = Cursor::new(Vec::new());
+    let mut writer = hound::WavWriter::new(&mut out, spec).context("Failed to write the clip")?;
+    let copied = match spec.sample_format {
+        hound::SampleFormat::Float => reader
+            .samples::<f32>()
+            .take(samples)
+            .try_for_each(|sample| writer.write_sample(sample?)),
+        hound::SampleFormat::Int => reader
+            .samples::<i32>()
+            .take(samples)
+            .try_for_each(|sample| writer.write_sample(sample?)),
+    };
+    copied.context("Failed to copy the audio of the clip")?;
+    writer.finalize().context("Failed to write the clip")?;
+    Ok(out.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn other_formats_stored_as_wav_are_unsupported() {
+        let path = std::env::temp_dir().join(format!("voice-recorder-clip-{}.wav", std::process::id()));
+        // An MP3 with an ID3 tag, as uploaded under a `.wav` name
+        std::fs::write(&path, b"ID3\x04\x00\x00\x00\x00\x00\x00\xff\xfb\x90\x64").unwrap();
+
+        let result = extract_clip(&path, 0.0, 1.0).await;
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(ClipError::Unsupported(_))), "{:?}", result);
+    }
+}
@@ -0,0 +1,119 @@
+// src/config/reload.rs
+//! Configuration hot-reload
+//!
+//! On Unix, sending `SIGHUP` to a running `voice-recorder web` process re-reads
+//! `config.toml` (or the legacy `config.json`) and the environment. The new
+//! configuration is validated before it replaces the active one; if loading or
+//! validation fails the previous configuration stays in effect.
+//!
+//! Fields that take effect on reload:
+//! - `ollama.*` (base URL, default model, timeouts)
+//! - `openai.*` (API key, models)
+//! - `analysis.*`, `transcription.*`
+//! - `recording.*` (applies to the next recording)
+//! - `storage.*` (applies to new reads and writes)
+//! - `server.admin_token`
+//!
+//! Fields that still require a restart:
+//! - `server.host`, `server.port`, `server.cors_origins`,
+//!   `server.request_timeout_secs`, `server.max_body_size`,
+//!   `server.idempotency_ttl_secs`
+//! - `storage.max_file_size` as the request body limit of `/api/v1/audio`
+//!   uploads (the file size check itself uses the reloaded value)
+//! - `ollama.timeout_secs` for the `/api/v1` Ollama endpoints, whose HTTP
+//!   client is created at startup (analyses use `analysis.timeout_secs`,
+//!   which is reloaded)
+//! - `database.*` (the connection pool is created at startup)
+//! - `logging.*` (the subscriber is installed once)
+
+use anyhow::Result;
+use std::sync::Arc;
+
+use super::{Config, SharedConfig};
+
+/// Reload configuration into `shared`, keeping the current one on failure
+pub fn reload(shared: &SharedConfig) -> Result<Arc<Config>> {
+    let new_config = Config::load()?;
+    let old_config = shared.current();
+
+    for field in restart_required_changes(&old_config, &new_config) {
+        log::warn!("Config field {} changed but only takes effect after a restart", field);
+    }
+
+    shared.replace(new_config);
+    Ok(shared.current())
+}
+
+/// List fields that differ between two configs but cannot be applied live
+fn restart_required_changes(old: &Config, new: &Config) -> Vec<&'static str> {
+    let mut changed = Vec::new();
+    if old.server.host != new.server.host {
+        changed.push("server.host");
+    }
+    if old.server.port != new.server.port {
+        changed.push("server.port");
+    }
+    if old.server.cors_origins != new.server.cors_origins {
+        changed.push("server.cors_origins");
+    }
+    if old.server.request_timeout_secs != new.server.request_timeout_secs {
+        changed.push("server.request_timeout_secs");
+    }
+    if old.server.max_body_size != new.server.max_body_size {
+        changed.push("server.max_body_size");
+    }
+    if old.server.idempotency_ttl_secs != new.server.idempotency_ttl_secs {
+        changed.push("server.idempotency_ttl_secs");
+    }
+    if old.ollama.timeout_secs != new.ollama.timeout_secs {
+        changed.push("ollama.timeout_secs");
+    }
+    if old.database.url != new.database.url {
+        changed.push("database.url");
+    }
+    if old.logging.level != new.logging.level
+        || old.logging.format != new.logging.format
+        || old.logging.file_path != new.logging.file_path
+    {
+        changed.push("logging");
+    }
+    changed
+}
+
+/// Spawn a task that reloads configuration whenever the process receives SIGHUP
+///
+/// `on_reload` is called with the new configuration after a successful reload,
+/// so callers can push changed values into long-lived services.
+#[cfg(unix)]
+pub fn spawn_sighup_handler<F>(shared: SharedConfig, on_reload: F) -> Result<()>
+where
+    F: Fn(&Config) + Send + 'static,
+{
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangups = signal(SignalKind::hangup())?;
+    tokio::spawn(async move {
+        while hangups.recv().await.is_some() {
+            log::info!("Received SIGHUP, reloading configuration...");
+            match reload(&shared) {
+                Ok(config) => {
+                    on_reload(&config);
+                    log::info!("Configuration reloaded");
+                }
+                Err(e) => {
+                    log::error!("Config reload rejected, keeping previous configuration: {:#}", e);
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+/// SIGHUP is not available on this platform; configuration is loaded once
+#[cfg(not(unix))]
+pub fn spawn_sighup_handler<F>(_shared: SharedConfig, _on_reload: F) -> Result<()>
+where
+    F: Fn(&Config) + Send + 'static,
+{
+    Ok(())
+}
@@ -0,0 +1,121 @@
+// src/disposition.rs
+//! `Content-Disposition` headers for downloads
+//!
+//! Filenames can come from user input, such as the original name of an
+//! uploaded file, so they are cleaned before going into the header: control
+//! characters (which could inject header lines) are dropped, path separators
+//! become `_`, and quotes or backslashes cannot end the quoted `filename`.
+//! Names that are not plain ASCII, e.g. Chinese session titles, also get an
+//! RFC 5987 `filename*=UTF-8''...` parameter with the full name, which
+//! browsers prefer over the ASCII-only `filename` fallback.
+
+/// Name used when nothing usable is left of a filename
+const FALLBACK_NAME: &str = "download";
+
+/// `Content-Disposition` value offering the response as a download
+pub fn attachment(filename: &str) -> String {
+    header_value("attachment", filename)
+}
+
+/// `Content-Disposition` value for a response shown in the browser, with
+/// the name to use when it is saved
+pub fn inline(filename: &str) -> String {
+    header_value("inline", filename)
+}
+
+fn header_value(disposition: &str, filename: &str) -> String {
+    let filename = sanitize(filename);
+    let fallback = ascii_fallback(&filename);
+    if fallback == filename {
+        format!("{}; filename=\"{}\"", disposition, filename)
+    } else {
+        format!(
+            "{}; filename=\"{}\"; filename*=UTF-8''{}",
+            disposition,
+            fallback,
+            percent_encode(&filename)
+        )
+    }
+}
+
+/// `filename` without control characters, path separators or a leading `.`
+fn sanitize(filename: &str) -> String {
+    let cleaned: String = filename
+        .chars()
+        .filter(|c| !c.is_control())
+        .map(|c| if matches!(c, '/' | '\\') { '_' } else { c })
+        .collect();
+    // Leading dots would hide the file; `_` is what is left of `../`
+    let cleaned = cleaned.trim().trim_start_matches(['.', '_']).trim_start();
+    if cleaned.is_empty() {
+        FALLBACK_NAME.to_string()
+    } else {
+        cleaned.to_string()
+    }
+}
+
+/// The printable ASCII part of `filename`, safe inside a quoted string
+///
+/// A name with nothing left before its extension, like `会议记录.txt`,
+/// becomes `download.txt`.
+fn ascii_fallback(filename: &str) -> String {
+    let kept: String = filename
+        .chars()
+        .filter(|c| (c.is_ascii_graphic() || *c == ' ') && !matches!(c, '"' | '\\'))
+        .collect();
+    let kept = kept.split_whitespace().collect::<Vec<_>>().join(" ");
+    let is_blank = |part: &str| part.trim_matches(['.', '_', '-', ' ']).is_empty();
+
+    match kept.rsplit_once('.') {
+        Some((stem, extension)) if is_blank(stem) => format!("{}.{}", FALLBACK_NAME, extension),
+        None if is_blank(&kept) => FALLBACK_NAME.to_string(),
+        _ => kept.trim_start_matches(['.', '_', '-', ' ']).to_string(),
+    }
+}
+
+/// Percent-encode everything but RFC 5987 `attr-char`s
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| {
+            if b.is_ascii_alphanumeric() || b"!#$&+-.^_`|~".contains(&b) {
+                (b as char).to_string()
+            } else {
+                format!("%{:02X}", b)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_names_are_quoted_as_they_are() {
+        assert_eq!(inline("session_1.wav"), "inline; filename=\"session_1.wav\"");
+    }
+
+    #[test]
+    fn chinese_names_are_percent_encoded_with_an_ascii_fallback() {
+        assert_eq!(
+            attachment("会议记录.txt"),
+            "attachment; filename=\"download.txt\"; filename*=UTF-8''%E4%BC%9A%E8%AE%AE%E8%AE%B0%E5%BD%95.txt"
+        );
+        assert_eq!(
+            attachment("周会 notes.md"),
+            "attachment; filename=\"notes.md\"; filename*=UTF-8''%E5%91%A8%E4%BC%9A%20notes.md"
+        );
+        assert_eq!(attachment("会议"), "attachment; filename=\"download\"; filename*=UTF-8''%E4%BC%9A%E8%AE%AE");
+    }
+
+    #[test]
+    fn quotes_line_breaks_and_paths_cannot_escape_the_header() {
+        assert_eq!(
+            attachment("a\"b\r\nSet-Cookie: x.txt"),
+            "attachment; filename=\"abSet-Cookie: x.txt\"; filename*=UTF-8''a%22bSet-Cookie%3A%20x.txt"
+        );
+        assert_eq!(attachment("../../etc/passwd"), "attachment; filename=\"etc_passwd\"");
+        assert_eq!(attachment("\r\n"), "attachment; filename=\"download\"");
+    }
+}
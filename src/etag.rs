@@ -0,0 +1,89 @@
+// src/etag.rs
+//! Entity tags for conditional GET requests
+//!
+//! Audio and export responses carry an `ETag`; a client sending it back in
+//! `If-None-Match` gets `304 Not Modified` instead of the full body while the
+//! content is unchanged.
+
+use axum::body::Body;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::Response;
+use sha2::{Digest, Sha256};
+
+/// Strong tag for a rendered response body
+///
+/// Hashing the body itself means the tag changes with anything that ends up
+/// in the output, e.g. an edited transcript or a different export format.
+pub fn for_content(content: &[u8]) -> String {
+    format!("\"{:x}\"", Sha256::digest(content))
+}
+
+/// Strong tag for a file whose SHA-256 checksum is already known
+pub fn for_checksum(checksum: &str) -> String {
+    format!("\"{}\"", checksum)
+}
+
+/// Weak tag for a file without a known checksum, from its size and
+/// modification time
+pub fn for_file_metadata(metadata: &std::fs::Metadata) -> String {
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|since_epoch| since_epoch.as_nanos())
+        .unwrap_or_default();
+    format!("W/\"{:x}-{:x}\"", metadata.len(), modified)
+}
+
+/// Whether the request's `If-None-Match` header matches `etag`
+///
+/// Uses the weak comparison RFC 9110 prescribes for `If-None-Match`.
+pub fn matches(headers: &HeaderMap, etag: &str) -> bool {
+    let etag = opaque_tag(etag);
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || opaque_tag(candidate) == etag)
+}
+
+fn opaque_tag(etag: &str) -> &str {
+    etag.strip_prefix("W/").unwrap_or(etag)
+}
+
+/// Empty `304 Not Modified` response for `etag`
+pub fn not_modified(etag: &str) -> Response {
+    Response::builder()
+        .status(StatusCode::NOT_MODIFIED)
+        .header(header::ETAG, etag)
+        .body(Body::empty())
+        .unwrap()
+}
+
+/// Respond with `content`, or `304 Not Modified` if the client's copy is
+/// current
+///
+/// `filename` adds an attachment `Content-Disposition`; it is sanitized, so
+/// it may be derived from user input.
+pub fn content_response(
+    headers: &HeaderMap,
+    content_type: &str,
+    filename: Option<&str>,
+    content: impl Into<Vec<u8>>,
+) -> Response {
+    let content = content.into();
+    let etag = for_content(&content);
+    if matches(headers, &etag) {
+        return not_modified(&etag);
+    }
+
+    let mut response = Response::builder()
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::ETAG, &etag);
+    if let Some(filename) = filename {
+        response = response.header(header::CONTENT_DISPOSITION, crate::disposition::attachment(filename));
+    }
+    response.body(Body::from(content)).unwrap()
+}
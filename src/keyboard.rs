@@ -9,10 +9,61 @@ use tokio::sync::Mutex as AsyncMutex;
 
 enum KeyEvent {
     StartRecord,
+    /// Stop the recording and save it as a session
     EndRecord,
+    /// Stop the recording and discard it
+    CancelRecord,
     Quit,
 }
 
+/// Keys bound to start, stop and quit, which the cancel key may not reuse
+pub const RESERVED_KEYS: &[Key] = &[Key::KeyR, Key::KeyE, Key::KeyQ];
+
+/// The key named by `recording.cancel_key`: a letter, a digit or "escape"
+pub fn parse_key(name: &str) -> Option<Key> {
+    let key = match name.to_ascii_lowercase().as_str() {
+        "a" => Key::KeyA,
+        "b" => Key::KeyB,
+        "c" => Key::KeyC,
+        "d" => Key::KeyD,
+        "e" => Key::KeyE,
+        "f" => Key::KeyF,
+        "g" => Key::KeyG,
+        "h" => Key::KeyH,
+        "i" => Key::KeyI,
+        "j" => Key::KeyJ,
+        "k" => Key::KeyK,
+        "l" => Key::KeyL,
+        "m" => Key::KeyM,
+        "n" => Key::KeyN,
+        "o" => Key::KeyO,
+        "p" => Key::KeyP,
+        "q" => Key::KeyQ,
+        "r" => Key::KeyR,
+        "s" => Key::KeyS,
+        "t" => Key::KeyT,
+        "u" => Key::KeyU,
+        "v" => Key::KeyV,
+        "w" => Key::KeyW,
+        "x" => Key::KeyX,
+        "y" => Key::KeyY,
+        "z" => Key::KeyZ,
+        "0" => Key::Num0,
+        "1" => Key::Num1,
+        "2" => Key::Num2,
+        "3" => Key::Num3,
+        "4" => Key::Num4,
+        "5" => Key::Num5,
+        "6" => Key::Num6,
+        "7" => Key::Num7,
+        "8" => Key::Num8,
+        "9" => Key::Num9,
+        "escape" | "esc" => Key::Escape,
+        _ => return None,
+    };
+    Some(key)
+}
+
 
 
 lazy_static! {
@@ -35,10 +86,12 @@ impl KeyboardHandler {
         *GLOBAL_SENDER.lock().unwrap() = Some(tx);
         
         let recorder = self.recorder.clone();
+        // Validated when the configuration is loaded
+        let cancel_key = parse_key(&crate::config::current().recording.cancel_key).unwrap_or(Key::KeyX);
         
         // Spawn thread for keyboard event listening
         std::thread::spawn(move || {
-            if let Err(error) = listen(Self::handle_event) {
+            if let Err(error) = listen(move |event| Self::handle_event(event, cancel_key)) {
                 println!("Error listening to keyboard events: {:?}", error);
             }
         });
@@ -50,12 +103,20 @@ impl KeyboardHandler {
                     Some(key_event) = rx.recv() => {
                         match key_event {
                             KeyEvent::StartRecord => {
-                                let result = {
+                                let (result, meter, session_id) = {
                                     let mut guard = recorder.lock().await;
-                                    guard.start_recording().await
+                                    let result = guard.start_recording().await;
+                                    (result, guard.level_meter(), guard.current_session_id())
                                 };
-                                if let Err(e) = result {
-                                    println!("Failed to start recording: {:?}", e);
+                                match result {
+                                    Ok(()) => {
+                                        tokio::spawn(crate::audio::run_level_meter_display(meter));
+                                        if let Some(session_id) = session_id {
+                                            crate::audio::spawn_max_duration_guard(recorder.clone(), session_id);
+                                        }
+                                    }
+                                    Err(e) if e.is::<crate::audio::AlreadyRecording>() => println!("{}", e),
+                                    Err(e) => println!("Failed to start recording: {:?}", e),
                                 }
                             }
                             KeyEvent::EndRecord => {
@@ -67,6 +128,17 @@ impl KeyboardHandler {
                                     println!("Failed to stop recording: {:?}", e);
                                 }
                             }
+                            KeyEvent::CancelRecord => {
+                                let result = {
+                                    let mut guard = recorder.lock().await;
+                                    guard.cancel_recording().await
+                                };
+                                match result {
+                                    Ok(true) => {}
+                                    Ok(false) => println!("No recording in progress; nothing to discard"),
+                                    Err(e) => println!("Failed to discard recording: {:?}", e),
+                                }
+                            }
                             KeyEvent::Quit => {
                                 println!("Quit event received. Exiting.");
                                 break;
@@ -82,9 +154,10 @@ impl KeyboardHandler {
         })
     }
 
-    fn handle_event(event: Event) {
+    fn handle_event(event: Event, cancel_key: Key) {
         let key_event_option = match event.event_type {
             EventType::KeyPress(key) => match key {
+                key if key == cancel_key => Some(KeyEvent::CancelRecord),
                 Key::KeyR => Some(KeyEvent::StartRecord),
                 Key::KeyE => Some(KeyEvent::EndRecord),
                 Key::KeyQ => Some(KeyEvent::Quit),
@@ -0,0 +1,53 @@
+// src/logging.rs
+//! Logging initialization
+//!
+//! Installs a `tracing` subscriber configured from `LoggingConfig`. Records
+//! emitted through the `log` crate are forwarded to the same subscriber, so the
+//! CLI commands and the web server share one output format.
+
+use anyhow::{Context, Result};
+use std::fs::OpenOptions;
+use std::sync::Arc;
+use tracing_subscriber::fmt::writer::{BoxMakeWriter, MakeWriterExt};
+use tracing_subscriber::EnvFilter;
+
+use crate::config::LoggingConfig;
+
+/// Initialize the global logger
+///
+/// The level comes from `RUST_LOG` if set, then `level` (the CLI's
+/// `--verbose`/`--quiet`), then `logging.level`. When `logging.format` is
+/// `json` each record is written as a single JSON line. Output goes to stderr
+/// and is also appended to `logging.file_path` if set.
+pub fn init(config: &LoggingConfig, level: Option<&str>) -> Result<()> {
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(level.unwrap_or(&config.level).to_lowercase()));
+
+    let writer = match &config.file_path {
+        Some(path) => {
+            if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                std::fs::create_dir_all(parent)
+                    .context(format!("Failed to create log directory: {}", parent.display()))?;
+            }
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .context(format!("Failed to open log file: {}", path.display()))?;
+            BoxMakeWriter::new(std::io::stderr.and(Arc::new(file)))
+        }
+        None => BoxMakeWriter::new(std::io::stderr),
+    };
+
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(writer)
+        .with_ansi(config.file_path.is_none());
+
+    let result = match config.format.to_lowercase().as_str() {
+        "json" => builder.json().try_init(),
+        _ => builder.try_init(),
+    };
+
+    result.map_err(|e| anyhow::anyhow!("Failed to initialize logging: {}", e))
+}
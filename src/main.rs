@@ -8,39 +8,119 @@
 use std::sync::Arc;
 
 use anyhow::Result;
-use clap::{Parser, Subcommand};
-use log::{info, warn/*, error*/};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use clap::{ArgAction, CommandFactory, FromArgMatches, Parser, Subcommand};
+use log::info;
 
 mod ai;
 mod audio;
 mod config;
 mod keyboard;
+mod logging;
+mod render;
 mod storage;
 mod web;
 mod ollama;
 mod backfill;
+mod batch;
+mod waveform;
+mod normalize;
+mod uploads;
+mod etag;
+mod openai;
+mod redact;
+mod webhook;
+mod reanalyze;
+mod response;
+mod disposition;
+mod probe;
+mod clip;
+mod analysis_lock;
+mod models;
+mod summary;
+#[cfg(feature = "database")]
+mod repository;
+#[cfg(feature = "database")]
+mod services {
+    pub mod migration;
+}
 
 #[derive(Parser)]
 #[command(name = "voice-recorder")]
 #[command(about = "A voice recording and AI analysis tool")]
 struct Cli {
+    /// Keep sessions, audio and transcripts in this directory instead of the
+    /// configured one (also STORAGE_DIR)
+    #[arg(long, global = true)]
+    storage_dir: Option<std::path::PathBuf>,
+    /// Log more: -v for debug, -vv for trace (RUST_LOG takes precedence)
+    #[arg(short, long, global = true, action = ArgAction::Count, conflicts_with = "quiet")]
+    verbose: u8,
+    /// Only log warnings and errors (RUST_LOG takes precedence)
+    #[arg(short, long, global = true)]
+    quiet: bool,
+    /// Print a failure as `{"error": ..., "command": ...}` JSON on stderr
+    /// instead of the usual error message, for tools wrapping the CLI
+    #[arg(long, global = true)]
+    json_errors: bool,
     #[command(subcommand)]
     command: Commands,
 }
 
+impl Cli {
+    /// Log level requested with --verbose or --quiet, if any
+    fn log_level(&self) -> Option<&'static str> {
+        match (self.quiet, self.verbose) {
+            (true, _) => Some("warn"),
+            (false, 0) => None,
+            (false, 1) => Some("debug"),
+            (false, _) => Some("trace"),
+        }
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Start the voice recorder application
     Start,
-    /// Transcribe an audio file
+    /// Transcribe an audio file, or every audio file in a directory
     Transcribe { 
+        #[arg(short, long, required_unless_present = "dir", conflicts_with = "dir")]
+        file: Option<String>,
+        /// Transcribe every supported audio file in this directory into new sessions
         #[arg(short, long)]
-        file: String,
+        dir: Option<String>,
+        /// Number of files to transcribe concurrently with --dir
+        #[arg(short, long, default_value = "2", requires = "dir")]
+        jobs: usize,
+        /// Write the transcript to this file instead of logging it
+        #[arg(short, long, conflicts_with = "dir")]
+        output: Option<String>,
     },
     /// Analyze a transcript
     Analyze { 
         #[arg(short, long)]
         file: String,
+        /// Write the analysis to this file instead of logging it
+        #[arg(short, long)]
+        output: Option<String>,
+        /// Output format for --output: json or md
+        #[arg(long, default_value = "json")]
+        format: String,
+        /// What to extract: full, summary, ideas, tasks or structured (Ollama only);
+        /// repeat to combine several, only their sections are printed
+        #[arg(long = "type", default_value = "full")]
+        analysis_types: Vec<ollama::AnalysisType>,
+        /// Write the analysis in this language, e.g. English, instead of the transcript's
+        #[arg(long)]
+        output_language: Option<String>,
+        /// Seconds to wait for the model instead of analysis.timeout_secs
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+        timeout: Option<u64>,
+        /// Ollama generation options over ollama.options, e.g. temperature=0.8,top_p=0.9
+        /// (temperature, top_p, top_k, repeat_penalty, seed)
+        #[arg(long, value_name = "KEY=VALUE,...")]
+        options: Option<ollama::ModelOptions>,
     },
     /// Play an audio file
     Play { 
@@ -48,11 +128,40 @@ enum Commands {
         file: String,
     },
     /// List all recorded sessions
-    List,
+    List {
+        /// Print the sessions as JSON on stdout (same as --format json)
+        #[arg(long)]
+        json: bool,
+        /// Print the sessions on stdout as json or csv (id, title, timestamp, duration_ms,
+        /// has_transcript, has_analysis)
+        #[arg(long, conflicts_with = "json")]
+        format: Option<String>,
+        /// Only list sessions in this state (recorded, transcribed, analyzed)
+        #[arg(short, long)]
+        status: Option<storage::SessionStatus>,
+        /// Only list sessions created on or after this date (YYYY-MM-DD, local time, or RFC 3339)
+        #[arg(long, value_parser = parse_cli_date)]
+        since: Option<DateTime<Utc>>,
+        /// Only list sessions created before this date (YYYY-MM-DD, local time, or RFC 3339)
+        #[arg(long, value_parser = parse_cli_date)]
+        until: Option<DateTime<Utc>>,
+        /// Maximum number of sessions to list
+        #[arg(short, long)]
+        limit: Option<usize>,
+        /// Field to sort by: created_at, title or duration
+        #[arg(long, default_value = "created_at")]
+        sort_by: storage::SessionSortField,
+        /// Sort direction: asc or desc
+        #[arg(long, default_value = "desc")]
+        sort_order: storage::SortOrder,
+    },
     /// Show details of a specific session
     Show { 
         #[arg(short, long)]
         id: String,
+        /// Output format: text, json or md
+        #[arg(short, long, default_value = "text")]
+        format: String,
     },
     /// Delete a specific session
     Delete { 
@@ -65,9 +174,15 @@ enum Commands {
         id: String,
         #[arg(short, long)]
         format: String,
+        /// Mask emails, phone numbers and card numbers; defaults to redaction.enabled
+        #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+        redact: Option<bool>,
     },
     /// Configure the application
-    Config,
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommands,
+    },
     /// Test Ollama analysis with a specific session
     TestOllama { 
         #[arg(short, long)]
@@ -80,27 +195,102 @@ enum Commands {
     },
     /// Backfill missing transcripts and analysis for all sessions
     Backfill,
+    /// Import the sessions of the file store, with their audio, transcript and analysis,
+    /// into the database at database.url; sessions imported before are skipped
+    #[cfg(feature = "database")]
+    Migrate,
+    /// Analyze sessions whose analysis was deferred, because the provider was unreachable
+    /// or analysis.auto_analyze is off
+    RetryAnalysis,
+    /// Analyze every session with a transcript again, keeping earlier analyses as history
+    Reanalyze {
+        /// Analysis provider: ollama or openai
+        #[arg(long)]
+        provider: String,
+        /// Model to use instead of the provider's configured model
+        #[arg(long)]
+        model: Option<String>,
+        /// Only re-analyze sessions created on or after this date (YYYY-MM-DD, local time, or RFC 3339)
+        #[arg(long, value_parser = parse_cli_date)]
+        since: Option<DateTime<Utc>>,
+        /// Number of sessions to analyze concurrently
+        #[arg(short, long, default_value = "2")]
+        jobs: usize,
+        /// Seconds to wait for the model per session instead of analysis.timeout_secs
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+        timeout: Option<u64>,
+    },
+    /// Write a 16kHz mono, loudness-normalized copy of a session's audio
+    Normalize {
+        #[arg(short, long)]
+        id: String,
+    },
+    /// List the models of each provider, marking the configured defaults
+    Models {
+        /// Only list the models of this provider: ollama or openai
+        #[arg(long)]
+        provider: Option<String>,
+    },
 }
 
-// #[derive(Subcommand)]
-// enum ConfigCommands {
-//     /// Set OpenAI API key
-//     SetOpenai { api_key: String },
-//     /// Set local model endpoint
-//     SetLocal { endpoint: String },
-//     /// Show current configuration
-//     Show,
-// }
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Write a default config file
+    Init {
+        /// Path of the config file to create
+        #[arg(short, long, default_value = "config.toml")]
+        path: String,
+        /// Overwrite the file if it already exists
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Show the effective configuration (file + environment) with secrets redacted
+    Show,
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    env_logger::Builder::new()
-        .filter_level(log::LevelFilter::Info)
-        .init();
+    // Parsed first so --verbose/--quiet apply to everything that is logged
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+    match run(&cli).await {
+        Err(e) if cli.json_errors => {
+            let error = serde_json::json!({
+                "error": format!("{:#}", e),
+                "command": matches.subcommand_name(),
+            });
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+        result => result,
+    }
+}
+
+async fn run(cli: &Cli) -> Result<()> {
+    let app_config = match config::Config::load() {
+        Ok(app_config) => app_config,
+        // A broken config file must not keep `config init --force` from replacing it
+        Err(e) if matches!(cli.command, Commands::Config { action: ConfigCommands::Init { .. } }) => {
+            eprintln!("Failed to load configuration, using defaults: {:#}", e);
+            config::Config::default()
+        }
+        Err(e) => return Err(e.context("Failed to load configuration")),
+    };
+    logging::init(&app_config.logging, cli.log_level())?;
+    config::init(app_config);
 
     info!("Starting voice-recorder application...");
 
-    let cli = Cli::parse();
+    // --storage-dir wins over STORAGE_DIR, which wins over the config files
+    let storage_dir = cli
+        .storage_dir
+        .clone()
+        .or_else(|| std::env::var_os("STORAGE_DIR").filter(|dir| !dir.is_empty()).map(Into::into));
+    if let Some(storage_dir) = storage_dir {
+        info!("Using storage directory {}", storage_dir.display());
+        config::set_storage_dir(storage_dir)?;
+    }
 
     match &cli.command {
         Commands::Start => {
@@ -109,76 +299,252 @@ async fn main() -> Result<()> {
             let mut keyboard_handler = keyboard::KeyboardHandler::new(recorder.clone());
             keyboard_handler.start_listening()?.await;
         }
-        Commands::Transcribe { file } => {
+        Commands::Transcribe { file: _, dir: Some(dir), jobs, .. } => {
+            info!("Transcribing audio files in: {}", dir);
+            let summary = batch::transcribe_directory(std::path::Path::new(dir), *jobs).await?;
+            batch::log_summary(&summary);
+            if summary.failed() > 0 {
+                anyhow::bail!("{} of {} files failed to transcribe", summary.failed(), summary.results.len());
+            }
+        }
+        Commands::Transcribe { file: Some(file), output, .. } => {
             info!("Transcribing file: {}", file);
             let audio_path = std::path::PathBuf::from(file);
             let transcript = ai::transcribe_audio(&audio_path).await?;
-            info!("Transcript: {}", transcript);
+            match output {
+                Some(path) => {
+                    write_output(path, &transcript).await?;
+                    info!("Transcript written to {}", path);
+                }
+                None => info!("Transcript: {}", transcript),
+            }
         }
-        Commands::Analyze { file } => {
-            info!("Analyzing file: {}", file);
+        Commands::Transcribe { file: None, dir: None, .. } => {
+            anyhow::bail!("Either --file or --dir is required");
+        }
+        Commands::Analyze { file, output, format, analysis_types, output_language, timeout, options } => {
+            let type_names: Vec<&str> = analysis_types.iter().map(|t| t.as_str()).collect();
+            info!("Analyzing file: {} ({} analysis)", file, type_names.join(", "));
+            if !matches!(format.as_str(), "json" | "md") {
+                anyhow::bail!("Unsupported format: {}. Supported formats: json, md", format);
+            }
             let transcript = tokio::fs::read_to_string(file).await?;
-            let analysis = ai::analyze_transcript(&transcript).await?;
-            info!("Analysis: {:#?}", analysis);
+            let analysis = ai::analyze_transcript_sections(
+                &transcript,
+                analysis_types,
+                output_language.as_deref(),
+                *timeout,
+                *options,
+            )
+            .await?;
+            let content = match format.as_str() {
+                "md" => render::analysis_sections_to_markdown(&analysis, analysis_types),
+                _ => serde_json::to_string_pretty(&render::analysis_sections_to_json(&analysis, analysis_types)?)?,
+            };
+            match output {
+                Some(path) => {
+                    write_output(path, &content).await?;
+                    info!("Analysis written to {}", path);
+                }
+                None => info!("Analysis:\n{}", content),
+            }
         }
         Commands::Play { file } => {
             info!("Playing file: {}", file);
             audio::VoiceRecorder::new().await?.play_audio_file(file).await?;
         }
-        Commands::List => {
-            info!("Listing sessions...");
-            let sessions = storage::list_sessions().await?;
-            for session in sessions {
-                info!("Session ID: {}, Title: {}, Created: {}", session.id, session.title, session.timestamp);
+        Commands::List { json, format, status, since, until, limit, sort_by, sort_order } => {
+            let format = if *json { Some("json") } else { format.as_deref() };
+            if let Some(format) = format.filter(|f| !matches!(*f, "json" | "csv")) {
+                anyhow::bail!("Unsupported format: {}. Supported formats: json, csv", format);
             }
-        }
-        Commands::Show { id } => {
-            info!("Showing session: {}", id);
-            if let Some(session) = storage::get_session(&id).await? {
-                info!("Session: {:#?}", session);
-            } else {
-                warn!("Session with ID {} not found.", id);
+            if let (Some(since), Some(until)) = (since, until) {
+                if since >= until {
+                    anyhow::bail!("--since ({}) must be earlier than --until ({})", since, until);
+                }
+            }
+            let filter = storage::SessionFilter {
+                status: *status,
+                created_after: *since,
+                created_before: *until,
+                sort: storage::SessionSort {
+                    field: *sort_by,
+                    order: *sort_order,
+                },
+                limit: *limit,
+                ..Default::default()
+            };
+            let sessions = storage::list_sessions_filtered(&filter).await?;
+            match format {
+                Some("json") => println!("{}", serde_json::to_string_pretty(&sessions)?),
+                Some(_) => print!("{}", render::sessions_to_csv(&sessions)?),
+                None => {
+                    info!("Listing sessions...");
+                    for session in sessions {
+                        info!("Session ID: {}, Title: {}, Created: {}", session.id, session.title, session.timestamp);
+                    }
+                }
             }
         }
+        Commands::Show { id, format } => {
+            let session = storage::get_session(id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Session with ID {} not found.", id))?;
+            let output = match format.as_str() {
+                "text" => render::session_to_text(&session),
+                "json" => serde_json::to_string_pretty(&session)?,
+                "md" => render::session_to_markdown(&session),
+                _ => anyhow::bail!("Unsupported format: {}. Supported formats: text, json, md", format),
+            };
+            println!("{}", output);
+        }
         Commands::Delete { id } => {
             info!("Deleting session: {}", id);
-            storage::delete_session(&id).await?;
+            if storage::get_session(id).await?.is_none() {
+                anyhow::bail!("Session with ID {} not found.", id);
+            }
+            storage::delete_session(id).await?;
             info!("Session {} deleted.", id);
         }
-        Commands::Export { id, format } => {
+        Commands::Export { id, format, redact } => {
             info!("Exporting session {} in format {}", id, format);
-            // Implement export logic here
-            warn!("Export functionality not yet implemented.");
-        }
-        Commands::Config => {
-            info!("Opening config file...");
-            // Implement config opening logic here
-            warn!("Config functionality not yet implemented.");
+            let mut session = storage::get_session(id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Session with ID {} not found.", id))?;
+            if redact::requested(*redact) {
+                session = redact::Redactor::from_config()?.redact_session(&session);
+            }
+            let output = match format.as_str() {
+                "json" => serde_json::to_string_pretty(&session)?,
+                "txt" => render::session_to_text(&session),
+                "md" => render::session_to_markdown(&session),
+                _ => anyhow::bail!("Unsupported export format: {}. Supported formats: json, txt, md", format),
+            };
+            println!("{}", output);
         }
-        Commands::TestOllama { id } => {
-            info!("Testing Ollama analysis for session: {}", id);
-            if let Some(session) = storage::get_session(&id).await? {
-                if let Some(transcript) = session.transcript {
-                    info!("Transcript found for session {}. Analyzing with Ollama...", id);
-                    let analysis = ai::analyze_transcript(&transcript).await?;
-                    info!("Ollama Analysis Result: {:#?}", analysis);
-                } else {
-                    warn!("No transcript found for session {}. Cannot perform Ollama analysis.", id);
+        Commands::Config { action } => match action {
+            ConfigCommands::Init { path, force } => {
+                if std::path::Path::new(path).exists() && !force {
+                    anyhow::bail!("Config file {} already exists. Use --force to overwrite it.", path);
                 }
-            } else {
-                warn!("Session with ID {} not found. Cannot perform Ollama analysis.", id);
+                config::Config::default().save_to_file(path)?;
+                info!("Wrote default configuration to {}", path);
+            }
+            ConfigCommands::Show => {
+                let effective = config::current().redacted();
+                println!("{}", toml::to_string_pretty(&effective)?);
             }
+        },
+        Commands::TestOllama { id } => {
+            info!("Testing Ollama analysis for session: {}", id);
+            let session = storage::get_session(id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Session with ID {} not found. Cannot perform Ollama analysis.", id))?;
+            let transcript = session
+                .transcript
+                .ok_or_else(|| anyhow::anyhow!("No transcript found for session {}. Cannot perform Ollama analysis.", id))?;
+            info!("Transcript found for session {}. Analyzing with Ollama...", id);
+            let analysis = ai::analyze_transcript(&transcript).await?;
+            info!("Ollama Analysis Result: {:#?}", analysis);
         }
         Commands::Web { port } => {
             info!("Starting web interface on port {}", port);
+            config::reload::spawn_sighup_handler(config::shared(), web::apply_config)?;
             let recorder = Arc::new(tokio::sync::Mutex::new(audio::VoiceRecorder::new().await?));
             web::start_server(*port, recorder).await?;
         }
+        Commands::Normalize { id } => {
+            info!("Normalizing audio for session: {}", id);
+            let mut session = storage::get_session(id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Session with ID {} not found.", id))?;
+            let normalization = normalize::normalize_session(&mut session).await?;
+            storage::save_session(&mut session, None).await?;
+            info!(
+                "Wrote {} ({}Hz, {} channel(s) -> {}Hz mono, {:+.1} dB gain{})",
+                normalization.path.display(),
+                normalization.source_sample_rate,
+                normalization.source_channels,
+                normalization.sample_rate,
+                normalization.gain_db,
+                if normalization.peak_limited { ", limited by peak" } else { "" }
+            );
+        }
         Commands::Backfill => {
             info!("Starting backfill process...");
             backfill::backfill_sessions().await?;
         }
+        #[cfg(feature = "database")]
+        Commands::Migrate => {
+            let pool = sqlx::PgPool::connect(config::current().database_url())
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to connect to database: {}", e))?;
+            let repositories = repository::PostgresRepositoryManager::new(pool);
+            let stats = services::migration::migrate_legacy_sessions(&repositories).await?;
+            if stats.failed > 0 {
+                anyhow::bail!("{} of {} sessions failed to import", stats.failed, stats.total);
+            }
+        }
+        Commands::RetryAnalysis => {
+            info!("Retrying pending analyses...");
+            backfill::retry_pending_analysis().await?;
+        }
+        Commands::Reanalyze { provider, model, since, jobs, timeout } => {
+            if !matches!(provider.to_lowercase().as_str(), "ollama" | "openai") {
+                anyhow::bail!("Unsupported provider: {}. Supported providers: ollama, openai", provider);
+            }
+            let options = reanalyze::ReanalyzeOptions {
+                provider: provider.clone(),
+                model: model.clone(),
+                since: *since,
+                jobs: *jobs,
+                timeout_secs: *timeout,
+            };
+            let summary = reanalyze::reanalyze_sessions(&options).await?;
+            reanalyze::log_summary(&summary);
+            if summary.failed() > 0 {
+                anyhow::bail!("{} of {} sessions failed to re-analyze", summary.failed(), summary.results.len());
+            }
+        }
+        Commands::Models { provider } => {
+            models::print_models(provider.as_deref()).await?;
+        }
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Write command output to `path`, creating parent directories as needed
+async fn write_output(path: &str, content: &str) -> Result<()> {
+    use anyhow::Context;
+
+    let path = std::path::Path::new(path);
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .with_context(|| format!("Failed to create output directory: {}", parent.display()))?;
+    }
+    tokio::fs::write(path, content)
+        .await
+        .with_context(|| format!("Failed to write output file: {}", path.display()))?;
+    Ok(())
+}
+
+/// Parse a `--since`/`--until` value: a date, taken as local midnight, or an
+/// RFC 3339 timestamp
+fn parse_cli_date(value: &str) -> std::result::Result<DateTime<Utc>, String> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+        format!(
+            "invalid date '{}': expected YYYY-MM-DD or an RFC 3339 timestamp such as 2024-01-01T09:00:00+08:00",
+            value
+        )
+    })?;
+    date.and_hms_opt(0, 0, 0)
+        .and_then(|midnight| midnight.and_local_timezone(Local).earliest())
+        .map(|midnight| midnight.with_timezone(&Utc))
+        .ok_or_else(|| format!("invalid date '{}': local midnight does not exist", value))
+}
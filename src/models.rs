@@ -0,0 +1,130 @@
+// src/models.rs
+//! Models that can be chosen with `--model`, per provider
+//!
+//! Ollama is asked which models are installed on the configured server.
+//! OpenAI models are a fixed list of the ones suited to transcription and
+//! analysis, since the API lists hundreds of unrelated models and needs a
+//! key to do so. The configured model of each provider is marked, and shown
+//! even when it is not installed or not in the list, e.g. on an
+//! OpenAI-compatible server.
+
+use anyhow::Result;
+
+use crate::config::{Config, OllamaConfig};
+use crate::ollama::{self, InstalledModel};
+
+/// Print the models of `provider` (`ollama` or `openai`), or of both
+///
+/// Fails if Ollama is unreachable only when it was asked for explicitly;
+/// otherwise that is printed and the OpenAI models are still listed.
+pub async fn print_models(provider: Option<&str>) -> Result<()> {
+    let config = crate::config::current();
+    let provider = provider.map(str::to_lowercase);
+    if let Some(provider) = provider.as_deref().filter(|p| !matches!(*p, "ollama" | "openai")) {
+        anyhow::bail!("Unsupported provider: {}. Supported providers: ollama, openai", provider);
+    }
+
+    if provider.as_deref().is_none_or(|p| p == "ollama") {
+        match ollama::list_models(&config.ollama).await {
+            Ok(models) => print!("{}", ollama_models_to_text(&config.ollama, &models)),
+            Err(e) if provider.is_some() => return Err(e),
+            Err(e) => println!("Ollama ({}):\n  unavailable: {}\n", config.ollama.base_url, e),
+        }
+    }
+    if provider.as_deref().is_none_or(|p| p == "openai") {
+        print!("{}", openai_models_to_text(&config));
+    }
+    Ok(())
+}
+
+fn ollama_models_to_text(ollama_config: &OllamaConfig, models: &[InstalledModel]) -> String {
+    let default_model = ollama::qualified_model_name(&ollama_config.default_model);
+    let embedding_model = ollama_config
+        .embedding_model
+        .as_deref()
+        .map(str::trim)
+        .filter(|model| !model.is_empty())
+        .map(ollama::qualified_model_name);
+    let mut out = format!("Ollama ({}):\n", ollama_config.base_url);
+    if models.is_empty() {
+        out.push_str("  no models installed; pull one with `ollama pull <model>`\n");
+    }
+
+    let width = models.iter().map(|model| model.name.chars().count()).max().unwrap_or(0);
+    for model in models {
+        // Parameter count and quantization, e.g. `8.0B Q4_0`, when Ollama reports them
+        let details: Vec<&str> = [&model.details.parameter_size, &model.details.quantization_level]
+            .into_iter()
+            .filter_map(|detail| detail.as_deref())
+            .collect();
+        let mut line = format!("  {:<width$}  {:>8}", model.name, format_size(model.size), width = width);
+        if !details.is_empty() {
+            line.push_str("  ");
+            line.push_str(&details.join(" "));
+        }
+
+        let mut marks = Vec::new();
+        if ollama::qualified_model_name(&model.name) == default_model {
+            marks.push("default");
+        }
+        if embedding_model.as_deref() == Some(ollama::qualified_model_name(&model.name).as_str()) {
+            marks.push("embedding");
+        }
+        out.push_str(&format!("{}{}\n", line, marks_suffix(&marks)));
+    }
+
+    if !models.iter().any(|model| ollama::qualified_model_name(&model.name) == default_model) {
+        out.push_str(&format!(
+            "  {} (default) is not installed; pull it with `ollama pull {}`\n",
+            ollama_config.default_model, ollama_config.default_model
+        ));
+    }
+    out.push('\n');
+    out
+}
+
+fn openai_models_to_text(config: &Config) -> String {
+    let mut out = String::from("OpenAI:\n");
+    for (purpose, known, default_model) in [
+        ("transcription", crate::openai::TRANSCRIPTION_MODELS, &config.openai.transcription_model),
+        ("analysis", crate::openai::ANALYSIS_MODELS, &config.openai.analysis_model),
+    ] {
+        out.push_str(&format!("  {}:\n", purpose));
+        for model in known {
+            let marks: &[&str] = if *model == default_model.as_str() { &["default"] } else { &[] };
+            out.push_str(&format!("    {}{}\n", model, marks_suffix(marks)));
+        }
+        if !known.contains(&default_model.as_str()) {
+            out.push_str(&format!("    {}{}\n", default_model, marks_suffix(&["default"])));
+        }
+    }
+    out
+}
+
+fn marks_suffix(marks: &[&str]) -> String {
+    if marks.is_empty() {
+        String::new()
+    } else {
+        format!("  ({})", marks.join(", "))
+    }
+}
+
+/// Size in bytes as e.g. `4.7 GB`, in decimal units as Ollama shows them
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1000 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1000.0;
+    let mut unit = 0;
+    while size >= 1000.0 && unit + 1 < UNITS.len() {
+        size /= 1000.0;
+        unit += 1;
+    }
+    // Whole units from 100 on, including sizes that round up to 100
+    if size >= 99.95 {
+        format!("{:.0} {}", size, UNITS[unit])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
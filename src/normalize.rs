@@ -0,0 +1,362 @@
+// src/normalize.rs
+//! Audio normalization ahead of transcription
+//!
+//! Whisper works on 16kHz mono audio and is noticeably less accurate on quiet
+//! recordings. Normalizing writes a copy of a session's WAV file next to the
+//! original, downmixed to mono, resampled to 16kHz and brought to a common
+//! loudness. The original recording is never modified.
+
+use anyhow::{bail, Context, Result};
+use log::{info, warn};
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+use std::path::{Path, PathBuf};
+
+use crate::storage::{NormalizationInfo, VoiceSession};
+
+/// Sample rate whisper models are trained on
+pub const TARGET_SAMPLE_RATE: u32 = 16_000;
+
+/// Loudness target, as the RMS level of non-silent blocks
+const TARGET_RMS_DBFS: f32 = -20.0;
+
+/// Highest peak level allowed after the gain is applied
+const PEAK_CEILING_DBFS: f32 = -1.0;
+
+/// Upper bound on the gain, so near-silent recordings don't turn into noise
+const MAX_GAIN_DB: f32 = 30.0;
+
+/// Length of the blocks loudness is measured over
+const BLOCK_MS: usize = 50;
+
+/// Blocks quieter than this are treated as silence and not measured
+const SILENCE_GATE_DBFS: f32 = -50.0;
+
+/// Zero crossings of the resampling filter on each side of a sample
+const SINC_HALF_TAPS: f64 = 16.0;
+
+/// Path of the normalized copy of `audio_path`
+pub fn normalized_path(audio_path: &Path) -> PathBuf {
+    let stem = audio_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    audio_path.with_file_name(format!("{}.normalized.wav", stem))
+}
+
+/// Normalize a session's audio, recording the result on the session
+///
+/// The caller is responsible for saving the session.
+pub async fn normalize_session(session: &mut VoiceSession) -> Result<NormalizationInfo> {
+    let input = session.audio_file_path.clone();
+    let output = normalized_path(&input);
+    let info = tokio::task::spawn_blocking(move || normalize_file(&input, &output))
+        .await
+        .context("Normalization task panicked")??;
+
+    info!(
+        "[{}] Normalized audio to {} ({}Hz, {:+.1} dB gain)",
+        session.id,
+        info.path.display(),
+        info.sample_rate,
+        info.gain_db
+    );
+    session.normalization = Some(info.clone());
+    Ok(info)
+}
+
+/// The audio file to transcribe for `session`
+///
+/// With `transcription.normalize_audio` enabled, a normalized copy is made
+/// the first time and reused afterwards. Transcription falls back to the
+/// original recording when normalization fails.
+pub async fn transcription_input(session: &mut VoiceSession) -> PathBuf {
+    if !crate::config::current().transcription.normalize_audio {
+        return session.audio_file_path.clone();
+    }
+
+    if let Some(normalization) = &session.normalization {
+        if normalization.path.exists() {
+            return normalization.path.clone();
+        }
+    }
+
+    match normalize_session(session).await {
+        Ok(info) => info.path,
+        Err(e) => {
+            warn!("[{}] Failed to normalize audio, transcribing the original: {:#}", session.id, e);
+            session.audio_file_path.clone()
+        }
+    }
+}
+
+/// Write a 16kHz mono, loudness-normalized copy of the WAV file `input` to `output`
+///
+/// The audio is streamed twice, once to measure its level and once to write
+/// it with the gain applied, so memory use doesn't grow with its length.
+pub fn normalize_file(input: &Path, output: &Path) -> Result<NormalizationInfo> {
+    let mut meter = LoudnessMeter::new(TARGET_SAMPLE_RATE);
+    let spec = for_each_resampled(input, |sample| {
+        meter.add(sample);
+        Ok(())
+    })?;
+    let (source_rms_dbfs, peak) = meter.finish();
+
+    let mut gain_db = source_rms_dbfs
+        .map(|rms| (TARGET_RMS_DBFS - rms).clamp(-MAX_GAIN_DB, MAX_GAIN_DB))
+        .unwrap_or(0.0);
+    let mut peak_limited = false;
+    if peak > 0.0 {
+        let peak_headroom_db = PEAK_CEILING_DBFS - to_dbfs(peak);
+        if gain_db > peak_headroom_db {
+            gain_db = peak_headroom_db;
+            peak_limited = true;
+        }
+    }
+
+    let gain = 10f32.powf(gain_db / 20.0);
+    let out_spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: TARGET_SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(output, out_spec)
+        .with_context(|| format!("Failed to create {}", output.display()))?;
+    for_each_resampled(input, |sample| {
+        let value = (sample * gain).clamp(-1.0, 1.0) * i16::MAX as f32;
+        writer
+            .write_sample(value.round() as i16)
+            .context("Failed to write normalized audio")
+    })?;
+    writer.finalize().context("Failed to finalize normalized audio")?;
+
+    Ok(NormalizationInfo {
+        path: output.to_path_buf(),
+        source_sample_rate: spec.sample_rate,
+        source_channels: spec.channels,
+        sample_rate: TARGET_SAMPLE_RATE,
+        gain_db,
+        target_rms_dbfs: TARGET_RMS_DBFS,
+        source_rms_dbfs,
+        peak_limited,
+    })
+}
+
+/// Decode the WAV file `input`, downmix it to mono and resample it to
+/// [`TARGET_SAMPLE_RATE`], passing each output sample to `emit` in order
+fn for_each_resampled(input: &Path, mut emit: impl FnMut(f32) -> Result<()>) -> Result<hound::WavSpec> {
+    let mut reader = hound::WavReader::open(input)
+        .with_context(|| format!("Failed to open WAV file: {}", input.display()))?;
+    let spec = reader.spec();
+    if spec.sample_rate == 0 {
+        bail!("Invalid sample rate in {}", input.display());
+    }
+    let channels = spec.channels.max(1) as usize;
+
+    let samples: Box<dyn Iterator<Item = hound::Result<f32>> + '_> = match spec.sample_format {
+        hound::SampleFormat::Float => Box::new(reader.samples::<f32>()),
+        hound::SampleFormat::Int => {
+            if spec.bits_per_sample == 0 || spec.bits_per_sample > 32 {
+                bail!("Unsupported bit depth: {}", spec.bits_per_sample);
+            }
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            Box::new(reader.samples::<i32>().map(move |s| s.map(|s| s as f32 / scale)))
+        }
+    };
+
+    let mut resampler = Resampler::new(spec.sample_rate, TARGET_SAMPLE_RATE);
+    let mut frame = Vec::with_capacity(channels);
+    for sample in samples {
+        frame.push(sample.context("Failed to decode WAV samples")?);
+        if frame.len() == channels {
+            resampler.push(frame.iter().sum::<f32>() / channels as f32, &mut emit)?;
+            frame.clear();
+        }
+    }
+    // A truncated last frame is averaged over the channels it has
+    if !frame.is_empty() {
+        resampler.push(frame.iter().sum::<f32>() / frame.len() as f32, &mut emit)?;
+    }
+    resampler.finish(&mut emit)?;
+    Ok(spec)
+}
+
+/// Streaming resampler with a Hann-windowed sinc filter
+///
+/// When downsampling the filter cutoff follows the output Nyquist frequency,
+/// so content above it is removed rather than aliased into the speech band.
+/// Only the input samples within the filter's reach are kept.
+struct Resampler {
+    ratio: f64,
+    cutoff: f64,
+    half_width: f64,
+    /// Input samples still in reach, starting at input index `offset`
+    window: VecDeque<f32>,
+    offset: usize,
+    /// Number of input samples pushed so far
+    pushed: usize,
+    /// Index of the next output sample
+    next: usize,
+}
+
+impl Resampler {
+    fn new(from: u32, to: u32) -> Self {
+        let ratio = to as f64 / from as f64;
+        let cutoff = ratio.min(1.0);
+        Self {
+            ratio,
+            cutoff,
+            half_width: SINC_HALF_TAPS / cutoff,
+            window: VecDeque::new(),
+            offset: 0,
+            pushed: 0,
+            next: 0,
+        }
+    }
+
+    /// Add the next input sample, emitting the output samples it completes
+    fn push(&mut self, sample: f32, emit: &mut impl FnMut(f32) -> Result<()>) -> Result<()> {
+        if self.ratio == 1.0 {
+            return emit(sample);
+        }
+
+        self.window.push_back(sample);
+        self.pushed += 1;
+        loop {
+            let center = self.next as f64 / self.ratio;
+            let last = (center + self.half_width).floor() as usize;
+            if last >= self.pushed {
+                return Ok(());
+            }
+            emit(self.output(center, last))?;
+            self.advance();
+        }
+    }
+
+    /// Emit the output samples that remain after the last input sample
+    fn finish(mut self, emit: &mut impl FnMut(f32) -> Result<()>) -> Result<()> {
+        if self.ratio == 1.0 || self.pushed == 0 {
+            return Ok(());
+        }
+
+        let output_len = (self.pushed as f64 * self.ratio).round() as usize;
+        while self.next < output_len {
+            let center = self.next as f64 / self.ratio;
+            let last = ((center + self.half_width).floor() as usize).min(self.pushed - 1);
+            emit(self.output(center, last))?;
+            self.advance();
+        }
+        Ok(())
+    }
+
+    /// Output sample centered on input position `center`, from the input
+    /// samples up to index `last`
+    fn output(&self, center: f64, last: usize) -> f32 {
+        let first = self.first_in_reach(center);
+        let mut acc = 0.0;
+        let mut weight_sum = 0.0;
+        for j in first..=last {
+            let x = center - j as f64;
+            let weight = sinc(x * self.cutoff) * hann(x / self.half_width);
+            acc += self.window[j - self.offset] as f64 * weight;
+            weight_sum += weight;
+        }
+        if weight_sum.abs() > f64::EPSILON {
+            (acc / weight_sum) as f32
+        } else {
+            0.0
+        }
+    }
+
+    /// Move on to the next output sample, dropping the input samples
+    /// that are out of its reach
+    fn advance(&mut self) {
+        self.next += 1;
+        let first = self.first_in_reach(self.next as f64 / self.ratio);
+        while self.offset < first && !self.window.is_empty() {
+            self.window.pop_front();
+            self.offset += 1;
+        }
+    }
+
+    fn first_in_reach(&self, center: f64) -> usize {
+        (center - self.half_width).ceil().max(0.0) as usize
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-9 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+/// Hann window over `-1.0..=1.0`
+fn hann(t: f64) -> f64 {
+    if t.abs() >= 1.0 {
+        0.0
+    } else {
+        0.5 * (1.0 + (PI * t).cos())
+    }
+}
+
+/// Peak and gated RMS level of audio fed to it sample by sample
+///
+/// The RMS level is taken over the blocks above the silence gate.
+struct LoudnessMeter {
+    block_len: usize,
+    /// Sum of squares and length of the block being filled
+    block_energy: f64,
+    block_samples: usize,
+    /// Sum of the mean squares of the blocks above the gate, and their number
+    energy: f64,
+    blocks: usize,
+    peak: f32,
+}
+
+impl LoudnessMeter {
+    fn new(sample_rate: u32) -> Self {
+        Self {
+            block_len: (sample_rate as usize * BLOCK_MS / 1000).max(1),
+            block_energy: 0.0,
+            block_samples: 0,
+            energy: 0.0,
+            blocks: 0,
+            peak: 0.0,
+        }
+    }
+
+    fn add(&mut self, sample: f32) {
+        self.peak = self.peak.max(sample.abs());
+        self.block_energy += (sample as f64).powi(2);
+        self.block_samples += 1;
+        if self.block_samples == self.block_len {
+            self.end_block();
+        }
+    }
+
+    fn end_block(&mut self) {
+        let mean_square = self.block_energy / self.block_samples as f64;
+        if to_dbfs(mean_square.sqrt() as f32) > SILENCE_GATE_DBFS {
+            self.energy += mean_square;
+            self.blocks += 1;
+        }
+        self.block_energy = 0.0;
+        self.block_samples = 0;
+    }
+
+    /// RMS level in dBFS, or `None` if all blocks are silent, and peak amplitude
+    fn finish(mut self) -> (Option<f32>, f32) {
+        if self.block_samples > 0 {
+            self.end_block();
+        }
+        let rms_dbfs = (self.blocks > 0).then(|| to_dbfs((self.energy / self.blocks as f64).sqrt() as f32));
+        (rms_dbfs, self.peak)
+    }
+}
+
+fn to_dbfs(amplitude: f32) -> f32 {
+    20.0 * amplitude.max(1e-9).log10()
+}
@@ -1,13 +1,169 @@
 use anyhow::{Context, Result};
-use log::info;
+use log::{info, warn};
 use reqwest::Client;
 use regex;
 use serde_json::{json, Value};
+use std::time::{Duration, Instant};
 
+use crate::config::{LanguageDetectionConfig, OllamaConfig, TRANSCRIPT_PLACEHOLDER};
 use crate::storage::AnalysisResult;
 
-// 检测文本主要语言 (复用原有函数)
-pub fn detect_language_v2(text: &str) -> &'static str {
+/// 分析类型：完整分析或只提取其中一部分
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AnalysisType {
+    /// 标题、摘要、观点、任务和结构化笔记
+    #[default]
+    Full,
+    /// 只生成标题和摘要
+    Summary,
+    /// 只提取观点
+    Ideas,
+    /// 只提取任务
+    Tasks,
+    /// 只生成结构化笔记
+    StructuredNotes,
+}
+
+impl AnalysisType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnalysisType::Full => "full",
+            AnalysisType::Summary => "summary",
+            AnalysisType::Ideas => "ideas",
+            AnalysisType::Tasks => "tasks",
+            AnalysisType::StructuredNotes => "structured",
+        }
+    }
+}
+
+impl std::str::FromStr for AnalysisType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "full" | "all" => Ok(AnalysisType::Full),
+            "summary" => Ok(AnalysisType::Summary),
+            "ideas" => Ok(AnalysisType::Ideas),
+            "tasks" => Ok(AnalysisType::Tasks),
+            "structured" | "structured_notes" | "notes" => Ok(AnalysisType::StructuredNotes),
+            other => Err(format!(
+                "Unknown analysis type: {}. Expected one of: full, summary, ideas, tasks, structured",
+                other
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for AnalysisType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 未设置生成参数时使用的温度，较低的温度使输出更确定，JSON 更稳定
+const DEFAULT_TEMPERATURE: f64 = 0.1;
+
+/// 随分析请求发送的 Ollama 生成参数，合并到请求的 `options` 中
+///
+/// 未设置的参数使用 `ollama.options`，其次是本程序或 Ollama 的默认值。
+/// `num_predict` 由 `ollama.num_predict` 控制，不能在这里设置。
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModelOptions {
+    /// 0 到 2，越高越有创造性，默认 0.1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    /// 0 到 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    /// 至少为 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u32>,
+    /// 大于 0，大于 1 时减少重复
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeat_penalty: Option<f64>,
+    /// 固定随机种子，使相同输入得到相同输出
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i64>,
+}
+
+impl ModelOptions {
+    /// 本请求未设置的参数取 `defaults` 中的值
+    pub fn or(self, defaults: ModelOptions) -> ModelOptions {
+        ModelOptions {
+            temperature: self.temperature.or(defaults.temperature),
+            top_p: self.top_p.or(defaults.top_p),
+            top_k: self.top_k.or(defaults.top_k),
+            repeat_penalty: self.repeat_penalty.or(defaults.repeat_penalty),
+            seed: self.seed.or(defaults.seed),
+        }
+    }
+
+    /// 检查各参数的取值范围
+    pub fn validate(&self) -> Result<(), String> {
+        if self.temperature.is_some_and(|temperature| !(0.0..=2.0).contains(&temperature)) {
+            return Err("temperature must be between 0 and 2".to_string());
+        }
+        if self.top_p.is_some_and(|top_p| !(0.0..=1.0).contains(&top_p)) {
+            return Err("top_p must be between 0 and 1".to_string());
+        }
+        if self.top_k == Some(0) {
+            return Err("top_k must be at least 1".to_string());
+        }
+        if self.repeat_penalty.is_some_and(|penalty| penalty.is_nan() || penalty <= 0.0) {
+            return Err("repeat_penalty must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+
+    /// 请求正文中的 `options`：默认温度，覆盖为已设置的参数，再加上 `num_predict`
+    fn to_request_options(self, num_predict: u32) -> Value {
+        let defaults = ModelOptions { temperature: Some(DEFAULT_TEMPERATURE), ..Default::default() };
+        let mut options = serde_json::to_value(self.or(defaults)).unwrap_or_else(|_| json!({}));
+        options["num_predict"] = json!(num_predict); // 输出过长时会被截断，见 ollama.num_predict
+        options
+    }
+}
+
+/// 命令行中的 `temperature=0.8,top_p=0.9`
+impl std::str::FromStr for ModelOptions {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = serde_json::Map::new();
+        for pair in s.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid option: {}. Expected KEY=VALUE, e.g. temperature=0.8", pair))?;
+            let value: Value = serde_json::from_str(value.trim())
+                .map_err(|_| format!("Invalid value for {}: {}. Expected a number", key.trim(), value.trim()))?;
+            fields.insert(key.trim().to_string(), value);
+        }
+        let options: ModelOptions = serde_json::from_value(Value::Object(fields)).map_err(|e| e.to_string())?;
+        options.validate()?;
+        Ok(options)
+    }
+}
+
+/// 转录文本的语言检测结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LanguageDetection {
+    /// "zh"、"en" 或 "mixed"
+    pub language: &'static str,
+    /// 中文字符占非空白字符的比例
+    pub cjk_ratio: f64,
+    /// 属于检测出的语言的字符占比：中文为中文字符占比，英文为其余字符占比，
+    /// 中英混杂为中文字符与英文字母占比之和；空文本为 0
+    pub confidence: f64,
+}
+
+// 检测文本主要语言 (复用原有函数)，返回 "zh"、"en" 或 "mixed"
+pub fn detect_language_v2(text: &str, detection: &LanguageDetectionConfig) -> &'static str {
+    detect_language_with_confidence(text, detection).language
+}
+
+// 检测文本主要语言，同时给出中文字符占比和置信度，便于调用方判断是否可信
+pub fn detect_language_with_confidence(text: &str, detection: &LanguageDetectionConfig) -> LanguageDetection {
     let chinese_chars = text.chars().filter(|c| {
         let code = *c as u32;
         // 中文字符范围：基本汉字、扩展A、扩展B等
@@ -24,14 +180,40 @@ pub fn detect_language_v2(text: &str) -> &'static str {
     let total_chars = text.chars().filter(|c| !c.is_whitespace()).count();
     
     if total_chars == 0 {
-        return "en"; // 默认英文
+        // 默认英文
+        return LanguageDetection { language: "en", cjk_ratio: 0.0, confidence: 0.0 };
     }
     
-    // 如果中文字符占比超过30%，认为是中文
-    if chinese_chars as f64 / total_chars as f64 > 0.3 {
-        "zh"
+    let chinese_ratio = chinese_chars as f64 / total_chars as f64;
+    
+    // 中英混杂：中文和英文字母的占比都达到混合阈值时使用双语 prompt
+    if let Some(mixed_threshold) = detection.mixed_threshold {
+        let latin_chars = text.chars().filter(|c| c.is_ascii_alphabetic()).count();
+        let latin_ratio = latin_chars as f64 / total_chars as f64;
+        if chinese_ratio >= mixed_threshold && latin_ratio >= mixed_threshold {
+            return LanguageDetection {
+                language: "mixed",
+                cjk_ratio: chinese_ratio,
+                confidence: (chinese_ratio + latin_ratio).min(1.0),
+            };
+        }
+    }
+    
+    // 如果中文字符占比超过阈值（默认30%），认为是中文
+    if chinese_ratio > detection.chinese_threshold {
+        LanguageDetection { language: "zh", cjk_ratio: chinese_ratio, confidence: chinese_ratio }
     } else {
-        "en"
+        LanguageDetection { language: "en", cjk_ratio: chinese_ratio, confidence: 1.0 - chinese_ratio }
+    }
+}
+
+impl From<LanguageDetection> for crate::storage::DetectedLanguage {
+    fn from(detection: LanguageDetection) -> Self {
+        Self {
+            language: detection.language.to_string(),
+            confidence: detection.confidence,
+            cjk_ratio: detection.cjk_ratio,
+        }
     }
 }
 
@@ -41,8 +223,8 @@ pub fn get_english_prompt_v2(transcript: &str) -> String {
 
 1.  **Title**: A concise, descriptive title for the entire note, summarizing its main topic.
 2.  **Summary**: A concise overview of the main points and outcomes discussed.
-3.  **Ideas**: A list of potential ideas or suggestions that arose from the discussion.
-4.  **Tasks**: A list of actionable tasks identified, including a title, optional description, and priority (Low, Medium, High, Urgent).
+3.  **Ideas**: A list of potential ideas or suggestions that arose from the discussion, each as an object with the idea's \"text\" and a \"confidence\" from 0 to 1 that it was really raised.
+4.  **Tasks**: A list of actionable tasks identified, including a title, optional description, priority (Low, Medium, High, Urgent) and a confidence from 0 to 1 that it is a real action item.
 5.  **Structured Notes**: A list of key discussion points or decisions, formatted as structured notes with a title, content, relevant tags (as a list of strings), and a note type (Meeting, Brainstorm, Decision, Action, Reference).
 
 IMPORTANT INSTRUCTIONS:
@@ -64,8 +246,8 @@ pub fn get_chinese_prompt_v2(transcript: &str) -> String {
 
 1.  **title（标题）**: 为文本内容提供一个简洁、描述性的标题，总结其主要话题。
 2.  **summary（摘要）**: 对文本的主要观点和内容进行客观、简洁的概述。
-3.  **ideas（观点）**: 文本中提到的主要观点、论述或见解列表。
-4.  **tasks（要点）**: 文本中提及的重要事项或关键信息，包括标题、可选描述和重要程度（Low、Medium、High、Urgent）。
+3.  **ideas（观点）**: 文本中提到的主要观点、论述或见解列表，每项为包含观点内容 \"text\" 和置信度 \"confidence\"（0 到 1，表示文本中确实提到该观点的把握）的对象。
+4.  **tasks（要点）**: 文本中提及的重要事项或关键信息，包括标题、可选描述、重要程度（Low、Medium、High、Urgent）和置信度 confidence（0 到 1）。
 5.  **structured_notes（结构化笔记）**: 文本的关键信息点，格式化为结构化笔记，包含标题、内容、相关标签（字符串列表）和类型（Meeting、Brainstorm、Decision、Action、Reference）。
 
 重要指示：
@@ -84,32 +266,240 @@ Transcript: {}
 JSON Output:", transcript)
 }
 
-pub async fn analyze_with_ollama_v2(transcript: &str, endpoint: &str) -> Result<AnalysisResult, anyhow::Error> {
-    // 使用指定的模型
-    let model_name = "deepseek-r1:8b-0528-qwen3-fp16";
+// 中英混杂文本的说明，附加在英文 prompt 之前
+const BILINGUAL_INSTRUCTIONS: &str = "The transcript mixes Chinese and English (code-switching). Keep each piece of information in the language it was spoken in: do not translate Chinese passages into English or English terms (such as technical terms and names) into Chinese. Write the title and summary in the transcript's predominant language.";
+
+// 获取中英混杂文本的 prompt
+fn get_bilingual_prompt(analysis_type: AnalysisType, transcript: &str) -> String {
+    format!("{}
+
+{}", BILINGUAL_INSTRUCTIONS, get_focused_english_prompt(analysis_type, transcript))
+}
+
+// 单一用途分析的英文 prompt，只要求模型输出对应的字段
+fn get_focused_english_prompt(analysis_type: AnalysisType, transcript: &str) -> String {
+    let task = match analysis_type {
+        AnalysisType::Summary => "Write a concise, descriptive title and a concise summary of the main points. Output: {\"title\": \"...\", \"summary\": \"...\"}",
+        AnalysisType::Ideas => "List the ideas or suggestions that arise in the transcript, one short sentence each, with a confidence from 0 to 1 that it was really raised. Output: {\"ideas\": [{\"text\": \"...\", \"confidence\": 0.9}]}",
+        AnalysisType::Tasks => "List the actionable tasks mentioned, each with a title, optional description, priority (Low, Medium, High, Urgent) and a confidence from 0 to 1 that it is a real action item. Output: {\"tasks\": [{\"title\": \"...\", \"description\": \"...\", \"priority\": \"Medium\", \"confidence\": 0.9}]}",
+        AnalysisType::StructuredNotes => "Turn the key discussion points or decisions into structured notes, each with a title, content, tags (list of strings) and type (Meeting, Brainstorm, Decision, Action, Reference). Output: {\"structured_notes\": [{\"title\": \"...\", \"content\": \"...\", \"tags\": [\"...\"], \"type\": \"Meeting\"}]}",
+        AnalysisType::Full => return get_english_prompt_v2(transcript),
+    };
+
+    format!("You are an AI assistant that analyzes transcripts. {}
+
+IMPORTANT INSTRUCTIONS:
+- Output ONLY the JSON object, with no other text, explanations or <think> tags.
+- If the transcript is empty or contains only whitespace, return an empty JSON object `{{}}`.
+
+Transcript: {}
+
+JSON Output:", task, transcript)
+}
+
+// 单一用途分析的中文 prompt
+fn get_focused_chinese_prompt(analysis_type: AnalysisType, transcript: &str) -> String {
+    let task = match analysis_type {
+        AnalysisType::Summary => "为文本生成一个简洁、描述性的标题，并对主要内容进行客观、简洁的概述。输出格式：{\"title\": \"...\", \"summary\": \"...\"}",
+        AnalysisType::Ideas => "列出文本中提到的主要观点或建议，每条一句话，并给出 0 到 1 的置信度。输出格式：{\"ideas\": [{\"text\": \"...\", \"confidence\": 0.9}]}",
+        AnalysisType::Tasks => "列出文本中提到的待办事项，包括标题、可选描述、重要程度（Low、Medium、High、Urgent）和 0 到 1 的置信度。输出格式：{\"tasks\": [{\"title\": \"...\", \"description\": \"...\", \"priority\": \"Medium\", \"confidence\": 0.9}]}",
+        AnalysisType::StructuredNotes => "将文本的关键信息整理为结构化笔记，包含标题、内容、标签（字符串列表）和类型（Meeting、Brainstorm、Decision、Action、Reference）。输出格式：{\"structured_notes\": [{\"title\": \"...\", \"content\": \"...\", \"tags\": [\"...\"], \"type\": \"Meeting\"}]}",
+        AnalysisType::Full => return get_chinese_prompt_v2(transcript),
+    };
+
+    format!("你是一个文本分析助手。{}
+
+重要指示：
+- 只输出JSON对象，不要包含任何其他文本、解释或<think>标签
+- 如果文本为空或仅包含空白字符，返回空的JSON对象 `{{}}`
+
+Transcript: {}
+
+JSON Output:", task, transcript)
+}
+
+/// Instruction placed before the prompt when the analysis should be written
+/// in `output_language` rather than the transcript's language
+///
+/// The JSON keys and the enum values are parsed as before, so they must stay
+/// as the prompt specifies them.
+pub fn output_language_instruction(output_language: &str) -> String {
+    format!(
+        "Write every text value of your answer (title, summary, ideas, task titles and descriptions, note titles, content and tags) in {}, whatever the language of the transcript. Keep the JSON keys and the priority and type values exactly as specified below.",
+        output_language
+    )
+}
+
+// 获取分析 prompt：配置了自定义模板时读取模板文件，否则使用内置 prompt；
+// 指定了输出语言时在 prompt 前加上对应的说明，再在最前面加上调用方的额外说明
+async fn build_prompt(
+    analysis_type: AnalysisType,
+    language: &str,
+    transcript: &str,
+    output_language: Option<&str>,
+    instructions: Option<&str>,
+) -> Result<String> {
+    let mut prompt = build_base_prompt(analysis_type, language, transcript).await?;
+    if let Some(output_language) = output_language {
+        prompt = format!("{}\n\n{}", output_language_instruction(output_language), prompt);
+    }
+    if let Some(instructions) = instructions.map(str::trim).filter(|instructions| !instructions.is_empty()) {
+        prompt = format!("{}\n\n{}", instructions, prompt);
+    }
+    Ok(prompt)
+}
+
+async fn build_base_prompt(analysis_type: AnalysisType, language: &str, transcript: &str) -> Result<String> {
+    let config = crate::config::current();
+    let templates = match language {
+        "zh" => &config.analysis.prompt_templates.zh,
+        "mixed" => &config.analysis.prompt_templates.mixed,
+        _ => &config.analysis.prompt_templates.en,
+    };
+    let template_path = match analysis_type {
+        AnalysisType::Full => &templates.full,
+        AnalysisType::Summary => &templates.summary,
+        AnalysisType::Ideas => &templates.ideas,
+        AnalysisType::Tasks => &templates.tasks,
+        AnalysisType::StructuredNotes => &templates.structured,
+    };
+
+    if let Some(path) = template_path {
+        info!("[Ollama V2] Using prompt template: {}", path.display());
+        let template = tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| format!("Failed to read prompt template: {}", path.display()))?;
+        return Ok(template.replace(TRANSCRIPT_PLACEHOLDER, transcript));
+    }
+
+    Ok(match language {
+        "zh" => get_focused_chinese_prompt(analysis_type, transcript),
+        "mixed" => get_bilingual_prompt(analysis_type, transcript),
+        _ => get_focused_english_prompt(analysis_type, transcript), // 默认使用英文
+    })
+}
+
+/// 连接 Ollama 服务器的超时时间，与等待模型生成结果的 `timeout` 分开，
+/// 服务器不可达时可以尽快失败
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 按指定的分析类型调用 Ollama，非完整分析时只生成和解析对应的字段
+///
+/// `timeout` 是整个请求（包括模型生成）的最长时间；`instructions` 是放在
+/// prompt 最前面的额外说明，例如重新生成分析时用户调整的要求
+pub async fn analyze_with_ollama_v2(
+    transcript: &str,
+    ollama_config: &OllamaConfig,
+    analysis_type: AnalysisType,
+    output_language: Option<&str>,
+    instructions: Option<&str>,
+    timeout: Duration,
+) -> Result<AnalysisResult, anyhow::Error> {
+    // 使用配置中的模型
+    let model_name = ollama_config.default_model.as_str();
     
     if transcript.trim().is_empty() {
         info!("[Ollama V2] Transcript is empty, returning empty analysis result.");
         return Ok(AnalysisResult::default());
     }
 
-    let client = Client::new();
+    let client = Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .build()
+        .context("Failed to create HTTP client for Ollama")?;
     
     // 检测转录文本的语言
-    let language = detect_language_v2(transcript);
+    let language = detect_language_v2(transcript, &crate::config::current().analysis.language_detection);
     info!("[Ollama V2] Detected language: {}", language);
     
     // 预处理转录文本，处理大量换行和特殊字符
     let processed_transcript = preprocess_transcript(transcript);
     
-    // 根据语言选择对应的 prompt
-    let prompt = match language {
-        "zh" => get_chinese_prompt_v2(&processed_transcript),
-        _ => get_english_prompt_v2(&processed_transcript), // 默认使用英文
+    // 根据语言和分析类型选择对应的 prompt，优先使用配置的自定义模板
+    let prompt = build_prompt(analysis_type, language, &processed_transcript, output_language, instructions).await?;
+
+    let mut num_predict = num_predict_for(&processed_transcript, ollama_config);
+    let options = ollama_config.options;
+    info!(
+        "[Ollama V2] Using model: {} (analysis type: {}, num_predict: {}, options: {:?})",
+        model_name, analysis_type, num_predict, options
+    );
+
+    // 使用 /api/chat 端点而不是 /api/generate
+    let endpoint = format!("{}/api/chat", ollama_config.base_url.trim_end_matches('/'));
+    // 重试也计入 timeout，整个分析不会超过这个时间
+    let deadline = Instant::now() + timeout;
+    let mut response = send_chat_request(&client, &endpoint, model_name, &prompt, options, num_predict, timeout).await?;
+
+    // 输出达到 num_predict 上限时被截断，JSON 多半不完整，修复后的结果也不可靠，
+    // 因此用加倍（不超过配置的 ollama.num_predict）的上限在剩余时间内重试一次
+    let retry_num_predict = num_predict.saturating_mul(2).min(ollama_config.num_predict);
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    if response.hit_predict_limit && retry_num_predict > num_predict && !remaining.is_zero() {
+        warn!(
+            "[Ollama V2] Model output reached the num_predict limit of {} tokens and was cut off; retrying with {} ({:.1}s left)",
+            num_predict, retry_num_predict, remaining.as_secs_f64()
+        );
+        num_predict = retry_num_predict;
+        response = send_chat_request(&client, &endpoint, model_name, &prompt, options, num_predict, remaining).await?;
+    }
+
+    let mut analysis = parse_chat_response(analysis_type, transcript, &response, num_predict)?;
+    if response.hit_predict_limit {
+        warn!(
+            "[Ollama V2] Model output was cut off at {} tokens; raise ollama.num_predict if analyses come back incomplete",
+            num_predict
+        );
+        analysis.warnings.push(format!(
+            "Output truncated: the model stopped at the num_predict limit of {} tokens, so this analysis may be incomplete. Raise ollama.num_predict and analyze again.",
+            num_predict
+        ));
+    }
+
+    Ok(analysis)
+}
+
+/// 完整分析的 prompt，供自行调用模型（例如流式生成）的调用方使用
+///
+/// `language` 为空时从转录文本检测；与 [`analyze_with_ollama_v2`] 一样优先使用配置的自定义模板
+pub async fn analysis_prompt(transcript: &str, language: Option<&str>, output_language: Option<&str>) -> Result<String> {
+    let language = match language {
+        Some(language) => language,
+        None => detect_language_v2(transcript, &crate::config::current().analysis.language_detection),
+    };
+    build_prompt(AnalysisType::Full, language, &preprocess_transcript(transcript), output_language, None).await
+}
+
+/// 解析模型对 [`analysis_prompt`] 的完整输出，JSON 不完整时与非流式分析一样尝试修复
+pub fn parse_analysis_output(transcript: &str, output: &str) -> Result<AnalysisResult> {
+    let response = ChatResponse {
+        text: output.to_string(),
+        json: json!({ "response": output }),
+        hit_predict_limit: false,
     };
+    parse_chat_response(AnalysisType::Full, transcript, &response, 0)
+}
 
-    info!("[Ollama V2] Using model: {}", model_name);
+/// Ollama /api/chat 的非流式响应
+struct ChatResponse {
+    /// 原始响应正文
+    text: String,
+    /// 解析后的响应 JSON
+    json: Value,
+    /// 模型是否因达到 num_predict 上限而停止（`done_reason` 为 `length`）
+    hit_predict_limit: bool,
+}
 
+// 发送一次分析请求并解析外层 JSON
+async fn send_chat_request(
+    client: &Client,
+    endpoint: &str,
+    model_name: &str,
+    prompt: &str,
+    options: ModelOptions,
+    num_predict: u32,
+    timeout: Duration,
+) -> Result<ChatResponse> {
     let request_body = json!({
         "model": model_name,
         "messages": [
@@ -119,27 +509,31 @@ pub async fn analyze_with_ollama_v2(transcript: &str, endpoint: &str) -> Result<
             }
         ],
         "stream": false, // 确保非流式响应，便于解析
-        "options": {
-            "temperature": 0.1, // 降低温度以获得更确定性的输出
-            "num_predict": 4096 // 增加预测token数量以处理长文本
-        }
+        "options": options.to_request_options(num_predict)
     });
 
-    // 使用 /api/chat 端点而不是 /api/generate
-    let endpoint = format!("{}/api/chat", endpoint.trim_end_matches('/'));
-    info!("[Ollama V2] Sending request to: {}", endpoint);
+    info!("[Ollama V2] Sending request to: {} (timeout: {}s)", endpoint, timeout.as_secs());
 
+    let started = Instant::now();
     let response = client
-        .post(&endpoint)
+        .post(endpoint)
         .json(&request_body)
-        .timeout(std::time::Duration::from_secs(180)) // 增加超时时间到3分钟
+        .timeout(timeout)
         .send()
         .await
-        .with_context(|| format!("Failed to connect to Ollama endpoint: {}", endpoint))?;
+        .map_err(|e| request_error(e, endpoint, timeout, started))?;
 
     let status = response.status();
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+
+    // 非流式响应在模型生成完成后才返回正文，读取正文时同样可能超时
     let result_text = response.text().await
-        .with_context(|| format!("Failed to read response body from {}. Status: {}", endpoint, status))?;
+        .map_err(|e| request_error(e, endpoint, timeout, started))?;
+    ensure_json_response(endpoint, status, content_type.as_deref(), &result_text)?;
 
     // 解析响应
     let parsed_outer_json: Value = match serde_json::from_str(&result_text) {
@@ -157,6 +551,20 @@ pub async fn analyze_with_ollama_v2(transcript: &str, endpoint: &str) -> Result<
         }
     };
 
+    let hit_predict_limit = parsed_outer_json.get("done_reason").and_then(Value::as_str) == Some("length");
+    Ok(ChatResponse { text: result_text, json: parsed_outer_json, hit_predict_limit })
+}
+
+// 从响应中提取模型输出并解析为分析结果，JSON 不完整时尝试修复或生成兜底结果
+fn parse_chat_response(
+    analysis_type: AnalysisType,
+    transcript: &str,
+    response: &ChatResponse,
+    num_predict: u32,
+) -> Result<AnalysisResult> {
+    let result_text = response.text.as_str();
+    let parsed_outer_json = &response.json;
+
     // 从响应中提取 JSON 内容
     let actual_json_data_str = parsed_outer_json
         .get("message")
@@ -169,19 +577,19 @@ pub async fn analyze_with_ollama_v2(transcript: &str, endpoint: &str) -> Result<
         Some(s) => s,
         None => {
             // 如果整个响应本身就是 JSON 对象
-            if parsed_outer_json.is_object() && parsed_outer_json.get("summary").is_some() {
+            if analysis_type == AnalysisType::Full && parsed_outer_json.is_object() && parsed_outer_json.get("summary").is_some() {
                  info!("[Ollama V2] Successfully parsed entire response as JSON.");
-                 return Ok(serde_json::from_value(parsed_outer_json)?);
-            } else if let Ok(analysis_json) = serde_json::from_str::<serde_json::Value>(&result_text) {
+                 return Ok(serde_json::from_value(parsed_outer_json.clone())?);
+            } else if let Ok(analysis_json) = serde_json::from_str::<serde_json::Value>(result_text) {
                     info!("[Ollama V2] Successfully parsed entire response as JSON.");
-                    return Ok(parse_analysis_json(&analysis_json));
+                    return Ok(parse_analysis_for_type(analysis_type, &analysis_json));
                 }
                 
             // 尝试从整个响应中提取JSON
-            let cleaned_full_response = clean_llm_response(&result_text);
+            let cleaned_full_response = clean_llm_response(result_text);
             if let Ok(extracted_json) = serde_json::from_str::<serde_json::Value>(&cleaned_full_response) {
                 info!("[Ollama V2] Successfully extracted JSON from full response.");
-                return Ok(parse_analysis_json(&extracted_json));
+                return Ok(parse_analysis_for_type(analysis_type, &extracted_json));
             }
                 
             info!("[Ollama V2] Could not extract JSON content string from Ollama's response. Full response: {}", result_text);
@@ -205,19 +613,230 @@ pub async fn analyze_with_ollama_v2(transcript: &str, endpoint: &str) -> Result<
                 Ok(fixed_value) => fixed_value,
                 Err(e2) => {
                     // 创建一个基本的分析结果，避免完全失败
-                    info!("[Ollama V2] Fallback parsing also failed: {}. Creating basic analysis result.", e2);
-                    return Ok(create_fallback_analysis_result(transcript, &cleaned_json_str));
+                    return match analysis_type {
+                        AnalysisType::Full => {
+                            info!("[Ollama V2] Fallback parsing also failed: {}. Creating basic analysis result.", e2);
+                            Ok(create_fallback_analysis_result(transcript, &cleaned_json_str))
+                        }
+                        AnalysisType::Summary => {
+                            info!("[Ollama V2] Fallback parsing also failed: {}. Extracting title and summary.", e2);
+                            let fallback = create_fallback_analysis_result(transcript, &cleaned_json_str);
+                            Ok(AnalysisResult { title: fallback.title, summary: fallback.summary, ..Default::default() })
+                        }
+                        _ if response.hit_predict_limit => Err(anyhow::anyhow!(
+                            "Failed to parse {} analysis from Ollama response: the output was cut off at the num_predict limit of {} tokens; raise ollama.num_predict. Response: {}",
+                            analysis_type,
+                            num_predict,
+                            cleaned_json_str
+                        )),
+                        _ => Err(anyhow::anyhow!("Failed to parse {} analysis from Ollama response: {}. Response: {}", analysis_type, e2, cleaned_json_str)),
+                    };
                 }
             }
         }
     };
     
-    // 解析 JSON 到 AnalysisResult 结构体
-    let analysis = parse_analysis_json(&analysis_json);
+    // 解析 JSON 到 AnalysisResult 结构体，只取分析类型对应的字段
+    let analysis = parse_analysis_for_type(analysis_type, &analysis_json);
     
     Ok(analysis)
 }
 
+// 模型最多生成的 token 数：设置了 num_predict_min 时从该值起按转录长度增加，
+// 每两个字符增加一个 token，不超过 num_predict
+fn num_predict_for(transcript: &str, ollama_config: &OllamaConfig) -> u32 {
+    match ollama_config.num_predict_min {
+        Some(min) => {
+            let scaled = min.saturating_add((transcript.chars().count() / 2).try_into().unwrap_or(u32::MAX));
+            scaled.min(ollama_config.num_predict)
+        }
+        None => ollama_config.num_predict,
+    }
+}
+
+// 区分超时和连接失败：前者应调大超时时间，后者应检查 Ollama 服务器
+fn request_error(error: reqwest::Error, endpoint: &str, timeout: Duration, started: Instant) -> anyhow::Error {
+    // 保留 reqwest 错误作为原因，调用方据此判断服务器是否不可达（见 ai::is_provider_unavailable）
+    if error.is_connect() {
+        let message = format!(
+            "Could not connect to Ollama at {}. Check that the server is running and ollama.base_url is correct",
+            endpoint
+        );
+        anyhow::Error::new(error).context(message)
+    } else if error.is_timeout() {
+        warn!(
+            "[Ollama V2] Analysis timed out after {:.1}s (limit: {}s)",
+            started.elapsed().as_secs_f64(),
+            timeout.as_secs()
+        );
+        let message = format!(
+            "Ollama did not finish the analysis within {} seconds. The model may need longer, e.g. on CPU; increase analysis.timeout_secs or pass --timeout",
+            timeout.as_secs()
+        );
+        anyhow::Error::new(error).context(message)
+    } else {
+        anyhow::Error::new(error).context(format!("Request to Ollama endpoint {} failed", endpoint))
+    }
+}
+
+/// 错误信息中引用的响应正文长度（字符数）
+const RESPONSE_EXCERPT_CHARS: usize = 200;
+
+// base_url 指向的不是 Ollama（例如返回 HTML 页面的代理）时直接报错，
+// 否则会把网页当作模型输出解析，最后得到一个莫名其妙的兜底结果
+fn ensure_json_response(
+    endpoint: &str,
+    status: reqwest::StatusCode,
+    content_type: Option<&str>,
+    body: &str,
+) -> Result<()> {
+    let declared_json = content_type.is_none_or(|content_type| content_type.to_ascii_lowercase().contains("json"));
+    let looks_like_markup = body.trim_start().starts_with('<');
+    if declared_json && !looks_like_markup {
+        return Ok(());
+    }
+
+    let excerpt: String = body.trim().chars().take(RESPONSE_EXCERPT_CHARS).collect();
+    warn!("[Ollama V2] {} returned a non-JSON response (HTTP {}, content type: {})", endpoint, status, content_type.unwrap_or("none"));
+    Err(anyhow::anyhow!(
+        "Ollama endpoint {} did not return JSON (HTTP {}, content type: {}); is OLLAMA_BASE_URL (ollama.base_url) correct? Response starts with: {}",
+        endpoint,
+        status,
+        content_type.unwrap_or("none"),
+        excerpt
+    ))
+}
+
+/// Full name of an Ollama model as Ollama resolves it, e.g. `llama2` is
+/// `llama2:latest`
+pub fn qualified_model_name(model: &str) -> String {
+    let name = model.rsplit('/').next().unwrap_or(model);
+    if name.contains(':') {
+        model.to_string()
+    } else {
+        format!("{}:latest", model)
+    }
+}
+
+/// 计算向量时最多使用的字符数，超出部分截断，避免超过向量模型的上下文长度
+const EMBEDDING_MAX_CHARS: usize = 8000;
+
+/// 用 `model` 计算文本的向量（Ollama `/api/embeddings`），用于语义搜索
+pub async fn embed(text: &str, ollama_config: &OllamaConfig, model: &str) -> Result<Vec<f32>> {
+    let client = Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .build()
+        .context("Failed to create HTTP client for Ollama")?;
+    let endpoint = format!("{}/api/embeddings", ollama_config.base_url.trim_end_matches('/'));
+    let prompt: String = text.chars().take(EMBEDDING_MAX_CHARS).collect();
+    let timeout = Duration::from_secs(ollama_config.timeout_secs);
+
+    let started = Instant::now();
+    let response = client
+        .post(&endpoint)
+        .json(&json!({ "model": model, "prompt": prompt }))
+        .timeout(timeout)
+        .send()
+        .await
+        .map_err(|e| request_error(e, &endpoint, timeout, started))?;
+
+    let status = response.status();
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let body = response.text().await
+        .map_err(|e| request_error(e, &endpoint, timeout, started))?;
+    ensure_json_response(&endpoint, status, content_type.as_deref(), &body)?;
+    if !status.is_success() {
+        return Err(anyhow::anyhow!("Ollama could not embed text with model {} (HTTP {}): {}", model, status, body));
+    }
+
+    let parsed: Value = serde_json::from_str(&body).context("Failed to parse the embedding response from Ollama")?;
+    let embedding: Vec<f32> = parsed
+        .get("embedding")
+        .and_then(Value::as_array)
+        .map(|values| values.iter().filter_map(Value::as_f64).map(|value| value as f32).collect())
+        .unwrap_or_default();
+    if embedding.is_empty() {
+        return Err(anyhow::anyhow!("Ollama returned no embedding for model {}; is it an embedding model?", model));
+    }
+    Ok(embedding)
+}
+
+/// 列出已安装模型的超时时间；`/api/tags` 不经过模型，很快就会返回
+const LIST_MODELS_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Ollama 服务器上已安装的模型（`/api/tags`）
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct InstalledModel {
+    pub name: String,
+    /// 模型文件大小（字节）
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default)]
+    pub details: InstalledModelDetails,
+}
+
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct InstalledModelDetails {
+    /// 参数量，例如 `8.0B`
+    #[serde(default)]
+    pub parameter_size: Option<String>,
+    /// 量化方式，例如 `Q4_0`
+    #[serde(default)]
+    pub quantization_level: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct TagsResponse {
+    #[serde(default)]
+    models: Vec<InstalledModel>,
+}
+
+/// 列出 Ollama 服务器上已安装的模型，按名称排序
+pub async fn list_models(ollama_config: &OllamaConfig) -> Result<Vec<InstalledModel>> {
+    let client = Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .build()
+        .context("Failed to create HTTP client for Ollama")?;
+    let endpoint = format!("{}/api/tags", ollama_config.base_url.trim_end_matches('/'));
+    // 列出模型不需要等模型生成，超时和连接失败一样说明服务器不可用
+    let unreachable = |e: reqwest::Error| {
+        let message = format!(
+            "Could not reach Ollama at {}. Check that the server is running (`ollama serve`) and ollama.base_url is correct",
+            endpoint
+        );
+        anyhow::Error::new(e).context(message)
+    };
+
+    let response = client
+        .get(&endpoint)
+        .timeout(LIST_MODELS_TIMEOUT)
+        .send()
+        .await
+        .map_err(unreachable)?;
+
+    let status = response.status();
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let body = response.text().await.map_err(unreachable)?;
+    ensure_json_response(&endpoint, status, content_type.as_deref(), &body)?;
+    if !status.is_success() {
+        return Err(anyhow::anyhow!("Ollama could not list its models (HTTP {}): {}", status, body));
+    }
+
+    let mut models = serde_json::from_str::<TagsResponse>(&body)
+        .context("Failed to parse the model list from Ollama")?
+        .models;
+    models.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(models)
+}
+
 // 预处理转录文本，处理大量换行和特殊字符
 fn preprocess_transcript(transcript: &str) -> String {
     // 合并连续的多个换行为单个换行
@@ -339,11 +958,13 @@ fn create_fallback_analysis_result(transcript: &str, partial_json: &str) -> Anal
         title,
         summary,
         ideas: vec!["[解析错误] 无法提取想法".to_string()],
+        idea_confidences: Vec::new(),
         tasks: vec![crate::storage::Task {
             title: "检查分析结果".to_string(),
             description: Some("由于解析错误，分析结果可能不完整，请检查原始转录".to_string()),
             priority: crate::storage::Priority::Medium,
             due_date: None,
+            confidence: None,
         }],
         structured_notes: vec![crate::storage::StructuredNote {
             title: "解析错误通知".to_string(),
@@ -353,70 +974,137 @@ fn create_fallback_analysis_result(transcript: &str, partial_json: &str) -> Anal
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
         }],
+        processing_time_ms: None,
+        provider: None,
+        model: None,
+        detected_language: None,
+        warnings: Vec::new(),
     }
 }
 
 // 辅助函数：解析 JSON 到 AnalysisResult 结构体
 fn parse_analysis_json(analysis_json: &Value) -> AnalysisResult {
+    let (ideas, idea_confidences) = parse_ideas(analysis_json);
     AnalysisResult {
-        title: analysis_json.get("title").and_then(Value::as_str).unwrap_or("").to_string(),
-        summary: analysis_json.get("summary").and_then(Value::as_str).unwrap_or("").to_string(),
-        ideas: analysis_json.get("ideas")
-            .and_then(Value::as_array)
-            .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
-            .unwrap_or_default(),
-        tasks: analysis_json.get("tasks")
-            .and_then(Value::as_array)
-            .map(|arr| arr.iter().filter_map(|task_val| {
-                let title = task_val.get("title")?.as_str()?.to_string();
-                let description = task_val.get("description").and_then(|d| d.as_str()).map(String::from);
-                let priority_str = task_val.get("priority")?.as_str()?;
-                let priority = match priority_str {
-                    "Low" => crate::storage::Priority::Low,
-                    "Medium" => crate::storage::Priority::Medium,
-                    "High" => crate::storage::Priority::High,
-                    "Urgent" => crate::storage::Priority::Urgent,
-                    _ => crate::storage::Priority::Medium, // 默认优先级
-                };
-                Some(crate::storage::Task {
-                    title,
-                    description,
-                    priority,
-                    due_date: None,
-                 })
-            }).collect())
-            .unwrap_or_default(),
-        structured_notes: analysis_json.get("structured_notes")
-            .and_then(Value::as_array)
-            .map(|arr| arr.iter().filter_map(|note_val| {
-                let title = note_val.get("title")?.as_str()?.to_string();
-                let content = note_val.get("content")?.as_str()?.to_string();
-                let tags: Vec<String> = note_val.get("tags")?.as_array()?
-                    .iter()
-                    .filter_map(|tag_val| tag_val.as_str().map(String::from))
-                    .collect();
-                let note_type_str = note_val.get("type")?.as_str()?;
-                let note_type = match note_type_str {
-                    "Meeting" => crate::storage::NoteType::Meeting,
-                    "Brainstorm" => crate::storage::NoteType::Brainstorm,
-                    "Decision" => crate::storage::NoteType::Decision,
-                    "Action" => crate::storage::NoteType::Action,
-                    "Reference" => crate::storage::NoteType::Reference,
-                    _ => crate::storage::NoteType::Reference, // 默认笔记类型
-                };
-                Some(crate::storage::StructuredNote {
-                    title,
-                    content,
-                    tags,
-                    note_type,
-                    created_at: chrono::Utc::now(),
-                    updated_at: chrono::Utc::now(),
-                 })
-            }).collect())
-            .unwrap_or_default(),
+        title: parse_string_field(analysis_json, "title"),
+        summary: parse_string_field(analysis_json, "summary"),
+        ideas,
+        idea_confidences,
+        tasks: parse_tasks(analysis_json),
+        structured_notes: parse_structured_notes(analysis_json),
+        processing_time_ms: None,
+        provider: None,
+        model: None,
+        detected_language: None,
+        warnings: Vec::new(),
     }
 }
 
+// 按分析类型解析，只填充请求的字段
+fn parse_analysis_for_type(analysis_type: AnalysisType, analysis_json: &Value) -> AnalysisResult {
+    match analysis_type {
+        AnalysisType::Full => parse_analysis_json(analysis_json),
+        AnalysisType::Summary => AnalysisResult {
+            title: parse_string_field(analysis_json, "title"),
+            summary: parse_string_field(analysis_json, "summary"),
+            ..Default::default()
+        },
+        AnalysisType::Ideas => {
+            let (ideas, idea_confidences) = parse_ideas(analysis_json);
+            AnalysisResult { ideas, idea_confidences, ..Default::default() }
+        }
+        AnalysisType::Tasks => AnalysisResult {
+            tasks: parse_tasks(analysis_json),
+            ..Default::default()
+        },
+        AnalysisType::StructuredNotes => AnalysisResult {
+            structured_notes: parse_structured_notes(analysis_json),
+            ..Default::default()
+        },
+    }
+}
+
+fn parse_string_field(analysis_json: &Value, field: &str) -> String {
+    analysis_json.get(field).and_then(Value::as_str).unwrap_or("").to_string()
+}
+
+// 观点可以是字符串，也可以是带置信度的对象 {"text": "...", "confidence": 0.9}
+fn parse_ideas(analysis_json: &Value) -> (Vec<String>, Vec<Option<f32>>) {
+    analysis_json.get("ideas")
+        .and_then(Value::as_array)
+        .map(|arr| arr.iter().filter_map(|v| match v {
+            Value::String(text) => Some((text.clone(), None)),
+            Value::Object(idea) => {
+                let text = ["text", "idea", "content"].iter().find_map(|key| idea.get(*key)?.as_str())?;
+                Some((text.to_string(), parse_confidence(v)))
+            }
+            _ => None,
+        }).unzip())
+        .unwrap_or_default()
+}
+
+// 模型给出的置信度，超出 0 到 1 的值截断到该范围
+fn parse_confidence(item: &Value) -> Option<f32> {
+    let confidence = item.get("confidence")?.as_f64()?;
+    Some(confidence.clamp(0.0, 1.0) as f32)
+}
+
+fn parse_tasks(analysis_json: &Value) -> Vec<crate::storage::Task> {
+    analysis_json.get("tasks")
+        .and_then(Value::as_array)
+        .map(|arr| arr.iter().filter_map(|task_val| {
+            let title = task_val.get("title")?.as_str()?.to_string();
+            let description = task_val.get("description").and_then(|d| d.as_str()).map(String::from);
+            let priority_str = task_val.get("priority")?.as_str()?;
+            let priority = match priority_str {
+                "Low" => crate::storage::Priority::Low,
+                "Medium" => crate::storage::Priority::Medium,
+                "High" => crate::storage::Priority::High,
+                "Urgent" => crate::storage::Priority::Urgent,
+                _ => crate::storage::Priority::Medium, // 默认优先级
+            };
+            Some(crate::storage::Task {
+                title,
+                description,
+                priority,
+                due_date: None,
+                confidence: parse_confidence(task_val),
+             })
+        }).collect())
+        .unwrap_or_default()
+}
+
+fn parse_structured_notes(analysis_json: &Value) -> Vec<crate::storage::StructuredNote> {
+    analysis_json.get("structured_notes")
+        .and_then(Value::as_array)
+        .map(|arr| arr.iter().filter_map(|note_val| {
+            let title = note_val.get("title")?.as_str()?.to_string();
+            let content = note_val.get("content")?.as_str()?.to_string();
+            let tags: Vec<String> = note_val.get("tags")?.as_array()?
+                .iter()
+                .filter_map(|tag_val| tag_val.as_str().map(String::from))
+                .collect();
+            let note_type_str = note_val.get("type")?.as_str()?;
+            let note_type = match note_type_str {
+                "Meeting" => crate::storage::NoteType::Meeting,
+                "Brainstorm" => crate::storage::NoteType::Brainstorm,
+                "Decision" => crate::storage::NoteType::Decision,
+                "Action" => crate::storage::NoteType::Action,
+                "Reference" => crate::storage::NoteType::Reference,
+                _ => crate::storage::NoteType::Reference, // 默认笔记类型
+            };
+            Some(crate::storage::StructuredNote {
+                title,
+                content,
+                tags,
+                note_type,
+                created_at: chrono::Utc::now(),
+                updated_at: chrono::Utc::now(),
+             })
+        }).collect())
+        .unwrap_or_default()
+}
+
 // 清理 LLM 响应，移除 <think> 标签、Markdown 代码块标记并提取 JSON 内容
 fn clean_llm_response(response: &str) -> String {
     let trimmed = response.trim();
@@ -488,4 +1176,76 @@ fn clean_llm_response(response: &str) -> String {
     
     // 如果没有找到完整的 JSON 对象，返回清理后的原始响应
     cleaned
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detection(chinese_threshold: f64, mixed_threshold: Option<f64>) -> LanguageDetectionConfig {
+        LanguageDetectionConfig { chinese_threshold, mixed_threshold }
+    }
+
+    #[test]
+    fn default_thresholds_keep_the_zh_en_split() {
+        let default = LanguageDetectionConfig::default();
+        assert_eq!(detect_language_v2("这个项目的进度很好，我们下周继续讨论", &default), "zh");
+        assert_eq!(detect_language_v2("We should ship the release next week", &default), "en");
+        // 3 of 10 characters are Chinese: exactly the threshold is still English
+        assert_eq!(detect_language_v2("中文字abcdefg", &default), "en");
+        assert_eq!(detect_language_v2("中文字abcdef", &default), "zh");
+        assert_eq!(detect_language_v2("   ", &default), "en");
+    }
+
+    #[test]
+    fn chinese_threshold_is_configurable() {
+        // 7 of 20 characters are Chinese
+        let text = "我们用 Kubernetes 部署 API 服务";
+        assert_eq!(detect_language_v2(text, &detection(0.3, None)), "zh");
+        assert_eq!(detect_language_v2(text, &detection(0.4, None)), "en");
+    }
+
+    #[test]
+    fn mixed_needs_both_languages_above_the_mixed_threshold() {
+        let mixed = detection(0.3, Some(0.2));
+        let samples = [
+            // 7 of 20 Chinese, 13 of 20 Latin
+            ("我们用 Kubernetes 部署 API 服务", "mixed"),
+            // 4 of 12 Chinese, 8 of 12 Latin
+            ("明天review一下PR", "mixed"),
+            // 2 of 33 Chinese
+            ("Let's review the deployment plan for 服务", "en"),
+            // 11 of 13 Chinese, 1 Latin
+            ("这个需求先放到下个版本，X", "zh"),
+        ];
+        for (text, expected) in samples {
+            assert_eq!(detect_language_v2(text, &mixed), expected, "{}", text);
+        }
+
+        let detected = detect_language_with_confidence("明天review一下PR", &mixed);
+        assert!((detected.cjk_ratio - 4.0 / 12.0).abs() < 1e-9);
+        assert!((detected.confidence - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn tasks_only_output_is_parsed_like_the_tasks_of_a_full_analysis() {
+        let output = json!({
+            "title": "ignored",
+            "tasks": [
+                {"title": "Send the minutes", "description": "To the whole team", "priority": "High", "confidence": 0.9},
+                {"title": "Maybe book a room", "priority": "Whenever", "confidence": 0.2},
+                {"title": "Review the PR", "priority": "Low"},
+                {"description": "No title"}
+            ]
+        });
+        let mut analysis = parse_analysis_for_type(AnalysisType::Tasks, &output);
+        assert!(analysis.title.is_empty());
+        assert_eq!(analysis.tasks.len(), 3);
+        assert!(matches!(analysis.tasks[0].priority, crate::storage::Priority::High));
+        assert_eq!(analysis.tasks[0].description.as_deref(), Some("To the whole team"));
+        assert!(matches!(analysis.tasks[1].priority, crate::storage::Priority::Medium));
+
+        assert_eq!(analysis.retain_confident(0.5), 1);
+        let titles: Vec<&str> = analysis.tasks.iter().map(|task| task.title.as_str()).collect();
+        assert_eq!(titles, ["Send the minutes", "Review the PR"]);
+    }
+}
@@ -0,0 +1,256 @@
+// src/openai.rs
+//! Requests to the OpenAI API (or a compatible server) with retries
+//!
+//! Transient failures are retried up to `openai.max_retries` times: rate
+//! limits (429), server errors (5xx), request timeouts (408) and requests
+//! that time out or fail to connect. A `Retry-After` header is honoured;
+//! otherwise the delay doubles with every attempt. Anything else, e.g. an
+//! invalid API key (401) or a malformed request (400), fails immediately.
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use log::{info, warn};
+use reqwest::{header, RequestBuilder, Response, StatusCode};
+use serde::Deserialize;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::config::OpenAIConfig;
+use crate::storage::{TranscriptSegment, Transcription};
+
+/// Delay before the first retry when the server gives no `Retry-After`
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Upper bound for any delay between attempts, including `Retry-After`
+const MAX_BACKOFF: Duration = Duration::from_secs(120);
+
+/// OpenAI models suited to transcription, for `voice-recorder models`;
+/// compatible servers have their own
+pub const TRANSCRIPTION_MODELS: &[&str] = &["whisper-1", "gpt-4o-transcribe", "gpt-4o-mini-transcribe"];
+
+/// OpenAI chat models suited to analysis, for `voice-recorder models`
+pub const ANALYSIS_MODELS: &[&str] = &["gpt-4o", "gpt-4o-mini", "gpt-4.1", "gpt-4.1-mini", "gpt-4.1-nano"];
+
+/// Send the request built by `build` until it succeeds, fails permanently
+/// or `max_retries` retries are used up
+///
+/// `build` is called for every attempt since a request, e.g. one with a
+/// multipart body, can only be sent once. `what` names the request in logs
+/// and errors.
+async fn send_with_retry<F>(config: &OpenAIConfig, what: &str, build: F) -> Result<Response>
+where
+    F: Fn() -> Result<RequestBuilder>,
+{
+    let mut attempt = 0;
+    loop {
+        let (error, retry_after) = match build()?.send().await {
+            Ok(response) if response.status().is_success() => return Ok(response),
+            Ok(response) => {
+                let status = response.status();
+                let retry_after = retry_after(&response);
+                let message = error_message(response).await;
+                let error = anyhow::anyhow!("OpenAI {} failed with {}: {}", what, status, message);
+                if !is_transient_status(status) {
+                    return Err(error);
+                }
+                (error, retry_after)
+            }
+            Err(e) if e.is_timeout() || e.is_connect() => {
+                (anyhow::Error::new(e).context(format!("OpenAI {} request failed", what)), None)
+            }
+            Err(e) => return Err(anyhow::Error::new(e).context(format!("OpenAI {} request failed", what))),
+        };
+
+        if attempt >= config.max_retries {
+            return Err(error.context(format!("Giving up after {} retries", attempt)));
+        }
+        let delay = retry_after
+            .unwrap_or_else(|| INITIAL_BACKOFF.saturating_mul(2u32.saturating_pow(attempt)))
+            .min(MAX_BACKOFF);
+        attempt += 1;
+        warn!(
+            "[OpenAI] {:#}; retrying {} in {:.1}s (retry {}/{})",
+            error,
+            what,
+            delay.as_secs_f32(),
+            attempt,
+            config.max_retries
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Statuses worth retrying: rate limits, request timeouts and server errors
+fn is_transient_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+        || status.is_server_error()
+}
+
+/// Delay requested by a `Retry-After` header, given either in seconds or
+/// as an HTTP date
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(header::RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<f64>() {
+        // Clamped before converting, which panics on values too large for a Duration
+        return (secs.is_finite() && secs >= 0.0).then(|| Duration::from_secs_f64(secs.min(MAX_BACKOFF.as_secs_f64())));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some((at.with_timezone(&Utc) - Utc::now()).to_std().unwrap_or_default())
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    error: ErrorDetail,
+}
+
+#[derive(Deserialize)]
+struct ErrorDetail {
+    message: String,
+}
+
+/// The `error.message` of an error response, or its raw body
+async fn error_message(response: Response) -> String {
+    let body = response.text().await.unwrap_or_default();
+    match serde_json::from_str::<ErrorBody>(&body) {
+        Ok(parsed) => parsed.error.message,
+        Err(_) => body,
+    }
+}
+
+fn client(config: &OpenAIConfig) -> Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(config.timeout_secs))
+        .build()
+        .context("Failed to build OpenAI HTTP client")
+}
+
+fn endpoint(config: &OpenAIConfig, path: &str) -> String {
+    format!("{}/{}", config.base_url.trim_end_matches('/'), path)
+}
+
+/// Add the API key and, if configured, the organization to a request
+fn authorize(config: &OpenAIConfig, request: RequestBuilder) -> RequestBuilder {
+    let request = request.bearer_auth(&config.api_key);
+    match &config.organization_id {
+        Some(org_id) => request.header("OpenAI-Organization", org_id),
+        None => request,
+    }
+}
+
+#[derive(Deserialize)]
+struct VerboseTranscription {
+    text: String,
+    #[serde(default)]
+    language: Option<String>,
+    #[serde(default)]
+    segments: Vec<VerboseSegment>,
+}
+
+#[derive(Deserialize)]
+struct VerboseSegment {
+    start: f64,
+    end: f64,
+    text: String,
+    #[serde(default)]
+    avg_logprob: Option<f64>,
+}
+
+/// Transcribe `audio_path` with `openai.transcription_model`
+///
+/// Requests `verbose_json` so the detected language and segment timestamps
+/// are kept; a segment's confidence is its average token probability.
+pub async fn transcribe(audio_path: &Path, config: &OpenAIConfig) -> Result<Transcription> {
+    let audio = tokio::fs::read(audio_path)
+        .await
+        .with_context(|| format!("Failed to read audio file {}", audio_path.display()))?;
+    let filename = audio_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "audio.wav".to_string());
+    info!("[OpenAI] Transcribing {} with {}", audio_path.display(), config.transcription_model);
+
+    let client = client(config)?;
+    let url = endpoint(config, "audio/transcriptions");
+    let response = send_with_retry(config, "transcription", || {
+        let file = reqwest::multipart::Part::bytes(audio.clone()).file_name(filename.clone());
+        let form = reqwest::multipart::Form::new()
+            .part("file", file)
+            .text("model", config.transcription_model.clone())
+            .text("response_format", "verbose_json")
+            .text("timestamp_granularities[]", "segment");
+        Ok(authorize(config, client.post(&url)).multipart(form))
+    })
+    .await?;
+
+    let transcription: VerboseTranscription = response
+        .json()
+        .await
+        .context("Failed to parse OpenAI transcription response")?;
+
+    let segments = transcription
+        .segments
+        .into_iter()
+        .filter(|segment| !segment.text.trim().is_empty())
+        .map(|segment| TranscriptSegment {
+            start_ms: (segment.start * 1000.0).round() as u64,
+            end_ms: (segment.end * 1000.0).round() as u64,
+            text: segment.text.trim().to_string(),
+            speaker: None,
+            confidence: segment.avg_logprob.map(|logprob| logprob.exp() as f32),
+        })
+        .collect();
+
+    Ok(Transcription {
+        text: transcription.text.trim().to_string(),
+        segments,
+        language: transcription.language,
+        provider: Some("openai".to_string()),
+        model: Some(config.transcription_model.clone()),
+    })
+}
+
+#[derive(Deserialize)]
+struct ChatCompletion {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
+#[derive(Deserialize)]
+struct ChatMessage {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+/// Run a chat completion with `openai.analysis_model` and return the reply
+pub async fn chat(config: &OpenAIConfig, system: &str, user: &str) -> Result<String> {
+    let client = client(config)?;
+    let url = endpoint(config, "chat/completions");
+    let body = serde_json::json!({
+        "model": config.analysis_model,
+        "messages": [
+            { "role": "system", "content": system },
+            { "role": "user", "content": user },
+        ],
+    });
+
+    let response = send_with_retry(config, "analysis", || {
+        Ok(authorize(config, client.post(&url)).json(&body))
+    })
+    .await?;
+
+    let completion: ChatCompletion = response
+        .json()
+        .await
+        .context("Failed to parse OpenAI chat completion response")?;
+    completion
+        .choices
+        .into_iter()
+        .next()
+        .map(|choice| choice.message.content.unwrap_or_default())
+        .context("OpenAI chat completion returned no choices")
+}
@@ -0,0 +1,161 @@
+// src/probe.rs
+//! Checking audio files before they are transcribed
+//!
+//! A truncated or corrupt recording otherwise fails deep inside the
+//! transcription provider with an error that says little about the cause.
+//! The format is recognized from the file's content rather than its name,
+//! since uploads are stored as `.wav` whatever they contain. WAV files are
+//! read by their header and must hold every sample it announces; other
+//! formats the providers accept are only recognized by their signature.
+
+use std::fmt;
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Audio container recognized by [`check`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioFormat {
+    Wav,
+    Mp3,
+    Flac,
+    Ogg,
+    Webm,
+    Mp4,
+}
+
+impl fmt::Display for AudioFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AudioFormat::Wav => "wav",
+            AudioFormat::Mp3 => "mp3",
+            AudioFormat::Flac => "flac",
+            AudioFormat::Ogg => "ogg",
+            AudioFormat::Webm => "webm",
+            AudioFormat::Mp4 => "mp4",
+        };
+        f.write_str(name)
+    }
+}
+
+/// What [`check`] found out about a readable audio file
+#[derive(Debug, Clone, Copy)]
+pub struct AudioInfo {
+    pub format: AudioFormat,
+    /// Only known for WAV files
+    pub duration_ms: Option<u64>,
+}
+
+/// An audio file that cannot be transcribed
+#[derive(Debug)]
+pub struct UnreadableAudio {
+    pub reason: String,
+    /// Format the file claims to be, if it was recognized
+    pub format: Option<AudioFormat>,
+    /// Duration announced by the header, if it could be read
+    pub duration_ms: Option<u64>,
+}
+
+impl fmt::Display for UnreadableAudio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "audio file is corrupt or unreadable: {}", self.reason)?;
+        match (self.format, self.duration_ms) {
+            (Some(format), Some(duration_ms)) => write!(f, " (format: {}, duration: {} ms)", format, duration_ms),
+            (Some(format), None) => write!(f, " (format: {})", format),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for UnreadableAudio {}
+
+/// Check that `path` is a non-empty audio file in a supported format
+pub fn check(path: &Path) -> Result<AudioInfo, UnreadableAudio> {
+    let file = std::fs::File::open(path).map_err(|e| UnreadableAudio {
+        reason: format!("cannot open {}: {}", path.display(), e),
+        format: None,
+        duration_ms: None,
+    })?;
+    check_reader(std::io::BufReader::new(file))
+}
+
+/// Check the contents of an audio file already read into memory
+pub fn check_bytes(data: &[u8]) -> Result<AudioInfo, UnreadableAudio> {
+    check_reader(Cursor::new(data))
+}
+
+fn check_reader<R: Read + Seek>(mut reader: R) -> Result<AudioInfo, UnreadableAudio> {
+    let unreadable = |reason: String| UnreadableAudio { reason, format: None, duration_ms: None };
+
+    let mut header = Vec::with_capacity(12);
+    (&mut reader)
+        .take(12)
+        .read_to_end(&mut header)
+        .and_then(|_| reader.seek(SeekFrom::Start(0)))
+        .map_err(|e| unreadable(format!("cannot read the file: {}", e)))?;
+    if header.is_empty() {
+        return Err(unreadable("the file is empty".to_string()));
+    }
+
+    let format = sniff(&header).ok_or_else(|| unreadable("not a supported audio format".to_string()))?;
+    let duration_ms = match format {
+        AudioFormat::Wav => Some(check_wav(reader)?),
+        _ => None,
+    };
+    Ok(AudioInfo { format, duration_ms })
+}
+
+/// Recognize a format by the first bytes of a file
+fn sniff(header: &[u8]) -> Option<AudioFormat> {
+    let format = if header.starts_with(b"RIFF") && header.get(8..12) == Some(b"WAVE") {
+        AudioFormat::Wav
+    } else if header.starts_with(b"fLaC") {
+        AudioFormat::Flac
+    } else if header.starts_with(b"OggS") {
+        AudioFormat::Ogg
+    } else if header.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        AudioFormat::Webm
+    } else if header.get(4..8) == Some(b"ftyp") {
+        AudioFormat::Mp4
+    } else if header.starts_with(b"ID3") || (header.len() >= 2 && header[0] == 0xFF && header[1] & 0xE0 == 0xE0) {
+        // An ID3 tag, or the sync word of an MPEG audio frame
+        AudioFormat::Mp3
+    } else {
+        return None;
+    };
+    Some(format)
+}
+
+/// Duration of a WAV file whose header and samples can all be read
+fn check_wav<R: Read + Seek>(reader: R) -> Result<u64, UnreadableAudio> {
+    let unreadable = |reason: String, duration_ms: Option<u64>| UnreadableAudio {
+        reason,
+        format: Some(AudioFormat::Wav),
+        duration_ms,
+    };
+
+    let mut reader = hound::WavReader::new(reader).map_err(|e| unreadable(format!("invalid WAV header: {}", e), None))?;
+    let spec = reader.spec();
+    let frames = reader.duration();
+    if spec.sample_rate == 0 || spec.channels == 0 {
+        return Err(unreadable("the WAV header has no sample rate or channels".to_string(), None));
+    }
+    let duration_ms = frames as u64 * 1000 / spec.sample_rate as u64;
+    if frames == 0 {
+        return Err(unreadable("the file contains no audio".to_string(), Some(duration_ms)));
+    }
+
+    // A truncated file announces more samples than it holds, so its last
+    // frame cannot be read
+    reader
+        .seek(frames - 1)
+        .map_err(|e| unreadable(format!("cannot seek to the end of the audio: {}", e), Some(duration_ms)))?;
+    let last = match spec.sample_format {
+        hound::SampleFormat::Float => reader.samples::<f32>().next().map(|sample| sample.map(|_| ())),
+        hound::SampleFormat::Int => reader.samples::<i32>().next().map(|sample| sample.map(|_| ())),
+    };
+    match last {
+        Some(Ok(())) => Ok(duration_ms),
+        Some(Err(e)) => Err(unreadable(format!("the audio data is truncated: {}", e), Some(duration_ms))),
+        None => Err(unreadable("the audio data is truncated".to_string(), Some(duration_ms))),
+    }
+}
@@ -0,0 +1,128 @@
+// src/reanalyze.rs
+//! Re-analysis of stored sessions with a chosen provider and model
+//!
+//! Unlike `backfill`, which only fills in missing analyses, every session
+//! with a transcript is analyzed again. The analysis it had is kept in the
+//! session's `analysis_history`, and its title is left as it is. Sessions
+//! without a transcript are skipped.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use log::{error, info, warn};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::ai::analyze_transcript_with_model;
+use crate::storage;
+use crate::summary::{Outcome, RunSummary};
+
+/// What to re-analyze and with which model
+#[derive(Debug, Clone)]
+pub struct ReanalyzeOptions {
+    /// `ollama` or `openai`
+    pub provider: String,
+    /// Model to use instead of the provider's configured one
+    pub model: Option<String>,
+    /// Only re-analyze sessions created at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Number of sessions analyzed concurrently
+    pub jobs: usize,
+    /// Seconds to wait for each analysis instead of `analysis.timeout_secs`
+    pub timeout_secs: Option<u64>,
+}
+
+/// Re-analyze every session with a transcript, at most `options.jobs` at a time
+pub async fn reanalyze_sessions(options: &ReanalyzeOptions) -> Result<RunSummary<String>> {
+    let filter = storage::SessionFilter {
+        created_after: options.since,
+        ..Default::default()
+    };
+    let sessions = storage::list_sessions_filtered(&filter)
+        .await
+        .context("Failed to list sessions")?;
+
+    let mut summary = RunSummary::default();
+    let mut ids = Vec::new();
+    for session in sessions {
+        if session.transcript.as_deref().map_or(true, |t| t.trim().is_empty()) {
+            summary.push(session.id, Outcome::Skipped { reason: "no transcript".to_string() });
+        } else {
+            ids.push(session.id);
+        }
+    }
+
+    let total = ids.len();
+    info!(
+        "Re-analyzing {} sessions with {}{} ({} without a transcript skipped)",
+        total,
+        options.provider,
+        options.model.as_deref().map(|m| format!(" ({})", m)).unwrap_or_default(),
+        summary.skipped()
+    );
+
+    let semaphore = Arc::new(Semaphore::new(options.jobs.max(1)));
+    let finished = Arc::new(AtomicUsize::new(0));
+    let mut tasks = JoinSet::new();
+    for id in ids {
+        let semaphore = semaphore.clone();
+        let finished = finished.clone();
+        let options = options.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await.expect("semaphore closed");
+            let outcome = match reanalyze_session(&id, &options).await {
+                Ok(outcome) => outcome,
+                Err(e) => Outcome::Failed { error: format!("{:#}", e) },
+            };
+            let done = finished.fetch_add(1, Ordering::Relaxed) + 1;
+            match &outcome {
+                Outcome::Failed { error } => warn!("[{}/{}] {} failed: {}", done, total, id, error),
+                _ => info!("[{}/{}] {} done", done, total, id),
+            }
+            (id, outcome)
+        });
+    }
+
+    while let Some(result) = tasks.join_next().await {
+        match result {
+            Ok((id, outcome)) => summary.push(id, outcome),
+            Err(e) => error!("Re-analysis task panicked: {}", e),
+        }
+    }
+    summary.sort();
+
+    Ok(summary)
+}
+
+/// Analyze one session again and save it with the new analysis
+async fn reanalyze_session(id: &str, options: &ReanalyzeOptions) -> Result<Outcome> {
+    let Some(_lock) = crate::analysis_lock::try_lock(id)? else {
+        return Ok(Outcome::Skipped { reason: "being analyzed elsewhere".to_string() });
+    };
+    let Some(session) = storage::get_session(id).await? else {
+        return Ok(Outcome::Skipped { reason: "deleted meanwhile".to_string() });
+    };
+    let Some(transcript) = session.transcript.clone() else {
+        return Ok(Outcome::Skipped { reason: "no transcript".to_string() });
+    };
+
+    let analysis =
+        analyze_transcript_with_model(&transcript, &options.provider, options.model.as_deref(), options.timeout_secs)
+            .await?;
+    let detail = format!("({})", analysis.model.as_deref().unwrap_or("unknown model"));
+
+    // Reload so changes made while the analysis ran are not overwritten
+    let mut session = storage::get_session(id)
+        .await?
+        .with_context(|| format!("Session {} was deleted during re-analysis", id))?;
+    session.replace_analysis(analysis);
+    storage::save_session(&mut session, None).await?;
+
+    Ok(Outcome::Done { detail })
+}
+
+/// Log a per-session summary of a re-analysis run
+pub fn log_summary(summary: &RunSummary<String>) {
+    summary.log("Re-analysis", "re-analyzed", |id| id.clone());
+}
@@ -0,0 +1,177 @@
+// src/redact.rs
+//! Masking of personal data in exported transcripts
+//!
+//! Email addresses, phone numbers, credit card numbers and any configured
+//! pattern are replaced with `redaction.replacement` when a transcript is
+//! exported. Stored transcripts are left as recorded, so an export with
+//! `redact=false` still returns the original text.
+//!
+//! The built-in patterns only use ASCII character classes, so they never
+//! match inside Chinese text, and matches are always replaced whole, which
+//! keeps the output valid UTF-8.
+
+use anyhow::{Context, Result};
+use regex::{Captures, Regex};
+
+use crate::config::RedactionConfig;
+use crate::storage::{AnalysisResult, VoiceSession};
+
+/// Names accepted in `redaction.patterns`, in the order they are applied
+///
+/// Card numbers go before phone numbers so a card is masked as a whole
+/// rather than the phone pattern taking its first 15 digits.
+pub const BUILTIN_PATTERNS: &[&str] = &["email", "credit_card", "phone"];
+
+const EMAIL_PATTERN: &str = r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}";
+const CREDIT_CARD_PATTERN: &str = r"[0-9](?:[ -]?[0-9]){12,18}";
+const PHONE_PATTERN: &str = r"\+?\(?[0-9](?:[ ().-]{0,2}[0-9]){6,14}";
+
+struct Rule {
+    regex: Regex,
+    /// Whether a match really is the kind of data the rule is after
+    accept: fn(&str) -> bool,
+    /// Skip matches that are only part of a longer run of digits
+    whole_number: bool,
+}
+
+/// Applies the configured redaction patterns to text
+pub struct Redactor {
+    rules: Vec<Rule>,
+    replacement: String,
+}
+
+impl Redactor {
+    /// Build a redactor, failing on unknown built-in names or invalid
+    /// custom patterns
+    pub fn new(config: &RedactionConfig) -> Result<Self> {
+        if let Some(unknown) = config.patterns.iter().find(|name| !BUILTIN_PATTERNS.contains(&name.as_str())) {
+            return Err(anyhow::anyhow!(
+                "Unknown redaction pattern: {}. Supported patterns: {}",
+                unknown,
+                BUILTIN_PATTERNS.join(", ")
+            ));
+        }
+
+        let mut rules = Vec::new();
+        for name in BUILTIN_PATTERNS.iter().filter(|name| config.patterns.iter().any(|p| p == *name)) {
+            let rule = match *name {
+                "email" => Rule { regex: Regex::new(EMAIL_PATTERN)?, accept: |_| true, whole_number: false },
+                "credit_card" => Rule { regex: Regex::new(CREDIT_CARD_PATTERN)?, accept: passes_luhn, whole_number: true },
+                _ => Rule { regex: Regex::new(PHONE_PATTERN)?, accept: is_phone_number, whole_number: true },
+            };
+            rules.push(rule);
+        }
+        for pattern in &config.custom_patterns {
+            let regex = Regex::new(pattern).context(format!("Invalid redaction pattern: {}", pattern))?;
+            rules.push(Rule { regex, accept: |_| true, whole_number: false });
+        }
+
+        Ok(Self { rules, replacement: config.replacement.clone() })
+    }
+
+    /// Build a redactor from the current configuration
+    pub fn from_config() -> Result<Self> {
+        Self::new(&crate::config::current().redaction)
+    }
+
+    /// `text` with every match of the configured patterns replaced
+    pub fn redact(&self, text: &str) -> String {
+        let mut redacted = text.to_string();
+        for rule in &self.rules {
+            let haystack = redacted.clone();
+            redacted = rule
+                .regex
+                .replace_all(&haystack, |caps: &Captures| {
+                    let m = caps.get(0).expect("capture group 0 is always present");
+                    let part_of_number = rule.whole_number
+                        && (haystack[..m.start()].ends_with(|c: char| c.is_ascii_digit())
+                            || haystack[m.end()..].starts_with(|c: char| c.is_ascii_digit()));
+                    if part_of_number || !(rule.accept)(m.as_str()) {
+                        m.as_str().to_string()
+                    } else {
+                        self.replacement.clone()
+                    }
+                })
+                .into_owned();
+        }
+        redacted
+    }
+
+    /// Redact the `text` of every segment in a JSON segment list
+    pub fn redact_segments(&self, segments: &mut serde_json::Value) {
+        let Some(segments) = segments.as_array_mut() else {
+            return;
+        };
+        for segment in segments {
+            if let Some(text) = segment.get_mut("text") {
+                if let Some(redacted) = text.as_str().map(|t| self.redact(t)) {
+                    *text = serde_json::Value::String(redacted);
+                }
+            }
+        }
+    }
+
+    /// A copy of `session` with its title, transcript, segments and the text
+    /// of its analysis and earlier analyses redacted
+    pub fn redact_session(&self, session: &VoiceSession) -> VoiceSession {
+        let mut session = session.clone();
+        session.title = self.redact(&session.title);
+        session.transcript = session.transcript.as_deref().map(|t| self.redact(t));
+        for segment in &mut session.segments {
+            segment.text = self.redact(&segment.text);
+        }
+        let history = session.analysis_history.iter_mut().map(|archived| &mut archived.analysis);
+        for analysis in session.analysis.iter_mut().chain(history) {
+            self.redact_analysis(analysis);
+        }
+        session
+    }
+
+    fn redact_analysis(&self, analysis: &mut AnalysisResult) {
+        analysis.title = self.redact(&analysis.title);
+        analysis.summary = self.redact(&analysis.summary);
+        for idea in &mut analysis.ideas {
+            *idea = self.redact(idea);
+        }
+        for task in &mut analysis.tasks {
+            task.title = self.redact(&task.title);
+            task.description = task.description.as_deref().map(|d| self.redact(d));
+        }
+        for note in &mut analysis.structured_notes {
+            note.title = self.redact(&note.title);
+            note.content = self.redact(&note.content);
+        }
+    }
+}
+
+/// Whether an export should be redacted: the request's `redact` value if
+/// given, otherwise `redaction.enabled`
+pub fn requested(redact: Option<bool>) -> bool {
+    redact.unwrap_or_else(|| crate::config::current().redaction.enabled)
+}
+
+/// Luhn checksum, so order numbers and other long digit runs are kept
+fn passes_luhn(candidate: &str) -> bool {
+    let digits: Vec<u32> = candidate.chars().filter_map(|c| c.to_digit(10)).collect();
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| if i % 2 == 1 { if d * 2 > 9 { d * 2 - 9 } else { d * 2 } } else { d })
+        .sum();
+    sum % 10 == 0
+}
+
+/// 7 to 15 digits, excluding dates such as 2026-10-16 or 2026/10/16
+fn is_phone_number(candidate: &str) -> bool {
+    let digits = candidate.chars().filter(|c| c.is_ascii_digit()).count();
+    if !(7..=15).contains(&digits) {
+        return false;
+    }
+    let groups: Vec<&str> = candidate.split(['-', '/', '.']).collect();
+    let is_date = groups.len() == 3
+        && groups[0].len() == 4
+        && groups[1..].iter().all(|g| (1..=2).contains(&g.len()))
+        && groups.iter().all(|g| g.chars().all(|c| c.is_ascii_digit()));
+    !is_date
+}
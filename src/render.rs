@@ -0,0 +1,199 @@
+// src/render.rs
+//! Human-readable rendering of sessions
+//!
+//! Used by the CLI to print sessions as plain text, markdown or CSV instead of
+//! Rust debug output.
+
+use std::fmt::Write;
+
+use crate::ollama::AnalysisType;
+use crate::storage::{AnalysisResult, VoiceSession};
+
+/// Render a session index as CSV with a header row, one session per row
+///
+/// Titles are quoted and escaped as needed, so commas, quotes and line breaks
+/// in them survive a round trip through a spreadsheet.
+pub fn sessions_to_csv(sessions: &[VoiceSession]) -> anyhow::Result<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(["id", "title", "timestamp", "duration_ms", "has_transcript", "has_analysis"])?;
+    for session in sessions {
+        writer.write_record([
+            session.id.clone(),
+            session.title.clone(),
+            session.timestamp.to_rfc3339(),
+            session.duration_ms.to_string(),
+            session.transcript.is_some().to_string(),
+            session.analysis.is_some().to_string(),
+        ])?;
+    }
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+/// Render a session as plain text
+pub fn session_to_text(session: &VoiceSession) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "Title:     {}", display_title(session));
+    let _ = writeln!(out, "ID:        {}", session.id);
+    let _ = writeln!(out, "Recorded:  {}", session.timestamp.format("%Y-%m-%d %H:%M:%S UTC"));
+    let _ = writeln!(out, "Duration:  {}", format_duration(session.duration_ms));
+    let _ = writeln!(out, "Audio:     {}", session.audio_file_path.display());
+
+    out.push_str("\nTranscript:\n");
+    match &session.transcript {
+        Some(transcript) => {
+            let _ = writeln!(out, "{}", transcript);
+        }
+        None => out.push_str("(not transcribed)\n"),
+    }
+
+    if let Some(analysis) = &session.analysis {
+        let _ = writeln!(out, "\nSummary:\n{}", analysis.summary);
+
+        if !analysis.ideas.is_empty() {
+            out.push_str("\nIdeas:\n");
+            for idea in &analysis.ideas {
+                let _ = writeln!(out, "  - {}", idea);
+            }
+        }
+
+        if !analysis.tasks.is_empty() {
+            out.push_str("\nTasks:\n");
+            for task in &analysis.tasks {
+                let _ = writeln!(out, "  - [{:?}] {}", task.priority, task.title);
+                if let Some(description) = &task.description {
+                    let _ = writeln!(out, "      {}", description);
+                }
+            }
+        }
+
+        if !analysis.structured_notes.is_empty() {
+            out.push_str("\nNotes:\n");
+            for note in &analysis.structured_notes {
+                let _ = writeln!(out, "  - {} ({:?})", note.title, note.note_type);
+            }
+        }
+    } else {
+        out.push_str("\nAnalysis: (not analyzed)\n");
+    }
+
+    out
+}
+
+/// Render a session as markdown
+pub fn session_to_markdown(session: &VoiceSession) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# {}\n", display_title(session));
+    let _ = writeln!(out, "- **ID:** {}", session.id);
+    let _ = writeln!(out, "- **Recorded:** {}", session.timestamp.format("%Y-%m-%d %H:%M:%S UTC"));
+    let _ = writeln!(out, "- **Duration:** {}\n", format_duration(session.duration_ms));
+
+    if let Some(analysis) = &session.analysis {
+        out.push_str(&analysis_to_markdown(analysis));
+    }
+
+    out.push_str("## Transcript\n\n");
+    match &session.transcript {
+        Some(transcript) => {
+            let _ = writeln!(out, "{}", transcript);
+        }
+        None => out.push_str("_Not transcribed._\n"),
+    }
+
+    out
+}
+
+/// Render the summary, ideas, tasks and notes of an analysis as markdown sections
+pub fn analysis_to_markdown(analysis: &AnalysisResult) -> String {
+    analysis_sections_to_markdown(analysis, &[AnalysisType::Full])
+}
+
+/// Whether the section produced by `section` was asked for
+fn wants_section(analysis_types: &[AnalysisType], section: AnalysisType) -> bool {
+    analysis_types.is_empty()
+        || analysis_types.contains(&AnalysisType::Full)
+        || analysis_types.contains(&section)
+}
+
+/// Render the sections of an analysis requested by `analysis_types` as
+/// markdown; `Full` renders all of them
+pub fn analysis_sections_to_markdown(analysis: &AnalysisResult, analysis_types: &[AnalysisType]) -> String {
+    let mut out = String::new();
+    if wants_section(analysis_types, AnalysisType::Summary) {
+        let _ = writeln!(out, "## Summary\n\n{}\n", analysis.summary);
+    }
+
+    if wants_section(analysis_types, AnalysisType::Ideas) && !analysis.ideas.is_empty() {
+        out.push_str("## Ideas\n\n");
+        for idea in &analysis.ideas {
+            let _ = writeln!(out, "- {}", idea);
+        }
+        out.push('\n');
+    }
+
+    if wants_section(analysis_types, AnalysisType::Tasks) && !analysis.tasks.is_empty() {
+        out.push_str("## Tasks\n\n");
+        for task in &analysis.tasks {
+            let _ = write!(out, "- [ ] **{}** _({:?})_", task.title, task.priority);
+            if let Some(due_date) = task.due_date {
+                let _ = write!(out, " — due {}", due_date.format("%Y-%m-%d"));
+            }
+            out.push('\n');
+            if let Some(description) = &task.description {
+                let _ = writeln!(out, "  {}", description);
+            }
+        }
+        out.push('\n');
+    }
+
+    if wants_section(analysis_types, AnalysisType::StructuredNotes) && !analysis.structured_notes.is_empty() {
+        out.push_str("## Notes\n\n");
+        for note in &analysis.structured_notes {
+            let _ = writeln!(out, "### {} ({:?})\n", note.title, note.note_type);
+            let _ = writeln!(out, "{}\n", note.content);
+            if !note.tags.is_empty() {
+                let _ = writeln!(out, "_Tags: {}_\n", note.tags.join(", "));
+            }
+        }
+    }
+
+    out
+}
+
+/// The fields of an analysis requested by `analysis_types` as a JSON object;
+/// `Full` keeps the whole analysis
+pub fn analysis_sections_to_json(
+    analysis: &AnalysisResult,
+    analysis_types: &[AnalysisType],
+) -> serde_json::Result<serde_json::Value> {
+    let serde_json::Value::Object(fields) = serde_json::to_value(analysis)? else {
+        unreachable!("an analysis serializes to an object");
+    };
+    if wants_section(analysis_types, AnalysisType::Full) {
+        return Ok(serde_json::Value::Object(fields));
+    }
+
+    let sections = fields
+        .into_iter()
+        .filter(|(key, _)| match key.as_str() {
+            "title" | "summary" => wants_section(analysis_types, AnalysisType::Summary),
+            "ideas" => wants_section(analysis_types, AnalysisType::Ideas),
+            "tasks" => wants_section(analysis_types, AnalysisType::Tasks),
+            "structured_notes" => wants_section(analysis_types, AnalysisType::StructuredNotes),
+            _ => true,
+        })
+        .collect();
+    Ok(serde_json::Value::Object(sections))
+}
+
+fn display_title(session: &VoiceSession) -> &str {
+    if session.title.is_empty() {
+        "Untitled session"
+    } else {
+        &session.title
+    }
+}
+
+fn format_duration(duration_ms: u64) -> String {
+    let total_secs = duration_ms / 1000;
+    format!("{}:{:02}", total_secs / 60, total_secs % 60)
+}
@@ -6,6 +6,7 @@
 
 pub mod traits;
 pub mod postgres;
+pub mod relations;
 
 // Re-export commonly used types and traits
 pub use traits::*;
@@ -20,6 +21,8 @@ pub trait RepositoryManager: Send + Sync {
     type IdeaRepo: IdeaRepository;
     type TaskRepo: TaskRepository;
     type StructuredNoteRepo: StructuredNoteRepository;
+    type NoteTemplateRepo: NoteTemplateRepository;
+    type ArchiveRepo: ArchiveRepository;
 
     /// Get session repository
     fn sessions(&self) -> &Self::SessionRepo;
@@ -41,4 +44,10 @@ pub trait RepositoryManager: Send + Sync {
     
     /// Get structured note repository
     fn structured_notes(&self) -> &Self::StructuredNoteRepo;
+    
+    /// Get note template repository
+    fn note_templates(&self) -> &Self::NoteTemplateRepo;
+    
+    /// Get archive repository for full exports and imports
+    fn archive(&self) -> &Self::ArchiveRepo;
 }
\ No newline at end of file
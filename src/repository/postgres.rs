@@ -8,7 +8,8 @@ use async_trait::async_trait;
 use sqlx::{PgPool, Row};
 use uuid::Uuid;
 use anyhow::{Result, Context};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashMap;
 
 use super::traits::*;
 
@@ -82,40 +83,40 @@ impl SessionRepository for PostgresSessionRepository {
         }))
     }
 
-    async fn list(&self, filter: &SessionFilter) -> Result<Vec<Session>> {
-        let mut query = "SELECT id, title, created_at, updated_at, duration_ms, status, metadata FROM sessions WHERE status != 'deleted'".to_string();
-        let mut conditions = Vec::new();
-        let mut params: Vec<Box<dyn sqlx::Encode<'_, sqlx::Postgres> + Send + Sync>> = Vec::new();
-        let mut param_count = 1;
-
-        if let Some(search) = &filter.search {
-            conditions.push(format!("title ILIKE ${}", param_count));
-            params.push(Box::new(format!("%{}%", search)));
-            param_count += 1;
-        }
-
-        if let Some(status) = &filter.status {
-            conditions.push(format!("status = ${}", param_count));
-            params.push(Box::new(status.clone()));
-            param_count += 1;
-        }
+    async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Session>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, title, created_at, updated_at, duration_ms, status as "status: SessionStatus", metadata
+            FROM sessions
+            WHERE id = ANY($1) AND status != 'deleted'
+            "#,
+            ids
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to find sessions by ids")?;
 
-        if let Some(created_after) = &filter.created_after {
-            conditions.push(format!("created_at >= ${}", param_count));
-            params.push(Box::new(*created_after));
-            param_count += 1;
-        }
+        let sessions = rows
+            .into_iter()
+            .map(|r| Session {
+                id: r.id,
+                title: r.title,
+                created_at: r.created_at,
+                updated_at: r.updated_at,
+                duration_ms: r.duration_ms,
+                status: r.status,
+                metadata: r.metadata,
+            })
+            .collect();
 
-        if let Some(created_before) = &filter.created_before {
-            conditions.push(format!("created_at <= ${}", param_count));
-            params.push(Box::new(*created_before));
-            param_count += 1;
-        }
+        Ok(sessions)
+    }
 
-        if !conditions.is_empty() {
-            query.push_str(" AND ");
-            query.push_str(&conditions.join(" AND "));
-        }
+    async fn list(&self, filter: &SessionFilter) -> Result<Vec<Session>> {
+        let mut query = sqlx::QueryBuilder::new(
+            "SELECT id, title, created_at, updated_at, duration_ms, status, metadata FROM sessions WHERE status != 'deleted'",
+        );
+        push_session_conditions(&mut query, filter);
 
         // Add sorting
         let sort_column = match filter.sort_by {
@@ -131,18 +132,19 @@ impl SessionRepository for PostgresSessionRepository {
             Some(SortOrder::Desc) | None => "DESC",
         };
 
-        query.push_str(&format!(" ORDER BY {} {}", sort_column, sort_order));
+        query.push(format!(" ORDER BY {} {}", sort_column, sort_order));
 
         // Add pagination
         if let Some(limit) = filter.limit {
-            query.push_str(&format!(" LIMIT {}", limit));
+            query.push(" LIMIT ").push_bind(limit);
         }
 
         if let Some(offset) = filter.offset {
-            query.push_str(&format!(" OFFSET {}", offset));
+            query.push(" OFFSET ").push_bind(offset);
         }
 
-        let rows = sqlx::query(&query)
+        let rows = query
+            .build()
             .fetch_all(&self.pool)
             .await
             .context("Failed to list sessions")?;
@@ -199,41 +201,57 @@ impl SessionRepository for PostgresSessionRepository {
 
     async fn delete(&self, id: &Uuid) -> Result<()> {
         let now = Utc::now();
-        
+        let mut tx = self.pool.begin().await.context("Failed to start session delete")?;
+
         sqlx::query!(
             "UPDATE sessions SET status = 'deleted', updated_at = $2 WHERE id = $1",
             id,
             now
         )
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await
         .context("Failed to delete session")?;
 
+        // The session's analyses and what was extracted from them go with it,
+        // so they don't keep showing up in their own lists
+        for table in ["ideas", "tasks", "structured_notes"] {
+            let sql = format!(
+                r#"
+                UPDATE {table} SET deleted_at = $2
+                WHERE deleted_at IS NULL
+                  AND analysis_id IN (SELECT id FROM analysis_results WHERE session_id = $1)
+                "#,
+                table = table
+            );
+            sqlx::query(&sql)
+                .bind(id)
+                .bind(now)
+                .execute(&mut *tx)
+                .await
+                .with_context(|| format!("Failed to soft-delete {} of session", table))?;
+        }
+        sqlx::query("UPDATE analysis_results SET deleted_at = $2 WHERE session_id = $1 AND deleted_at IS NULL")
+            .bind(id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to soft-delete analyses of session")?;
+
+        tx.commit().await.context("Failed to commit session delete")?;
         Ok(())
     }
 
-    async fn count(&self, filter: &SessionFilter) -> Result<i64> {
-        let mut query = "SELECT COUNT(*) FROM sessions WHERE status != 'deleted'".to_string();
-        let mut conditions = Vec::new();
-
-        if let Some(search) = &filter.search {
-            conditions.push(format!("title ILIKE '%{}%'", search));
-        }
-
-        if let Some(status) = &filter.status {
-            conditions.push(format!("status = '{}'", match status {
-                SessionStatus::Active => "active",
-                SessionStatus::Archived => "archived",
-                SessionStatus::Deleted => "deleted",
-            }));
-        }
+    async fn purge(&self, id: &Uuid) -> Result<bool> {
+        // Audio files, transcripts and analyses go with it by ON DELETE CASCADE
+        purge(&self.pool, "sessions", id).await
+    }
 
-        if !conditions.is_empty() {
-            query.push_str(" AND ");
-            query.push_str(&conditions.join(" AND "));
-        }
+    async fn count(&self, filter: &SessionFilter) -> Result<i64> {
+        let mut query = sqlx::QueryBuilder::new("SELECT COUNT(*) FROM sessions WHERE status != 'deleted'");
+        push_session_conditions(&mut query, filter);
 
-        let row = sqlx::query(&query)
+        let row = query
+            .build()
             .fetch_one(&self.pool)
             .await
             .context("Failed to count sessions")?;
@@ -270,6 +288,106 @@ impl SessionRepository for PostgresSessionRepository {
 
         Ok(sessions)
     }
+
+    async fn find_by_legacy_id(&self, legacy_id: &str) -> Result<Option<Session>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT id, title, created_at, updated_at, duration_ms, status as "status: SessionStatus", metadata
+            FROM sessions
+            WHERE metadata->>'legacy_session_id' = $1 AND status != 'deleted'
+            LIMIT 1
+            "#,
+            legacy_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to find session by legacy id")?;
+
+        Ok(row.map(|r| Session {
+            id: r.id,
+            title: r.title,
+            created_at: r.created_at,
+            updated_at: r.updated_at,
+            duration_ms: r.duration_ms,
+            status: r.status,
+            metadata: r.metadata,
+        }))
+    }
+
+    async fn merge_metadata(
+        &self,
+        id: &Uuid,
+        patch: &serde_json::Map<String, serde_json::Value>,
+    ) -> Result<Option<Session>> {
+        let (removed, set): (Vec<_>, Vec<_>) = patch.iter().partition(|(_, value)| value.is_null());
+        let set: serde_json::Map<String, serde_json::Value> =
+            set.into_iter().map(|(key, value)| (key.clone(), value.clone())).collect();
+        let removed: Vec<String> = removed.into_iter().map(|(key, _)| key.clone()).collect();
+
+        // Metadata that is not an object, from before it was validated, is replaced
+        let row = sqlx::query(
+            r#"
+            UPDATE sessions
+            SET metadata = (
+                    CASE WHEN jsonb_typeof(metadata) = 'object' THEN metadata ELSE '{}'::jsonb END || $2
+                ) - $3::text[],
+                updated_at = NOW()
+            WHERE id = $1 AND status != 'deleted'
+            RETURNING id, title, created_at, updated_at, duration_ms, status, metadata
+            "#,
+        )
+        .bind(id)
+        .bind(serde_json::Value::Object(set))
+        .bind(&removed)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to update session metadata")?;
+
+        Ok(row.map(|row| Session {
+            id: row.get("id"),
+            title: row.get("title"),
+            created_at: row.get("created_at"),
+            updated_at: row.get("updated_at"),
+            duration_ms: row.get("duration_ms"),
+            status: row.get("status"),
+            metadata: row.get("metadata"),
+        }))
+    }
+}
+
+/// Append the `WHERE` conditions of `filter` to a query on `sessions`
+///
+/// Metadata filters use containment (`@>`), which the GIN index on
+/// `sessions.metadata` serves.
+fn push_session_conditions(query: &mut sqlx::QueryBuilder<'_, sqlx::Postgres>, filter: &SessionFilter) {
+    if let Some(search) = &filter.search {
+        query.push(" AND title ILIKE ").push_bind(format!("%{}%", search));
+    }
+
+    if let Some(status) = &filter.status {
+        query.push(" AND status = ").push_bind(status.clone());
+    }
+
+    if let Some(created_after) = &filter.created_after {
+        query.push(" AND created_at >= ").push_bind(*created_after);
+    }
+
+    if let Some(created_before) = &filter.created_before {
+        query.push(" AND created_at <= ").push_bind(*created_before);
+    }
+
+    for (key, value) in &filter.metadata {
+        query
+            .push(" AND (metadata @> ")
+            .push_bind(serde_json::json!({ key: value }));
+        let scalar = serde_json::from_str::<serde_json::Value>(value)
+            .ok()
+            .filter(|scalar| scalar.is_number() || scalar.is_boolean());
+        if let Some(scalar) = scalar {
+            query.push(" OR metadata @> ").push_bind(serde_json::json!({ key: scalar }));
+        }
+        query.push(")");
+    }
 }
 
 /// PostgreSQL audio repository implementation
@@ -348,12 +466,45 @@ impl AudioRepository for PostgresAudioRepository {
         }))
     }
 
+    async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<AudioFile>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, session_id, file_path, file_size, format, sample_rate, channels, created_at, checksum
+            FROM audio_files
+            WHERE id = ANY($1)
+            "#,
+            ids
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to find audio files by ids")?;
+
+        let audio_files = rows
+            .into_iter()
+            .map(|row| AudioFile {
+                id: row.id,
+                session_id: row.session_id,
+                file_path: row.file_path,
+                file_size: row.file_size,
+                format: row.format,
+                sample_rate: row.sample_rate,
+                channels: row.channels,
+                created_at: row.created_at,
+                checksum: row.checksum,
+            })
+            .collect();
+
+        Ok(audio_files)
+    }
+
     async fn find_by_session_id(&self, session_id: &Uuid) -> Result<Option<AudioFile>> {
         let row = sqlx::query!(
             r#"
             SELECT id, session_id, file_path, file_size, format, sample_rate, channels, created_at, checksum
             FROM audio_files
             WHERE session_id = $1
+            ORDER BY created_at, id
+            LIMIT 1
             "#,
             session_id
         )
@@ -374,6 +525,38 @@ impl AudioRepository for PostgresAudioRepository {
         }))
     }
 
+    async fn find_all_by_session_id(&self, session_id: &Uuid) -> Result<Vec<AudioFile>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, session_id, file_path, file_size, format, sample_rate, channels, created_at, checksum
+            FROM audio_files
+            WHERE session_id = $1
+            ORDER BY created_at, id
+            "#,
+            session_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to find audio files by session id")?;
+
+        let audio_files = rows
+            .into_iter()
+            .map(|row| AudioFile {
+                id: row.id,
+                session_id: row.session_id,
+                file_path: row.file_path,
+                file_size: row.file_size,
+                format: row.format,
+                sample_rate: row.sample_rate,
+                channels: row.channels,
+                created_at: row.created_at,
+                checksum: row.checksum,
+            })
+            .collect();
+
+        Ok(audio_files)
+    }
+
     async fn delete(&self, id: &Uuid) -> Result<()> {
         sqlx::query!("DELETE FROM audio_files WHERE id = $1", id)
             .execute(&self.pool)
@@ -395,6 +578,34 @@ impl AudioRepository for PostgresAudioRepository {
 
         Ok(())
     }
+
+    async fn find_by_checksum(&self, checksum: &str) -> Result<Option<AudioFile>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT id, session_id, file_path, file_size, format, sample_rate, channels, created_at, checksum
+            FROM audio_files
+            WHERE checksum = $1
+            ORDER BY created_at
+            LIMIT 1
+            "#,
+            checksum
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to find audio file by checksum")?;
+
+        Ok(row.map(|r| AudioFile {
+            id: r.id,
+            session_id: r.session_id,
+            file_path: r.file_path,
+            file_size: r.file_size,
+            format: r.format,
+            sample_rate: r.sample_rate,
+            channels: r.channels,
+            created_at: r.created_at,
+            checksum: r.checksum,
+        }))
+    }
 }
 
 /// PostgreSQL repository manager implementation
@@ -406,6 +617,8 @@ pub struct PostgresRepositoryManager {
     ideas: PostgresIdeaRepository,
     tasks: PostgresTaskRepository,
     structured_notes: PostgresStructuredNoteRepository,
+    note_templates: PostgresNoteTemplateRepository,
+    archive: PostgresArchiveRepository,
 }
 
 impl PostgresRepositoryManager {
@@ -417,7 +630,9 @@ impl PostgresRepositoryManager {
             analysis_results: PostgresAnalysisRepository::new(pool.clone()),
             ideas: PostgresIdeaRepository::new(pool.clone()),
             tasks: PostgresTaskRepository::new(pool.clone()),
-            structured_notes: PostgresStructuredNoteRepository::new(pool),
+            structured_notes: PostgresStructuredNoteRepository::new(pool.clone()),
+            note_templates: PostgresNoteTemplateRepository::new(pool.clone()),
+            archive: PostgresArchiveRepository::new(pool),
         }
     }
 }
@@ -430,6 +645,8 @@ impl super::RepositoryManager for PostgresRepositoryManager {
     type IdeaRepo = PostgresIdeaRepository;
     type TaskRepo = PostgresTaskRepository;
     type StructuredNoteRepo = PostgresStructuredNoteRepository;
+    type NoteTemplateRepo = PostgresNoteTemplateRepository;
+    type ArchiveRepo = PostgresArchiveRepository;
 
     fn sessions(&self) -> &Self::SessionRepo {
         &self.sessions
@@ -458,6 +675,14 @@ impl super::RepositoryManager for PostgresRepositoryManager {
     fn structured_notes(&self) -> &Self::StructuredNoteRepo {
         &self.structured_notes
     }
+
+    fn note_templates(&self) -> &Self::NoteTemplateRepo {
+        &self.note_templates
+    }
+
+    fn archive(&self) -> &Self::ArchiveRepo {
+        &self.archive
+    }
 }
 
 // Placeholder implementations for other repositories
@@ -481,9 +706,9 @@ impl TranscriptRepository for PostgresTranscriptRepository {
         
         let row = sqlx::query!(
             r#"
-            INSERT INTO transcripts (id, session_id, content, language, confidence_score, provider, created_at, processing_time_ms)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-            RETURNING id, session_id, content, language, confidence_score, provider, created_at, processing_time_ms
+            INSERT INTO transcripts (id, session_id, content, language, confidence_score, provider, created_at, processing_time_ms, segments, model_used)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING id, session_id, content, language, confidence_score, provider, created_at, processing_time_ms, segments, model_used
             "#,
             id,
             transcript.session_id,
@@ -492,7 +717,9 @@ impl TranscriptRepository for PostgresTranscriptRepository {
             transcript.confidence_score,
             transcript.provider,
             now,
-            transcript.processing_time_ms
+            transcript.processing_time_ms,
+            transcript.segments,
+            transcript.model_used
         )
         .fetch_one(&self.pool)
         .await
@@ -507,13 +734,15 @@ impl TranscriptRepository for PostgresTranscriptRepository {
             provider: row.provider,
             created_at: row.created_at,
             processing_time_ms: row.processing_time_ms,
+            segments: row.segments,
+            model_used: row.model_used,
         })
     }
 
     async fn find_by_id(&self, id: &Uuid) -> Result<Option<Transcript>> {
         let row = sqlx::query!(
             r#"
-            SELECT id, session_id, content, language, confidence_score, provider, created_at, processing_time_ms
+            SELECT id, session_id, content, language, confidence_score, provider, created_at, processing_time_ms, segments, model_used
             FROM transcripts
             WHERE id = $1
             "#,
@@ -532,13 +761,15 @@ impl TranscriptRepository for PostgresTranscriptRepository {
             provider: r.provider,
             created_at: r.created_at,
             processing_time_ms: r.processing_time_ms,
+            segments: r.segments,
+            model_used: r.model_used,
         }))
     }
 
     async fn find_by_session_id(&self, session_id: &Uuid) -> Result<Option<Transcript>> {
         let row = sqlx::query!(
             r#"
-            SELECT id, session_id, content, language, confidence_score, provider, created_at, processing_time_ms
+            SELECT id, session_id, content, language, confidence_score, provider, created_at, processing_time_ms, segments, model_used
             FROM transcripts
             WHERE session_id = $1
             "#,
@@ -557,9 +788,41 @@ impl TranscriptRepository for PostgresTranscriptRepository {
             provider: r.provider,
             created_at: r.created_at,
             processing_time_ms: r.processing_time_ms,
+            segments: r.segments,
+            model_used: r.model_used,
         }))
     }
 
+    async fn find_by_session_ids(&self, session_ids: &[Uuid]) -> Result<Vec<Transcript>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, session_id, content, language, confidence_score, provider, created_at, processing_time_ms, segments, model_used
+            FROM transcripts
+            WHERE session_id = ANY($1)
+            "#,
+            session_ids
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to find transcripts by session ids")?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| Transcript {
+                id: r.id,
+                session_id: r.session_id,
+                content: r.content,
+                language: r.language,
+                confidence_score: r.confidence_score,
+                provider: r.provider,
+                created_at: r.created_at,
+                processing_time_ms: r.processing_time_ms,
+                segments: r.segments,
+                model_used: r.model_used,
+            })
+            .collect())
+    }
+
     async fn delete(&self, id: &Uuid) -> Result<()> {
         sqlx::query!("DELETE FROM transcripts WHERE id = $1", id)
             .execute(&self.pool)
@@ -572,7 +835,7 @@ impl TranscriptRepository for PostgresTranscriptRepository {
     async fn find_by_provider(&self, provider: &str) -> Result<Vec<Transcript>> {
         let rows = sqlx::query!(
             r#"
-            SELECT id, session_id, content, language, confidence_score, provider, created_at, processing_time_ms
+            SELECT id, session_id, content, language, confidence_score, provider, created_at, processing_time_ms, segments, model_used
             FROM transcripts
             WHERE provider = $1
             ORDER BY created_at DESC
@@ -594,11 +857,209 @@ impl TranscriptRepository for PostgresTranscriptRepository {
                 provider: row.provider,
                 created_at: row.created_at,
                 processing_time_ms: row.processing_time_ms,
+                segments: row.segments,
+                model_used: row.model_used,
             })
             .collect();
 
         Ok(transcripts)
     }
+
+    async fn update_content(&self, id: &Uuid, content: &str) -> Result<Option<Transcript>> {
+        let mut tx = self.pool.begin().await.context("Failed to start transcript update")?;
+        if !save_transcript_version(&mut tx, id).await? {
+            return Ok(None);
+        }
+
+        sqlx::query("UPDATE transcripts SET content = $2 WHERE id = $1")
+            .bind(id)
+            .bind(content)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to update transcript content")?;
+        drop_transcript_embeddings(&mut tx, id).await?;
+        tx.commit().await.context("Failed to commit transcript update")?;
+
+        self.find_by_id(id).await
+    }
+
+    async fn versions(&self, id: &Uuid) -> Result<Vec<TranscriptVersion>> {
+        let versions = sqlx::query_as::<_, TranscriptVersion>(
+            r#"
+            SELECT transcript_id, version, content, segments, created_at
+            FROM transcript_versions
+            WHERE transcript_id = $1
+            ORDER BY version DESC
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to list transcript versions")?;
+
+        Ok(versions)
+    }
+
+    async fn revert(&self, id: &Uuid, version: i32) -> Result<Option<Transcript>> {
+        let mut tx = self.pool.begin().await.context("Failed to start transcript revert")?;
+        let target = sqlx::query_as::<_, (String, serde_json::Value)>(
+            "SELECT content, segments FROM transcript_versions WHERE transcript_id = $1 AND version = $2",
+        )
+        .bind(id)
+        .bind(version)
+        .fetch_optional(&mut *tx)
+        .await
+        .context("Failed to find transcript version")?;
+        let Some((content, segments)) = target else {
+            return Ok(None);
+        };
+        if !save_transcript_version(&mut tx, id).await? {
+            return Ok(None);
+        }
+
+        sqlx::query("UPDATE transcripts SET content = $2, segments = $3 WHERE id = $1")
+            .bind(id)
+            .bind(content)
+            .bind(segments)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to revert transcript")?;
+        drop_transcript_embeddings(&mut tx, id).await?;
+        tx.commit().await.context("Failed to commit transcript revert")?;
+
+        self.find_by_id(id).await
+    }
+
+    async fn count_by_model(&self) -> Result<HashMap<String, i64>> {
+        count_by(&self.pool, "transcripts", "model_used").await
+    }
+
+    async fn daily_counts(&self, window: &StatsWindow) -> Result<Vec<DailyCount>> {
+        ensure_time_zone(&self.pool, &window.time_zone).await?;
+        daily_counts(&self.pool, "transcripts", window).await
+    }
+
+    async fn save_embedding(&self, id: &Uuid, model: &str, embedding: &[f32]) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO transcript_embeddings (transcript_id, model, embedding)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (transcript_id, model)
+            DO UPDATE SET embedding = EXCLUDED.embedding, created_at = NOW()
+            "#,
+        )
+        .bind(id)
+        .bind(model)
+        .bind(embedding)
+        .execute(&self.pool)
+        .await
+        .context("Failed to save transcript embedding")?;
+
+        Ok(())
+    }
+
+    async fn embeddings(&self, model: &str) -> Result<Vec<TranscriptEmbedding>> {
+        let embeddings = sqlx::query_as::<_, TranscriptEmbedding>(
+            r#"
+            SELECT e.transcript_id, t.session_id, e.embedding
+            FROM transcript_embeddings e
+            JOIN transcripts t ON t.id = e.transcript_id
+            JOIN sessions s ON s.id = t.session_id
+            WHERE e.model = $1 AND s.status != 'deleted'
+            "#,
+        )
+        .bind(model)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to list transcript embeddings")?;
+
+        Ok(embeddings)
+    }
+
+    async fn find_without_embedding(&self, model: &str, limit: i64) -> Result<Vec<Transcript>> {
+        let transcripts = sqlx::query_as::<_, Transcript>(
+            r#"
+            SELECT id, session_id, content, language, confidence_score, provider, created_at, processing_time_ms, segments, model_used
+            FROM transcripts t
+            JOIN sessions s ON s.id = t.session_id
+            WHERE btrim(t.content) <> ''
+              AND s.status != 'deleted'
+              AND NOT EXISTS (
+                  SELECT 1 FROM transcript_embeddings e
+                  WHERE e.transcript_id = t.id AND e.model = $1
+              )
+            ORDER BY t.created_at
+            LIMIT $2
+            "#,
+        )
+        .bind(model)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to find transcripts without an embedding")?;
+
+        Ok(transcripts)
+    }
+}
+
+/// Drop the embeddings of a transcript whose content is being replaced, so
+/// semantic search embeds the new content
+async fn drop_transcript_embeddings(tx: &mut sqlx::Transaction<'_, sqlx::Postgres>, id: &Uuid) -> Result<()> {
+    sqlx::query("DELETE FROM transcript_embeddings WHERE transcript_id = $1")
+        .bind(id)
+        .execute(&mut **tx)
+        .await
+        .context("Failed to drop transcript embeddings")?;
+
+    Ok(())
+}
+
+/// Save the current content of a transcript as its next version and drop
+/// versions beyond [`MAX_TRANSCRIPT_VERSIONS`]; `false` if there is no such
+/// transcript
+///
+/// The transcript row stays locked until `tx` ends, so concurrent edits
+/// cannot claim the same version number.
+async fn save_transcript_version(tx: &mut sqlx::Transaction<'_, sqlx::Postgres>, id: &Uuid) -> Result<bool> {
+    let locked = sqlx::query("SELECT id FROM transcripts WHERE id = $1 FOR UPDATE")
+        .bind(id)
+        .fetch_optional(&mut **tx)
+        .await
+        .context("Failed to lock transcript")?;
+    if locked.is_none() {
+        return Ok(false);
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO transcript_versions (transcript_id, version, content, segments)
+        SELECT t.id,
+               COALESCE((SELECT MAX(v.version) FROM transcript_versions v WHERE v.transcript_id = t.id), 0) + 1,
+               t.content,
+               t.segments
+        FROM transcripts t
+        WHERE t.id = $1
+        "#,
+    )
+    .bind(id)
+    .execute(&mut **tx)
+    .await
+    .context("Failed to save transcript version")?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM transcript_versions
+        WHERE transcript_id = $1
+          AND version <= (SELECT MAX(version) FROM transcript_versions WHERE transcript_id = $1) - $2
+        "#,
+    )
+    .bind(id)
+    .bind(MAX_TRANSCRIPT_VERSIONS)
+    .execute(&mut **tx)
+    .await
+    .context("Failed to prune transcript versions")?;
+
+    Ok(true)
 }
 
 // Placeholder structs for other repositories - these would be fully implemented
@@ -606,6 +1067,8 @@ pub struct PostgresAnalysisRepository { pool: PgPool }
 pub struct PostgresIdeaRepository { pool: PgPool }
 pub struct PostgresTaskRepository { pool: PgPool }
 pub struct PostgresStructuredNoteRepository { pool: PgPool }
+pub struct PostgresNoteTemplateRepository { pool: PgPool }
+pub struct PostgresArchiveRepository { pool: PgPool }
 
 impl PostgresAnalysisRepository {
     pub fn new(pool: PgPool) -> Self { Self { pool } }
@@ -623,32 +1086,202 @@ impl PostgresStructuredNoteRepository {
     pub fn new(pool: PgPool) -> Self { Self { pool } }
 }
 
+impl PostgresNoteTemplateRepository {
+    pub fn new(pool: PgPool) -> Self { Self { pool } }
+}
+
+impl PostgresArchiveRepository {
+    pub fn new(pool: PgPool) -> Self { Self { pool } }
+}
+
 // Placeholder trait implementations - these would be fully implemented
 #[async_trait]
 impl AnalysisRepository for PostgresAnalysisRepository {
-    async fn create(&self, _analysis: &NewAnalysisResult) -> Result<AnalysisResult> {
-        todo!("Implement analysis repository create")
+    async fn create(&self, analysis: &NewAnalysisResult) -> Result<AnalysisResult> {
+        let created = sqlx::query_as::<_, AnalysisResult>(
+            r#"
+            INSERT INTO analysis_results (id, session_id, title, summary, provider, model_version, created_at, processing_time_ms, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $7)
+            RETURNING id, session_id, title, summary, provider, model_version, created_at,
+                      processing_time_ms, result_data, metadata, updated_at, deleted_at
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(analysis.session_id)
+        .bind(&analysis.title)
+        .bind(&analysis.summary)
+        .bind(&analysis.provider)
+        .bind(&analysis.model_version)
+        .bind(Utc::now())
+        .bind(analysis.processing_time_ms)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to create analysis result")?;
+
+        Ok(created)
     }
     
-    async fn find_by_id(&self, _id: &Uuid) -> Result<Option<AnalysisResult>> {
-        todo!("Implement analysis repository find_by_id")
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<AnalysisResult>> {
+        let analysis = sqlx::query_as::<_, AnalysisResult>(
+            r#"
+            SELECT id, session_id, title, summary, provider, model_version, created_at,
+                   processing_time_ms, result_data, metadata, updated_at, deleted_at
+            FROM analysis_results
+            WHERE id = $1 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to find analysis result by id")?;
+
+        Ok(analysis)
     }
     
-    async fn find_by_session_id(&self, _session_id: &Uuid) -> Result<Option<AnalysisResult>> {
-        todo!("Implement analysis repository find_by_session_id")
+    async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<AnalysisResult>> {
+        let analyses = sqlx::query_as::<_, AnalysisResult>(
+            r#"
+            SELECT id, session_id, title, summary, provider, model_version, created_at,
+                   processing_time_ms, result_data, metadata, updated_at, deleted_at
+            FROM analysis_results
+            WHERE id = ANY($1) AND deleted_at IS NULL
+            "#,
+        )
+        .bind(ids)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to find analysis results by ids")?;
+
+        Ok(analyses)
+    }
+
+    async fn find_by_session_id(&self, session_id: &Uuid) -> Result<Option<AnalysisResult>> {
+        let analysis = sqlx::query_as::<_, AnalysisResult>(
+            r#"
+            SELECT id, session_id, title, summary, provider, model_version, created_at,
+                   processing_time_ms, result_data, metadata, updated_at, deleted_at
+            FROM analysis_results
+            WHERE session_id = $1 AND deleted_at IS NULL
+            ORDER BY created_at DESC, id DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(session_id)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to find current analysis by session id")?;
+
+        Ok(analysis)
+    }
+
+    async fn find_all_by_session_id(&self, session_id: &Uuid) -> Result<Vec<AnalysisResult>> {
+        let analyses = sqlx::query_as::<_, AnalysisResult>(
+            r#"
+            SELECT id, session_id, title, summary, provider, model_version, created_at,
+                   processing_time_ms, result_data, metadata, updated_at, deleted_at
+            FROM analysis_results
+            WHERE session_id = $1 AND deleted_at IS NULL
+            ORDER BY created_at, id
+            "#,
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to find analyses by session id")?;
+
+        Ok(analyses)
     }
     
     async fn update(&self, _id: &Uuid, _updates: &AnalysisUpdate) -> Result<AnalysisResult> {
         todo!("Implement analysis repository update")
     }
+
+    async fn regenerate(&self, id: &Uuid, regeneration: &AnalysisRegeneration) -> Result<Option<AnalysisResult>> {
+        // The right-hand sides read the row as it was before the update, so
+        // the replaced result is archived in the same statement
+        let analysis = sqlx::query_as::<_, AnalysisResult>(
+            r#"
+            UPDATE analysis_results
+            SET title = $2,
+                summary = $3,
+                provider = $4,
+                model_version = $5,
+                processing_time_ms = $6,
+                result_data = $7,
+                metadata = jsonb_set(
+                    COALESCE(metadata, '{}'::jsonb),
+                    '{previous_results}',
+                    COALESCE(metadata -> 'previous_results', '[]'::jsonb) || jsonb_build_array(jsonb_build_object(
+                        'title', title,
+                        'summary', summary,
+                        'provider', provider,
+                        'model_version', model_version,
+                        'processing_time_ms', processing_time_ms,
+                        'result_data', result_data,
+                        'updated_at', updated_at
+                    ))
+                ),
+                updated_at = $8
+            WHERE id = $1 AND deleted_at IS NULL
+            RETURNING id, session_id, title, summary, provider, model_version, created_at,
+                      processing_time_ms, result_data, metadata, updated_at, deleted_at
+            "#,
+        )
+        .bind(id)
+        .bind(&regeneration.title)
+        .bind(&regeneration.summary)
+        .bind(&regeneration.provider)
+        .bind(&regeneration.model_version)
+        .bind(regeneration.processing_time_ms)
+        .bind(&regeneration.result_data)
+        .bind(Utc::now())
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to regenerate analysis result")?;
+
+        Ok(analysis)
+    }
     
-    async fn delete(&self, _id: &Uuid) -> Result<()> {
-        todo!("Implement analysis repository delete")
+    async fn delete(&self, id: &Uuid) -> Result<()> {
+        soft_delete(&self.pool, "analysis_results", id).await?;
+        Ok(())
+    }
+
+    async fn restore(&self, id: &Uuid) -> Result<bool> {
+        restore_deleted(&self.pool, "analysis_results", id).await
+    }
+
+    async fn purge(&self, id: &Uuid) -> Result<bool> {
+        purge(&self.pool, "analysis_results", id).await
     }
     
     async fn find_by_provider(&self, _provider: &str) -> Result<Vec<AnalysisResult>> {
         todo!("Implement analysis repository find_by_provider")
     }
+
+    async fn count_by_model(&self) -> Result<HashMap<String, i64>> {
+        count_by(&self.pool, "analysis_results", "model_version").await
+    }
+    
+    async fn average_processing_time_ms(&self) -> Result<Option<f64>> {
+        let average = sqlx::query_scalar!(
+            r#"
+            SELECT AVG(processing_time_ms)::float8
+            FROM analysis_results
+            WHERE processing_time_ms IS NOT NULL AND deleted_at IS NULL
+            "#
+        )
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to compute average analysis processing time")?;
+        
+        Ok(average)
+    }
+
+    async fn daily_counts(&self, window: &StatsWindow) -> Result<Vec<DailyCount>> {
+        ensure_time_zone(&self.pool, &window.time_zone).await?;
+        daily_counts(&self.pool, "analysis_results", window).await
+    }
 }
 
 #[async_trait]
@@ -669,13 +1302,74 @@ impl IdeaRepository for PostgresIdeaRepository {
         todo!("Implement idea repository update")
     }
     
-    async fn delete(&self, _id: &Uuid) -> Result<()> {
-        todo!("Implement idea repository delete")
+    async fn delete(&self, id: &Uuid) -> Result<()> {
+        soft_delete(&self.pool, "ideas", id).await?;
+        Ok(())
+    }
+
+    async fn restore(&self, id: &Uuid) -> Result<bool> {
+        restore_deleted(&self.pool, "ideas", id).await
+    }
+
+    async fn purge(&self, id: &Uuid) -> Result<bool> {
+        purge(&self.pool, "ideas", id).await
     }
     
     async fn find_by_category(&self, _category: &str) -> Result<Vec<Idea>> {
         todo!("Implement idea repository find_by_category")
     }
+
+    async fn stats(&self, window: &StatsWindow) -> Result<IdeaStats> {
+        ensure_time_zone(&self.pool, &window.time_zone).await?;
+
+        let (total, completed, avg_confidence) = sqlx::query_as::<_, (i64, i64, Option<f64>)>(
+            r#"
+            SELECT COUNT(*),
+                   COUNT(*) FILTER (WHERE status = 'completed'),
+                   AVG(confidence_score)::float8
+            FROM ideas
+            WHERE deleted_at IS NULL
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to compute idea totals")?;
+
+        let categories = sqlx::query_as::<_, (String, i64, i64, Option<f64>)>(
+            r#"
+            SELECT COALESCE(category, 'uncategorized'),
+                   COUNT(*),
+                   COUNT(*) FILTER (WHERE status = 'completed'),
+                   AVG(confidence_score)::float8
+            FROM ideas
+            WHERE deleted_at IS NULL
+            GROUP BY 1
+            ORDER BY 2 DESC, 1
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to group ideas by category")?
+        .into_iter()
+        .map(|(category, count, completed, avg_confidence)| IdeaCategoryStats {
+            category,
+            count,
+            completed,
+            avg_confidence,
+        })
+        .collect();
+
+        Ok(IdeaStats {
+            total,
+            completed,
+            by_priority: count_by(&self.pool, "ideas", "priority").await?,
+            by_status: count_by(&self.pool, "ideas", "status").await?,
+            by_tag: count_by_tag(&self.pool, "ideas").await?,
+            avg_confidence,
+            categories,
+            per_day: daily_counts(&self.pool, "ideas", window).await?,
+        })
+    }
 }
 
 #[async_trait]
@@ -692,12 +1386,68 @@ impl TaskRepository for PostgresTaskRepository {
         todo!("Implement task repository find_by_analysis_id")
     }
     
-    async fn update(&self, _id: &Uuid, _updates: &TaskUpdate) -> Result<Task> {
-        todo!("Implement task repository update")
+    async fn update(&self, id: &Uuid, updates: &TaskUpdate) -> Result<Task> {
+        let now = Utc::now();
+
+        // completed_at keeps the first completion time while the task stays
+        // completed and is cleared when it is reopened or cancelled
+        let row = sqlx::query!(
+            r#"
+            UPDATE tasks
+            SET title = COALESCE($2, title),
+                description = COALESCE($3, description),
+                priority = COALESCE($4, priority),
+                status = COALESCE($5, status),
+                due_date = COALESCE($6, due_date),
+                completed_at = CASE
+                    WHEN $5 IS NULL THEN completed_at
+                    WHEN $5 = 'completed' THEN COALESCE(completed_at, $7)
+                    ELSE NULL
+                END,
+                updated_at = $7
+            WHERE id = $1 AND deleted_at IS NULL
+            RETURNING id, analysis_id, title, description, priority as "priority: Priority",
+                      status as "status: TaskStatus", due_date, completed_at, created_at, updated_at,
+                      deleted_at
+            "#,
+            id,
+            updates.title,
+            updates.description,
+            updates.priority.as_ref().map(|p| p.clone() as Priority),
+            updates.status.as_ref().map(|s| s.clone() as TaskStatus),
+            updates.due_date,
+            now
+        )
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to update task")?;
+
+        Ok(Task {
+            id: row.id,
+            analysis_id: row.analysis_id,
+            title: row.title,
+            description: row.description,
+            priority: row.priority,
+            status: row.status,
+            due_date: row.due_date,
+            completed_at: row.completed_at,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+            deleted_at: row.deleted_at,
+        })
     }
     
-    async fn delete(&self, _id: &Uuid) -> Result<()> {
-        todo!("Implement task repository delete")
+    async fn delete(&self, id: &Uuid) -> Result<()> {
+        soft_delete(&self.pool, "tasks", id).await?;
+        Ok(())
+    }
+
+    async fn restore(&self, id: &Uuid) -> Result<bool> {
+        restore_deleted(&self.pool, "tasks", id).await
+    }
+
+    async fn purge(&self, id: &Uuid) -> Result<bool> {
+        purge(&self.pool, "tasks", id).await
     }
     
     async fn find_by_status(&self, _status: TaskStatus) -> Result<Vec<Task>> {
@@ -708,8 +1458,76 @@ impl TaskRepository for PostgresTaskRepository {
         todo!("Implement task repository find_by_priority")
     }
     
-    async fn mark_completed(&self, _id: &Uuid) -> Result<Task> {
-        todo!("Implement task repository mark_completed")
+    async fn mark_completed(&self, id: &Uuid) -> Result<Task> {
+        self.update(
+            id,
+            &TaskUpdate {
+                title: None,
+                description: None,
+                priority: None,
+                status: Some(TaskStatus::Completed),
+                due_date: None,
+            },
+        )
+        .await
+    }
+
+    async fn stats(&self, window: &StatsWindow) -> Result<TaskStats> {
+        ensure_time_zone(&self.pool, &window.time_zone).await?;
+
+        let (total, completed, overdue, upcoming, avg_confidence, avg_completion_days) =
+            sqlx::query_as::<_, (i64, i64, i64, i64, Option<f64>, Option<f64>)>(
+                r#"
+                SELECT COUNT(*),
+                       COUNT(*) FILTER (WHERE status = 'completed'),
+                       COUNT(*) FILTER (
+                           WHERE due_date < now() AND status NOT IN ('completed', 'cancelled')
+                       ),
+                       COUNT(*) FILTER (
+                           WHERE due_date >= now() AND due_date < now() + interval '7 days'
+                             AND status NOT IN ('completed', 'cancelled')
+                       ),
+                       AVG(confidence_score)::float8,
+                       (AVG(EXTRACT(EPOCH FROM completed_at - created_at)) / 86400)::float8
+                FROM tasks
+                WHERE deleted_at IS NULL
+                "#,
+            )
+            .fetch_one(&self.pool)
+            .await
+            .context("Failed to compute task totals")?;
+
+        let mut by_priority = HashMap::new();
+        let mut completed_by_priority = HashMap::new();
+        let priorities = sqlx::query_as::<_, (String, i64, i64)>(
+            r#"
+            SELECT priority::text, COUNT(*), COUNT(*) FILTER (WHERE status = 'completed')
+            FROM tasks
+            WHERE deleted_at IS NULL
+            GROUP BY 1
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to group tasks by priority")?;
+        for (priority, count, completed) in priorities {
+            completed_by_priority.insert(priority.clone(), completed);
+            by_priority.insert(priority, count);
+        }
+
+        Ok(TaskStats {
+            total,
+            completed,
+            by_priority,
+            completed_by_priority,
+            by_status: count_by(&self.pool, "tasks", "status").await?,
+            by_tag: count_by_tag(&self.pool, "tasks").await?,
+            avg_confidence,
+            overdue,
+            upcoming,
+            avg_completion_days,
+            per_day: daily_counts(&self.pool, "tasks", window).await?,
+        })
     }
 }
 
@@ -731,8 +1549,17 @@ impl StructuredNoteRepository for PostgresStructuredNoteRepository {
         todo!("Implement structured note repository update")
     }
     
-    async fn delete(&self, _id: &Uuid) -> Result<()> {
-        todo!("Implement structured note repository delete")
+    async fn delete(&self, id: &Uuid) -> Result<()> {
+        soft_delete(&self.pool, "structured_notes", id).await?;
+        Ok(())
+    }
+
+    async fn restore(&self, id: &Uuid) -> Result<bool> {
+        restore_deleted(&self.pool, "structured_notes", id).await
+    }
+
+    async fn purge(&self, id: &Uuid) -> Result<bool> {
+        purge(&self.pool, "structured_notes", id).await
     }
     
     async fn find_by_note_type(&self, _note_type: NoteType) -> Result<Vec<StructuredNote>> {
@@ -742,4 +1569,522 @@ impl StructuredNoteRepository for PostgresStructuredNoteRepository {
     async fn find_by_tags(&self, _tags: &[String]) -> Result<Vec<StructuredNote>> {
         todo!("Implement structured note repository find_by_tags")
     }
-}
\ No newline at end of file
+    async fn stats(&self, window: &StatsWindow) -> Result<NoteStats> {
+        ensure_time_zone(&self.pool, &window.time_zone).await?;
+
+        let (total, public, total_views, avg_content_length) =
+            sqlx::query_as::<_, (i64, i64, i64, Option<f64>)>(
+                r#"
+                SELECT COUNT(*),
+                       COUNT(*) FILTER (WHERE is_public),
+                       COALESCE(SUM(view_count), 0)::int8,
+                       AVG(length(content::text))::float8
+                FROM structured_notes
+                WHERE deleted_at IS NULL
+                "#,
+            )
+            .fetch_one(&self.pool)
+            .await
+            .context("Failed to compute note totals")?;
+
+        Ok(NoteStats {
+            total,
+            public,
+            total_views,
+            avg_content_length,
+            by_note_type: count_by(&self.pool, "structured_notes", "note_type").await?,
+            by_template: count_by(&self.pool, "structured_notes", "template_id").await?,
+            by_tag: count_by_tag(&self.pool, "structured_notes").await?,
+            per_day: daily_counts(&self.pool, "structured_notes", window).await?,
+        })
+    }
+}
+
+/// Columns of a note template row, including its live usage count
+const NOTE_TEMPLATE_COLUMNS: &str = r#"
+    t.id, t.name, t.description, t.note_type, t.schema, t.default_content, t.sections, t.is_system,
+    t.usage_count, t.created_at, t.updated_at
+"#;
+
+#[async_trait]
+impl NoteTemplateRepository for PostgresNoteTemplateRepository {
+    async fn create(&self, template: &NewNoteTemplate) -> Result<NoteTemplate> {
+        sqlx::query(
+            r#"
+            INSERT INTO note_templates (id, name, description, note_type, schema, default_content, sections)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(&template.id)
+        .bind(&template.name)
+        .bind(&template.description)
+        .bind(&template.note_type)
+        .bind(&template.schema)
+        .bind(&template.default_content)
+        .bind(&template.sections)
+        .execute(&self.pool)
+        .await
+        .context("Failed to create note template")?;
+
+        self.find_by_id(&template.id)
+            .await?
+            .context("Created note template not found")
+    }
+
+    async fn find_by_id(&self, id: &str) -> Result<Option<NoteTemplate>> {
+        let template = sqlx::query_as::<_, NoteTemplate>(&format!(
+            "SELECT {} FROM note_templates t WHERE t.id = $1",
+            NOTE_TEMPLATE_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to find note template by id")?;
+
+        Ok(template)
+    }
+
+    async fn list(&self) -> Result<Vec<NoteTemplate>> {
+        let templates = sqlx::query_as::<_, NoteTemplate>(&format!(
+            "SELECT {} FROM note_templates t ORDER BY t.is_system DESC, t.name, t.id",
+            NOTE_TEMPLATE_COLUMNS
+        ))
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to list note templates")?;
+
+        Ok(templates)
+    }
+
+    async fn update(&self, id: &str, updates: &NoteTemplateUpdate) -> Result<Option<NoteTemplate>> {
+        let updated = sqlx::query(
+            r#"
+            UPDATE note_templates
+            SET name = COALESCE($2, name),
+                description = COALESCE($3, description),
+                note_type = COALESCE($4, note_type),
+                schema = COALESCE($5, schema),
+                default_content = COALESCE($6, default_content),
+                sections = COALESCE($7, sections),
+                updated_at = NOW()
+            WHERE id = $1 AND NOT is_system
+            "#,
+        )
+        .bind(id)
+        .bind(&updates.name)
+        .bind(&updates.description)
+        .bind(&updates.note_type)
+        .bind(&updates.schema)
+        .bind(&updates.default_content)
+        .bind(&updates.sections)
+        .execute(&self.pool)
+        .await
+        .context("Failed to update note template")?;
+
+        if updated.rows_affected() == 0 {
+            return Ok(None);
+        }
+        self.find_by_id(id).await
+    }
+
+    async fn delete(&self, id: &str) -> Result<bool> {
+        let deleted = sqlx::query("DELETE FROM note_templates WHERE id = $1 AND NOT is_system")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .context("Failed to delete note template")?;
+
+        Ok(deleted.rows_affected() > 0)
+    }
+
+    async fn increment_usage(&self, id: &str) -> Result<bool> {
+        // A single UPDATE, so concurrent note creations never lose a count
+        let updated = sqlx::query("UPDATE note_templates SET usage_count = usage_count + 1 WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .context("Failed to increment note template usage")?;
+
+        Ok(updated.rows_affected() > 0)
+    }
+
+    async fn most_used(&self, limit: i64) -> Result<Vec<NoteTemplate>> {
+        let templates = sqlx::query_as::<_, NoteTemplate>(&format!(
+            "SELECT {} FROM note_templates t WHERE t.usage_count > 0 ORDER BY t.usage_count DESC, t.id LIMIT $1",
+            NOTE_TEMPLATE_COLUMNS
+        ))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to list most used note templates")?;
+
+        Ok(templates)
+    }
+}
+
+// Table names come from `ArchiveEntity::table`, never from the archive itself.
+#[async_trait]
+impl ArchiveRepository for PostgresArchiveRepository {
+    async fn export_page(
+        &self,
+        entity: ArchiveEntity,
+        after: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<serde_json::Value>> {
+        let sql = format!(
+            "SELECT to_jsonb(t) FROM {table} t \
+             WHERE $1::text IS NULL OR t.id > CAST($1::text AS {id_type}) \
+             ORDER BY t.id LIMIT $2",
+            table = entity.table(),
+            id_type = entity.id_type()
+        );
+        let rows = sqlx::query_scalar::<_, serde_json::Value>(&sql)
+            .bind(after)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+            .with_context(|| format!("Failed to export {}", entity.table()))?;
+
+        Ok(rows)
+    }
+
+    async fn import_rows(&self, entity: ArchiveEntity, rows: &[serde_json::Value]) -> Result<u64> {
+        // Columns missing from a row are NULL, not their default, so archives
+        // only import into a schema at least as new as the one they came from
+        let sql = format!(
+            "INSERT INTO {table} SELECT * FROM jsonb_populate_record(NULL::{table}, $1) \
+             ON CONFLICT (id) DO NOTHING",
+            table = entity.table()
+        );
+
+        let mut tx = self.pool.begin().await.context("Failed to start import transaction")?;
+        let mut inserted = 0;
+        for row in rows {
+            let result = sqlx::query(&sql)
+                .bind(row)
+                .execute(&mut *tx)
+                .await
+                .with_context(|| format!("Failed to import into {}", entity.table()))?;
+            inserted += result.rows_affected();
+        }
+        tx.commit().await.context("Failed to commit import")?;
+
+        Ok(inserted)
+    }
+}
+
+// Stats helpers. `table` and `column` are always literals from this module,
+// never user input, so formatting them into the SQL is safe.
+
+/// Fail with [`UnknownTimeZone`] unless Postgres knows `time_zone`
+async fn ensure_time_zone(pool: &PgPool, time_zone: &str) -> Result<()> {
+    let known = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS (SELECT 1 FROM pg_timezone_names WHERE name = $1)",
+    )
+    .bind(time_zone)
+    .fetch_one(pool)
+    .await
+    .context("Failed to look up time zone")?;
+
+    if !known {
+        return Err(UnknownTimeZone(time_zone.to_string()).into());
+    }
+    Ok(())
+}
+
+/// Tables whose rows are soft-deleted by setting `deleted_at`
+const SOFT_DELETE_TABLES: &[&str] = &["analysis_results", "ideas", "tasks", "structured_notes"];
+
+/// SQL condition excluding soft-deleted rows of `table`, with the column
+/// qualified by `alias`
+fn live_rows(table: &str, alias: &str) -> String {
+    if SOFT_DELETE_TABLES.contains(&table) {
+        format!("{}.deleted_at IS NULL", alias)
+    } else {
+        "TRUE".to_string()
+    }
+}
+
+/// Row counts grouped by a text-like column, skipping NULLs
+async fn count_by(pool: &PgPool, table: &str, column: &str) -> Result<HashMap<String, i64>> {
+    let sql = format!(
+        "SELECT {column}::text, COUNT(*) FROM {table} t WHERE {column} IS NOT NULL AND {live} GROUP BY 1",
+        column = column,
+        table = table,
+        live = live_rows(table, "t")
+    );
+    let rows = sqlx::query_as::<_, (String, i64)>(&sql)
+        .fetch_all(pool)
+        .await
+        .with_context(|| format!("Failed to group {} by {}", table, column))?;
+
+    Ok(rows.into_iter().collect())
+}
+
+/// Row counts per tag of a table with a `tags` array column
+async fn count_by_tag(pool: &PgPool, table: &str) -> Result<HashMap<String, i64>> {
+    let sql = format!(
+        "SELECT tag, COUNT(*) FROM {table} t, unnest(t.tags) AS tag WHERE {live} GROUP BY tag",
+        table = table,
+        live = live_rows(table, "t")
+    );
+    let rows = sqlx::query_as::<_, (String, i64)>(&sql)
+        .fetch_all(pool)
+        .await
+        .with_context(|| format!("Failed to group {} by tag", table))?;
+
+    Ok(rows.into_iter().collect())
+}
+
+/// Rows created per calendar day in `window.time_zone`, from the whole day
+/// containing `window.since` through today, including days without any rows
+async fn daily_counts(pool: &PgPool, table: &str, window: &StatsWindow) -> Result<Vec<DailyCount>> {
+    let sql = format!(
+        r#"
+        SELECT day::date, COUNT(t.created_at)
+        FROM generate_series(
+            ($2 AT TIME ZONE $1)::date,
+            (now() AT TIME ZONE $1)::date,
+            interval '1 day'
+        ) AS day
+        LEFT JOIN {table} t
+            ON t.created_at >= (($2 AT TIME ZONE $1)::date::timestamp AT TIME ZONE $1)
+           AND (t.created_at AT TIME ZONE $1)::date = day::date
+           AND {live}
+        GROUP BY day
+        ORDER BY day
+        "#,
+        table = table,
+        live = live_rows(table, "t")
+    );
+    let rows = sqlx::query_as::<_, (NaiveDate, i64)>(&sql)
+        .bind(&window.time_zone)
+        .bind(window.since)
+        .fetch_all(pool)
+        .await
+        .with_context(|| format!("Failed to count {} per day", table))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(date, count)| DailyCount { date, count })
+        .collect())
+}
+
+// Soft delete helpers for the tables in `SOFT_DELETE_TABLES`
+
+/// Mark a live row deleted; `false` if there was none with this ID
+async fn soft_delete(pool: &PgPool, table: &str, id: &Uuid) -> Result<bool> {
+    let sql = format!(
+        "UPDATE {table} SET deleted_at = now() WHERE id = $1 AND deleted_at IS NULL",
+        table = table
+    );
+    let result = sqlx::query(&sql)
+        .bind(id)
+        .execute(pool)
+        .await
+        .with_context(|| format!("Failed to soft-delete from {}", table))?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Clear `deleted_at` on a soft-deleted row; `false` if there was none with this ID
+async fn restore_deleted(pool: &PgPool, table: &str, id: &Uuid) -> Result<bool> {
+    let sql = format!(
+        "UPDATE {table} SET deleted_at = NULL WHERE id = $1 AND deleted_at IS NOT NULL",
+        table = table
+    );
+    let result = sqlx::query(&sql)
+        .bind(id)
+        .execute(pool)
+        .await
+        .with_context(|| format!("Failed to restore row in {}", table))?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Delete a row for good, whether or not it was soft-deleted
+async fn purge(pool: &PgPool, table: &str, id: &Uuid) -> Result<bool> {
+    let sql = format!("DELETE FROM {table} WHERE id = $1", table = table);
+    let result = sqlx::query(&sql)
+        .bind(id)
+        .execute(pool)
+        .await
+        .with_context(|| format!("Failed to purge row from {}", table))?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn create_session(pool: &PgPool, title: &str) -> Session {
+        PostgresSessionRepository::new(pool.clone())
+            .create(&NewSession { title: title.to_string(), duration_ms: 1000, metadata: None })
+            .await
+            .unwrap()
+    }
+
+    fn new_audio_file(session_id: Uuid, file_path: &str, checksum: Option<&str>) -> NewAudioFile {
+        NewAudioFile {
+            session_id,
+            file_path: file_path.to_string(),
+            file_size: 44,
+            format: "wav".to_string(),
+            sample_rate: Some(16000),
+            channels: Some(1),
+            checksum: checksum.map(str::to_string),
+        }
+    }
+
+    #[sqlx::test]
+    async fn find_by_checksum_finds_the_earliest_of_duplicate_files(pool: PgPool) {
+        let audio = PostgresAudioRepository::new(pool.clone());
+        let first = create_session(&pool, "first").await;
+        let second = create_session(&pool, "second").await;
+
+        let original = audio.create(&new_audio_file(first.id, "first.wav", Some("abc123"))).await.unwrap();
+        audio.create(&new_audio_file(second.id, "second.wav", Some("abc123"))).await.unwrap();
+        audio.create(&new_audio_file(second.id, "other.wav", Some("def456"))).await.unwrap();
+
+        let found = audio.find_by_checksum("abc123").await.unwrap().unwrap();
+        assert_eq!(found.id, original.id);
+        assert_eq!(found.session_id, first.id);
+    }
+
+    #[sqlx::test]
+    async fn created_analysis_keeps_its_processing_time(pool: PgPool) {
+        let analyses = PostgresAnalysisRepository::new(pool.clone());
+        let session = create_session(&pool, "session").await;
+        let new_analysis = |processing_time_ms| NewAnalysisResult {
+            session_id: session.id,
+            title: Some("Title".to_string()),
+            summary: None,
+            provider: "ollama".to_string(),
+            model_version: Some("llama3".to_string()),
+            processing_time_ms,
+        };
+
+        let created = analyses.create(&new_analysis(Some(1500))).await.unwrap();
+        assert_eq!(created.processing_time_ms, Some(1500));
+        analyses.create(&new_analysis(Some(500))).await.unwrap();
+        analyses.create(&new_analysis(None)).await.unwrap();
+
+        assert_eq!(analyses.average_processing_time_ms().await.unwrap(), Some(1000.0));
+    }
+
+    #[sqlx::test]
+    async fn deleting_a_session_soft_deletes_its_analyses(pool: PgPool) {
+        let sessions = PostgresSessionRepository::new(pool.clone());
+        let analyses = PostgresAnalysisRepository::new(pool.clone());
+        let deleted = create_session(&pool, "deleted").await;
+        let kept = create_session(&pool, "kept").await;
+        let new_analysis = |session_id| NewAnalysisResult {
+            session_id,
+            title: Some("Title".to_string()),
+            summary: None,
+            provider: "ollama".to_string(),
+            model_version: None,
+            processing_time_ms: None,
+        };
+        let deleted_analysis = analyses.create(&new_analysis(deleted.id)).await.unwrap();
+        let kept_analysis = analyses.create(&new_analysis(kept.id)).await.unwrap();
+
+        sessions.delete(&deleted.id).await.unwrap();
+
+        assert!(analyses.find_by_session_id(&deleted.id).await.unwrap().is_none());
+        let found = analyses.find_by_ids(&[deleted_analysis.id, kept_analysis.id]).await.unwrap();
+        assert_eq!(found.into_iter().map(|analysis| analysis.id).collect::<Vec<_>>(), vec![kept_analysis.id]);
+        // Still there to be purged with the session
+        assert!(analyses.purge(&deleted_analysis.id).await.unwrap());
+    }
+
+    #[sqlx::test]
+    async fn embeddings_leave_out_deleted_sessions(pool: PgPool) {
+        let sessions = PostgresSessionRepository::new(pool.clone());
+        let transcripts = PostgresTranscriptRepository::new(pool.clone());
+        let deleted = create_session(&pool, "deleted").await;
+        let kept = create_session(&pool, "kept").await;
+        let new_transcript = |session_id| NewTranscript {
+            session_id,
+            content: "Plan the release".to_string(),
+            language: Some("en".to_string()),
+            confidence_score: None,
+            provider: "whisper_cpp".to_string(),
+            processing_time_ms: None,
+            segments: serde_json::json!([]),
+            model_used: None,
+        };
+        let deleted_transcript = transcripts.create(&new_transcript(deleted.id)).await.unwrap();
+        let kept_transcript = transcripts.create(&new_transcript(kept.id)).await.unwrap();
+        transcripts.save_embedding(&deleted_transcript.id, "nomic-embed-text", &[1.0, 0.0]).await.unwrap();
+        transcripts.save_embedding(&kept_transcript.id, "nomic-embed-text", &[0.0, 1.0]).await.unwrap();
+        let unembedded = transcripts.create(&new_transcript(deleted.id)).await.unwrap();
+
+        sessions.delete(&deleted.id).await.unwrap();
+
+        let embeddings = transcripts.embeddings("nomic-embed-text").await.unwrap();
+        assert_eq!(embeddings.len(), 1);
+        assert_eq!(embeddings[0].transcript_id, kept_transcript.id);
+        let missing = transcripts.find_without_embedding("nomic-embed-text", 10).await.unwrap();
+        assert!(missing.iter().all(|transcript| transcript.id != unembedded.id));
+    }
+
+    #[sqlx::test]
+    async fn regenerating_an_analysis_replaces_it_in_place_and_keeps_the_previous_result(pool: PgPool) {
+        let analyses = PostgresAnalysisRepository::new(pool.clone());
+        let session = create_session(&pool, "session").await;
+        let original = analyses
+            .create(&NewAnalysisResult {
+                session_id: session.id,
+                title: Some("First".to_string()),
+                summary: Some("First summary".to_string()),
+                provider: "ollama".to_string(),
+                model_version: Some("llama3".to_string()),
+                processing_time_ms: Some(1500),
+            })
+            .await
+            .unwrap();
+        assert_eq!(original.updated_at, original.created_at);
+        let regeneration = |title: &str| AnalysisRegeneration {
+            title: Some(title.to_string()),
+            summary: Some(format!("{} summary", title)),
+            provider: "openai".to_string(),
+            model_version: Some("gpt-4o".to_string()),
+            processing_time_ms: Some(800),
+            result_data: serde_json::json!({ "title": title, "ideas": ["Ship it"] }),
+        };
+
+        let second = analyses.regenerate(&original.id, &regeneration("Second")).await.unwrap().unwrap();
+        let third = analyses.regenerate(&original.id, &regeneration("Third")).await.unwrap().unwrap();
+
+        assert_eq!(third.id, original.id);
+        assert_eq!(third.title.as_deref(), Some("Third"));
+        assert_eq!(third.provider, "openai");
+        assert_eq!(third.result_data.as_ref().unwrap()["ideas"][0], "Ship it");
+        assert!(third.updated_at > second.updated_at && second.updated_at > original.created_at);
+        assert_eq!(third.created_at, original.created_at);
+        let previous = third.metadata.as_ref().unwrap()["previous_results"].as_array().unwrap().clone();
+        assert_eq!(previous.len(), 2);
+        assert_eq!(previous[0]["title"], "First");
+        assert_eq!(previous[0]["model_version"], "llama3");
+        assert_eq!(previous[0]["result_data"], serde_json::Value::Null);
+        assert_eq!(previous[1]["result_data"]["title"], "Second");
+        // Still the session's one analysis
+        assert_eq!(analyses.find_all_by_session_id(&session.id).await.unwrap().len(), 1);
+
+        analyses.delete(&original.id).await.unwrap();
+        assert!(analyses.regenerate(&original.id, &regeneration("Fourth")).await.unwrap().is_none());
+        assert!(analyses.regenerate(&Uuid::new_v4(), &regeneration("Fifth")).await.unwrap().is_none());
+    }
+
+    #[sqlx::test]
+    async fn find_by_checksum_ignores_files_without_a_checksum(pool: PgPool) {
+        let audio = PostgresAudioRepository::new(pool.clone());
+        let session = create_session(&pool, "session").await;
+        audio.create(&new_audio_file(session.id, "unhashed.wav", None)).await.unwrap();
+
+        assert!(audio.find_by_checksum("abc123").await.unwrap().is_none());
+        assert!(audio.find_by_checksum("").await.unwrap().is_none());
+    }
+}
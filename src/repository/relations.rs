@@ -0,0 +1,245 @@
+// src/repository/relations.rs
+//! Batched loading of the entities a page of results refers to
+//!
+//! List endpoints show the session title, analysis and the like next to each
+//! row. Looking them up row by row costs a query per row and relation;
+//! loading the distinct ids of a page with one `find_by_ids` query per
+//! relation keeps the number of queries independent of the page size.
+
+use log::warn;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::traits::{AnalysisRepository, AnalysisResult, Session, SessionRepository};
+
+/// The distinct IDs among `ids`, to load the entities a page of results
+/// refers to with one `find_by_ids` query
+pub fn unique_ids(ids: impl IntoIterator<Item = Uuid>) -> Vec<Uuid> {
+    let mut ids: Vec<_> = ids.into_iter().collect();
+    ids.sort_unstable();
+    ids.dedup();
+    ids
+}
+
+/// Related entities loaded by a `find_by_ids` query, keyed by `id`
+///
+/// List endpoints only use them to fill in fields such as a session's title,
+/// so a failed query is logged and leaves those fields empty.
+pub fn index_by_id<T>(
+    loaded: anyhow::Result<Vec<T>>,
+    what: &str,
+    id: impl Fn(&T) -> Uuid,
+) -> HashMap<Uuid, T> {
+    match loaded {
+        Ok(items) => items.into_iter().map(|item| (id(&item), item)).collect(),
+        Err(e) => {
+            warn!("Failed to load related {}: {:#}", what, e);
+            HashMap::new()
+        }
+    }
+}
+
+/// The sessions and analyses a page of ideas, tasks or notes refers to,
+/// keyed by `id`
+///
+/// Loaded with one `find_by_ids` query each, however many rows the page has,
+/// so the page takes three queries with its own instead of two per row.
+pub async fn page_relations<S, A>(
+    sessions: &S,
+    analyses: &A,
+    session_ids: impl IntoIterator<Item = Uuid>,
+    analysis_ids: impl IntoIterator<Item = Uuid>,
+) -> (HashMap<Uuid, Session>, HashMap<Uuid, AnalysisResult>)
+where
+    S: SessionRepository + ?Sized,
+    A: AnalysisRepository + ?Sized,
+{
+    let session_ids = unique_ids(session_ids);
+    let analysis_ids = unique_ids(analysis_ids);
+    let (loaded_sessions, loaded_analyses) =
+        futures::join!(sessions.find_by_ids(&session_ids), analyses.find_by_ids(&analysis_ids));
+    (
+        index_by_id(loaded_sessions, "sessions", |session| session.id),
+        index_by_id(loaded_analyses, "analyses", |analysis| analysis.id),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::traits::{
+        AnalysisRegeneration, AnalysisUpdate, DailyCount, NewAnalysisResult, NewSession, SessionFilter, SessionStatus,
+        SessionUpdate, StatsWindow,
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    /// Sessions and analyses held in memory, counting the lookups made
+    #[derive(Default)]
+    struct CountingRepositories {
+        sessions: Vec<Session>,
+        analyses: Vec<AnalysisResult>,
+        by_id: AtomicUsize,
+        /// Number of IDs of every `find_by_ids` call
+        by_ids: Mutex<Vec<usize>>,
+    }
+
+    impl CountingRepositories {
+        fn lookup<T: Clone>(&self, rows: &[T], ids: &[Uuid], id: impl Fn(&T) -> Uuid) -> anyhow::Result<Vec<T>> {
+            self.by_ids.lock().unwrap().push(ids.len());
+            Ok(rows.iter().filter(|row| ids.contains(&id(row))).cloned().collect())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl SessionRepository for CountingRepositories {
+        async fn create(&self, _session: &NewSession) -> anyhow::Result<Session> {
+            unreachable!("not used by list pages")
+        }
+        async fn find_by_id(&self, id: &Uuid) -> anyhow::Result<Option<Session>> {
+            self.by_id.fetch_add(1, Ordering::SeqCst);
+            Ok(self.sessions.iter().find(|session| session.id == *id).cloned())
+        }
+        async fn find_by_ids(&self, ids: &[Uuid]) -> anyhow::Result<Vec<Session>> {
+            self.lookup(&self.sessions, ids, |session| session.id)
+        }
+        async fn list(&self, _filter: &SessionFilter) -> anyhow::Result<Vec<Session>> {
+            unreachable!("not used by list pages")
+        }
+        async fn update(&self, _id: &Uuid, _updates: &SessionUpdate) -> anyhow::Result<Session> {
+            unreachable!("not used by list pages")
+        }
+        async fn delete(&self, _id: &Uuid) -> anyhow::Result<()> {
+            unreachable!("not used by list pages")
+        }
+        async fn purge(&self, _id: &Uuid) -> anyhow::Result<bool> {
+            unreachable!("not used by list pages")
+        }
+        async fn count(&self, _filter: &SessionFilter) -> anyhow::Result<i64> {
+            unreachable!("not used by list pages")
+        }
+        async fn find_by_status(&self, _status: SessionStatus) -> anyhow::Result<Vec<Session>> {
+            unreachable!("not used by list pages")
+        }
+        async fn find_by_legacy_id(&self, _legacy_id: &str) -> anyhow::Result<Option<Session>> {
+            unreachable!("not used by list pages")
+        }
+        async fn merge_metadata(
+            &self,
+            _id: &Uuid,
+            _patch: &serde_json::Map<String, serde_json::Value>,
+        ) -> anyhow::Result<Option<Session>> {
+            unreachable!("not used by list pages")
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl AnalysisRepository for CountingRepositories {
+        async fn create(&self, _analysis: &NewAnalysisResult) -> anyhow::Result<AnalysisResult> {
+            unreachable!("not used by list pages")
+        }
+        async fn find_by_id(&self, id: &Uuid) -> anyhow::Result<Option<AnalysisResult>> {
+            self.by_id.fetch_add(1, Ordering::SeqCst);
+            Ok(self.analyses.iter().find(|analysis| analysis.id == *id).cloned())
+        }
+        async fn find_by_ids(&self, ids: &[Uuid]) -> anyhow::Result<Vec<AnalysisResult>> {
+            self.lookup(&self.analyses, ids, |analysis| analysis.id)
+        }
+        async fn find_by_session_id(&self, _session_id: &Uuid) -> anyhow::Result<Option<AnalysisResult>> {
+            unreachable!("not used by list pages")
+        }
+        async fn find_all_by_session_id(&self, _session_id: &Uuid) -> anyhow::Result<Vec<AnalysisResult>> {
+            unreachable!("not used by list pages")
+        }
+        async fn update(&self, _id: &Uuid, _updates: &AnalysisUpdate) -> anyhow::Result<AnalysisResult> {
+            unreachable!("not used by list pages")
+        }
+        async fn regenerate(
+            &self,
+            _id: &Uuid,
+            _regeneration: &AnalysisRegeneration,
+        ) -> anyhow::Result<Option<AnalysisResult>> {
+            unreachable!("not used by list pages")
+        }
+        async fn delete(&self, _id: &Uuid) -> anyhow::Result<()> {
+            unreachable!("not used by list pages")
+        }
+        async fn restore(&self, _id: &Uuid) -> anyhow::Result<bool> {
+            unreachable!("not used by list pages")
+        }
+        async fn purge(&self, _id: &Uuid) -> anyhow::Result<bool> {
+            unreachable!("not used by list pages")
+        }
+        async fn find_by_provider(&self, _provider: &str) -> anyhow::Result<Vec<AnalysisResult>> {
+            unreachable!("not used by list pages")
+        }
+        async fn count_by_model(&self) -> anyhow::Result<HashMap<String, i64>> {
+            unreachable!("not used by list pages")
+        }
+        async fn average_processing_time_ms(&self) -> anyhow::Result<Option<f64>> {
+            unreachable!("not used by list pages")
+        }
+        async fn daily_counts(&self, _window: &StatsWindow) -> anyhow::Result<Vec<DailyCount>> {
+            unreachable!("not used by list pages")
+        }
+    }
+
+    fn session(title: &str) -> Session {
+        Session {
+            id: Uuid::new_v4(),
+            title: title.to_string(),
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+            duration_ms: 1000,
+            status: SessionStatus::Active,
+            metadata: None,
+        }
+    }
+
+    fn analysis(session: &Session) -> AnalysisResult {
+        AnalysisResult {
+            id: Uuid::new_v4(),
+            session_id: session.id,
+            title: Some(session.title.clone()),
+            summary: None,
+            provider: "ollama".to_string(),
+            model_version: None,
+            created_at: chrono::Utc::now(),
+            processing_time_ms: None,
+            result_data: None,
+            metadata: None,
+            updated_at: chrono::Utc::now(),
+            deleted_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn a_page_loads_its_sessions_and_analyses_with_one_lookup_each() {
+        let sessions: Vec<Session> = (0..5).map(|n| session(&format!("Session {}", n))).collect();
+        let analyses: Vec<AnalysisResult> = sessions.iter().map(analysis).collect();
+        let repositories = CountingRepositories {
+            sessions: sessions.clone(),
+            analyses: analyses.clone(),
+            ..Default::default()
+        };
+
+        // A page of 100 tasks, 20 per session and analysis
+        let rows: Vec<(Uuid, Uuid)> = (0..100).map(|n| (sessions[n % 5].id, analyses[n % 5].id)).collect();
+        let (found_sessions, found_analyses) = page_relations(
+            &repositories,
+            &repositories,
+            rows.iter().map(|row| row.0),
+            rows.iter().map(|row| row.1),
+        )
+        .await;
+
+        assert_eq!(found_sessions[&sessions[2].id].title, "Session 2");
+        assert_eq!(found_analyses[&analyses[4].id].session_id, sessions[4].id);
+        assert_eq!(found_sessions.len(), 5);
+        assert_eq!(found_analyses.len(), 5);
+        // Two lookups of the distinct IDs, three queries with the page's own,
+        // instead of two per row
+        assert_eq!(*repositories.by_ids.lock().unwrap(), vec![5, 5]);
+        assert_eq!(repositories.by_id.load(Ordering::SeqCst), 0);
+    }
+}
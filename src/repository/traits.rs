@@ -5,8 +5,9 @@
 //! Each entity has its own repository trait with CRUD operations and specific queries.
 
 use async_trait::async_trait;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use uuid::Uuid;
 use anyhow::Result;
 
@@ -24,6 +25,10 @@ pub struct SessionFilter {
     pub offset: Option<i64>,
     pub sort_by: Option<SessionSortBy>,
     pub sort_order: Option<SortOrder>,
+    /// Keys and values the metadata must contain; a value also matches the
+    /// number or boolean it spells, so `count=3` finds `{"count": 3}`
+    #[serde(default)]
+    pub metadata: Vec<(String, String)>,
 }
 
 /// Session status enumeration
@@ -116,6 +121,33 @@ pub struct Transcript {
     pub provider: String,
     pub created_at: DateTime<Utc>,
     pub processing_time_ms: Option<i32>,
+    /// Timestamped segments as stored by [`crate::storage::TranscriptSegment`]
+    pub segments: serde_json::Value,
+    /// Exact model that produced the transcript, e.g. `whisper-1`
+    pub model_used: Option<String>,
+}
+
+/// Number of earlier versions kept per transcript; older ones are dropped
+pub const MAX_TRANSCRIPT_VERSIONS: i32 = 20;
+
+/// Content a transcript had before it was edited or reverted
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TranscriptVersion {
+    pub transcript_id: Uuid,
+    /// Starts at 1 and increases with every change to the transcript
+    pub version: i32,
+    pub content: String,
+    pub segments: serde_json::Value,
+    /// When this content was replaced
+    pub created_at: DateTime<Utc>,
+}
+
+/// Embedding of a transcript's content, used by semantic session search
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct TranscriptEmbedding {
+    pub transcript_id: Uuid,
+    pub session_id: Uuid,
+    pub embedding: Vec<f32>,
 }
 
 /// New transcript data for creation
@@ -127,6 +159,8 @@ pub struct NewTranscript {
     pub confidence_score: Option<rust_decimal::Decimal>,
     pub provider: String,
     pub processing_time_ms: Option<i32>,
+    pub segments: serde_json::Value,
+    pub model_used: Option<String>,
 }
 
 /// Analysis result data model
@@ -140,6 +174,15 @@ pub struct AnalysisResult {
     pub model_version: Option<String>,
     pub created_at: DateTime<Utc>,
     pub processing_time_ms: Option<i32>,
+    /// The full result as produced by the model, with ideas, tasks and notes;
+    /// `None` for analyses stored before it was kept
+    pub result_data: Option<serde_json::Value>,
+    /// `previous_results` lists the results replaced by regenerations, oldest first
+    pub metadata: Option<serde_json::Value>,
+    /// Time of the last regeneration, `created_at` until then
+    pub updated_at: DateTime<Utc>,
+    /// Set while the analysis is soft-deleted
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
 /// New analysis result data for creation
@@ -153,6 +196,17 @@ pub struct NewAnalysisResult {
     pub processing_time_ms: Option<i32>,
 }
 
+/// New result of an analysis regenerated in place, see [`AnalysisRepository::regenerate`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalysisRegeneration {
+    pub title: Option<String>,
+    pub summary: Option<String>,
+    pub provider: String,
+    pub model_version: Option<String>,
+    pub processing_time_ms: Option<i32>,
+    pub result_data: serde_json::Value,
+}
+
 /// Analysis result update data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnalysisUpdate {
@@ -170,6 +224,8 @@ pub struct Idea {
     pub category: Option<String>,
     pub priority: i32,
     pub created_at: DateTime<Utc>,
+    /// Set while the idea is soft-deleted
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
 /// New idea data for creation
@@ -191,8 +247,12 @@ pub struct Task {
     pub priority: Priority,
     pub status: TaskStatus,
     pub due_date: Option<DateTime<Utc>>,
+    /// Set when the task moves to `Completed`, cleared when it leaves that status
+    pub completed_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Set while the task is soft-deleted
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
 /// Task status enumeration
@@ -236,6 +296,8 @@ pub struct StructuredNote {
     pub tags: Vec<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Set while the note is soft-deleted
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
 /// New structured note data for creation
@@ -257,8 +319,135 @@ pub struct StructuredNoteUpdate {
     pub tags: Option<Vec<String>>,
 }
 
+/// Note template data model
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct NoteTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub note_type: String,
+    /// JSON schema the content of notes using the template must match
+    pub schema: serde_json::Value,
+    pub default_content: serde_json::Value,
+    /// Editor sections, as a JSON array
+    pub sections: serde_json::Value,
+    /// Seeded templates, which cannot be changed or deleted
+    pub is_system: bool,
+    /// Number of notes created from the template
+    pub usage_count: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// New note template data for creation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewNoteTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub note_type: String,
+    pub schema: serde_json::Value,
+    pub default_content: serde_json::Value,
+    pub sections: serde_json::Value,
+}
+
+/// Note template update data
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NoteTemplateUpdate {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub note_type: Option<String>,
+    pub schema: Option<serde_json::Value>,
+    pub default_content: Option<serde_json::Value>,
+    pub sections: Option<serde_json::Value>,
+}
+
 // Repository trait definitions
 
+/// Number of records created on one calendar day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyCount {
+    pub date: NaiveDate,
+    pub count: i64,
+}
+
+/// Time window and time zone for the daily series of a stats query
+#[derive(Debug, Clone)]
+pub struct StatsWindow {
+    /// IANA time zone name used to assign records to calendar days
+    pub time_zone: String,
+    /// First instant included in the daily series
+    pub since: DateTime<Utc>,
+}
+
+/// Error returned by stats queries for a time zone the database does not know
+#[derive(Debug)]
+pub struct UnknownTimeZone(pub String);
+
+impl std::fmt::Display for UnknownTimeZone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unknown time zone: {}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownTimeZone {}
+
+/// Aggregated task statistics
+#[derive(Debug, Clone, Default)]
+pub struct TaskStats {
+    pub total: i64,
+    pub completed: i64,
+    pub by_priority: HashMap<String, i64>,
+    pub completed_by_priority: HashMap<String, i64>,
+    pub by_status: HashMap<String, i64>,
+    pub by_tag: HashMap<String, i64>,
+    pub avg_confidence: Option<f64>,
+    /// Open tasks whose due date has passed
+    pub overdue: i64,
+    /// Open tasks due within the next 7 days
+    pub upcoming: i64,
+    /// Average days from creation to completion of completed tasks
+    pub avg_completion_days: Option<f64>,
+    pub per_day: Vec<DailyCount>,
+}
+
+/// Aggregated idea statistics for one category
+#[derive(Debug, Clone)]
+pub struct IdeaCategoryStats {
+    pub category: String,
+    pub count: i64,
+    pub completed: i64,
+    pub avg_confidence: Option<f64>,
+}
+
+/// Aggregated idea statistics
+#[derive(Debug, Clone, Default)]
+pub struct IdeaStats {
+    pub total: i64,
+    pub completed: i64,
+    pub by_priority: HashMap<String, i64>,
+    pub by_status: HashMap<String, i64>,
+    pub by_tag: HashMap<String, i64>,
+    pub avg_confidence: Option<f64>,
+    /// Per-category figures, most used first
+    pub categories: Vec<IdeaCategoryStats>,
+    pub per_day: Vec<DailyCount>,
+}
+
+/// Aggregated structured note statistics
+#[derive(Debug, Clone, Default)]
+pub struct NoteStats {
+    pub total: i64,
+    pub public: i64,
+    pub total_views: i64,
+    /// Average length of the serialized note content, in characters
+    pub avg_content_length: Option<f64>,
+    pub by_note_type: HashMap<String, i64>,
+    pub by_template: HashMap<String, i64>,
+    pub by_tag: HashMap<String, i64>,
+    pub per_day: Vec<DailyCount>,
+}
+
 /// Session repository trait for managing voice recording sessions
 #[async_trait]
 pub trait SessionRepository: Send + Sync {
@@ -267,6 +456,10 @@ pub trait SessionRepository: Send + Sync {
     
     /// Find a session by ID
     async fn find_by_id(&self, id: &Uuid) -> Result<Option<Session>>;
+
+    /// Find the sessions with the given IDs, in no particular order; deleted
+    /// and unknown IDs are left out
+    async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Session>>;
     
     /// List sessions with filtering and pagination
     async fn list(&self, filter: &SessionFilter) -> Result<Vec<Session>>;
@@ -274,14 +467,31 @@ pub trait SessionRepository: Send + Sync {
     /// Update a session
     async fn update(&self, id: &Uuid, updates: &SessionUpdate) -> Result<Session>;
     
-    /// Delete a session (soft delete by setting status to Deleted)
+    /// Delete a session (soft delete by setting status to Deleted), along
+    /// with its analysis results and their ideas, tasks and notes
     async fn delete(&self, id: &Uuid) -> Result<()>;
+
+    /// Permanently delete a session with its audio files, transcripts and
+    /// analyses, deleted or not; `false` if it did not exist
+    async fn purge(&self, id: &Uuid) -> Result<bool>;
     
     /// Count total sessions matching filter
     async fn count(&self, filter: &SessionFilter) -> Result<i64>;
     
     /// Find sessions by status
     async fn find_by_status(&self, status: SessionStatus) -> Result<Vec<Session>>;
+    
+    /// Find the session imported from a legacy file-store session
+    async fn find_by_legacy_id(&self, legacy_id: &str) -> Result<Option<Session>>;
+
+    /// Set the top-level metadata keys of `patch`, removing those whose value
+    /// is `null`, and leave the other keys alone; `None` if there is no such
+    /// session
+    async fn merge_metadata(
+        &self,
+        id: &Uuid,
+        patch: &serde_json::Map<String, serde_json::Value>,
+    ) -> Result<Option<Session>>;
 }
 
 /// Audio file repository trait for managing audio files
@@ -292,15 +502,28 @@ pub trait AudioRepository: Send + Sync {
     
     /// Find audio file by ID
     async fn find_by_id(&self, id: &Uuid) -> Result<Option<AudioFile>>;
+
+    /// Find the audio files with the given IDs, in no particular order;
+    /// unknown IDs are left out
+    async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<AudioFile>>;
     
-    /// Find audio file by session ID
+    /// Find the first audio file of a session
+    ///
+    /// Most sessions have a single recording; use `find_all_by_session_id`
+    /// for sessions assembled from several clips.
     async fn find_by_session_id(&self, session_id: &Uuid) -> Result<Option<AudioFile>>;
     
+    /// Find all audio files of a session, in recording order
+    async fn find_all_by_session_id(&self, session_id: &Uuid) -> Result<Vec<AudioFile>>;
+    
     /// Delete audio file record
     async fn delete(&self, id: &Uuid) -> Result<()>;
     
     /// Update audio file checksum
     async fn update_checksum(&self, id: &Uuid, checksum: &str) -> Result<()>;
+    
+    /// Find the earliest audio file with the given content checksum
+    async fn find_by_checksum(&self, checksum: &str) -> Result<Option<AudioFile>>;
 }
 
 /// Transcript repository trait for managing transcription data
@@ -314,12 +537,45 @@ pub trait TranscriptRepository: Send + Sync {
     
     /// Find transcript by session ID
     async fn find_by_session_id(&self, session_id: &Uuid) -> Result<Option<Transcript>>;
+
+    /// Transcripts of the sessions in `session_ids`, in no particular order
+    async fn find_by_session_ids(&self, session_ids: &[Uuid]) -> Result<Vec<Transcript>>;
     
     /// Delete transcript
     async fn delete(&self, id: &Uuid) -> Result<()>;
     
     /// Find transcripts by provider
     async fn find_by_provider(&self, provider: &str) -> Result<Vec<Transcript>>;
+
+    /// Replace a transcript's content, saving the previous content and
+    /// segments as a new version; `None` if there is no such transcript
+    async fn update_content(&self, id: &Uuid, content: &str) -> Result<Option<Transcript>>;
+
+    /// Saved versions of a transcript, newest first
+    async fn versions(&self, id: &Uuid) -> Result<Vec<TranscriptVersion>>;
+
+    /// Restore the content and segments of `version`, saving the current ones
+    /// as a new version; `None` if the transcript has no such version
+    async fn revert(&self, id: &Uuid, version: i32) -> Result<Option<Transcript>>;
+
+    /// Number of transcripts per model; transcripts without a recorded model are not counted
+    async fn count_by_model(&self) -> Result<HashMap<String, i64>>;
+
+    /// Transcripts created per day of `window`; fails with [`UnknownTimeZone`]
+    /// for an unknown `window.time_zone`
+    async fn daily_counts(&self, window: &StatsWindow) -> Result<Vec<DailyCount>>;
+
+    /// Store the embedding of a transcript's current content made with
+    /// `model`, replacing an earlier one
+    async fn save_embedding(&self, id: &Uuid, model: &str, embedding: &[f32]) -> Result<()>;
+
+    /// All transcript embeddings made with `model`, except those of deleted sessions
+    async fn embeddings(&self, model: &str) -> Result<Vec<TranscriptEmbedding>>;
+
+    /// Up to `limit` transcripts of sessions that are not deleted, with
+    /// content but no embedding made with `model`, oldest first; changing
+    /// the content drops its embeddings
+    async fn find_without_embedding(&self, model: &str, limit: i64) -> Result<Vec<Transcript>>;
 }
 
 /// Analysis repository trait for managing AI analysis results
@@ -328,20 +584,50 @@ pub trait AnalysisRepository: Send + Sync {
     /// Create a new analysis result
     async fn create(&self, analysis: &NewAnalysisResult) -> Result<AnalysisResult>;
     
-    /// Find analysis result by ID
+    /// Find analysis result by ID; soft-deleted analysis results are not returned
     async fn find_by_id(&self, id: &Uuid) -> Result<Option<AnalysisResult>>;
+
+    /// Find the analysis results with the given IDs, in no particular order;
+    /// soft-deleted and unknown IDs are left out
+    async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<AnalysisResult>>;
     
-    /// Find analysis result by session ID
+    /// Find the current analysis result of a session, i.e. the most recently
+    /// created one that is not soft-deleted
     async fn find_by_session_id(&self, session_id: &Uuid) -> Result<Option<AnalysisResult>>;
+
+    /// All analysis results of a session that are not soft-deleted, oldest
+    /// first; the last one is the current analysis
+    async fn find_all_by_session_id(&self, session_id: &Uuid) -> Result<Vec<AnalysisResult>>;
     
     /// Update analysis result
     async fn update(&self, id: &Uuid, updates: &AnalysisUpdate) -> Result<AnalysisResult>;
+
+    /// Replace the result of a live analysis, keeping its id and bumping
+    /// `updated_at`; the replaced result is appended to
+    /// `metadata.previous_results`. `None` if there is no such live analysis.
+    async fn regenerate(&self, id: &Uuid, regeneration: &AnalysisRegeneration) -> Result<Option<AnalysisResult>>;
     
-    /// Delete analysis result
+    /// Soft-delete an analysis result, hiding it until it is restored or purged
     async fn delete(&self, id: &Uuid) -> Result<()>;
+
+    /// Restore a soft-deleted analysis result; `false` if no such analysis result is deleted
+    async fn restore(&self, id: &Uuid) -> Result<bool>;
+
+    /// Permanently delete an analysis result, soft-deleted or not; `false` if it did not exist
+    async fn purge(&self, id: &Uuid) -> Result<bool>;
     
     /// Find analysis results by provider
     async fn find_by_provider(&self, provider: &str) -> Result<Vec<AnalysisResult>>;
+
+    /// Number of analyses per `model_version`; analyses without a recorded model are not counted
+    async fn count_by_model(&self) -> Result<HashMap<String, i64>>;
+    
+    /// Average processing time of analyses that recorded one, if any did
+    async fn average_processing_time_ms(&self) -> Result<Option<f64>>;
+
+    /// Analyses created per day of `window`; fails with [`UnknownTimeZone`]
+    /// for an unknown `window.time_zone`
+    async fn daily_counts(&self, window: &StatsWindow) -> Result<Vec<DailyCount>>;
 }
 
 /// Idea repository trait for managing extracted ideas
@@ -350,7 +636,7 @@ pub trait IdeaRepository: Send + Sync {
     /// Create a new idea
     async fn create(&self, idea: &NewIdea) -> Result<Idea>;
     
-    /// Find idea by ID
+    /// Find idea by ID; soft-deleted ideas are not returned
     async fn find_by_id(&self, id: &Uuid) -> Result<Option<Idea>>;
     
     /// Find ideas by analysis ID
@@ -359,11 +645,21 @@ pub trait IdeaRepository: Send + Sync {
     /// Update idea
     async fn update(&self, id: &Uuid, content: &str, category: Option<&str>, priority: i32) -> Result<Idea>;
     
-    /// Delete idea
+    /// Soft-delete an idea, hiding it until it is restored or purged
     async fn delete(&self, id: &Uuid) -> Result<()>;
+
+    /// Restore a soft-deleted idea; `false` if no such idea is deleted
+    async fn restore(&self, id: &Uuid) -> Result<bool>;
+
+    /// Permanently delete an idea, soft-deleted or not; `false` if it did not exist
+    async fn purge(&self, id: &Uuid) -> Result<bool>;
     
     /// Find ideas by category
     async fn find_by_category(&self, category: &str) -> Result<Vec<Idea>>;
+
+    /// Aggregate idea statistics; fails with [`UnknownTimeZone`] for an
+    /// unknown `window.time_zone`
+    async fn stats(&self, window: &StatsWindow) -> Result<IdeaStats>;
 }
 
 /// Task repository trait for managing extracted tasks
@@ -372,17 +668,23 @@ pub trait TaskRepository: Send + Sync {
     /// Create a new task
     async fn create(&self, task: &NewTask) -> Result<Task>;
     
-    /// Find task by ID
+    /// Find task by ID; soft-deleted tasks are not returned
     async fn find_by_id(&self, id: &Uuid) -> Result<Option<Task>>;
     
     /// Find tasks by analysis ID
     async fn find_by_analysis_id(&self, analysis_id: &Uuid) -> Result<Vec<Task>>;
     
-    /// Update task
+    /// Update task, maintaining `completed_at` when the status changes
     async fn update(&self, id: &Uuid, updates: &TaskUpdate) -> Result<Task>;
     
-    /// Delete task
+    /// Soft-delete a task, hiding it until it is restored or purged
     async fn delete(&self, id: &Uuid) -> Result<()>;
+
+    /// Restore a soft-deleted task; `false` if no such task is deleted
+    async fn restore(&self, id: &Uuid) -> Result<bool>;
+
+    /// Permanently delete a task, soft-deleted or not; `false` if it did not exist
+    async fn purge(&self, id: &Uuid) -> Result<bool>;
     
     /// Find tasks by status
     async fn find_by_status(&self, status: TaskStatus) -> Result<Vec<Task>>;
@@ -392,6 +694,10 @@ pub trait TaskRepository: Send + Sync {
     
     /// Mark task as completed
     async fn mark_completed(&self, id: &Uuid) -> Result<Task>;
+
+    /// Aggregate task statistics; fails with [`UnknownTimeZone`] for an
+    /// unknown `window.time_zone`
+    async fn stats(&self, window: &StatsWindow) -> Result<TaskStats>;
 }
 
 /// Structured note repository trait for managing structured notes
@@ -400,7 +706,7 @@ pub trait StructuredNoteRepository: Send + Sync {
     /// Create a new structured note
     async fn create(&self, note: &NewStructuredNote) -> Result<StructuredNote>;
     
-    /// Find structured note by ID
+    /// Find structured note by ID; soft-deleted structured notes are not returned
     async fn find_by_id(&self, id: &Uuid) -> Result<Option<StructuredNote>>;
     
     /// Find structured notes by analysis ID
@@ -409,12 +715,118 @@ pub trait StructuredNoteRepository: Send + Sync {
     /// Update structured note
     async fn update(&self, id: &Uuid, updates: &StructuredNoteUpdate) -> Result<StructuredNote>;
     
-    /// Delete structured note
+    /// Soft-delete a structured note, hiding it until it is restored or purged
     async fn delete(&self, id: &Uuid) -> Result<()>;
+
+    /// Restore a soft-deleted structured note; `false` if no such structured note is deleted
+    async fn restore(&self, id: &Uuid) -> Result<bool>;
+
+    /// Permanently delete a structured note, soft-deleted or not; `false` if it did not exist
+    async fn purge(&self, id: &Uuid) -> Result<bool>;
     
     /// Find structured notes by type
     async fn find_by_note_type(&self, note_type: NoteType) -> Result<Vec<StructuredNote>>;
     
     /// Find structured notes by tags
     async fn find_by_tags(&self, tags: &[String]) -> Result<Vec<StructuredNote>>;
+
+    /// Aggregate structured note statistics; fails with [`UnknownTimeZone`]
+    /// for an unknown `window.time_zone`
+    async fn stats(&self, window: &StatsWindow) -> Result<NoteStats>;
+}
+
+/// Note template repository trait for managing system and custom templates
+#[async_trait]
+pub trait NoteTemplateRepository: Send + Sync {
+    /// Create a custom note template
+    async fn create(&self, template: &NewNoteTemplate) -> Result<NoteTemplate>;
+
+    /// Find a note template by ID
+    async fn find_by_id(&self, id: &str) -> Result<Option<NoteTemplate>>;
+
+    /// List all note templates, system templates first, then by name
+    async fn list(&self) -> Result<Vec<NoteTemplate>>;
+
+    /// Update a custom note template; `None` if there is no such custom template
+    async fn update(&self, id: &str, updates: &NoteTemplateUpdate) -> Result<Option<NoteTemplate>>;
+
+    /// Delete a custom note template; `false` if there is no such custom template.
+    /// Notes using it keep its ID.
+    async fn delete(&self, id: &str) -> Result<bool>;
+
+    /// Count a note created from the template; `false` if there is no such template
+    async fn increment_usage(&self, id: &str) -> Result<bool>;
+
+    /// The `limit` most used templates, most used first
+    async fn most_used(&self, limit: i64) -> Result<Vec<NoteTemplate>>;
+}
+
+/// Kinds of rows in a full data archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveEntity {
+    NoteTemplate,
+    Session,
+    AudioFile,
+    Transcript,
+    Analysis,
+    Idea,
+    Task,
+    Note,
+}
+
+impl ArchiveEntity {
+    /// Every entity, referenced rows before the rows referencing them, so an
+    /// archive written in this order can be imported front to back
+    pub const ALL: [ArchiveEntity; 8] = [
+        ArchiveEntity::NoteTemplate,
+        ArchiveEntity::Session,
+        ArchiveEntity::AudioFile,
+        ArchiveEntity::Transcript,
+        ArchiveEntity::Analysis,
+        ArchiveEntity::Idea,
+        ArchiveEntity::Task,
+        ArchiveEntity::Note,
+    ];
+
+    /// Table holding the entity
+    pub fn table(self) -> &'static str {
+        match self {
+            ArchiveEntity::NoteTemplate => "note_templates",
+            ArchiveEntity::Session => "sessions",
+            ArchiveEntity::AudioFile => "audio_files",
+            ArchiveEntity::Transcript => "transcripts",
+            ArchiveEntity::Analysis => "analysis_results",
+            ArchiveEntity::Idea => "ideas",
+            ArchiveEntity::Task => "tasks",
+            ArchiveEntity::Note => "structured_notes",
+        }
+    }
+
+    /// SQL type of the table's `id` column
+    pub fn id_type(self) -> &'static str {
+        match self {
+            ArchiveEntity::NoteTemplate => "varchar",
+            _ => "uuid",
+        }
+    }
+}
+
+/// Raw row access for full exports and imports of every table
+#[async_trait]
+pub trait ArchiveRepository: Send + Sync {
+    /// Up to `limit` rows of `entity` as JSON objects keyed by column, ordered
+    /// by ID and starting after the row with ID `after`. Soft-deleted rows are
+    /// included.
+    async fn export_page(
+        &self,
+        entity: ArchiveEntity,
+        after: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<serde_json::Value>>;
+
+    /// Insert rows exported by [`export_page`](Self::export_page) in one
+    /// transaction, skipping rows whose ID already exists; returns the number
+    /// inserted
+    async fn import_rows(&self, entity: ArchiveEntity, rows: &[serde_json::Value]) -> Result<u64>;
 }
\ No newline at end of file
@@ -0,0 +1,73 @@
+// src/response.rs
+//! JSON envelope shared by the web UI routes and the v1 API
+//!
+//! Every successful JSON response is
+//! `{"success", "data", "message", "total", "page", "per_page", "timestamp"}`.
+//! `message` and the pagination fields are always present and `null` when
+//! they do not apply, so clients can read any endpoint the same way. Errors
+//! keep their own `{"error": ...}` bodies.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Envelope of a successful JSON response
+#[derive(Debug, Serialize)]
+pub struct ApiResponse<T> {
+    pub success: bool,
+    pub data: T,
+    pub message: Option<String>,
+    /// Number of items across all pages
+    pub total: Option<i64>,
+    /// 1-based page number
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl<T> ApiResponse<T> {
+    pub fn success(data: T) -> Self {
+        Self {
+            success: true,
+            data,
+            message: None,
+            total: None,
+            page: None,
+            per_page: None,
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// One page of a list
+    pub fn paginated(data: T, total: i64, page: i64, per_page: i64) -> Self {
+        Self {
+            total: Some(total),
+            page: Some(page),
+            per_page: Some(per_page),
+            ..Self::success(data)
+        }
+    }
+
+    /// A complete, unpaginated list of `total` items
+    pub fn with_total(mut self, total: i64) -> Self {
+        self.total = Some(total);
+        self
+    }
+
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    /// Replace the data, keeping the message and pagination
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> ApiResponse<U> {
+        ApiResponse {
+            success: self.success,
+            data: f(self.data),
+            message: self.message,
+            total: self.total,
+            page: self.page,
+            per_page: self.per_page,
+            timestamp: self.timestamp,
+        }
+    }
+}
@@ -0,0 +1,267 @@
+// src/services/migration.rs
+//! Import of legacy file-store sessions into the repository-backed store
+//!
+//! Sessions recorded through the CLI or `voice-recorder web` are stored as
+//! JSON files by [`crate::storage`]. Importing them, with their audio,
+//! transcript and analysis, makes them visible through the API as well.
+//!
+//! The import is idempotent. A session is skipped when a repository session
+//! already carries its legacy id, or when its audio checksum is already known
+//! (e.g. the same recording was uploaded through the API).
+
+use anyhow::{Context, Result};
+use log::{info, warn};
+use std::path::Path;
+use uuid::Uuid;
+
+use crate::repository::{
+    AnalysisRepository, AudioRepository, IdeaRepository, NewAnalysisResult, NewAudioFile, NewIdea,
+    NewSession, NewStructuredNote, NewTask, NewTranscript, RepositoryManager, SessionRepository,
+    StructuredNoteRepository, TaskRepository, TranscriptRepository,
+};
+use crate::storage::VoiceSession;
+
+/// Session metadata key holding the id of the imported legacy session
+pub const LEGACY_ID_KEY: &str = "legacy_session_id";
+
+/// Provider recorded on imported transcripts and analyses
+const LEGACY_PROVIDER: &str = "legacy";
+
+/// Priority given to imported ideas, which the legacy store does not rank
+const DEFAULT_IDEA_PRIORITY: i32 = 1;
+
+/// Outcome counts of a legacy import run
+#[derive(Debug, Default)]
+pub struct MigrationStats {
+    pub total: usize,
+    pub imported: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+/// Import every legacy session that is not in the repositories yet
+///
+/// A session that fails to import is rolled back and counted in
+/// [`MigrationStats::failed`], so a later run retries it.
+pub async fn migrate_legacy_sessions<R: RepositoryManager>(repositories: &R) -> Result<MigrationStats> {
+    let sessions = crate::storage::list_sessions()
+        .await
+        .context("Failed to list legacy sessions")?;
+
+    let mut stats = MigrationStats {
+        total: sessions.len(),
+        ..Default::default()
+    };
+    info!("Migrating {} legacy sessions", stats.total);
+
+    for session in &sessions {
+        match migrate_session(repositories, session).await {
+            Ok(Some(new_id)) => {
+                info!("[{}] Imported as session {}", session.id, new_id);
+                stats.imported += 1;
+            }
+            Ok(None) => stats.skipped += 1,
+            Err(e) => {
+                warn!("[{}] Failed to import session: {:#}", session.id, e);
+                stats.failed += 1;
+            }
+        }
+    }
+
+    info!(
+        "Migration finished: {} imported, {} already present, {} failed",
+        stats.imported, stats.skipped, stats.failed
+    );
+    Ok(stats)
+}
+
+/// Import a single session, returning its new id, or `None` when it is
+/// already present
+async fn migrate_session<R: RepositoryManager>(
+    repositories: &R,
+    session: &VoiceSession,
+) -> Result<Option<Uuid>> {
+    if repositories.sessions().find_by_legacy_id(&session.id).await?.is_some() {
+        return Ok(None);
+    }
+
+    let audio_exists = session.audio_file_path.exists();
+    let checksum = match &session.audio_checksum {
+        Some(checksum) => Some(checksum.clone()),
+        None if audio_exists => Some(
+            crate::storage::file_checksum(&session.audio_file_path)
+                .await
+                .with_context(|| format!("Failed to hash {}", session.audio_file_path.display()))?,
+        ),
+        None => None,
+    };
+    if let Some(checksum) = &checksum {
+        if repositories.audio_files().find_by_checksum(checksum).await?.is_some() {
+            return Ok(None);
+        }
+    }
+
+    let created = repositories
+        .sessions()
+        .create(&NewSession {
+            title: session.title.clone(),
+            duration_ms: session.duration_ms as i64,
+            metadata: Some(serde_json::json!({
+                LEGACY_ID_KEY: session.id,
+                "recorded_at": session.timestamp,
+            })),
+        })
+        .await
+        .context("Failed to create session")?;
+
+    // Without a transaction, undo the partial import so the session is
+    // retried rather than skipped on the next run. A soft delete would leave
+    // its audio file row, whose checksum would skip the session from then on.
+    if let Err(e) = import_session_data(repositories, session, created.id, audio_exists, checksum).await {
+        if let Err(rollback) = repositories.sessions().purge(&created.id).await {
+            warn!("[{}] Failed to roll back partial import: {:#}", session.id, rollback);
+        }
+        return Err(e);
+    }
+
+    Ok(Some(created.id))
+}
+
+async fn import_session_data<R: RepositoryManager>(
+    repositories: &R,
+    session: &VoiceSession,
+    session_id: Uuid,
+    audio_exists: bool,
+    checksum: Option<String>,
+) -> Result<()> {
+    if audio_exists {
+        let path = &session.audio_file_path;
+        let file_size = tokio::fs::metadata(path)
+            .await
+            .with_context(|| format!("Failed to read {}", path.display()))?
+            .len();
+        let (sample_rate, channels) = wav_format(path);
+
+        repositories
+            .audio_files()
+            .create(&NewAudioFile {
+                session_id,
+                file_path: path.display().to_string(),
+                file_size: file_size as i64,
+                format: path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .map(str::to_lowercase)
+                    .unwrap_or_else(|| "wav".to_string()),
+                sample_rate,
+                channels,
+                checksum,
+            })
+            .await
+            .context("Failed to create audio file")?;
+    } else {
+        warn!(
+            "[{}] Audio file {} not found, importing without audio",
+            session.id,
+            session.audio_file_path.display()
+        );
+    }
+
+    if let Some(transcript) = &session.transcript {
+        repositories
+            .transcripts()
+            .create(&NewTranscript {
+                session_id,
+                content: transcript.clone(),
+                language: session.language.clone(),
+                confidence_score: None,
+                provider: session
+                    .transcription_provider
+                    .clone()
+                    .unwrap_or_else(|| LEGACY_PROVIDER.to_string()),
+                processing_time_ms: None,
+                segments: serde_json::to_value(&session.segments)
+                    .context("Failed to serialize transcript segments")?,
+                model_used: session.transcription_model.clone(),
+            })
+            .await
+            .context("Failed to create transcript")?;
+    }
+
+    let Some(analysis) = &session.analysis else {
+        return Ok(());
+    };
+
+    let analysis_id = repositories
+        .analysis_results()
+        .create(&NewAnalysisResult {
+            session_id,
+            title: Some(analysis.title.clone()).filter(|title| !title.is_empty()),
+            summary: Some(analysis.summary.clone()).filter(|summary| !summary.is_empty()),
+            provider: analysis
+                .provider
+                .clone()
+                .unwrap_or_else(|| LEGACY_PROVIDER.to_string()),
+            model_version: analysis.model.clone(),
+            processing_time_ms: analysis
+                .processing_time_ms
+                .map(|ms| ms.min(i32::MAX as u64) as i32),
+        })
+        .await
+        .context("Failed to create analysis result")?
+        .id;
+
+    for idea in &analysis.ideas {
+        repositories
+            .ideas()
+            .create(&NewIdea {
+                analysis_id,
+                content: idea.clone(),
+                category: None,
+                priority: DEFAULT_IDEA_PRIORITY,
+            })
+            .await
+            .context("Failed to create idea")?;
+    }
+
+    for task in &analysis.tasks {
+        repositories
+            .tasks()
+            .create(&NewTask {
+                analysis_id,
+                title: task.title.clone(),
+                description: task.description.clone(),
+                priority: task.priority.clone(),
+                due_date: task.due_date,
+            })
+            .await
+            .context("Failed to create task")?;
+    }
+
+    for note in &analysis.structured_notes {
+        repositories
+            .structured_notes()
+            .create(&NewStructuredNote {
+                analysis_id,
+                title: note.title.clone(),
+                content: note.content.clone(),
+                note_type: note.note_type.clone(),
+                tags: note.tags.clone(),
+            })
+            .await
+            .context("Failed to create structured note")?;
+    }
+
+    Ok(())
+}
+
+/// Sample rate and channel count of a WAV file, if it can be read as one
+fn wav_format(path: &Path) -> (Option<i32>, Option<i32>) {
+    match hound::WavReader::open(path) {
+        Ok(reader) => {
+            let spec = reader.spec();
+            (Some(spec.sample_rate as i32), Some(spec.channels as i32))
+        }
+        Err(_) => (None, None),
+    }
+}
@@ -13,6 +13,7 @@ pub mod transcription;
 pub mod analysis;
 pub mod session;
 pub mod file_storage;
+pub mod migration;
 
 // Re-export commonly used types and traits
 pub use traits::*;
@@ -44,7 +45,10 @@ impl<R: RepositoryManager + 'static> ServiceManager<R> {
         );
         
         let ollama_service = Arc::new(
-            ollama::OllamaServiceImpl::new(&config.ollama.base_url)
+            ollama::OllamaServiceImpl::new(
+                &config.ollama.base_url,
+                std::time::Duration::from_secs(config.ollama.timeout_secs),
+            )
         );
         
         let audio_service = Arc::new(
@@ -58,6 +62,7 @@ impl<R: RepositoryManager + 'static> ServiceManager<R> {
             transcription::TranscriptionServiceImpl::new(
                 repository_manager.clone(),
                 &config.openai.api_key,
+                &config.openai.base_url,
             )
         );
         
@@ -104,6 +109,11 @@ impl<R: RepositoryManager + 'static> ServiceManager<R> {
         }
     }
     
+    /// Apply reloadable configuration values to long-lived services
+    pub fn apply_config(&self, config: &crate::config::Config) {
+        self.ollama_service.set_base_url(&config.ollama.base_url);
+    }
+    
     /// Get audio service
     pub fn audio(&self) -> &dyn AudioService {
         self.audio_service.as_ref()
@@ -6,41 +6,73 @@
 
 use async_trait::async_trait;
 use anyhow::{Result, Context};
+use futures::stream::{BoxStream, StreamExt};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::RwLock;
 use std::time::Duration;
 
-use super::traits::{OllamaService, OllamaModel, OllamaModelDetails, OllamaOptions};
+use super::traits::{
+    ModelReadiness, OllamaService, OllamaModel, OllamaModelDetails, OllamaOptions, PullProgress, PullStream,
+    TextStream,
+};
+
+/// Time allowed to establish a connection, kept short so an unreachable
+/// server is reported quickly whatever the request timeout
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Rough model load throughput used to estimate a cold start before one has
+/// been measured (bytes per millisecond, i.e. about 500 MB/s)
+const ESTIMATED_LOAD_BYTES_PER_MS: u64 = 500_000;
 
 /// Ollama service implementation
 pub struct OllamaServiceImpl {
     client: Client,
-    base_url: String,
+    /// Client without an overall timeout, for pulls that run as long as the
+    /// download takes
+    stream_client: Client,
+    base_url: RwLock<String>,
+    /// Last measured load time per model, in milliseconds
+    cold_starts: RwLock<HashMap<String, u64>>,
 }
 
 impl OllamaServiceImpl {
-    /// Create a new Ollama service instance
-    pub fn new(base_url: &str) -> Self {
+    /// Create a new Ollama service instance whose requests, including model
+    /// generation, may take up to `timeout`; model pulls have no overall limit
+    pub fn new(base_url: &str, timeout: Duration) -> Self {
         let client = Client::builder()
-            .timeout(Duration::from_secs(300)) // 5 minutes timeout for model operations
+            .connect_timeout(CONNECT_TIMEOUT)
+            .timeout(timeout)
+            .build()
+            .expect("Failed to create HTTP client");
+        let stream_client = Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
             .build()
             .expect("Failed to create HTTP client");
         
         Self {
             client,
-            base_url: base_url.trim_end_matches('/').to_string(),
+            stream_client,
+            base_url: RwLock::new(base_url.trim_end_matches('/').to_string()),
+            cold_starts: RwLock::new(HashMap::new()),
         }
     }
     
     /// Build URL for Ollama API endpoint
     fn build_url(&self, endpoint: &str) -> String {
-        format!("{}/api/{}", self.base_url, endpoint.trim_start_matches('/'))
+        let base_url = self.base_url.read().unwrap();
+        format!("{}/api/{}", base_url, endpoint.trim_start_matches('/'))
     }
 }
 
 #[async_trait]
 impl OllamaService for OllamaServiceImpl {
+    fn set_base_url(&self, base_url: &str) {
+        *self.base_url.write().unwrap() = base_url.trim_end_matches('/').to_string();
+    }
+
     async fn is_available(&self) -> bool {
         match self.client.get(&self.build_url("tags")).send().await {
             Ok(response) => response.status().is_success(),
@@ -78,7 +110,7 @@ impl OllamaService for OllamaServiceImpl {
         };
         
         let response = self
-            .client
+            .stream_client
             .post(&self.build_url("pull"))
             .json(&request_body)
             .send()
@@ -134,6 +166,105 @@ impl OllamaService for OllamaServiceImpl {
         Ok(response_data.response)
     }
     
+    async fn generate_stream(
+        &self,
+        model: &str,
+        prompt: &str,
+        options: Option<OllamaOptions>,
+    ) -> Result<TextStream> {
+        let request_body = OllamaGenerateRequest {
+            model: model.to_string(),
+            prompt: prompt.to_string(),
+            stream: Some(true),
+            options: options.map(|opts| serde_json::to_value(opts).unwrap_or_default()),
+        };
+        
+        let response = self
+            .client
+            .post(&self.build_url("generate"))
+            .json(&request_body)
+            .send()
+            .await
+            .context("Failed to send generate request to Ollama")?;
+        
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(anyhow::anyhow!(
+                "Ollama generation failed: {}",
+                error_text
+            ));
+        }
+        
+        let stream = ndjson_stream::<OllamaGenerateResponse>(response)
+            .map(|item| item.map(|chunk| chunk.response));
+        
+        Ok(stream.boxed())
+    }
+    
+    async fn pull_model_stream(&self, model_name: &str) -> Result<PullStream> {
+        let request_body = OllamaPullRequest {
+            name: model_name.to_string(),
+            stream: Some(true),
+        };
+        
+        let response = self
+            .stream_client
+            .post(&self.build_url("pull"))
+            .json(&request_body)
+            .send()
+            .await
+            .context("Failed to send pull request to Ollama")?;
+        
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(anyhow::anyhow!(
+                "Failed to pull model '{}': {}",
+                model_name,
+                error_text
+            ));
+        }
+        
+        // Errors during the pull arrive as `{"error": "..."}` lines
+        let stream = ndjson_stream::<OllamaPullChunk>(response).map(|item| {
+            let chunk = item?;
+            if let Some(error) = chunk.error {
+                return Err(anyhow::anyhow!("Ollama pull failed: {}", error));
+            }
+            Ok(PullProgress {
+                status: chunk.status.unwrap_or_default(),
+                digest: chunk.digest,
+                total: chunk.total,
+                completed: chunk.completed,
+            })
+        });
+        
+        Ok(stream.boxed())
+    }
+    
+    async fn delete_model(&self, model_name: &str) -> Result<()> {
+        let response = self
+            .client
+            .delete(&self.build_url("delete"))
+            .json(&serde_json::json!({ "name": model_name }))
+            .send()
+            .await
+            .context("Failed to send delete request to Ollama")?;
+        
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(anyhow::anyhow!("Model '{}' not found", model_name));
+        }
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(anyhow::anyhow!(
+                "Failed to delete model '{}': {}",
+                model_name,
+                error_text
+            ));
+        }
+        
+        Ok(())
+    }
+    
     async fn generate_structured<T>(
         &self,
         model: &str,
@@ -165,6 +296,122 @@ Please respond with valid JSON only, no additional text or explanation.",
         let models = self.list_models().await?;
         Ok(models.into_iter().find(|m| m.name == model_name))
     }
+    
+    async fn list_loaded_models(&self) -> Result<Vec<String>> {
+        let response = self
+            .client
+            .get(&self.build_url("ps"))
+            .send()
+            .await
+            .context("Failed to send request to Ollama")?;
+        
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Ollama API returned error: {}",
+                response.status()
+            ));
+        }
+        
+        let response_data: OllamaPsResponse = response
+            .json()
+            .await
+            .context("Failed to parse Ollama response")?;
+        
+        Ok(response_data.models.into_iter().map(|m| m.name).collect())
+    }
+    
+    async fn model_readiness(&self, model_name: &str) -> Result<ModelReadiness> {
+        let loaded = self
+            .list_loaded_models()
+            .await?
+            .iter()
+            .any(|name| model_name_matches(name, model_name));
+        let installed = self
+            .list_models()
+            .await?
+            .into_iter()
+            .find(|m| model_name_matches(&m.name, model_name));
+        
+        let estimated_cold_start_ms = if loaded {
+            Some(0)
+        } else {
+            let measured = self.cold_starts.read().unwrap().get(model_name).copied();
+            measured.or_else(|| {
+                installed
+                    .as_ref()
+                    .map(|m| m.size.max(0) as u64 / ESTIMATED_LOAD_BYTES_PER_MS)
+            })
+        };
+        
+        Ok(ModelReadiness {
+            model: model_name.to_string(),
+            installed: installed.is_some(),
+            loaded,
+            estimated_cold_start_ms,
+        })
+    }
+    
+    async fn warmup(&self, model_name: &str) -> Result<u64> {
+        // An empty prompt makes Ollama load the model without generating
+        let request_body = OllamaGenerateRequest {
+            model: model_name.to_string(),
+            prompt: String::new(),
+            stream: Some(false),
+            options: None,
+        };
+        
+        let started = std::time::Instant::now();
+        let response = self
+            .client
+            .post(&self.build_url("generate"))
+            .json(&request_body)
+            .send()
+            .await
+            .context("Failed to send warmup request to Ollama")?;
+        
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(anyhow::anyhow!(
+                "Failed to load model '{}': {}",
+                model_name,
+                error_text
+            ));
+        }
+        
+        let response_data: OllamaGenerateResponse = response
+            .json()
+            .await
+            .context("Failed to parse Ollama response")?;
+        
+        // Prefer Ollama's own measurement; fall back to the request time
+        let load_ms = response_data
+            .load_duration
+            .map(|ns| ns.max(0) as u64 / 1_000_000)
+            .unwrap_or_else(|| started.elapsed().as_millis() as u64);
+        
+        // A load that took almost no time means the model was already resident
+        // and says nothing about cold starts
+        if load_ms > 0 {
+            self.cold_starts
+                .write()
+                .unwrap()
+                .insert(model_name.to_string(), load_ms);
+        }
+        
+        Ok(load_ms)
+    }
+}
+
+/// Whether an Ollama model name refers to `wanted`, treating a missing tag as `:latest`
+fn model_name_matches(name: &str, wanted: &str) -> bool {
+    let with_tag = |n: &str| {
+        if n.contains(':') {
+            n.to_string()
+        } else {
+            format!("{}:latest", n)
+        }
+    };
+    with_tag(name) == with_tag(wanted)
 }
 
 impl OllamaServiceImpl {
@@ -192,6 +439,52 @@ impl OllamaServiceImpl {
     }
 }
 
+/// Decode a streamed Ollama response body of newline-delimited JSON objects
+///
+/// A network chunk may hold several lines or end halfway through one. The
+/// stream ends after the first error. Dropping it closes the connection.
+fn ndjson_stream<T>(response: reqwest::Response) -> BoxStream<'static, Result<T>>
+where
+    T: for<'de> Deserialize<'de> + Send + 'static,
+{
+    let state = (response.bytes_stream(), Vec::<u8>::new(), false);
+    futures::stream::unfold(state, |(mut bytes, mut buffer, done)| async move {
+        loop {
+            if done {
+                return None;
+            }
+            
+            if let Some(newline) = buffer.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=newline).collect();
+                if line.iter().all(u8::is_ascii_whitespace) {
+                    continue;
+                }
+                let item = serde_json::from_slice::<T>(&line)
+                    .context("Failed to parse Ollama stream chunk");
+                let done = item.is_err();
+                return Some((item, (bytes, buffer, done)));
+            }
+            
+            match bytes.next().await {
+                Some(Ok(chunk)) => buffer.extend_from_slice(&chunk),
+                Some(Err(e)) => {
+                    let error = anyhow::Error::new(e).context("Ollama stream interrupted");
+                    return Some((Err(error), (bytes, buffer, true)));
+                }
+                None if buffer.iter().all(u8::is_ascii_whitespace) => return None,
+                // Final line without a trailing newline
+                None => {
+                    let item = serde_json::from_slice::<T>(&buffer)
+                        .context("Failed to parse Ollama stream chunk");
+                    buffer.clear();
+                    return Some((item, (bytes, buffer, true)));
+                }
+            }
+        }
+    })
+    .boxed()
+}
+
 // Ollama API request/response types
 
 #[derive(Debug, Serialize)]
@@ -232,20 +525,39 @@ struct OllamaGenerateResponse {
     eval_duration: Option<i64>,
 }
 
+#[derive(Debug, Deserialize)]
+struct OllamaPullChunk {
+    status: Option<String>,
+    digest: Option<String>,
+    total: Option<u64>,
+    completed: Option<u64>,
+    error: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct OllamaTagsResponse {
     models: Vec<OllamaModel>,
 }
 
+#[derive(Debug, Deserialize)]
+struct OllamaPsResponse {
+    models: Vec<OllamaPsModel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaPsModel {
+    name: String,
+}
+
 /// Enhanced Ollama service with language detection integration
 pub struct EnhancedOllamaService {
     ollama: OllamaServiceImpl,
 }
 
 impl EnhancedOllamaService {
-    pub fn new(base_url: &str) -> Self {
+    pub fn new(base_url: &str, timeout: Duration) -> Self {
         Self {
-            ollama: OllamaServiceImpl::new(base_url),
+            ollama: OllamaServiceImpl::new(base_url, timeout),
         }
     }
     
@@ -409,6 +721,35 @@ impl OllamaService for EnhancedOllamaService {
         self.ollama.generate(model, prompt, options).await
     }
     
+    async fn generate_stream(
+        &self,
+        model: &str,
+        prompt: &str,
+        options: Option<OllamaOptions>,
+    ) -> Result<TextStream> {
+        self.ollama.generate_stream(model, prompt, options).await
+    }
+    
+    async fn pull_model_stream(&self, model_name: &str) -> Result<PullStream> {
+        self.ollama.pull_model_stream(model_name).await
+    }
+    
+    async fn delete_model(&self, model_name: &str) -> Result<()> {
+        self.ollama.delete_model(model_name).await
+    }
+    
+    async fn list_loaded_models(&self) -> Result<Vec<String>> {
+        self.ollama.list_loaded_models().await
+    }
+    
+    async fn model_readiness(&self, model_name: &str) -> Result<ModelReadiness> {
+        self.ollama.model_readiness(model_name).await
+    }
+    
+    async fn warmup(&self, model_name: &str) -> Result<u64> {
+        self.ollama.warmup(model_name).await
+    }
+    
     async fn generate_structured<T>(
         &self,
         model: &str,
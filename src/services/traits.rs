@@ -5,6 +5,7 @@
 //! and coordinate between the API layer and repository layer.
 
 use async_trait::async_trait;
+use futures::stream::BoxStream;
 use uuid::Uuid;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -12,6 +13,38 @@ use std::path::Path;
 
 use crate::repository::traits::*;
 
+/// Incremental text output of a model, chunk by chunk
+///
+/// Dropping the stream aborts the underlying request.
+pub type TextStream = BoxStream<'static, Result<String>>;
+
+/// Events emitted while a streaming analysis is running
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AnalysisStreamEvent {
+    /// Progress update with the number of characters generated so far
+    Progress { chars_generated: usize },
+    /// Newly generated text, usually a fragment of the JSON result
+    Partial { text: String },
+    /// The analysis finished and was persisted
+    Complete { analysis: AnalysisResult },
+}
+
+/// Stream of analysis events; dropping it cancels the in-flight model request
+pub type AnalysisStream = BoxStream<'static, Result<AnalysisStreamEvent>>;
+
+/// Progress update reported while a model is being pulled
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PullProgress {
+    pub status: String,
+    pub digest: Option<String>,
+    pub total: Option<u64>,
+    pub completed: Option<u64>,
+}
+
+/// Stream of progress updates for a model pull
+pub type PullStream = BoxStream<'static, Result<PullProgress>>;
+
 /// Audio processing service for handling audio file operations
 #[async_trait]
 pub trait AudioService: Send + Sync {
@@ -80,11 +113,21 @@ pub trait TranscriptionService: Send + Sync {
 #[async_trait]
 pub trait AnalysisService: Send + Sync {
     /// Analyze transcript content and extract structured information
+    ///
+    /// The stored result's `processing_time_ms` covers the model request,
+    /// including retries. `output_language` overrides the language the
+    /// results are written in, which defaults to the transcript's.
+    /// Extracted tasks and ideas less confident than `min_confidence`, or
+    /// `analysis.min_confidence_to_persist` when it is `None`, are not saved.
+    /// Ollama generation `options` take precedence over `ollama.options`.
     async fn analyze_transcript(
         &self,
         session_id: &Uuid,
         transcript_content: &str,
         language: Option<&str>,
+        output_language: Option<&str>,
+        min_confidence: Option<f64>,
+        options: Option<crate::ollama::ModelOptions>,
     ) -> Result<AnalysisResult>;
     
     /// Get analysis result by session ID
@@ -255,6 +298,9 @@ pub trait StructuredNoteService: Send + Sync {
 /// Ollama service for local AI model integration
 #[async_trait]
 pub trait OllamaService: Send + Sync {
+    /// Point the service at a different Ollama server
+    fn set_base_url(&self, base_url: &str);
+    
     /// Check if Ollama service is available
     async fn is_available(&self) -> bool;
     
@@ -264,6 +310,12 @@ pub trait OllamaService: Send + Sync {
     /// Pull/download a model
     async fn pull_model(&self, model_name: &str) -> Result<()>;
     
+    /// Pull/download a model, streaming download progress
+    async fn pull_model_stream(&self, model_name: &str) -> Result<PullStream>;
+    
+    /// Remove an installed model
+    async fn delete_model(&self, model_name: &str) -> Result<()>;
+    
     /// Generate text completion
     async fn generate(
         &self,
@@ -272,6 +324,14 @@ pub trait OllamaService: Send + Sync {
         options: Option<OllamaOptions>,
     ) -> Result<String>;
     
+    /// Generate text completion, streaming chunks as they are produced
+    async fn generate_stream(
+        &self,
+        model: &str,
+        prompt: &str,
+        options: Option<OllamaOptions>,
+    ) -> Result<TextStream>;
+    
     /// Generate structured response (JSON)
     async fn generate_structured<T>(
         &self,
@@ -284,6 +344,27 @@ pub trait OllamaService: Send + Sync {
     
     /// Get model information
     async fn get_model_info(&self, model_name: &str) -> Result<Option<OllamaModel>>;
+    
+    /// List models currently loaded in memory
+    async fn list_loaded_models(&self) -> Result<Vec<String>>;
+    
+    /// Report whether a model is loaded and how long loading it would take
+    async fn model_readiness(&self, model_name: &str) -> Result<ModelReadiness>;
+    
+    /// Load a model into memory, returning the load time in milliseconds
+    async fn warmup(&self, model_name: &str) -> Result<u64>;
+}
+
+/// Load state of an Ollama model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelReadiness {
+    pub model: String,
+    pub installed: bool,
+    /// Whether the model is currently loaded, so the first request is fast
+    pub loaded: bool,
+    /// Expected delay before the first token when the model is not loaded;
+    /// measured on the last warmup, otherwise estimated from the model size
+    pub estimated_cold_start_ms: Option<u64>,
 }
 
 /// Ollama model information
@@ -1,11 +1,44 @@
 // src/storage.rs
 use anyhow::Result;
 use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tokio::fs;
+use tokio::sync::RwLock;
 use uuid::Uuid;
 
+lazy_static! {
+    /// Serializes access to the session store within the process
+    ///
+    /// Saving and deleting take it exclusively, reading and listing shared,
+    /// so e.g. the keyboard recorder finalizing a session never interleaves
+    /// with the web server listing sessions. Other processes are covered by
+    /// `write_atomic` alone.
+    static ref STORE_LOCK: RwLock<()> = RwLock::new(());
+}
+
+/// Write `contents` to `path` through a temporary file in the same directory
+/// and a rename, so readers see either the old or the new file, never a
+/// partially written one
+async fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("Not a file path: {}", path.display()))?
+        .to_string_lossy();
+    let temp_path = path.with_file_name(format!(".{}.{}.tmp", file_name, Uuid::new_v4()));
+
+    if let Err(e) = fs::write(&temp_path, contents).await {
+        let _ = fs::remove_file(&temp_path).await;
+        return Err(e.into());
+    }
+    if let Err(e) = fs::rename(&temp_path, path).await {
+        let _ = fs::remove_file(&temp_path).await;
+        return Err(e.into());
+    }
+    Ok(())
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VoiceSession {
     pub id: String,
@@ -17,6 +50,98 @@ pub struct VoiceSession {
     pub duration_ms: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub audio_url: Option<String>,
+    /// SHA-256 of the source audio, used to detect re-imports
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_checksum: Option<String>,
+    /// Timestamped parts of `transcript`, empty when the transcription
+    /// provider reported no timestamps or for sessions transcribed before
+    /// segments were stored
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub segments: Vec<TranscriptSegment>,
+    /// Language of `transcript` as detected by the transcription provider
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Provider that produced `transcript`, e.g. `whisper_cpp`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transcription_provider: Option<String>,
+    /// Model that produced `transcript`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transcription_model: Option<String>,
+    /// Normalized copy of the audio used for transcription, if one was made
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub normalization: Option<NormalizationInfo>,
+    /// Earlier analyses replaced by re-analysis, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub analysis_history: Vec<ArchivedAnalysis>,
+    /// Analysis was deferred when the session was processed, because the
+    /// provider was unreachable or `analysis.auto_analyze` is off;
+    /// `retry-analysis`, `backfill` and the web UI analyze it later
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub analysis_pending: bool,
+}
+
+/// An analysis kept after the session was analyzed again
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedAnalysis {
+    /// When the analysis was replaced
+    pub replaced_at: DateTime<Utc>,
+    pub analysis: AnalysisResult,
+}
+
+/// How a session's audio was normalized for transcription
+///
+/// The original recording is left untouched; `path` is the normalized copy.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NormalizationInfo {
+    pub path: PathBuf,
+    pub source_sample_rate: u32,
+    pub source_channels: u16,
+    pub sample_rate: u32,
+    /// Gain applied to reach the loudness target, in dB
+    pub gain_db: f32,
+    /// Loudness target as gated RMS level, in dBFS
+    pub target_rms_dbfs: f32,
+    /// Measured gated RMS level of the source, in dBFS; `None` for silence
+    pub source_rms_dbfs: Option<f32>,
+    /// Whether the gain was reduced to keep peaks below the ceiling
+    pub peak_limited: bool,
+}
+
+/// A stretch of a transcript with its position in the recording
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptSegment {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
+    /// Provider's confidence in `text`, from 0 to 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+}
+
+/// Output of a transcription provider
+#[derive(Debug, Clone, Default)]
+pub struct Transcription {
+    pub text: String,
+    pub segments: Vec<TranscriptSegment>,
+    pub language: Option<String>,
+    pub provider: Option<String>,
+    pub model: Option<String>,
+}
+
+impl Transcription {
+    /// Build a transcription whose text is derived from `segments`, one
+    /// segment per line as in whisper's plain-text output
+    pub fn from_segments(segments: Vec<TranscriptSegment>) -> Self {
+        let text = segments
+            .iter()
+            .map(|segment| segment.text.trim())
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        Self { text, segments, ..Default::default() }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,9 +149,41 @@ pub struct VoiceSession {
 pub struct AnalysisResult {
     pub title: String,
     pub ideas: Vec<String>,
+    /// Confidence the model gave each of `ideas`, by index, if any
+    ///
+    /// Only used to filter the ideas of a new analysis, so it is not saved.
+    #[serde(skip)]
+    pub idea_confidences: Vec<Option<f32>>,
     pub tasks: Vec<Task>,
     pub structured_notes: Vec<StructuredNote>,
     pub summary: String,
+    /// Time spent waiting for the analysis provider, in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub processing_time_ms: Option<u64>,
+    /// Provider that produced the analysis, `openai` or `ollama`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// Exact model that produced the analysis, including the Ollama tag
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Language the transcript was detected as when it was analyzed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detected_language: Option<DetectedLanguage>,
+    /// Problems that may make the analysis incomplete, e.g. model output
+    /// cut off at the token limit
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// Result of the transcript language detection, kept with the analysis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectedLanguage {
+    /// `zh`, `en` or `mixed`
+    pub language: String,
+    /// Share of the text written in the detected language, from 0 to 1
+    pub confidence: f64,
+    /// Share of the text that is Chinese characters, from 0 to 1
+    pub cjk_ratio: f64,
 }
 
 impl AnalysisResult {
@@ -34,11 +191,36 @@ impl AnalysisResult {
         AnalysisResult {
             title: "Untitled Note".to_string(),
             ideas: Vec::new(),
+            idea_confidences: Vec::new(),
             tasks: Vec::new(),
             structured_notes: Vec::new(),
             summary,
+            processing_time_ms: None,
+            provider: None,
+            model: None,
+            detected_language: None,
+            warnings: Vec::new(),
         }
     }
+
+    /// Drop the tasks and ideas whose confidence is below `min_confidence`,
+    /// keeping those without one; returns how many were dropped
+    pub fn retain_confident(&mut self, min_confidence: f32) -> usize {
+        let confident = |confidence: Option<f32>| confidence.is_none_or(|confidence| confidence >= min_confidence);
+        let before = self.ideas.len() + self.tasks.len();
+
+        let confidences = std::mem::take(&mut self.idea_confidences);
+        let mut index = 0;
+        self.ideas.retain(|_| {
+            let keep = confident(confidences.get(index).copied().flatten());
+            index += 1;
+            keep
+        });
+        self.idea_confidences = confidences.into_iter().filter(|confidence| confident(*confidence)).collect();
+        self.tasks.retain(|task| confident(task.confidence));
+
+        before - self.ideas.len() - self.tasks.len()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,6 +229,9 @@ pub struct Task {
     pub description: Option<String>,
     pub priority: Priority,
     pub due_date: Option<DateTime<Utc>>,
+    /// Model's confidence that this is a real task, from 0 to 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -79,17 +264,252 @@ pub enum NoteType {
 
 
 
+/// Compute the hex-encoded SHA-256 checksum of a file, reading it in chunks
+/// so long recordings are never held in memory
+pub async fn file_checksum(path: &std::path::Path) -> Result<String> {
+    use sha2::{Digest, Sha256};
+    use tokio::io::{AsyncReadExt, BufReader};
+
+    let mut reader = BufReader::new(fs::File::open(path).await?);
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = reader.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Processing state of a session, derived from which results it has
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionStatus {
+    /// Audio recorded, not yet transcribed
+    Recorded,
+    /// Transcript available, not yet analyzed
+    Transcribed,
+    /// Transcript and analysis available
+    Analyzed,
+}
+
+impl std::str::FromStr for SessionStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "recorded" => Ok(SessionStatus::Recorded),
+            "transcribed" => Ok(SessionStatus::Transcribed),
+            "analyzed" => Ok(SessionStatus::Analyzed),
+            _ => Err(format!(
+                "Invalid session status: {}. Expected one of: recorded, transcribed, analyzed",
+                s
+            )),
+        }
+    }
+}
+
+impl VoiceSession {
+    /// Store a transcription's text, segments, language, provider and model
+    /// on this session
+    ///
+    /// The transcript files are rewritten from these by the next
+    /// [`save_session`].
+    pub fn set_transcription(&mut self, transcription: Transcription) {
+        self.transcript = Some(transcription.text);
+        self.segments = transcription.segments;
+        self.language = transcription.language;
+        self.transcription_provider = transcription.provider;
+        self.transcription_model = transcription.model;
+    }
+
+    /// Make `analysis` the session's analysis, moving the current one, if
+    /// any, to `analysis_history`
+    pub fn replace_analysis(&mut self, analysis: AnalysisResult) {
+        if let Some(previous) = self.analysis.replace(analysis) {
+            self.analysis_history.push(ArchivedAnalysis {
+                replaced_at: Utc::now(),
+                analysis: previous,
+            });
+        }
+    }
+
+    /// Get the processing state of this session
+    pub fn status(&self) -> SessionStatus {
+        match (&self.transcript, &self.analysis) {
+            (_, Some(_)) => SessionStatus::Analyzed,
+            (Some(_), None) => SessionStatus::Transcribed,
+            (None, None) => SessionStatus::Recorded,
+        }
+    }
+}
+
+/// Field to order session listings by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SessionSortField {
+    #[default]
+    CreatedAt,
+    Title,
+    Duration,
+}
+
+impl SessionSortField {
+    /// Names accepted by [`FromStr`](std::str::FromStr)
+    pub const ALLOWED: &'static [&'static str] = &["created_at", "timestamp", "title", "duration"];
+}
+
+impl std::str::FromStr for SessionSortField {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "created_at" | "timestamp" => Ok(SessionSortField::CreatedAt),
+            "title" => Ok(SessionSortField::Title),
+            "duration" | "duration_ms" => Ok(SessionSortField::Duration),
+            _ => Err(format!(
+                "Invalid sort field: {}. Expected one of: {}",
+                s,
+                Self::ALLOWED.join(", ")
+            )),
+        }
+    }
+}
+
+/// Direction of a session listing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl std::str::FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "asc" => Ok(SortOrder::Asc),
+            "desc" => Ok(SortOrder::Desc),
+            _ => Err(format!("Invalid sort order: {}. Expected asc or desc", s)),
+        }
+    }
+}
+
+/// Ordering of session listings; newest first by default
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SessionSort {
+    pub field: SessionSortField,
+    pub order: SortOrder,
+}
+
+impl SessionSort {
+    /// Sort `sessions` in place; ties keep the newest session first
+    pub fn apply(&self, sessions: &mut [VoiceSession]) {
+        sessions.sort_by(|a, b| {
+            let ordering = match self.field {
+                SessionSortField::CreatedAt => a.timestamp.cmp(&b.timestamp),
+                SessionSortField::Title => a.title.to_lowercase().cmp(&b.title.to_lowercase()),
+                SessionSortField::Duration => a.duration_ms.cmp(&b.duration_ms),
+            };
+            let ordering = match self.order {
+                SortOrder::Asc => ordering,
+                SortOrder::Desc => ordering.reverse(),
+            };
+            ordering.then_with(|| b.timestamp.cmp(&a.timestamp))
+        });
+    }
+}
+
+/// Filters for listing sessions
+#[derive(Debug, Clone, Default)]
+pub struct SessionFilter {
+    /// Only include sessions in this state
+    pub status: Option<SessionStatus>,
+    /// Only include sessions created at or after this time
+    pub created_after: Option<DateTime<Utc>>,
+    /// Only include sessions created before this time
+    pub created_before: Option<DateTime<Utc>>,
+    /// Order of the returned sessions
+    pub sort: SessionSort,
+    /// Number of matching sessions to skip
+    pub offset: usize,
+    /// Maximum number of sessions to return
+    pub limit: Option<usize>,
+}
+
 pub async fn save_session(session: &mut VoiceSession, analysis_result: Option<AnalysisResult>) -> Result<()> {
     if let Some(analysis) = analysis_result {
         session.title = analysis.title.clone();
         session.analysis = Some(analysis);
     }
+    if session.analysis.is_some() {
+        session.analysis_pending = false;
+    }
     let storage_dir = crate::config::get_storage_dir();
     let session_file = storage_dir.join("sessions").join(format!("{}.json", session.id));
     
     let content = serde_json::to_string_pretty(session)?;
-    fs::write(session_file, content).await?;
-    
+    let _guard = STORE_LOCK.write().await;
+    write_atomic(&session_file, content).await?;
+
+    save_transcript_files(session).await?;
+
+    Ok(())
+}
+
+/// Machine-readable transcript written next to the plain-text one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptDocument {
+    pub session_id: String,
+    pub language: Option<String>,
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub text: String,
+    pub segments: Vec<TranscriptSegment>,
+}
+
+/// Paths of a session's plain-text and JSON transcript files
+pub fn transcript_paths(id: &str) -> (PathBuf, PathBuf) {
+    let transcripts_dir = crate::config::get_storage_dir().join("transcripts");
+    (
+        transcripts_dir.join(format!("{}.txt", id)),
+        transcripts_dir.join(format!("{}.json", id)),
+    )
+}
+
+/// Write a session's transcript as `transcripts/<id>.txt` and
+/// `transcripts/<id>.json`, or remove both if it has no transcript
+///
+/// Both files are derived from the session on every save, so a
+/// re-transcription replaces them together.
+async fn save_transcript_files(session: &VoiceSession) -> Result<()> {
+    let (txt_file, json_file) = transcript_paths(&session.id);
+
+    let Some(text) = &session.transcript else {
+        for file in [txt_file, json_file] {
+            if file.exists() {
+                fs::remove_file(file).await?;
+            }
+        }
+        return Ok(());
+    };
+
+    if let Some(dir) = txt_file.parent() {
+        fs::create_dir_all(dir).await?;
+    }
+    let document = TranscriptDocument {
+        session_id: session.id.clone(),
+        language: session.language.clone(),
+        provider: session.transcription_provider.clone(),
+        model: session.transcription_model.clone(),
+        text: text.clone(),
+        segments: session.segments.clone(),
+    };
+    write_atomic(&txt_file, text).await?;
+    write_atomic(&json_file, serde_json::to_string_pretty(&document)?).await?;
+
     Ok(())
 }
 
@@ -97,6 +517,7 @@ pub async fn get_session(id: &str) -> Result<Option<VoiceSession>> {
     let storage_dir = crate::config::get_storage_dir();
     let session_file = storage_dir.join("sessions").join(format!("{}.json", id));
     
+    let _guard = STORE_LOCK.read().await;
     if !session_file.exists() {
         return Ok(None);
     }
@@ -107,11 +528,42 @@ pub async fn get_session(id: &str) -> Result<Option<VoiceSession>> {
     Ok(Some(session))
 }
 
+/// A session with everything its detail view shows, see [`get_session_full`]
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionDetail {
+    #[serde(flatten)]
+    pub session: VoiceSession,
+    /// Ideas of the current analysis, empty without one
+    pub ideas: Vec<String>,
+    /// Tasks of the current analysis, empty without one
+    pub tasks: Vec<Task>,
+    /// Structured notes of the current analysis, empty without one
+    pub structured_notes: Vec<StructuredNote>,
+}
+
+/// Get a session with its transcript, analysis and the ideas, tasks and
+/// notes of that analysis in one structure
+///
+/// The file store keeps the transcript and analysis in the session file, so
+/// this is a single read under the store lock; the detail view needs no
+/// further requests for them.
+pub async fn get_session_full(id: &str) -> Result<Option<SessionDetail>> {
+    let Some(session) = get_session(id).await? else {
+        return Ok(None);
+    };
+    let (ideas, tasks, structured_notes) = match &session.analysis {
+        Some(analysis) => (analysis.ideas.clone(), analysis.tasks.clone(), analysis.structured_notes.clone()),
+        None => (Vec::new(), Vec::new(), Vec::new()),
+    };
+    Ok(Some(SessionDetail { session, ideas, tasks, structured_notes }))
+}
+
 pub async fn list_sessions() -> Result<Vec<VoiceSession>> {
     let storage_dir = crate::config::get_storage_dir();
     let sessions_dir = storage_dir.join("sessions");
     
     let mut sessions = Vec::new();
+    let _guard = STORE_LOCK.read().await;
     let mut entries = fs::read_dir(sessions_dir).await?;
     
     while let Some(entry) = entries.next_entry().await? {
@@ -127,24 +579,59 @@ pub async fn list_sessions() -> Result<Vec<VoiceSession>> {
     Ok(sessions)
 }
 
+/// List sessions matching `filter`, in the filter's sort order
+pub async fn list_sessions_filtered(filter: &SessionFilter) -> Result<Vec<VoiceSession>> {
+    let mut sessions = list_sessions().await?;
+    sessions.retain(|session| {
+        filter.status.map_or(true, |status| session.status() == status)
+            && filter.created_after.map_or(true, |after| session.timestamp >= after)
+            && filter.created_before.map_or(true, |before| session.timestamp < before)
+    });
+    filter.sort.apply(&mut sessions);
+
+    let sessions = sessions
+        .into_iter()
+        .skip(filter.offset)
+        .take(filter.limit.unwrap_or(usize::MAX))
+        .collect();
+
+    Ok(sessions)
+}
+
 pub async fn delete_session(id: &str) -> Result<()> {
     let storage_dir = crate::config::get_storage_dir();
     let session_file = storage_dir.join("sessions").join(format!("{}.json", id));
-    let audio_file = storage_dir.join("audio").join(format!("{}.wav", id));
+    // Imported recordings keep their own format, e.g. `<id>.mp3`
+    let audio_file = match get_session(id).await {
+        Ok(Some(session)) if session.audio_file_path.starts_with(crate::config::get_audio_dir()) => {
+            session.audio_file_path
+        }
+        _ => crate::config::get_audio_dir().join(format!("{}.wav", id)),
+    };
+    let normalized_file = crate::normalize::normalized_path(&audio_file);
+    let (transcript_txt, transcript_json) = transcript_paths(id);
 
+    let _guard = STORE_LOCK.write().await;
     if session_file.exists() {
         fs::remove_file(session_file).await?;
     }
     if audio_file.exists() {
         fs::remove_file(audio_file).await?;
     }
+    if normalized_file.exists() {
+        fs::remove_file(normalized_file).await?;
+    }
+    for file in [transcript_txt, transcript_json] {
+        if file.exists() {
+            fs::remove_file(file).await?;
+        }
+    }
     Ok(())
 }
 
 pub fn create_new_session() -> VoiceSession {
     let id = Uuid::new_v4().to_string();
-    let storage_dir = crate::config::get_storage_dir();
-    let audio_file_path = storage_dir.join("audio").join(format!("{}.wav", id));
+    let audio_file_path = crate::config::get_audio_dir().join(format!("{}.wav", id));
     
     VoiceSession {
         id,
@@ -155,5 +642,78 @@ pub fn create_new_session() -> VoiceSession {
         title: String::new(), // Initialize with an empty string
         duration_ms: 0,
         audio_url: None,
+        audio_checksum: None,
+        segments: Vec::new(),
+        language: None,
+        transcription_provider: None,
+        transcription_model: None,
+        normalization: None,
+        analysis_history: Vec::new(),
+        analysis_pending: false,
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_saves_never_expose_a_partial_session() {
+        let dir = std::env::temp_dir().join(format!("voice-recorder-storage-{}", std::process::id()));
+        let _storage_dir = crate::config::TestStorageDir::set(dir.clone()).await;
+        fs::create_dir_all(dir.join("sessions")).await.unwrap();
+
+        let mut session = create_new_session();
+        save_session(&mut session, None).await.unwrap();
+        let id = session.id.clone();
+
+        let writers: Vec<_> = (0..4)
+            .map(|writer| {
+                let mut session = session.clone();
+                tokio::spawn(async move {
+                    for round in 0..50 {
+                        // Lengths vary, so a torn write would leave invalid JSON behind
+                        session.title = format!("writer {} round {}", writer, round);
+                        session.transcript = Some("word ".repeat((writer * 50 + round) * 20));
+                        save_session(&mut session, None).await.unwrap();
+                    }
+                })
+            })
+            .collect();
+        let reader = tokio::spawn(async move {
+            for _ in 0..200 {
+                let found = get_session(&id).await.unwrap().unwrap();
+                assert_eq!(found.id, id);
+                // Sessions that fail to parse are left out of the list
+                assert_eq!(list_sessions().await.unwrap().len(), 1);
+            }
+        });
+        for writer in writers {
+            writer.await.unwrap();
+        }
+        reader.await.unwrap();
+
+        // No temporary files are left behind
+        let mut entries = fs::read_dir(dir.join("sessions")).await.unwrap();
+        let mut names = Vec::new();
+        while let Some(entry) = entries.next_entry().await.unwrap() {
+            names.push(entry.file_name().to_string_lossy().into_owned());
+        }
+        assert_eq!(names, vec![format!("{}.json", session.id)]);
+        fs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn file_checksum_matches_a_digest_of_the_whole_file() {
+        use sha2::{Digest, Sha256};
+
+        // Larger than one read buffer and not a multiple of it
+        let data: Vec<u8> = (0..200_003u32).map(|i| (i % 251) as u8).collect();
+        let path = std::env::temp_dir().join(format!("voice-recorder-checksum-{}.bin", std::process::id()));
+        fs::write(&path, &data).await.unwrap();
+
+        let checksum = file_checksum(&path).await.unwrap();
+        fs::remove_file(&path).await.unwrap();
+        assert_eq!(checksum, format!("{:x}", Sha256::digest(&data)));
+    }
+}
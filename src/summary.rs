@@ -0,0 +1,99 @@
+// src/summary.rs
+//! Per-item results of commands that process many files or sessions
+//!
+//! `transcribe --dir`, `reanalyze` and `retry-analysis` record what happened
+//! to each item in a [`RunSummary`] and log it the same way once they finish.
+
+use log::{info, warn};
+
+/// Result of processing a single item
+#[derive(Debug)]
+pub enum Outcome {
+    /// Processed; `detail` is shown next to the item in the summary
+    Done { detail: String },
+    /// Left alone, e.g. because there was nothing to do
+    Skipped { reason: String },
+    /// Processing failed; the item is unchanged
+    Failed { error: String },
+}
+
+/// Per-item results of a run, keyed by file path or session id
+#[derive(Debug)]
+pub struct RunSummary<K> {
+    pub results: Vec<(K, Outcome)>,
+}
+
+impl<K> Default for RunSummary<K> {
+    fn default() -> Self {
+        Self { results: Vec::new() }
+    }
+}
+
+impl<K> RunSummary<K> {
+    pub fn push(&mut self, key: K, outcome: Outcome) {
+        self.results.push((key, outcome));
+    }
+
+    pub fn done(&self) -> usize {
+        self.count(|o| matches!(o, Outcome::Done { .. }))
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.count(|o| matches!(o, Outcome::Skipped { .. }))
+    }
+
+    pub fn failed(&self) -> usize {
+        self.count(|o| matches!(o, Outcome::Failed { .. }))
+    }
+
+    fn count(&self, predicate: impl Fn(&Outcome) -> bool) -> usize {
+        self.results.iter().filter(|(_, outcome)| predicate(outcome)).count()
+    }
+
+    /// Sort the results by key, since concurrent runs finish in any order
+    pub fn sort(&mut self)
+    where
+        K: Ord,
+    {
+        self.results.sort_by(|a, b| a.0.cmp(&b.0));
+    }
+
+    /// Log one line per item, then the totals as
+    /// "`<run>` finished: N `<done>`, N skipped, N failed"
+    pub fn log(&self, run: &str, done: &str, label: impl Fn(&K) -> String) {
+        for (key, outcome) in &self.results {
+            match outcome {
+                Outcome::Done { detail } => info!("  OK      {} {}", label(key), detail),
+                Outcome::Skipped { reason } => info!("  SKIPPED {} ({})", label(key), reason),
+                Outcome::Failed { error } => warn!("  FAILED  {}: {}", label(key), error),
+            }
+        }
+        info!(
+            "{} finished: {} {}, {} skipped, {} failed",
+            run,
+            self.done(),
+            done,
+            self.skipped(),
+            self.failed()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_and_sorts_outcomes_by_key() {
+        let mut summary = RunSummary::default();
+        summary.push("b", Outcome::Failed { error: "timeout".to_string() });
+        summary.push("a", Outcome::Done { detail: "(llama3)".to_string() });
+        summary.push("c", Outcome::Skipped { reason: "no transcript".to_string() });
+        summary.push("d", Outcome::Done { detail: "(llama3)".to_string() });
+        summary.sort();
+
+        assert_eq!((summary.done(), summary.skipped(), summary.failed()), (2, 1, 1));
+        let keys: Vec<_> = summary.results.iter().map(|(key, _)| *key).collect();
+        assert_eq!(keys, ["a", "b", "c", "d"]);
+    }
+}
@@ -0,0 +1,257 @@
+// src/uploads.rs
+//! Resumable audio uploads
+//!
+//! A client starts an upload, sends the file in pieces and completes it once
+//! everything has arrived. Received bytes are kept in `uploads/<id>.part`
+//! under the storage directory, with the upload's metadata next to it in
+//! `uploads/<id>.json`. The size of the part file is the received offset, so
+//! an interrupted upload can resume from wherever the server got to, even
+//! across restarts.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tokio::fs;
+use uuid::Uuid;
+
+/// Metadata of an upload that has not been completed yet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingUpload {
+    pub id: String,
+    /// Original file name, if the client sent one
+    pub filename: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// Time the last chunk was received
+    pub updated_at: DateTime<Utc>,
+    /// Number of bytes received so far
+    #[serde(skip)]
+    pub offset: u64,
+}
+
+/// Upload failures the web layer reports with their own status codes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UploadError {
+    /// No pending upload with this id, or it has expired
+    NotFound(String),
+    /// The chunk does not start where the received data ends
+    OffsetMismatch { expected: u64, received: u64 },
+    /// Another request is appending to the same upload
+    Busy(String),
+}
+
+impl fmt::Display for UploadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UploadError::NotFound(id) => write!(f, "Upload {} not found", id),
+            UploadError::OffsetMismatch { expected, received } => write!(
+                f,
+                "Chunk offset {} does not match the {} bytes received so far",
+                received, expected
+            ),
+            UploadError::Busy(id) => write!(f, "Upload {} is already receiving a chunk", id),
+        }
+    }
+}
+
+impl std::error::Error for UploadError {}
+
+/// Ids of uploads currently being appended to
+static ACTIVE_APPENDS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Exclusive right to append to one upload
+pub struct AppendGuard {
+    _claim: Claim,
+    pub file: fs::File,
+    pub offset: u64,
+}
+
+/// Claim on an upload id, released on drop, so a request that is cancelled
+/// mid-chunk doesn't leave the upload busy
+struct Claim(String);
+
+impl Drop for Claim {
+    fn drop(&mut self) {
+        ACTIVE_APPENDS.lock().unwrap().retain(|active_id| active_id != &self.0);
+    }
+}
+
+fn claim(id: &str) -> Result<Claim> {
+    let mut active = ACTIVE_APPENDS.lock().unwrap();
+    if active.iter().any(|active_id| active_id == id) {
+        return Err(UploadError::Busy(id.to_string()).into());
+    }
+    active.push(id.to_string());
+    Ok(Claim(id.to_string()))
+}
+
+fn uploads_dir() -> PathBuf {
+    crate::config::get_storage_dir().join("uploads")
+}
+
+fn part_path(id: &str) -> PathBuf {
+    uploads_dir().join(format!("{}.part", id))
+}
+
+fn metadata_path(id: &str) -> PathBuf {
+    uploads_dir().join(format!("{}.json", id))
+}
+
+/// Upload ids are generated here; anything else must not reach the filesystem
+fn is_valid_id(id: &str) -> bool {
+    Uuid::parse_str(id).is_ok()
+}
+
+/// Start a new upload
+pub async fn create(filename: Option<String>) -> Result<PendingUpload> {
+    fs::create_dir_all(uploads_dir())
+        .await
+        .context("Failed to create uploads directory")?;
+
+    let now = Utc::now();
+    let upload = PendingUpload {
+        id: Uuid::new_v4().to_string(),
+        filename,
+        created_at: now,
+        updated_at: now,
+        offset: 0,
+    };
+    fs::File::create(part_path(&upload.id))
+        .await
+        .context("Failed to create upload file")?;
+    write_metadata(&upload).await?;
+    Ok(upload)
+}
+
+/// Look up a pending upload and the number of bytes received so far
+pub async fn get(id: &str) -> Result<Option<PendingUpload>> {
+    if !is_valid_id(id) {
+        return Ok(None);
+    }
+    let metadata_file = metadata_path(id);
+    if !metadata_file.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&metadata_file)
+        .await
+        .context("Failed to read upload metadata")?;
+    let mut upload: PendingUpload =
+        serde_json::from_str(&content).context("Failed to parse upload metadata")?;
+    upload.offset = match fs::metadata(part_path(id)).await {
+        Ok(metadata) => metadata.len(),
+        Err(_) => return Ok(None),
+    };
+    Ok(Some(upload))
+}
+
+/// Open an upload for appending a chunk that starts at `offset`
+pub async fn begin_append(id: &str, offset: u64) -> Result<AppendGuard> {
+    let claim = claim(id)?;
+    let file = open_part(id).await?;
+    let received = file
+        .metadata()
+        .await
+        .context("Failed to read upload file size")?
+        .len();
+    if received != offset {
+        return Err(UploadError::OffsetMismatch {
+            expected: received,
+            received: offset,
+        }
+        .into());
+    }
+    Ok(AppendGuard {
+        _claim: claim,
+        file,
+        offset,
+    })
+}
+
+async fn open_part(id: &str) -> Result<fs::File> {
+    if get(id).await?.is_none() {
+        return Err(UploadError::NotFound(id.to_string()).into());
+    }
+    fs::OpenOptions::new()
+        .append(true)
+        .open(part_path(id))
+        .await
+        .context("Failed to open upload file")
+}
+
+/// Record that a chunk was received, keeping the upload from expiring
+pub async fn touch(id: &str) -> Result<()> {
+    let mut upload = get(id)
+        .await?
+        .ok_or_else(|| UploadError::NotFound(id.to_string()))?;
+    upload.updated_at = Utc::now();
+    write_metadata(&upload).await
+}
+
+/// Finish an upload, moving the received file to `destination`
+pub async fn complete(id: &str, destination: &std::path::Path) -> Result<PendingUpload> {
+    let _claim = claim(id)?;
+    let upload = get(id)
+        .await?
+        .ok_or_else(|| UploadError::NotFound(id.to_string()))?;
+    fs::rename(part_path(id), destination)
+        .await
+        .context("Failed to move completed upload")?;
+    fs::remove_file(metadata_path(id))
+        .await
+        .context("Failed to remove upload metadata")?;
+    Ok(upload)
+}
+
+/// Remove uploads that have not received a chunk for `ttl`
+///
+/// Returns the number of uploads removed.
+pub async fn remove_expired(ttl: Duration) -> Result<usize> {
+    let dir = uploads_dir();
+    if !dir.exists() {
+        return Ok(0);
+    }
+
+    let cutoff = Utc::now() - ttl;
+    let mut removed = 0;
+    let mut entries = fs::read_dir(&dir).await.context("Failed to read uploads directory")?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        // Leave uploads alone while a chunk is being written to them
+        let Ok(_claim) = claim(id) else {
+            continue;
+        };
+
+        let expired = match get(id).await {
+            Ok(Some(upload)) => upload.updated_at < cutoff,
+            Ok(None) => true,
+            Err(e) => {
+                warn!("Skipping unreadable upload {}: {}", id, e);
+                false
+            }
+        };
+        if expired {
+            let _ = fs::remove_file(part_path(id)).await;
+            let _ = fs::remove_file(&path).await;
+            info!("Removed abandoned upload {}", id);
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+async fn write_metadata(upload: &PendingUpload) -> Result<()> {
+    let content = serde_json::to_string_pretty(upload)?;
+    fs::write(metadata_path(&upload.id), content)
+        .await
+        .context("Failed to write upload metadata")
+}
@@ -0,0 +1,200 @@
+// src/waveform.rs
+//! Downsampled waveform peaks for drawing a scrubber in the web UI
+//!
+//! Peaks are computed from the session's WAV file, streaming its samples so
+//! long recordings are never held in memory, and cached keyed by the audio
+//! checksum, since the audio of a session never changes. Resolutions are
+//! limited to [`RESOLUTIONS`] and the cache to the [`CACHE_CAPACITY`] most
+//! recently used waveforms, so clients cannot grow it without bound.
+
+use anyhow::{bail, Context, Result};
+use lazy_static::lazy_static;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Number of points returned when the client does not ask for a resolution
+pub const DEFAULT_POINTS: usize = 1000;
+
+/// Resolutions a waveform is computed at; a request is served at the
+/// smallest one that is at least the requested number of points
+pub const RESOLUTIONS: [usize; 5] = [250, 500, 1000, 2000, 4000];
+
+/// Number of waveforms kept in memory
+const CACHE_CAPACITY: usize = 32;
+
+/// Audio checksum and resolution of a cached waveform
+type CacheKey = (String, usize);
+
+lazy_static! {
+    /// Cached waveforms, least recently used first
+    static ref PEAK_CACHE: Mutex<VecDeque<(CacheKey, Arc<Waveform>)>> = Mutex::new(VecDeque::new());
+}
+
+/// Peak amplitudes of an audio file, normalized to `0.0..=1.0`
+#[derive(Debug, Clone, Serialize)]
+pub struct Waveform {
+    pub duration_ms: u64,
+    pub sample_rate: u32,
+    pub peaks: Vec<f32>,
+}
+
+/// Get the waveform of `audio_path`, computing it on a cache miss
+///
+/// `checksum` identifies the audio content; when it is not known the file is
+/// hashed first. `points` is rounded up to one of [`RESOLUTIONS`].
+pub async fn get_waveform(audio_path: &Path, checksum: Option<&str>, points: usize) -> Result<Arc<Waveform>> {
+    let points = resolution(points);
+    let checksum = match checksum {
+        Some(checksum) => checksum.to_string(),
+        None => crate::storage::file_checksum(audio_path)
+            .await
+            .with_context(|| format!("Failed to read audio file: {}", audio_path.display()))?,
+    };
+
+    let key = (checksum, points);
+    if let Some(waveform) = cached(&key) {
+        return Ok(waveform);
+    }
+
+    let path: PathBuf = audio_path.to_path_buf();
+    let waveform = tokio::task::spawn_blocking(move || compute_peaks(&path, points))
+        .await
+        .context("Waveform task panicked")??;
+    let waveform = Arc::new(waveform);
+
+    let mut cache = PEAK_CACHE.lock().unwrap();
+    cache.retain(|(cached_key, _)| *cached_key != key);
+    if cache.len() >= CACHE_CAPACITY {
+        cache.pop_front();
+    }
+    cache.push_back((key, waveform.clone()));
+    Ok(waveform)
+}
+
+/// The smallest of [`RESOLUTIONS`] holding `points`, or the largest one
+fn resolution(points: usize) -> usize {
+    RESOLUTIONS
+        .into_iter()
+        .find(|&resolution| resolution >= points)
+        .unwrap_or(RESOLUTIONS[RESOLUTIONS.len() - 1])
+}
+
+/// Look up a cached waveform, marking it as most recently used
+fn cached(key: &CacheKey) -> Option<Arc<Waveform>> {
+    let mut cache = PEAK_CACHE.lock().unwrap();
+    let index = cache.iter().position(|(cached_key, _)| cached_key == key)?;
+    let entry = cache.remove(index)?;
+    let waveform = entry.1.clone();
+    cache.push_back(entry);
+    Some(waveform)
+}
+
+/// Read a WAV file and reduce it to `points` peak amplitudes
+///
+/// All channels are folded together; each point is the largest absolute
+/// sample value within its slice of the recording. Samples are read one at a
+/// time, so memory use does not depend on the length of the recording.
+fn compute_peaks(path: &Path, points: usize) -> Result<Waveform> {
+    let reader = hound::WavReader::open(path)
+        .with_context(|| format!("Failed to open WAV file: {}", path.display()))?;
+    let spec = reader.spec();
+    let frames = reader.duration() as usize;
+
+    let mut peaks = PeakBuckets::new(points, frames, spec.channels);
+    match spec.sample_format {
+        hound::SampleFormat::Float => {
+            for sample in reader.into_samples::<f32>() {
+                peaks.push(sample.context("Failed to decode WAV samples")?);
+            }
+        }
+        hound::SampleFormat::Int => {
+            if spec.bits_per_sample == 0 || spec.bits_per_sample > 32 {
+                bail!("Unsupported bit depth: {}", spec.bits_per_sample);
+            }
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            for sample in reader.into_samples::<i32>() {
+                peaks.push(sample.context("Failed to decode WAV samples")? as f32 / scale);
+            }
+        }
+    }
+
+    let duration_ms = if spec.sample_rate > 0 {
+        frames as u64 * 1000 / spec.sample_rate as u64
+    } else {
+        0
+    };
+
+    Ok(Waveform {
+        duration_ms,
+        sample_rate: spec.sample_rate,
+        peaks: peaks.peaks,
+    })
+}
+
+/// Running per-bucket peaks of interleaved samples
+struct PeakBuckets {
+    peaks: Vec<f32>,
+    frames: usize,
+    channels: usize,
+    /// Index of the next sample pushed, across all channels
+    sample_index: usize,
+}
+
+impl PeakBuckets {
+    fn new(points: usize, frames: usize, channels: u16) -> Self {
+        Self {
+            peaks: vec![0.0; points.min(frames.max(1))],
+            frames,
+            channels: channels.max(1) as usize,
+            sample_index: 0,
+        }
+    }
+
+    fn push(&mut self, sample: f32) {
+        let frame_index = self.sample_index / self.channels;
+        self.sample_index += 1;
+        // Samples beyond the length announced by the header are ignored
+        if frame_index >= self.frames {
+            return;
+        }
+        let buckets = self.peaks.len();
+        let bucket = (frame_index * buckets / self.frames).min(buckets - 1);
+        let peak = &mut self.peaks[bucket];
+        *peak = peak.max(sample.abs().min(1.0));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requested_points_are_rounded_up_to_a_resolution() {
+        assert_eq!(resolution(0), 250);
+        assert_eq!(resolution(250), 250);
+        assert_eq!(resolution(251), 500);
+        assert_eq!(resolution(1000), 1000);
+        assert_eq!(resolution(10_000), 4000);
+    }
+
+    #[test]
+    fn peaks_are_taken_per_bucket_across_channels() {
+        // 4 stereo frames into 2 buckets
+        let mut peaks = PeakBuckets::new(2, 4, 2);
+        for sample in [0.1, -0.2, 0.3, 0.0, -0.9, 0.5, 0.2, 0.4, 1.0] {
+            peaks.push(sample);
+        }
+        assert_eq!(peaks.peaks, vec![0.3, 0.9]);
+    }
+
+    #[test]
+    fn short_recordings_get_one_point_per_frame() {
+        let mut peaks = PeakBuckets::new(250, 3, 1);
+        for sample in [0.5, -0.25, 2.0] {
+            peaks.push(sample);
+        }
+        assert_eq!(peaks.peaks, vec![0.5, 0.25, 1.0]);
+    }
+}
@@ -1,22 +1,31 @@
-use anyhow::Result;
-use axum::extract::{Path, State, Query, Multipart};
+use anyhow::{Context, Result};
+use axum::extract::{DefaultBodyLimit, Path, State, Query, Multipart};
 use axum::response::{Html, IntoResponse, Json, Response};
 use axum::routing::{get, post, delete};
 use axum::Router;
-use axum::http::StatusCode;
+use axum::http::{header, HeaderValue, Method, StatusCode};
 use serde::{Deserialize, Serialize};
-use tokio::sync::Mutex as AsyncMutex;
+use tokio::sync::{Mutex as AsyncMutex, Notify};
 use tower_http::cors::CorsLayer;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
 use tokio::fs::File;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
 use axum::body::{Body, Bytes};
 use uuid::Uuid;
 use chrono::Utc;
+use log::{debug, error, info, warn};
 
 use crate::audio::VoiceRecorder;
+use crate::clip::{self, ClipError};
 use crate::config::LegacyConfig;
+use crate::disposition;
+use crate::etag;
+use crate::redact::{self, Redactor};
+use crate::response::ApiResponse;
 use crate::storage::{self, VoiceSession};
+use crate::uploads::{self, UploadError};
+use crate::waveform::{self, Waveform};
 
 #[derive(Debug, Deserialize)]
 struct SessionQuery {
@@ -27,45 +36,263 @@ struct SessionQuery {
     offset: Option<usize>,
 }
 
-#[derive(Debug, Serialize)]
-struct ApiResponse<T> {
-    data: T,
-    message: Option<String>,
-    error: Option<String>,
+#[derive(Debug, Deserialize)]
+struct ExportQuery {
+    /// Overrides `redaction.enabled` for this export
+    redact: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WaveformQuery {
+    points: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClipQuery {
+    /// Seconds from the start of the recording
+    start: Option<f64>,
+    end: Option<f64>,
+}
+
+/// Error returned by the web handlers as `{"error": <kind>, "message": <text>}`
+///
+/// The legacy counterpart of `api::error::ApiError`, so the web UI can show
+/// why a request failed. Internal errors are logged in full and reported to
+/// the client by their message only.
+#[derive(Debug)]
+enum WebError {
+    BadRequest(String),
+    NotFound(String),
+    Conflict(String),
+    PayloadTooLarge(String),
+    UnprocessableEntity(String),
+    Internal(String),
+}
+
+impl WebError {
+    /// Log `err` and report `message` as an internal error
+    fn internal(message: impl Into<String>, err: impl std::fmt::Debug) -> Self {
+        let message = message.into();
+        eprintln!("[ERROR] {}: {:?}", message, err);
+        WebError::Internal(message)
+    }
+
+    fn session_not_found(id: &str) -> Self {
+        WebError::NotFound(format!("Session {} not found", id))
+    }
+}
+
+impl IntoResponse for WebError {
+    fn into_response(self) -> Response {
+        let (status, error, message) = match self {
+            WebError::BadRequest(message) => (StatusCode::BAD_REQUEST, "bad_request", message),
+            WebError::NotFound(message) => (StatusCode::NOT_FOUND, "not_found", message),
+            WebError::Conflict(message) => (StatusCode::CONFLICT, "conflict", message),
+            WebError::PayloadTooLarge(message) => (StatusCode::PAYLOAD_TOO_LARGE, "payload_too_large", message),
+            WebError::UnprocessableEntity(message) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, "unprocessable_entity", message)
+            }
+            WebError::Internal(message) => (StatusCode::INTERNAL_SERVER_ERROR, "internal_error", message),
+        };
+        (status, Json(serde_json::json!({ "error": error, "message": message }))).into_response()
+    }
 }
 
+type WebResult<T> = Result<T, WebError>;
+
+/// When `start_server` was called, for the health endpoint's uptime
+static SERVER_STARTED: OnceLock<Instant> = OnceLock::new();
+
 pub async fn start_server(port: u16, recorder: Arc<AsyncMutex<VoiceRecorder>>) -> Result<()> {
+    SERVER_STARTED.get_or_init(Instant::now);
+
     let app = Router::new()
         .route("/", get(index_handler))
+        .route("/api/health", get(health_handler))
         .route("/api/sessions", get(list_sessions_handler))
         .route("/api/sessions/:id", get(get_session_handler))
         .route("/api/sessions/:id", delete(delete_session_handler))
         .route("/api/sessions/:id/export", get(export_session_handler))
         .route("/api/sessions/:id/audio", get(audio_handler))
+        .route("/api/sessions/:id/audio/clip", get(audio_clip_handler))
+        .route("/api/sessions/:id/waveform", get(waveform_handler))
         .route("/api/sessions/:id/transcript", get(get_transcript_handler))
         .route("/api/sessions/:id/analysis", get(get_analysis_handler))
+        .route("/api/sessions/:id/analyze", post(analyze_session_handler))
         .route("/api/config", get(get_config_handler))
         .route("/api/record/start", post(start_record_handler))
         .route("/api/record/stop", post(stop_record_handler))
         .route("/api/record/status", get(record_status_handler))
-        .route("/api/sessions/upload", post(upload_audio_handler))
-        .with_state(recorder)
-        .layer(CorsLayer::permissive());
+        // The upload handler enforces storage.max_file_size itself while streaming
+        .route(
+            "/api/sessions/upload",
+            post(upload_audio_handler).layer(DefaultBodyLimit::disable()),
+        )
+        .route("/api/sessions/upload/init", post(init_upload_handler))
+        .route(
+            "/api/sessions/upload/:id",
+            get(upload_status_handler)
+                .patch(upload_chunk_handler)
+                .layer(DefaultBodyLimit::disable()),
+        )
+        .route("/api/sessions/upload/:id/complete", post(complete_upload_handler))
+        .with_state(recorder.clone())
+        .layer(cors_layer(&crate::config::current().server.cors_origins)?);
+
+    tokio::spawn(remove_abandoned_uploads());
 
     let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", port)).await?;
-    println!("Web interface available at http://{}", listener.local_addr()?);
-    axum::serve(listener, app).await?;
+    info!("Web interface available at http://{}", listener.local_addr()?);
+
+    let shutdown_started = Arc::new(Notify::new());
+    let server = {
+        let shutdown_started = shutdown_started.clone();
+        axum::serve(listener, app).with_graceful_shutdown(async move {
+            shutdown_signal().await;
+            info!("Shutdown requested; no longer accepting connections");
+            shutdown_started.notify_one();
+        })
+    };
+
+    // Give in-flight requests up to the request timeout to finish
+    let grace_period = Duration::from_secs(crate::config::current().server.request_timeout_secs);
+    tokio::select! {
+        result = server => result?,
+        _ = async {
+            shutdown_started.notified().await;
+            tokio::time::sleep(grace_period).await;
+        } => {
+            warn!("In-flight requests did not finish within {}s; closing them", grace_period.as_secs());
+        }
+    }
+
+    finalize_recording(&recorder).await;
+    info!("Web server stopped");
 
     Ok(())
 }
 
+/// Apply a configuration reloaded on SIGHUP
+///
+/// The handlers read the configuration per request, so nothing has to be
+/// pushed; the analysis settings in effect are reported so a reload can be
+/// confirmed. The `/api/v1` services are updated by `api::spawn_config_reload`.
+pub fn apply_config(config: &crate::config::Config) {
+    println!(
+        "Analyses now use {} (Ollama {} at {})",
+        config.analysis.default_provider, config.ollama.default_model, config.ollama.base_url
+    );
+}
+
+/// Resolve when the process receives SIGINT (Ctrl-C) or SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+/// Stop and save a recording that is still running when the server shuts down
+async fn finalize_recording(recorder: &Arc<AsyncMutex<VoiceRecorder>>) {
+    let mut guard = recorder.lock().await;
+    if !guard.is_recording() {
+        return;
+    }
+
+    info!("Saving in-progress recording before exit...");
+    match guard.stop_recording().await {
+        Ok(()) => info!("In-progress recording saved"),
+        Err(e) => error!("Failed to save in-progress recording: {:?}", e),
+    }
+}
+
+/// Build the CORS policy from `server.cors_origins`
+///
+/// An empty list or a `"*"` entry allows any origin, which is only meant for
+/// local development.
+fn cors_layer(origins: &[String]) -> Result<CorsLayer> {
+    if origins.is_empty() || origins.iter().any(|origin| origin == "*") {
+        warn!("CORS: allowing any origin (server.cors_origins is empty or \"*\")");
+        return Ok(CorsLayer::permissive());
+    }
+
+    let allowed = origins
+        .iter()
+        .map(|origin| {
+            origin
+                .trim_end_matches('/')
+                .parse::<HeaderValue>()
+                .with_context(|| format!("Invalid CORS origin: {}", origin))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    info!("CORS: allowing origins {}", origins.join(", "));
+
+    Ok(CorsLayer::new()
+        .allow_origin(allowed)
+        .allow_methods([Method::GET, Method::POST, Method::PATCH, Method::DELETE])
+        .allow_headers([header::CONTENT_TYPE, header::RANGE, header::IF_NONE_MATCH])
+        // Lets the audio player and exports on another origin see which bytes
+        // they got and revalidate them
+        .expose_headers([header::ACCEPT_RANGES, header::CONTENT_RANGE, header::ETAG]))
+}
+
 async fn index_handler() -> impl IntoResponse {
     Html(include_str!("../web/index.html"))
 }
 
+#[derive(Debug, Serialize)]
+struct HealthStatus {
+    status: &'static str,
+    version: &'static str,
+    uptime_secs: u64,
+    /// Null when the recorder is busy starting or stopping a recording
+    active_recording: Option<bool>,
+}
+
+/// Report that the server is up, for monitoring
+///
+/// Never waits for the recorder, which is locked while a recording is being
+/// started or saved, so the check stays fast.
+async fn health_handler(
+    State(recorder): State<Arc<AsyncMutex<VoiceRecorder>>>
+) -> Json<HealthStatus> {
+    let uptime = SERVER_STARTED.get().map(Instant::elapsed).unwrap_or_default();
+    Json(HealthStatus {
+        status: "healthy",
+        version: env!("CARGO_PKG_VERSION"),
+        uptime_secs: uptime.as_secs(),
+        active_recording: recorder.try_lock().ok().map(|guard| guard.is_recording()),
+    })
+}
+
 async fn list_sessions_handler(
     Query(query): Query<SessionQuery>
-) -> Result<Json<ApiResponse<Vec<VoiceSession>>>, StatusCode> {
+) -> WebResult<Json<ApiResponse<Vec<VoiceSession>>>> {
+    let sort = parse_session_sort(&query).map_err(WebError::BadRequest)?;
+
     match storage::list_sessions().await {
         Ok(mut sessions) => {
             // Apply search filter if provided
@@ -76,109 +303,102 @@ async fn list_sessions_handler(
                 });
             }
 
-            // Apply sorting
-            if let Some(sort_by) = query.sort_by {
-                match sort_by.as_str() {
-                    "title" => {
-                        sessions.sort_by(|a, b| {
-                            let order = query.sort_order.as_deref().unwrap_or("asc");
-                            if order == "desc" {
-                                b.title.cmp(&a.title)
-                            } else {
-                                a.title.cmp(&b.title)
-                            }
-                        });
-                    }
-                    "timestamp" => {
-                        sessions.sort_by(|a, b| {
-                            let order = query.sort_order.as_deref().unwrap_or("desc");
-                            if order == "asc" {
-                                a.timestamp.cmp(&b.timestamp)
-                            } else {
-                                b.timestamp.cmp(&a.timestamp)
-                            }
-                        });
-                    }
-                    _ => {}
-                }
-            }
+            sort.apply(&mut sessions);
 
-            // Apply pagination
+            // Apply pagination; the shared envelope reports pages, so the
+            // offset has to fall on a page boundary
             let offset = query.offset.unwrap_or(0);
-            let limit = query.limit.unwrap_or(sessions.len());
+            if query.limit == Some(0) {
+                return Err(WebError::BadRequest("limit must be at least 1".to_string()));
+            }
+            if let Some(limit) = query.limit {
+                if offset % limit != 0 {
+                    return Err(WebError::BadRequest(format!(
+                        "offset must be a multiple of limit, got offset {} and limit {}",
+                        offset, limit
+                    )));
+                }
+            }
+            let total = sessions.len();
             let paginated_sessions = sessions.into_iter()
                 .skip(offset)
-                .take(limit)
+                .take(query.limit.unwrap_or(usize::MAX))
                 .map(|mut s| {
                     s.audio_url = Some(format!("/api/sessions/{}/audio", s.id));
                     s
                 })
                 .collect();
 
-            Ok(Json(ApiResponse {
-                data: paginated_sessions,
-                message: Some("Sessions retrieved successfully".to_string()),
-                error: None,
-            }))
+            let response = match query.limit {
+                Some(limit) => {
+                    let page = offset / limit + 1;
+                    ApiResponse::paginated(paginated_sessions, total as i64, page as i64, limit as i64)
+                }
+                None => ApiResponse::success(paginated_sessions).with_total(total as i64),
+            };
+            Ok(Json(response.with_message("Sessions retrieved successfully")))
         },
-        Err(e) => {
-            eprintln!("Failed to list sessions: {:?}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
-        }
+        Err(e) => Err(WebError::internal("Failed to list sessions", e)),
     }
 }
 
+/// Parse the sort parameters of a session listing, rejecting unknown values
+fn parse_session_sort(query: &SessionQuery) -> Result<storage::SessionSort, String> {
+    let mut sort = storage::SessionSort::default();
+    if let Some(sort_by) = &query.sort_by {
+        sort.field = sort_by.parse()?;
+    }
+    if let Some(sort_order) = &query.sort_order {
+        sort.order = sort_order.parse()?;
+    }
+    Ok(sort)
+}
+
+/// A session with its transcript, analysis and that analysis's ideas, tasks
+/// and notes, for the session detail view
 async fn get_session_handler(
     Path(id): Path<String>
-) -> Result<Json<ApiResponse<VoiceSession>>, StatusCode> {
-    match storage::get_session(&id).await {
-        Ok(Some(mut session)) => {
-            session.audio_url = Some(format!("/api/sessions/{}/audio", session.id));
-            Ok(Json(ApiResponse {
-                data: session,
-                message: Some("Session retrieved successfully".to_string()),
-                error: None,
-            }))
+) -> WebResult<Json<ApiResponse<storage::SessionDetail>>> {
+    match storage::get_session_full(&id).await {
+        Ok(Some(mut detail)) => {
+            detail.session.audio_url = Some(format!("/api/sessions/{}/audio", detail.session.id));
+            Ok(Json(ApiResponse::success(detail).with_message("Session retrieved successfully")))
         },
-        Ok(None) => Err(StatusCode::NOT_FOUND),
-        Err(e) => {
-            eprintln!("Failed to get session {}: {:?}", id, e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
-        }
+        Ok(None) => Err(WebError::session_not_found(&id)),
+        Err(e) => Err(WebError::internal(format!("Failed to get session {}", id), e)),
     }
 }
 
 async fn delete_session_handler(
     Path(id): Path<String>
-) -> Result<Json<ApiResponse<()>>, StatusCode> {
+) -> WebResult<Json<ApiResponse<()>>> {
     match storage::delete_session(&id).await {
-        Ok(_) => Ok(Json(ApiResponse {
-            data: (),
-            message: Some("Session deleted successfully".to_string()),
-            error: None,
-        })),
-        Err(e) => {
-            eprintln!("Failed to delete session {}: {:?}", id, e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
-        }
+        Ok(_) => Ok(Json(ApiResponse::success(()).with_message("Session deleted successfully"))),
+        Err(e) => Err(WebError::internal(format!("Failed to delete session {}", id), e)),
     }
 }
 
 async fn export_session_handler(
     Path(id): Path<String>,
-    Query(format): Query<String>
-) -> Result<Response, StatusCode> {
+    Query(format): Query<String>,
+    Query(export): Query<ExportQuery>,
+    headers: axum::http::HeaderMap,
+) -> WebResult<Response> {
     match storage::get_session(&id).await {
         Ok(Some(session)) => {
+            let session = if redact::requested(export.redact) {
+                let redactor = Redactor::from_config()
+                    .map_err(|e| WebError::internal("Failed to build redactor", e))?;
+                redactor.redact_session(&session)
+            } else {
+                session
+            };
             match format.as_str() {
                 "json" => {
                     let json = serde_json::to_string_pretty(&session)
-                        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-                    Ok(Response::builder()
-                        .header("Content-Type", "application/json")
-                        .header("Content-Disposition", format!("attachment; filename=\"session_{}.json\"", id))
-                        .body(Body::from(json))
-                        .unwrap())
+                        .map_err(|e| WebError::internal(format!("Failed to serialize session {}", id), e))?;
+                    let filename = format!("session_{}.json", id);
+                    Ok(etag::content_response(&headers, "application/json", Some(&filename), json))
                 },
                 "txt" => {
                     let content = format!(
@@ -189,100 +409,316 @@ async fn export_session_handler(
                         session.transcript.unwrap_or_default(),
                         serde_json::to_string_pretty(&session.analysis).unwrap_or_default()
                     );
-                    Ok(Response::builder()
-                        .header("Content-Type", "text/plain")
-                        .header("Content-Disposition", format!("attachment; filename=\"session_{}.txt\"", id))
-                        .body(Body::from(content))
-                        .unwrap())
+                    let filename = format!("session_{}.txt", id);
+                    Ok(etag::content_response(&headers, "text/plain", Some(&filename), content))
                 },
-                _ => Err(StatusCode::BAD_REQUEST)
+                _ => Err(WebError::BadRequest(format!(
+                    "Unsupported export format: {}. Supported formats: json, txt",
+                    format
+                ))),
             }
         },
-        Ok(None) => Err(StatusCode::NOT_FOUND),
-        Err(e) => {
-            eprintln!("Failed to export session {}: {:?}", id, e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
-        }
+        Ok(None) => Err(WebError::session_not_found(&id)),
+        Err(e) => Err(WebError::internal(format!("Failed to export session {}", id), e)),
     }
 }
 
-async fn audio_handler(Path(id): Path<String>) -> Result<Response, StatusCode> {
-    let storage_dir = crate::config::get_storage_dir();
-    let audio_file_path = storage_dir.join("audio").join(format!("{}.wav", id));
+/// Chunk size used when streaming audio files
+const AUDIO_CHUNK_BYTES: u64 = 64 * 1024;
+
+/// Serve a session's recording, honouring single `Range` requests so
+/// browsers can seek without downloading the whole file
+async fn audio_handler(
+    Path(id): Path<String>,
+    headers: axum::http::HeaderMap,
+) -> WebResult<Response> {
+    // Imported recordings keep their original format; fall back to the
+    // recorder's naming for files without a session
+    let (audio_file_path, checksum) = match storage::get_session(&id).await {
+        Ok(Some(session)) => (session.audio_file_path, session.audio_checksum),
+        Ok(None) => (crate::config::get_audio_dir().join(format!("{}.wav", id)), None),
+        Err(e) => return Err(WebError::internal(format!("Failed to get session {}", id), e)),
+    };
 
     if !audio_file_path.as_path().exists() {
-        return Err(StatusCode::NOT_FOUND);
+        return Err(WebError::NotFound(format!("No audio file for session {}", id)));
     }
 
-    match File::open(&audio_file_path).await {
-        Ok(mut file) => {
-            let mut buffer = Vec::new();
-            if let Err(e) = file.read_to_end(&mut buffer).await {
-                eprintln!("Failed to read audio file {}: {:?}", id, e);
-                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    let mut file = File::open(&audio_file_path)
+        .await
+        .map_err(|e| WebError::internal(format!("Failed to open audio file {}", id), e))?;
+    let metadata = file
+        .metadata()
+        .await
+        .map_err(|e| WebError::internal(format!("Failed to read audio file metadata {}", id), e))?;
+    let len = metadata.len();
+
+    // The checksum recorded at upload identifies the content exactly;
+    // recordings without one fall back to size and modification time
+    let etag = match checksum.as_deref() {
+        Some(checksum) => etag::for_checksum(checksum),
+        None => etag::for_file_metadata(&metadata),
+    };
+    if etag::matches(&headers, &etag) {
+        return Ok(etag::not_modified(&etag));
+    }
+
+    let extension = audio_file_path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("wav")
+        .to_lowercase();
+    let response = Response::builder()
+        .header(header::CONTENT_TYPE, audio_content_type(&extension))
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::ETAG, &etag)
+        .header(
+            header::CONTENT_DISPOSITION,
+            disposition::inline(&format!("session_{}.{}", id, extension)),
+        );
+
+    let range = headers
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| parse_range(value, len))
+        .unwrap_or(RangeRequest::Full);
+
+    let response = match range {
+        RangeRequest::Full => response
+            .status(StatusCode::OK)
+            .header(header::CONTENT_LENGTH, len)
+            .body(file_body(file, len)),
+        RangeRequest::Partial { start, end } => {
+            if let Err(e) = file.seek(std::io::SeekFrom::Start(start)).await {
+                return Err(WebError::internal(format!("Failed to seek in audio file {}", id), e));
             }
-            Ok(Response::builder()
-                .header("Content-Type", "audio/wav")
-                .header("Content-Disposition", format!("inline; filename=\"session_{}.wav\"", id))
-                .body(Body::from(Bytes::from(buffer)))
-                .unwrap())
+            let window = end - start + 1;
+            response
+                .status(StatusCode::PARTIAL_CONTENT)
+                .header(header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, len))
+                .header(header::CONTENT_LENGTH, window)
+                .body(file_body(file, window))
+        }
+        RangeRequest::Unsatisfiable => response
+            .status(StatusCode::RANGE_NOT_SATISFIABLE)
+            .header(header::CONTENT_RANGE, format!("bytes */{}", len))
+            .body(Body::empty()),
+    };
+
+    response.map_err(|e| WebError::internal(format!("Failed to build audio response {}", id), e))
+}
+
+/// Serve the part of a session's recording between `start` and `end`
+/// seconds as a WAV file, e.g. the audio of one transcript segment
+async fn audio_clip_handler(
+    Path(id): Path<String>,
+    Query(query): Query<ClipQuery>,
+) -> WebResult<Response> {
+    let (Some(start), Some(end)) = (query.start, query.end) else {
+        return Err(WebError::BadRequest("start and end are required, in seconds".to_string()));
+    };
+    let session = match storage::get_session(&id).await {
+        Ok(Some(session)) => session,
+        Ok(None) => return Err(WebError::session_not_found(&id)),
+        Err(e) => return Err(WebError::internal(format!("Failed to get session {}", id), e)),
+    };
+    if !session.audio_file_path.exists() {
+        return Err(WebError::NotFound(format!("No audio file for session {}", id)));
+    }
+
+    let data = match clip::extract_clip(&session.audio_file_path, start, end).await {
+        Ok(data) => data,
+        Err(e @ (ClipError::InvalidRange(_) | ClipError::Unsupported(_))) => {
+            return Err(WebError::UnprocessableEntity(e.to_string()))
+        }
+        Err(ClipError::Failed(e)) => return Err(WebError::internal(format!("Failed to clip audio of session {}", id), e)),
+    };
+
+    Response::builder()
+        .header(header::CONTENT_TYPE, audio_content_type("wav"))
+        .header(header::CONTENT_LENGTH, data.len())
+        .header(
+            header::CONTENT_DISPOSITION,
+            disposition::inline(&format!("session_{}_{}-{}.wav", id, start, end)),
+        )
+        .body(Body::from(data))
+        .map_err(|e| WebError::internal(format!("Failed to build audio response {}", id), e))
+}
+
+/// MIME type of an audio file extension
+fn audio_content_type(extension: &str) -> &'static str {
+    match extension {
+        "mp3" => "audio/mpeg",
+        "m4a" | "mp4" => "audio/mp4",
+        "flac" => "audio/flac",
+        "ogg" | "opus" => "audio/ogg",
+        "webm" => "audio/webm",
+        _ => "audio/wav",
+    }
+}
+
+/// How to answer a request given its `Range` header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RangeRequest {
+    /// Serve the whole file
+    Full,
+    /// Serve bytes `start..=end`
+    Partial { start: u64, end: u64 },
+    /// The range lies outside the file
+    Unsatisfiable,
+}
+
+/// Interpret a `Range` header for a file of `len` bytes
+///
+/// Only a single `bytes` range is supported. Headers asking for several
+/// ranges, other units or malformed ones are ignored and the whole file is
+/// served, as RFC 9110 allows.
+fn parse_range(header: &str, len: u64) -> RangeRequest {
+    let Some(spec) = header.trim().strip_prefix("bytes=") else {
+        return RangeRequest::Full;
+    };
+    if spec.contains(',') {
+        return RangeRequest::Full;
+    }
+    let Some((first, last)) = spec.trim().split_once('-') else {
+        return RangeRequest::Full;
+    };
+
+    let (start, end) = match (first.trim(), last.trim()) {
+        // bytes=-N: the last N bytes
+        ("", suffix) => match suffix.parse::<u64>() {
+            Ok(0) => return RangeRequest::Unsatisfiable,
+            Ok(suffix) => (len.saturating_sub(suffix), len.saturating_sub(1)),
+            Err(_) => return RangeRequest::Full,
         },
-        Err(e) => {
-            eprintln!("Failed to open audio file {}: {:?}", id, e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        // bytes=N-: from N to the end
+        (first, "") => match first.parse::<u64>() {
+            Ok(start) => (start, len.saturating_sub(1)),
+            Err(_) => return RangeRequest::Full,
+        },
+        (first, last) => match (first.parse::<u64>(), last.parse::<u64>()) {
+            (Ok(start), Ok(end)) if start <= end => (start, end.min(len.saturating_sub(1))),
+            _ => return RangeRequest::Full,
+        },
+    };
+
+    if len == 0 || start >= len {
+        return RangeRequest::Unsatisfiable;
+    }
+    RangeRequest::Partial { start, end }
+}
+
+/// Stream the next `len` bytes of `file` as a response body
+fn file_body(file: File, len: u64) -> Body {
+    let chunks = futures::stream::unfold((file, len), |(mut file, remaining)| async move {
+        if remaining == 0 {
+            return None;
+        }
+        let mut buffer = vec![0u8; remaining.min(AUDIO_CHUNK_BYTES) as usize];
+        match file.read(&mut buffer).await {
+            Ok(0) => None,
+            Ok(read) => {
+                buffer.truncate(read);
+                Some((Ok(Bytes::from(buffer)), (file, remaining - read as u64)))
+            }
+            Err(e) => Some((Err(e), (file, 0))),
         }
+    });
+    Body::from_stream(chunks)
+}
+
+async fn waveform_handler(
+    Path(id): Path<String>,
+    Query(query): Query<WaveformQuery>,
+) -> WebResult<Json<ApiResponse<Waveform>>> {
+    let session = match storage::get_session(&id).await {
+        Ok(Some(session)) => session,
+        Ok(None) => return Err(WebError::session_not_found(&id)),
+        Err(e) => return Err(WebError::internal(format!("Failed to get session {}", id), e)),
+    };
+
+    if !session.audio_file_path.exists() {
+        return Err(WebError::NotFound(format!("No audio file for session {}", id)));
+    }
+
+    let points = query.points.unwrap_or(waveform::DEFAULT_POINTS);
+    match waveform::get_waveform(&session.audio_file_path, session.audio_checksum.as_deref(), points).await {
+        Ok(waveform) => Ok(Json(ApiResponse::success((*waveform).clone()).with_message("Waveform computed successfully"))),
+        Err(e) => Err(WebError::internal(format!("Failed to compute waveform for session {}", id), e)),
     }
 }
 
 async fn get_transcript_handler(
     Path(id): Path<String>
-) -> Result<Json<ApiResponse<String>>, StatusCode> {
+) -> WebResult<Json<ApiResponse<String>>> {
     match storage::get_session(&id).await {
         Ok(Some(session)) => {
-            Ok(Json(ApiResponse {
-                data: session.transcript.unwrap_or_default(),
-                message: Some("Transcript retrieved successfully".to_string()),
-                error: None,
-            }))
+            Ok(Json(ApiResponse::success(session.transcript.unwrap_or_default()).with_message("Transcript retrieved successfully")))
         },
-        Ok(None) => Err(StatusCode::NOT_FOUND),
-        Err(e) => {
-            eprintln!("Failed to get transcript for session {}: {:?}", id, e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
-        }
+        Ok(None) => Err(WebError::session_not_found(&id)),
+        Err(e) => Err(WebError::internal(format!("Failed to get transcript for session {}", id), e)),
     }
 }
 
 async fn get_analysis_handler(
     Path(id): Path<String>
-) -> Result<Json<ApiResponse<Option<crate::storage::AnalysisResult>>>, StatusCode> {
+) -> WebResult<Json<ApiResponse<Option<crate::storage::AnalysisResult>>>> {
     match storage::get_session(&id).await {
         Ok(Some(session)) => {
-            Ok(Json(ApiResponse {
-                data: session.analysis,
-                message: Some("Analysis retrieved successfully".to_string()),
-                error: None,
-            }))
+            Ok(Json(ApiResponse::success(session.analysis).with_message("Analysis retrieved successfully")))
         },
-        Ok(None) => Err(StatusCode::NOT_FOUND),
-        Err(e) => {
-            eprintln!("Failed to get analysis for session {}: {:?}", id, e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
-        }
+        Ok(None) => Err(WebError::session_not_found(&id)),
+        Err(e) => Err(WebError::internal(format!("Failed to get analysis for session {}", id), e)),
     }
 }
 
-async fn get_config_handler() -> Result<Json<ApiResponse<LegacyConfig>>, StatusCode> {
+/// Analyze a session's transcript now, e.g. one saved without analysis
+/// because `analysis.auto_analyze` is off; an earlier analysis is kept in
+/// `analysis_history`. 409 while the session is being analyzed, e.g. by
+/// `backfill`.
+async fn analyze_session_handler(
+    Path(id): Path<String>
+) -> WebResult<Json<ApiResponse<VoiceSession>>> {
+    // The id names the lock file, so only lock sessions that exist
+    match storage::get_session(&id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return Err(WebError::session_not_found(&id)),
+        Err(e) => return Err(WebError::internal(format!("Failed to get session {}", id), e)),
+    }
+    let _lock = match crate::analysis_lock::try_lock(&id) {
+        Ok(Some(lock)) => lock,
+        Ok(None) => return Err(WebError::Conflict(format!("Session {} is already being analyzed", id))),
+        Err(e) => return Err(WebError::internal(format!("Failed to lock session {}", id), e)),
+    };
+    // Read again under the lock, after any analysis that held it was saved
+    let mut session = match storage::get_session(&id).await {
+        Ok(Some(session)) => session,
+        Ok(None) => return Err(WebError::session_not_found(&id)),
+        Err(e) => return Err(WebError::internal(format!("Failed to get session {}", id), e)),
+    };
+    let Some(transcript) = session.transcript.clone() else {
+        return Err(WebError::UnprocessableEntity(format!("Session {} has no transcript to analyze", id)));
+    };
+
+    let analysis = match crate::ai::analyze_transcript(&transcript).await {
+        Ok(analysis) => analysis,
+        Err(e) => return Err(WebError::internal(format!("Failed to analyze session {}", id), e)),
+    };
+    if !analysis.title.is_empty() {
+        session.title = analysis.title.clone();
+    }
+    session.replace_analysis(analysis);
+    if let Err(e) = storage::save_session(&mut session, None).await {
+        return Err(WebError::internal(format!("Failed to save session {}", id), e));
+    }
+
+    Ok(Json(ApiResponse::success(session).with_message("Session analyzed successfully")))
+}
+
+async fn get_config_handler() -> WebResult<Json<ApiResponse<LegacyConfig>>> {
     match crate::config::load_config().await {
-        Ok(config) => Ok(Json(ApiResponse {
-            data: config,
-            message: Some("Configuration retrieved successfully".to_string()),
-            error: None,
-        })),
-        Err(e) => {
-            eprintln!("Failed to load config: {:?}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
-        }
+        Ok(config) => Ok(Json(ApiResponse::success(config).with_message("Configuration retrieved successfully"))),
+        Err(e) => Err(WebError::internal("Failed to load config", e)),
     }
 }
 
@@ -290,48 +726,62 @@ async fn get_config_handler() -> Result<Json<ApiResponse<LegacyConfig>>, StatusC
 
 async fn start_record_handler(
     State(recorder): State<Arc<AsyncMutex<VoiceRecorder>>>
-) -> Result<Json<ApiResponse<()>>, StatusCode> {
+) -> WebResult<Json<ApiResponse<()>>> {
     let mut guard = recorder.lock().await;
     match guard.start_recording().await {
-        Ok(_) => Ok(Json(ApiResponse {
-            data: (),
-            message: Some("Recording started successfully".to_string()),
-            error: None,
-        })),
-        Err(e) => {
-            eprintln!("Failed to start recording from web: {:?}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        Ok(_) => {
+            if let Some(session_id) = guard.current_session_id() {
+                crate::audio::spawn_max_duration_guard(recorder.clone(), session_id);
+            }
+            Ok(Json(ApiResponse::success(()).with_message("Recording started successfully")))
         }
+        Err(e) if e.is::<crate::audio::AlreadyRecording>() => Err(WebError::Conflict(e.to_string())),
+        Err(e) => Err(WebError::internal("Failed to start recording", e)),
     }
 }
 
 async fn stop_record_handler(
     State(recorder): State<Arc<AsyncMutex<VoiceRecorder>>>
-) -> Result<Json<ApiResponse<()>>, StatusCode> {
+) -> WebResult<Json<ApiResponse<()>>> {
     let mut guard = recorder.lock().await;
     match guard.stop_recording().await {
-        Ok(_) => Ok(Json(ApiResponse {
-            data: (),
-            message: Some("Recording stopped successfully".to_string()),
-            error: None,
-        })),
-        Err(e) => {
-            eprintln!("Failed to stop recording from web: {:?}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
-        }
+        Ok(_) => Ok(Json(ApiResponse::success(()).with_message("Recording stopped successfully"))),
+        Err(e) => Err(WebError::internal("Failed to stop recording", e)),
     }
 }
 
+/// Polled by the UI for its timer and level meter, so kept small; the
+/// optional fields are null while idle
+#[derive(Debug, Serialize)]
+struct RecordStatus {
+    recording: bool,
+    session_id: Option<String>,
+    elapsed_ms: Option<u64>,
+    /// Smoothed RMS input level in 0.0..=1.0
+    input_level: Option<f32>,
+}
+
 async fn record_status_handler(
     State(recorder): State<Arc<AsyncMutex<VoiceRecorder>>>
-) -> Result<Json<ApiResponse<bool>>, StatusCode> {
+) -> WebResult<Json<ApiResponse<RecordStatus>>> {
     let guard = recorder.lock().await;
-    let is_recording = guard.is_recording();
-    Ok(Json(ApiResponse {
-        data: is_recording,
-        message: Some(if is_recording { "Recording in progress" } else { "Not recording" }.to_string()),
-        error: None,
-    }))
+    let recording = guard.is_recording();
+    let status = if recording {
+        RecordStatus {
+            recording,
+            session_id: guard.current_session_id(),
+            elapsed_ms: guard.elapsed().map(|elapsed| elapsed.as_millis() as u64),
+            input_level: guard.input_level().map(|level| level.rms),
+        }
+    } else {
+        RecordStatus {
+            recording,
+            session_id: None,
+            elapsed_ms: None,
+            input_level: None,
+        }
+    };
+    Ok(Json(ApiResponse::success(status)))
 }
 
 /**
@@ -340,93 +790,81 @@ async fn record_status_handler(
  */
 async fn upload_audio_handler(
     mut multipart: Multipart,
-) -> Result<Json<ApiResponse<VoiceSession>>, StatusCode> {
-    println!("[DEBUG] Starting audio upload process");
-    
-    let mut audio_data: Option<Bytes> = None;
-    let mut filename: Option<String> = None;
+) -> WebResult<Json<ApiResponse<VoiceSession>>> {
+    debug!("Starting audio upload process");
 
-    // Process multipart form data
-    println!("[DEBUG] Processing multipart form data");
-    while let Some(field) = multipart.next_field().await.map_err(|e| {
-        eprintln!("[ERROR] Failed to get next multipart field: {:?}", e);
-        StatusCode::BAD_REQUEST
-    })? {
-        let field_name = field.name().unwrap_or("").to_string();
-        println!("[DEBUG] Processing field: {}", field_name);
-        
-        if field_name == "audio" {
-            filename = field.file_name().map(|s| s.to_string());
-            println!("[DEBUG] Found audio field, filename: {:?}", filename);
-            
-            match field.bytes().await {
-                Ok(bytes) => {
-                    println!("[DEBUG] Successfully read audio data, size: {} bytes", bytes.len());
-                    audio_data = Some(bytes);
-                },
-                Err(e) => {
-                    eprintln!("[ERROR] Failed to read audio field bytes: {:?}", e);
-                    return Err(StatusCode::BAD_REQUEST);
-                }
-            }
-        }
-    }
-
-    let audio_bytes = match audio_data {
-        Some(bytes) => {
-            println!("[DEBUG] Audio data extracted successfully, size: {} bytes", bytes.len());
-            bytes
-        },
-        None => {
-            eprintln!("[ERROR] No audio data found in multipart form");
-            return Err(StatusCode::BAD_REQUEST);
-        }
-    };
-    
     // Generate unique session ID
     let session_id = Uuid::new_v4().to_string();
-    println!("[DEBUG] Generated session ID: {}", session_id);
-    
+    debug!("Generated session ID: {}", session_id);
+
     // Create storage directories
     let storage_dir = crate::config::get_storage_dir();
-    let audio_dir = storage_dir.join("audio");
-    println!("[DEBUG] Storage directory: {:?}", storage_dir);
-    println!("[DEBUG] Audio directory: {:?}", audio_dir);
-    
+    let audio_dir = crate::config::get_audio_dir();
+    debug!("Storage directory: {:?}", storage_dir);
+    debug!("Audio directory: {:?}", audio_dir);
+
     match tokio::fs::create_dir_all(&audio_dir).await {
-        Ok(_) => println!("[DEBUG] Audio directory created/verified successfully"),
-        Err(e) => {
-            eprintln!("[ERROR] Failed to create audio directory: {:?}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
-        }
+        Ok(_) => debug!("Audio directory created/verified successfully"),
+        Err(e) => return Err(WebError::internal("Failed to create audio directory", e)),
     }
-    
-    // Save audio file
+
     let audio_filename = format!("{}.wav", session_id);
     let audio_file_path = audio_dir.join(&audio_filename);
-    println!("[DEBUG] Audio file path: {:?}", audio_file_path);
-    
-    let mut file = match tokio::fs::File::create(&audio_file_path).await {
-        Ok(file) => {
-            println!("[DEBUG] Audio file created successfully");
-            file
-        },
-        Err(e) => {
-            eprintln!("[ERROR] Failed to create audio file: {:?}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    debug!("Audio file path: {:?}", audio_file_path);
+
+    // Written under a temporary name that is removed unless the whole form is
+    // read, including when the client disconnects mid-upload
+    let partial_file = PartialFile::new(audio_dir.join(format!("{}.part", audio_filename)));
+    let max_file_size = crate::config::current().storage.max_file_size;
+    let mut checksum: Option<String> = None;
+
+    // Process multipart form data, streaming the audio field straight to disk
+    debug!("Processing multipart form data");
+    while let Some(field) = multipart.next_field().await.map_err(|e| {
+        error!("Failed to get next multipart field: {:?}", e);
+        WebError::BadRequest(format!("Invalid multipart body: {}", e))
+    })? {
+        let field_name = field.name().unwrap_or("").to_string();
+        debug!("Processing field: {}", field_name);
+
+        if field_name == "audio" {
+            debug!("Found audio field, filename: {:?}", field.file_name());
+
+            let (size, digest) = stream_field_to_file(field, &partial_file.path, max_file_size).await?;
+            debug!("Audio data written to file successfully, size: {} bytes", size);
+            checksum = Some(digest);
         }
-    };
-    
-    match file.write_all(&audio_bytes).await {
-        Ok(_) => println!("[DEBUG] Audio data written to file successfully"),
-        Err(e) => {
-            eprintln!("[ERROR] Failed to write audio data to file: {:?}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    let checksum = match checksum {
+        Some(checksum) => checksum,
+        None => {
+            error!("No audio data found in multipart form");
+            return Err(WebError::BadRequest("No audio field in the multipart form".to_string()));
         }
+    };
+    partial_file.persist(&audio_file_path).await?;
+
+    process_uploaded_audio(session_id, audio_file_path, checksum).await
+}
+
+/**
+ * Create a voice session for an uploaded audio file, then transcribe and analyze it
+ */
+async fn process_uploaded_audio(
+    session_id: String,
+    audio_file_path: std::path::PathBuf,
+    checksum: String,
+) -> WebResult<Json<ApiResponse<VoiceSession>>> {
+    // A corrupt file would only fail later inside the transcription provider
+    if let Err(e) = crate::probe::check(&audio_file_path) {
+        error!("Rejecting uploaded audio {:?}: {}", audio_file_path, e);
+        let _ = tokio::fs::remove_file(&audio_file_path).await;
+        return Err(WebError::UnprocessableEntity(e.to_string()));
     }
-    
+
     // Create voice session
-    println!("[DEBUG] Creating voice session object");
+    debug!("Creating voice session object");
     let mut session = VoiceSession {
         id: session_id.clone(),
         timestamp: Utc::now(),
@@ -436,95 +874,352 @@ async fn upload_audio_handler(
         title: "Processing...".to_string(),
         duration_ms: 0,
         audio_url: Some(format!("/api/sessions/{}/audio", session_id)),
+        audio_checksum: Some(checksum),
+        segments: Vec::new(),
+        language: None,
+        transcription_provider: None,
+        transcription_model: None,
+        normalization: None,
+        analysis_history: Vec::new(),
+        analysis_pending: false,
     };
-    println!("[DEBUG] Voice session created with ID: {}", session.id);
+    debug!("Voice session created with ID: {}", session.id);
     
     // Process audio file
-    println!("[DEBUG] Starting audio transcription for file: {:?}", audio_file_path);
-    match crate::ai::transcribe_audio(&audio_file_path).await {
-        Ok(transcript) => {
-            println!("[DEBUG] Audio transcription successful, transcript length: {} characters", transcript.len());
-            println!("[DEBUG] Transcript preview: {}", 
-                if transcript.len() > 100 { 
-                    format!("{}...", &transcript[..100]) 
-                } else { 
-                    transcript.clone() 
-                }
-            );
-            session.transcript = Some(transcript.clone());
+    debug!("Starting audio transcription for file: {:?}", audio_file_path);
+    match crate::ai::transcribe_session_audio(&mut session).await {
+        Ok(transcription) => {
+            let transcript = transcription.text.clone();
+            debug!("Audio transcription successful, transcript length: {} characters", transcript.len());
+            debug!("Transcript preview: {}", transcript.chars().take(100).collect::<String>());
+            session.set_transcription(transcription);
             
-            // Analyze transcript
-            println!("[DEBUG] Starting transcript analysis");
-            match crate::ai::analyze_transcript(&transcript).await {
-                Ok(analysis) => {
-                    println!("[DEBUG] Transcript analysis successful");
-                    println!("[DEBUG] Analysis contains: {} ideas, {} tasks, {} structured notes", 
+            // Analyze transcript, unless analysis.auto_analyze is off
+            let analysis = if crate::config::current().analysis.auto_analyze {
+                debug!("Starting transcript analysis");
+                Some(crate::ai::analyze_transcript(&transcript).await)
+            } else {
+                debug!("Automatic analysis is off, skipping transcript analysis");
+                None
+            };
+            match analysis {
+                Some(Ok(analysis)) => {
+                    debug!("Transcript analysis successful");
+                    debug!("Analysis contains: {} ideas, {} tasks, {} structured notes", 
                         analysis.ideas.len(), analysis.tasks.len(), analysis.structured_notes.len());
                     
                     // Generate title from analysis
                     let title = if let Some(first_idea) = analysis.ideas.first() {
-                        println!("[DEBUG] Using first idea as title: {}", first_idea);
+                        debug!("Using first idea as title: {}", first_idea);
                         first_idea.clone()
                     } else if !analysis.tasks.is_empty() {
-                        println!("[DEBUG] Using first task title: {}", analysis.tasks[0].title);
+                        debug!("Using first task title: {}", analysis.tasks[0].title);
                         analysis.tasks[0].title.clone()
                     } else if !analysis.structured_notes.is_empty() {
-                        println!("[DEBUG] Using first structured note title: {}", analysis.structured_notes[0].title);
+                        debug!("Using first structured note title: {}", analysis.structured_notes[0].title);
                         analysis.structured_notes[0].title.clone()
                     } else {
-                        println!("[DEBUG] No specific content found, using default title");
+                        debug!("No specific content found, using default title");
                         "Voice Note".to_string()
                     };
                     
                     session.title = title.clone();
                     session.analysis = Some(analysis.clone());
-                    println!("[DEBUG] Session title set to: {}", title);
+                    debug!("Session title set to: {}", title);
                     
                     // Save session with analysis
-                    println!("[DEBUG] Saving session with analysis");
+                    debug!("Saving session with analysis");
                     if let Err(e) = crate::storage::save_session(&mut session, Some(analysis)).await {
-                        eprintln!("[ERROR] Failed to save session with analysis: {:?}", e);
-                        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                        return Err(WebError::internal("Failed to save session with analysis", e));
                     }
-                    println!("[DEBUG] Session saved successfully with analysis");
+                    debug!("Session saved successfully with analysis");
                 },
-                Err(e) => {
-                    eprintln!("[ERROR] Failed to analyze transcript: {:?}", e);
+                Some(Err(e)) => {
+                    error!("Failed to analyze transcript: {:?}", e);
                     session.title = "Voice Note".to_string();
-                    println!("[DEBUG] Set default title due to analysis failure");
+                    debug!("Set default title due to analysis failure");
+                    if crate::ai::is_provider_unavailable(&e) {
+                        // Analyzed later by `retry-analysis` or `backfill`
+                        session.analysis_pending = true;
+                        debug!("Analysis provider unavailable, marking session as analysis pending");
+                    }
                     
                     // Save session without analysis
-                    println!("[DEBUG] Saving session without analysis due to analysis failure");
+                    debug!("Saving session without analysis due to analysis failure");
+                    if let Err(e) = crate::storage::save_session(&mut session, None).await {
+                        return Err(WebError::internal("Failed to save session without analysis", e));
+                    }
+                    debug!("Session saved successfully without analysis");
+                }
+                None => {
+                    session.title = "Voice Note".to_string();
+                    session.analysis_pending = true;
                     if let Err(e) = crate::storage::save_session(&mut session, None).await {
-                        eprintln!("[ERROR] Failed to save session without analysis: {:?}", e);
-                        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                        return Err(WebError::internal("Failed to save session without analysis", e));
                     }
-                    println!("[DEBUG] Session saved successfully without analysis");
+                    debug!("Session saved without analysis, to be analyzed on demand");
                 }
             }
         },
         Err(e) => {
-            eprintln!("[ERROR] Failed to transcribe audio: {:?}", e);
+            error!("Failed to transcribe audio: {:?}", e);
             session.title = "Voice Note".to_string();
-            println!("[DEBUG] Set default title due to transcription failure");
+            debug!("Set default title due to transcription failure");
             
             // Save session without transcript
-            println!("[DEBUG] Saving session without transcript due to transcription failure");
+            debug!("Saving session without transcript due to transcription failure");
             if let Err(e) = crate::storage::save_session(&mut session, None).await {
-                eprintln!("[ERROR] Failed to save session without transcript: {:?}", e);
-                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                return Err(WebError::internal("Failed to save session without transcript", e));
             }
-            println!("[DEBUG] Session saved successfully without transcript");
+            debug!("Session saved successfully without transcript");
         }
     }
     
-    println!("[DEBUG] Audio upload and processing completed successfully");
-    println!("[DEBUG] Final session - ID: {}, Title: {}, Has transcript: {}, Has analysis: {}", 
+    debug!("Audio upload and processing completed successfully");
+    crate::webhook::notify_session_processed(&session);
+    debug!("Final session - ID: {}, Title: {}, Has transcript: {}, Has analysis: {}", 
         session.id, session.title, session.transcript.is_some(), session.analysis.is_some());
     
-    Ok(Json(ApiResponse {
-        data: session,
-        message: Some("Audio uploaded and processed successfully".to_string()),
-        error: None,
-    }))
-}
\ No newline at end of file
+    Ok(Json(ApiResponse::success(session).with_message("Audio uploaded and processed successfully")))
+}
+
+/// Write a multipart field to `path` chunk by chunk, returning its size and
+/// hex-encoded SHA-256 checksum
+///
+/// Fails with 413 as soon as more than `max_size` bytes have arrived, so an
+/// oversized upload is never buffered or written in full. The caller removes
+/// the partial file on error.
+async fn stream_field_to_file(
+    mut field: axum::extract::multipart::Field<'_>,
+    path: &std::path::Path,
+    max_size: u64,
+) -> WebResult<(u64, String)> {
+    use sha2::{Digest, Sha256};
+
+    let mut file = File::create(path)
+        .await
+        .map_err(|e| WebError::internal("Failed to create audio file", e))?;
+    let mut hasher = Sha256::new();
+    let mut size: u64 = 0;
+
+    while let Some(chunk) = field.chunk().await.map_err(|e| {
+        error!("Failed to read audio field chunk: {:?}", e);
+        WebError::BadRequest(format!("Failed to read the audio field: {}", e))
+    })? {
+        size += chunk.len() as u64;
+        if size > max_size {
+            error!("Upload exceeds maximum file size of {} bytes", max_size);
+            return Err(file_too_large(max_size));
+        }
+        hasher.update(&chunk);
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| WebError::internal("Failed to write audio data to file", e))?;
+    }
+
+    file.flush()
+        .await
+        .map_err(|e| WebError::internal("Failed to flush audio file", e))?;
+    Ok((size, format!("{:x}", hasher.finalize())))
+}
+
+/// A file being written that is removed when dropped, unless it was persisted
+struct PartialFile {
+    path: std::path::PathBuf,
+    persisted: bool,
+}
+
+impl PartialFile {
+    fn new(path: std::path::PathBuf) -> Self {
+        Self { path, persisted: false }
+    }
+
+    /// Move the finished file to `path`
+    async fn persist(mut self, path: &std::path::Path) -> WebResult<()> {
+        tokio::fs::rename(&self.path, path)
+            .await
+            .map_err(|e| WebError::internal("Failed to move the uploaded audio into place", e))?;
+        self.persisted = true;
+        Ok(())
+    }
+}
+
+impl Drop for PartialFile {
+    fn drop(&mut self) {
+        if !self.persisted {
+            // The file may not have been created yet
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct InitUploadRequest {
+    filename: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UploadChunkQuery {
+    offset: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct UploadStatus {
+    upload_id: String,
+    offset: u64,
+    max_file_size: u64,
+}
+
+impl UploadStatus {
+    fn new(upload_id: String, offset: u64) -> Self {
+        Self {
+            upload_id,
+            offset,
+            max_file_size: crate::config::current().storage.max_file_size,
+        }
+    }
+}
+
+/// Map resumable upload failures to web errors
+fn upload_error(err: anyhow::Error) -> WebError {
+    match err.downcast_ref::<UploadError>() {
+        Some(e @ UploadError::NotFound(_)) => WebError::NotFound(e.to_string()),
+        Some(e @ (UploadError::OffsetMismatch { .. } | UploadError::Busy(_))) => WebError::Conflict(e.to_string()),
+        None => WebError::internal("Resumable upload failed", err),
+    }
+}
+
+fn file_too_large(max_size: u64) -> WebError {
+    WebError::PayloadTooLarge(format!("Upload exceeds the maximum file size of {} bytes", max_size))
+}
+
+/**
+ * Start a resumable upload
+ * Returns the upload id chunks are sent to
+ */
+async fn init_upload_handler(
+    body: Option<Json<InitUploadRequest>>,
+) -> WebResult<Json<ApiResponse<UploadStatus>>> {
+    let Json(request) = body.unwrap_or_default();
+    let upload = uploads::create(request.filename)
+        .await
+        .map_err(upload_error)?;
+
+    Ok(Json(ApiResponse::success(UploadStatus::new(upload.id, upload.offset)).with_message("Upload started")))
+}
+
+/**
+ * Report how many bytes of a resumable upload have been received
+ * Clients resume by sending the rest of the file from this offset
+ */
+async fn upload_status_handler(
+    Path(id): Path<String>,
+) -> WebResult<Json<ApiResponse<UploadStatus>>> {
+    let upload = uploads::get(&id)
+        .await
+        .map_err(upload_error)?
+        .ok_or_else(|| WebError::NotFound(format!("Upload {} not found", id)))?;
+
+    Ok(Json(ApiResponse::success(UploadStatus::new(upload.id, upload.offset))))
+}
+
+/**
+ * Append the request body to a resumable upload
+ * The chunk must start at the number of bytes received so far (`?offset=`);
+ * otherwise 409 is returned and the client should query the upload status.
+ */
+async fn upload_chunk_handler(
+    Path(id): Path<String>,
+    Query(query): Query<UploadChunkQuery>,
+    body: Body,
+) -> WebResult<Json<ApiResponse<UploadStatus>>> {
+    use futures::StreamExt;
+
+    let max_file_size = crate::config::current().storage.max_file_size;
+    let mut append = uploads::begin_append(&id, query.offset)
+        .await
+        .map_err(upload_error)?;
+
+    // Keep whatever arrived before a dropped connection; the client resumes from there
+    let mut stream = body.into_data_stream();
+    let mut result = Ok(());
+    while let Some(chunk) = stream.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                error!("Upload {} chunk interrupted: {:?}", id, e);
+                result = Err(WebError::BadRequest(format!("Chunk interrupted: {}", e)));
+                break;
+            }
+        };
+        if append.offset + chunk.len() as u64 > max_file_size {
+            error!("Upload {} exceeds maximum file size of {} bytes", id, max_file_size);
+            result = Err(file_too_large(max_file_size));
+            break;
+        }
+        if let Err(e) = append.file.write_all(&chunk).await {
+            result = Err(WebError::internal(format!("Failed to write upload {} chunk", id), e));
+            break;
+        }
+        append.offset += chunk.len() as u64;
+    }
+
+    if let Err(e) = append.file.flush().await {
+        result = Err(WebError::internal(format!("Failed to flush upload {}", id), e));
+    }
+    let offset = append.offset;
+    drop(append);
+    uploads::touch(&id).await.map_err(upload_error)?;
+    result?;
+
+    Ok(Json(ApiResponse::success(UploadStatus::new(id, offset))))
+}
+
+/**
+ * Finish a resumable upload
+ * The received file becomes a new voice session, processed like a direct upload
+ */
+async fn complete_upload_handler(
+    Path(id): Path<String>,
+) -> WebResult<Json<ApiResponse<VoiceSession>>> {
+    // Checked before completing, which removes the upload, so the client can
+    // still send its data
+    let pending = uploads::get(&id)
+        .await
+        .map_err(upload_error)?
+        .ok_or_else(|| WebError::NotFound(format!("Upload {} not found", id)))?;
+    if pending.offset == 0 {
+        error!("Upload {} was completed without any audio data", id);
+        return Err(WebError::BadRequest(format!("Upload {} has no audio data", id)));
+    }
+
+    let audio_dir = crate::config::get_audio_dir();
+    if let Err(e) = tokio::fs::create_dir_all(&audio_dir).await {
+        return Err(WebError::internal("Failed to create audio directory", e));
+    }
+
+    let session_id = Uuid::new_v4().to_string();
+    let audio_file_path = audio_dir.join(format!("{}.wav", session_id));
+    let upload = uploads::complete(&id, &audio_file_path)
+        .await
+        .map_err(upload_error)?;
+
+    let checksum = storage::file_checksum(&audio_file_path)
+        .await
+        .map_err(|e| WebError::internal(format!("Failed to checksum upload {}", id), e))?;
+    debug!("Upload {} completed as session {}, size: {} bytes", id, session_id, upload.offset);
+
+    process_uploaded_audio(session_id, audio_file_path, checksum).await
+}
+
+/// Periodically remove resumable uploads that stopped receiving chunks
+async fn remove_abandoned_uploads() {
+    let mut interval = tokio::time::interval(Duration::from_secs(60 * 60));
+    loop {
+        interval.tick().await;
+        let ttl_secs = crate::config::current().storage.upload_ttl_secs;
+        if let Err(e) = uploads::remove_expired(chrono::Duration::seconds(ttl_secs as i64)).await {
+            error!("Failed to remove abandoned uploads: {:?}", e);
+        }
+    }
+}
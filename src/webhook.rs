@@ -0,0 +1,179 @@
+// src/webhook.rs
+//! Notification of finished sessions through `webhook.url`
+//!
+//! Once a recorded or uploaded session has been transcribed, analyzed and
+//! saved, a JSON summary is POSTed to the webhook in the background, so a
+//! slow or broken endpoint never holds up the recording pipeline. Failed
+//! deliveries (connection errors, timeouts and non-2xx responses) are retried
+//! `webhook.max_retries` times with doubling delays, then dropped with a
+//! warning.
+//!
+//! With `webhook.secret` set, each request carries
+//! `X-Voice-Recorder-Signature: sha256=<hex>`, the HMAC-SHA256 of the raw
+//! request body keyed with the secret.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use serde::Serialize;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::time::Duration;
+
+use crate::config::WebhookConfig;
+use crate::storage::VoiceSession;
+
+/// Header carrying the body signature
+pub const SIGNATURE_HEADER: &str = "X-Voice-Recorder-Signature";
+
+/// Delay before the first retry
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Body POSTed to the webhook
+#[derive(Debug, Serialize)]
+struct WebhookPayload<'a> {
+    event: &'static str,
+    session: SessionSummary<'a>,
+}
+
+#[derive(Debug, Serialize)]
+struct SessionSummary<'a> {
+    id: &'a str,
+    title: &'a str,
+    timestamp: DateTime<Utc>,
+    duration_ms: u64,
+    /// Transcript length in characters; 0 if transcription failed
+    transcript_length: usize,
+    /// Summary from the analysis, if the session was analyzed
+    analysis_summary: Option<&'a str>,
+}
+
+/// Send a `session.processed` notification for `session` in the background
+///
+/// Does nothing when no webhook is configured. Must be called from within
+/// the tokio runtime.
+pub fn notify_session_processed(session: &VoiceSession) {
+    let config = crate::config::current().webhook.clone();
+    let Some(url) = config.url.clone() else {
+        return;
+    };
+
+    let payload = WebhookPayload {
+        event: "session.processed",
+        session: SessionSummary {
+            id: &session.id,
+            title: &session.title,
+            timestamp: session.timestamp,
+            duration_ms: session.duration_ms,
+            transcript_length: session.transcript.as_deref().map_or(0, |t| t.chars().count()),
+            analysis_summary: session.analysis.as_ref().map(|a| a.summary.as_str()),
+        },
+    };
+    let body = match serde_json::to_vec(&payload) {
+        Ok(body) => body,
+        Err(e) => {
+            warn!("[Webhook] Failed to serialize notification for session {}: {}", session.id, e);
+            return;
+        }
+    };
+    let session_id = session.id.clone();
+
+    tokio::spawn(async move {
+        match deliver(&config, &url, body).await {
+            Ok(()) => info!("[Webhook] Delivered notification for session {}", session_id),
+            Err(e) => warn!("[Webhook] Dropped notification for session {}: {:#}", session_id, e),
+        }
+    });
+}
+
+/// POST `body` to `url`, retrying failed attempts
+async fn deliver(config: &WebhookConfig, url: &str, body: Vec<u8>) -> Result<()> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(config.timeout_secs))
+        .build()
+        .context("Failed to build webhook HTTP client")?;
+    let signature = config
+        .secret
+        .as_deref()
+        .map(|secret| format!("sha256={}", hex(&hmac_sha256(secret.as_bytes(), &body))));
+
+    let mut attempt = 0;
+    loop {
+        let mut request = client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.clone());
+        if let Some(signature) = &signature {
+            request = request.header(SIGNATURE_HEADER, signature);
+        }
+
+        let error = match request.send().await {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) => anyhow::anyhow!("Webhook responded with {}", response.status()),
+            Err(e) => anyhow::Error::new(e).context("Webhook request failed"),
+        };
+
+        if attempt >= config.max_retries {
+            return Err(error.context(format!("Giving up after {} retries", attempt)));
+        }
+        let delay = INITIAL_BACKOFF.saturating_mul(2u32.saturating_pow(attempt));
+        attempt += 1;
+        warn!(
+            "[Webhook] {:#}; retrying in {}s (retry {}/{})",
+            error,
+            delay.as_secs(),
+            attempt,
+            config.max_retries
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().into()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unhex(hex: &str) -> Vec<u8> {
+        (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect()
+    }
+
+    // Test cases 1, 2, 4 and 6 of RFC 4231
+    #[test]
+    fn signature_matches_rfc_4231() {
+        let cases: [(Vec<u8>, Vec<u8>, &str); 4] = [
+            (
+                vec![0x0b; 20],
+                b"Hi There".to_vec(),
+                "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+            ),
+            (
+                b"Jefe".to_vec(),
+                b"what do ya want for nothing?".to_vec(),
+                "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            ),
+            (
+                unhex("0102030405060708090a0b0c0d0e0f10111213141516171819"),
+                vec![0xcd; 50],
+                "82558a389a443c0ea4cc819899f2083a85f0faa3e578f8077a2e3ff46729665b",
+            ),
+            (
+                vec![0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First".to_vec(),
+                "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+            ),
+        ];
+        for (key, message, expected) in cases {
+            assert_eq!(hex(&hmac_sha256(&key, &message)), expected);
+        }
+    }
+}
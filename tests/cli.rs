@@ -0,0 +1,52 @@
+// tests/cli.rs
+//! Exit codes of the command line interface
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+const MISSING_SESSION: &str = "00000000-0000-4000-8000-000000000000";
+
+/// Run the binary in an empty working directory with an empty storage
+/// directory, so no config file or stored session of the developer is used
+fn run(name: &str, args: &[&str]) -> Output {
+    let dir: PathBuf = std::env::temp_dir().join(format!("voice-recorder-cli-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_voice-recorder"))
+        .current_dir(&dir)
+        .env_remove("STORAGE_DIR")
+        .arg("--storage-dir")
+        .arg(dir.join("storage"))
+        .args(args)
+        .output()
+        .unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    output
+}
+
+#[test]
+fn missing_session_exits_with_failure() {
+    let commands: [(&str, &[&str]); 3] = [
+        ("show", &["show", "--id", MISSING_SESSION]),
+        ("delete", &["delete", "--id", MISSING_SESSION]),
+        ("export", &["export", "--id", MISSING_SESSION, "--format", "json"]),
+    ];
+    for (name, args) in commands {
+        let output = run(name, args);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(!output.status.success(), "{} succeeded for a missing session", name);
+        assert!(stderr.contains("not found"), "{} printed: {}", name, stderr);
+    }
+}
+
+#[test]
+fn missing_session_is_reported_as_json_error() {
+    let output = run("json-errors", &["--json-errors", "show", "--id", MISSING_SESSION]);
+    assert_eq!(output.status.code(), Some(1));
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let line = stderr.lines().last().unwrap();
+    let error: serde_json::Value = serde_json::from_str(line).unwrap();
+    assert_eq!(error["command"], "show");
+    assert!(error["error"].as_str().unwrap().contains("not found"));
+}
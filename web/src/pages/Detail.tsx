@@ -19,12 +19,18 @@ import {
   FireIcon,
 } from "@heroicons/react/24/outline";
 import { copyToClipboard, formatDate } from "../utils";
-import { fetchSession, deleteSession, getAudioUrl, fetchTranscript, fetchAnalysis } from "../services/api";
-import type { Note, VoiceSession } from "../types";
+import { fetchSession, deleteSession, getAudioUrl, analyzeSession } from "../services/api";
+import type { Note, SessionDetail } from "../types";
 import { voiceSessionToNote } from "../utils/dataTransform";
 
 // Removed mock data - now using real API data
 
+const LANGUAGE_NAMES: Record<string, string> = {
+  zh: 'Chinese',
+  en: 'English',
+  mixed: 'Chinese and English',
+};
+
 const Detail: React.FC = () => {
   const { id } = useParams<{ id: string }>();
   const navigate = useNavigate();
@@ -33,11 +39,13 @@ const Detail: React.FC = () => {
   const [isPlaying, setIsPlaying] = useState(false);
   const [currentTime, setCurrentTime] = useState(0);
   const [duration, setDuration] = useState(0);
-  const [session, setSession] = useState<VoiceSession | null>(null);
+  const [session, setSession] = useState<SessionDetail | null>(null);
   const [note, setNote] = useState<Note | null>(null);
   const [loading, setLoading] = useState(true);
   const [error, setError] = useState<string | null>(null);
   const [audioUrl, setAudioUrl] = useState<string | null>(null);
+  const [analyzing, setAnalyzing] = useState(false);
+  const [analyzeError, setAnalyzeError] = useState<string | null>(null);
   
   // Load session data on component mount
   useEffect(() => {
@@ -145,6 +153,21 @@ const Detail: React.FC = () => {
     }
   };
 
+  const handleAnalyze = async () => {
+    if (!id) return;
+    try {
+      setAnalyzing(true);
+      setAnalyzeError(null);
+      await analyzeSession(id);
+      setSession(await fetchSession(id));
+    } catch (err) {
+      console.error('Analysis failed:', err);
+      setAnalyzeError('Analysis failed. Check that the analysis provider is running and try again.');
+    } finally {
+      setAnalyzing(false);
+    }
+  };
+
   const handleDelete = async () => {
     if (window.confirm('Are you sure you want to delete this recording?')) {
       try {
@@ -370,6 +393,12 @@ const Detail: React.FC = () => {
                     <DocumentIcon className="w-6 h-6 text-yellow-600" />
                 </div>
                 <h2 className="text-xl font-semibold text-gray-700 mb">Transcript</h2>
+                {session?.analysis?.detected_language && (
+                  <span className="text-sm text-gray-500">
+                    Detected: {LANGUAGE_NAMES[session.analysis.detected_language.language] ?? session.analysis.detected_language.language}{' '}
+                    ({session.analysis.detected_language.confidence.toFixed(2)})
+                  </span>
+                )}
               </div>
               <div className="bg-slate-50 p-4 rounded-lg text-slate-700 leading-relaxed max-h-72 overflow-y-auto border border-slate-200 prose prose-sm max-w-none">
                 {detail.transcript ? (
@@ -380,8 +409,34 @@ const Detail: React.FC = () => {
               </div>
             </div>
 
+            {/* Deferred Analysis */}
+            {session?.transcript && !session.analysis && (
+              <div className="mb-8 bg-slate-50 border border-slate-200 text-slate-700 text-sm rounded-lg p-4 flex items-center justify-between gap-4">
+                <div>
+                  <p>This recording has not been analyzed yet.</p>
+                  {analyzeError && <p className="text-red-600 mt-1">{analyzeError}</p>}
+                </div>
+                <button
+                  onClick={handleAnalyze}
+                  disabled={analyzing}
+                  className="bg-indigo-600 text-white px-4 py-2 rounded-lg hover:bg-indigo-700 disabled:opacity-50 transition-colors whitespace-nowrap"
+                >
+                  {analyzing ? 'Analyzing...' : 'Analyze now'}
+                </button>
+              </div>
+            )}
+
+            {/* Analysis Warnings */}
+            {session?.analysis?.warnings && session.analysis.warnings.length > 0 && (
+              <div className="mb-8 bg-amber-50 border border-amber-200 text-amber-800 text-sm rounded-lg p-4 space-y-1">
+                {session.analysis.warnings.map((warning, index) => (
+                  <p key={index}>{warning}</p>
+                ))}
+              </div>
+            )}
+
             {/* Ideas Section */}
-            {session?.analysis?.ideas && session.analysis.ideas.length > 0 && (
+            {session && session.ideas.length > 0 && (
               <div className="mb-8">
                 <div className="flex items-center space-x-3 mb-6">
                   <div className="p-2 bg-gradient-to-br from-yellow-100 to-orange-100 rounded-xl">
@@ -389,11 +444,11 @@ const Detail: React.FC = () => {
                   </div>
                   <div>
                     <h2 className="text-xl font-semibold text-gray-800">Ideas</h2>
-                    <p className="text-sm text-gray-500">{session.analysis.ideas.length} creative insights</p>
+                    <p className="text-sm text-gray-500">{session.ideas.length} creative insights</p>
                   </div>
                 </div>
                 <div className="grid gap-4 md:grid-cols-2">
-                  {session.analysis.ideas.map((idea, index) => (
+                  {session.ideas.map((idea, index) => (
                     <div key={index} className="group relative">
                       {/* Background glow effect */}
                       <div className="absolute inset-0 bg-gradient-to-br from-yellow-200/30 to-orange-200/30 rounded-2xl blur-xl group-hover:blur-2xl transition-all duration-300 opacity-0 group-hover:opacity-100"></div>
@@ -423,7 +478,7 @@ const Detail: React.FC = () => {
             )}
 
             {/* Tasks Section */}
-            {session?.analysis?.tasks && session.analysis.tasks.length > 0 && (
+            {session && session.tasks.length > 0 && (
               <div className="mb-8">
                 <div className="flex items-center space-x-3 mb-6">
                   <div className="p-2 bg-gradient-to-br from-blue-100 to-indigo-100 rounded-xl">
@@ -431,11 +486,11 @@ const Detail: React.FC = () => {
                   </div>
                   <div>
                     <h2 className="text-xl font-semibold text-gray-800">Tasks</h2>
-                    <p className="text-sm text-gray-500">{session.analysis.tasks.length} action items</p>
+                    <p className="text-sm text-gray-500">{session.tasks.length} action items</p>
                   </div>
                 </div>
                 <div className="grid gap-4 md:grid-cols-2">
-                  {session.analysis.tasks.map((task, index) => {
+                  {session.tasks.map((task, index) => {
                     const getPriorityColor = (priority: string) => {
                       switch (priority) {
                         case 'Urgent': return 'from-red-500 to-pink-600';
@@ -496,11 +551,11 @@ const Detail: React.FC = () => {
             )}
 
             {/* Structured Notes Section */}
-            {session?.analysis?.structured_notes && session.analysis.structured_notes.length > 0 && (
+            {session && session.structured_notes.length > 0 && (
               <div className="mb-8">
                 <h2 className="text-xl font-semibold text-gray-700 mb-4">Structured Notes</h2>
                 <div className="space-y-4">
-                  {session.analysis.structured_notes.map((note, index) => {
+                  {session.structured_notes.map((note, index) => {
                     const getNoteTypeIcon = (noteType: string) => {
                       switch (noteType) {
                         case 'Meeting': return <ChatBubbleLeftRightIcon className="w-5 h-5 text-blue-600" />;
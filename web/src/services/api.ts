@@ -9,18 +9,44 @@ export interface VoiceSession {
   timestamp: string;
   audio_file_path: string;
   transcript?: string;
+  /** Timestamped transcript parts, absent when no timestamps are known */
+  segments?: TranscriptSegment[];
   analysis?: AnalysisResult;
+  /** Analysis was deferred (provider unreachable or automatic analysis off) */
+  analysis_pending?: boolean;
   title: string;
   duration_ms: number;
   audio_url?: string;
 }
 
+/** A session with the ideas, tasks and notes of its current analysis */
+export interface SessionDetail extends VoiceSession {
+  ideas: string[];
+  tasks: Task[];
+  structured_notes: StructuredNote[];
+}
+
+export interface TranscriptSegment {
+  start_ms: number;
+  end_ms: number;
+  text: string;
+  speaker?: string;
+}
+
 export interface AnalysisResult {
   title: string;
   ideas: string[];
   tasks: Task[];
   structured_notes: StructuredNote[];
   summary: string;
+  detected_language?: DetectedLanguage;
+  warnings?: string[];
+}
+
+export interface DetectedLanguage {
+  language: 'zh' | 'en' | 'mixed';
+  confidence: number;
+  cjk_ratio: number;
 }
 
 export interface Task {
@@ -40,9 +66,13 @@ export interface StructuredNote {
 }
 
 export interface ApiResponse<T> {
+  success: boolean;
   data: T;
-  message?: string;
-  error?: string;
+  message: string | null;
+  total: number | null;
+  page: number | null;
+  per_page: number | null;
+  timestamp: string;
 }
 
 const API_BASE_URL = 'http://localhost:3000/api';
@@ -78,8 +108,8 @@ apiClient.interceptors.response.use(
  */
 export async function fetchSessions(params?: {
   search?: string;
-  sort_by?: string;
-  sort_order?: string;
+  sort_by?: 'created_at' | 'timestamp' | 'title' | 'duration';
+  sort_order?: 'asc' | 'desc';
   limit?: number;
   offset?: number;
 }): Promise<VoiceSession[]> {
@@ -90,10 +120,11 @@ export async function fetchSessions(params?: {
 }
 
 /**
- * Fetch a specific voice session by ID
+ * Fetch a specific voice session by ID, with the ideas, tasks and notes of
+ * its analysis
  */
-export async function fetchSession(id: string): Promise<VoiceSession> {
-  const response = await apiClient.get<ApiResponse<VoiceSession>>(`/sessions/${id}`);
+export async function fetchSession(id: string): Promise<SessionDetail> {
+  const response = await apiClient.get<ApiResponse<SessionDetail>>(`/sessions/${id}`);
   return response.data.data;
 }
 
@@ -124,11 +155,24 @@ export async function stopRecording(): Promise<void> {
 /**
  * Get recording status
  */
-export async function getRecordingStatus(): Promise<boolean> {
-  const response = await apiClient.get<ApiResponse<boolean>>('/record/status');
+export interface RecordStatus {
+  recording: boolean;
+  session_id: string | null;
+  elapsed_ms: number | null;
+  /** Smoothed RMS input level, 0..1 */
+  input_level: number | null;
+}
+
+export async function fetchRecordStatus(): Promise<RecordStatus> {
+  const response = await apiClient.get<ApiResponse<RecordStatus>>('/record/status');
   return response.data.data;
 }
 
+export async function getRecordingStatus(): Promise<boolean> {
+  const status = await fetchRecordStatus();
+  return status.recording;
+}
+
 /**
  * Get audio URL for a session
  */
@@ -136,6 +180,22 @@ export function getAudioUrl(sessionId: string): string {
   return `${API_BASE_URL}/sessions/${sessionId}/audio`;
 }
 
+/**
+ * Get downsampled waveform peaks for a session
+ */
+export interface Waveform {
+  duration_ms: number;
+  sample_rate: number;
+  peaks: number[];
+}
+
+export async function fetchWaveform(sessionId: string, points?: number): Promise<Waveform> {
+  const response = await apiClient.get<ApiResponse<Waveform>>(`/sessions/${sessionId}/waveform`, {
+    params: points ? { points } : undefined,
+  });
+  return response.data.data;
+}
+
 /**
  * Get transcript for a session
  */
@@ -152,6 +212,18 @@ export async function fetchAnalysis(id: string): Promise<AnalysisResult | null>
   return response.data.data;
 }
 
+/**
+ * Analyze a session's transcript now, e.g. one saved without analysis
+ * because automatic analysis is off, and return the updated session
+ */
+export async function analyzeSession(id: string): Promise<VoiceSession> {
+  const response = await apiClient.post<ApiResponse<VoiceSession>>(`/sessions/${id}/analyze`, undefined, {
+    // Local models can take minutes; analysis.timeout_secs defaults to 300
+    timeout: 300000,
+  });
+  return response.data.data;
+}
+
 /**
  * Upload audio file and create a new session
  * This function handles the complete flow of uploading an audio file,
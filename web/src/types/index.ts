@@ -28,18 +28,42 @@ export interface VoiceSession {
   timestamp: string;
   audio_file_path: string;
   transcript?: string;
+  /** Timestamped transcript parts, absent when no timestamps are known */
+  segments?: TranscriptSegment[];
   analysis?: AnalysisResult;
   title: string;
   duration_ms: number;
   audio_url?: string;
 }
 
+/** A session with the ideas, tasks and notes of its current analysis */
+export interface SessionDetail extends VoiceSession {
+  ideas: string[];
+  tasks: Task[];
+  structured_notes: StructuredNote[];
+}
+
+export interface TranscriptSegment {
+  start_ms: number;
+  end_ms: number;
+  text: string;
+  speaker?: string;
+}
+
 export interface AnalysisResult {
   title: string;
   ideas: string[];
   tasks: Task[];
   structured_notes: StructuredNote[];
   summary: string;
+  detected_language?: DetectedLanguage;
+  warnings?: string[];
+}
+
+export interface DetectedLanguage {
+  language: 'zh' | 'en' | 'mixed';
+  confidence: number;
+  cjk_ratio: number;
 }
 
 export interface Task {
@@ -47,6 +71,8 @@ export interface Task {
   description?: string;
   priority: 'Low' | 'Medium' | 'High' | 'Urgent';
   due_date?: string;
+  /** Model's confidence that this is a real task, from 0 to 1 */
+  confidence?: number;
 }
 
 export interface StructuredNote {